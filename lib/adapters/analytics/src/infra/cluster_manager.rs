@@ -1,15 +1,49 @@
 use anyhow::Result;
 use chrono::Utc;
-use std::collections::HashMap;
+use k8s_openapi::api::apps::v1::{Deployment, StatefulSet};
+use k8s_openapi::api::core::v1::PodTemplateSpec;
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 
-use phenome_domain::{ClusterHealth, ClusterId, ClusterMetadata, MetricSample, MetricsQuery};
+use phenome_domain::{
+    ClusterHealth, ClusterId, ClusterMetadata, ContainerResources, MetricSample, MetricsQuery,
+    Unit, parse_quantity,
+};
 
-#[derive(Clone, Default)]
+use crate::storage::StoragePort;
+
+const CLUSTER_EVENT_CAPACITY: usize = 16;
+
+/// A cluster joining or leaving the set being collected from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClusterEvent {
+    Added(ClusterId),
+    Removed(ClusterId),
+}
+
+/// Adding a cluster whose context is already registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterExists {
+    pub context: String,
+}
+
+impl std::fmt::Display for ClusterExists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cluster {} is already registered", self.context)
+    }
+}
+
+impl std::error::Error for ClusterExists {}
+
+#[derive(Clone)]
 pub struct ClusterManager {
     clusters: Arc<RwLock<HashMap<ClusterId, ClusterMetadata>>>,
     clients: Arc<RwLock<HashMap<ClusterId, kube::Client>>>,
+    storage: Option<Arc<dyn StoragePort>>,
+    events: broadcast::Sender<ClusterEvent>,
 }
 
 impl std::fmt::Debug for ClusterManager {
@@ -19,24 +53,66 @@ impl std::fmt::Debug for ClusterManager {
         f.debug_struct("ClusterManager")
             .field("clusters_count", &clusters_len)
             .field("clients_count", &clients_len)
+            .field("persistent", &self.storage.is_some())
             .finish()
     }
 }
 
+impl Default for ClusterManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ClusterManager {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(CLUSTER_EVENT_CAPACITY);
         Self {
             clusters: Arc::new(RwLock::new(HashMap::new())),
             clients: Arc::new(RwLock::new(HashMap::new())),
+            storage: None,
+            events,
         }
     }
 
-    pub async fn add_cluster(&self, context: String) -> Result<ClusterId> {
+    /// Persist clusters as they are added, removed and polled.
+    pub fn with_storage(mut self, storage: Arc<dyn StoragePort>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Reload the clusters persisted by earlier runs.
+    pub async fn restore_clusters(&self) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        let stored = storage.list_clusters().await?;
+        let mut clusters = self.clusters.write().await;
+        for cluster in stored {
+            clusters.insert(cluster.id.clone(), cluster);
+        }
+        Ok(())
+    }
+
+    /// Changes to the registered clusters from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ClusterEvent> {
+        self.events.subscribe()
+    }
+
+    /// Register the cluster behind a kubeconfig context, named `name`.
+    /// Fails with [`ClusterExists`] if the context is already registered.
+    pub async fn add_cluster(&self, name: String, context: String) -> Result<ClusterMetadata> {
+        if context.trim().is_empty() {
+            anyhow::bail!("cluster context is required");
+        }
         let mut clusters = self.clusters.write().await;
         let id = context.clone();
+        if clusters.contains_key(&id) {
+            return Err(ClusterExists { context }.into());
+        }
         let metadata = ClusterMetadata {
             id: id.clone(),
-            name: context.clone(),
+            name,
             context,
             api_server: String::new(),
             health_status: ClusterHealth::Healthy,
@@ -45,19 +121,65 @@ impl ClusterManager {
             node_count: 0,
             namespace_count: 0,
         };
-        clusters.insert(id.clone(), metadata);
-        Ok(id)
+        if let Some(storage) = &self.storage {
+            storage.upsert_cluster(metadata.clone()).await?;
+        }
+        clusters.insert(id.clone(), metadata.clone());
+        drop(clusters);
+
+        let _ = self.events.send(ClusterEvent::Added(id));
+        Ok(metadata)
     }
 
-    pub async fn remove_cluster(&self, id: &ClusterId) -> Result<()> {
+    /// Register a cluster from the config file, unless its context is
+    /// already registered or was removed over the API; removals outlast
+    /// restarts. Returns the cluster if it was added.
+    pub async fn add_configured_cluster(
+        &self,
+        name: String,
+        context: String,
+    ) -> Result<Option<ClusterMetadata>> {
+        if self.get_cluster(&context).await.is_some() {
+            return Ok(None);
+        }
+        let removed = match &self.storage {
+            Some(storage) => storage.removed_clusters().await?,
+            None => Vec::new(),
+        };
+        if removed.contains(&context) {
+            return Ok(None);
+        }
+        self.add_cluster(name, context).await.map(Some)
+    }
+
+    /// Stop collecting from a cluster and return it, or `None` if no
+    /// cluster has that id.
+    pub async fn remove_cluster(&self, id: &ClusterId) -> Result<Option<ClusterMetadata>> {
         let mut clusters = self.clusters.write().await;
-        clusters.remove(id);
-        Ok(())
+        if !clusters.contains_key(id) {
+            return Ok(None);
+        }
+        if let Some(storage) = &self.storage {
+            storage.delete_cluster(id.clone()).await?;
+        }
+        let removed = clusters.remove(id);
+        drop(clusters);
+
+        self.clients.write().await.remove(id);
+        let _ = self.events.send(ClusterEvent::Removed(id.clone()));
+        Ok(removed)
     }
 
+    /// Registered clusters by name.
     pub async fn list_clusters(&self) -> Vec<ClusterMetadata> {
         let clusters = self.clusters.read().await;
-        clusters.values().cloned().collect()
+        let mut clusters: Vec<_> = clusters.values().cloned().collect();
+        clusters.sort_by(|a, b| a.name.cmp(&b.name));
+        clusters
+    }
+
+    pub async fn get_cluster(&self, id: &ClusterId) -> Option<ClusterMetadata> {
+        self.clusters.read().await.get(id).cloned()
     }
 
     pub async fn get_cluster_health(&self, id: &ClusterId) -> ClusterHealth {
@@ -68,6 +190,40 @@ impl ClusterManager {
             .unwrap_or(ClusterHealth::Unreachable)
     }
 
+    /// Record the outcome of polling a cluster. Reachable clusters are
+    /// marked seen, and a full poll's `samples` refresh the resource counts.
+    async fn record_poll(
+        &self,
+        cluster_id: &ClusterId,
+        health: ClusterHealth,
+        samples: Option<&[MetricSample]>,
+    ) {
+        let mut clusters = self.clusters.write().await;
+        let Some(cluster) = clusters.get_mut(cluster_id) else {
+            return;
+        };
+        cluster.health_status = health;
+        if health != ClusterHealth::Unreachable {
+            cluster.last_seen = Utc::now().timestamp_millis();
+        }
+        if let Some(samples) = samples {
+            let counts = ResourceCounts::from_samples(samples);
+            cluster.node_count = counts.nodes;
+            cluster.pod_count = counts.pods;
+            cluster.namespace_count = counts.namespaces;
+        }
+        let cluster = cluster.clone();
+
+        // Persist under the lock so a concurrent removal cannot be undone.
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        if let Err(e) = storage.upsert_cluster(cluster).await {
+            tracing::warn!("Failed to persist cluster {}: {}", cluster_id, e);
+        }
+        drop(clusters);
+    }
+
     async fn get_client(&self, context: &str) -> Result<kube::Client> {
         let clients = self.clients.read().await;
         if let Some(client) = clients.get(context) {
@@ -91,12 +247,51 @@ impl ClusterManager {
         let user_config = kube::config::Kubeconfig::read()
             .map_err(|e| anyhow::anyhow!("Failed into read kubeconfig: {}", e))?;
         let config = kube::Config::from_custom_kubeconfig(user_config, &options).await?;
+        let api_server = config.cluster_url.to_string();
         let client = kube::Client::try_from(config)?;
 
         clients.insert(context.to_string(), client.clone());
+        drop(clients);
+        if let Some(cluster) = self.clusters.write().await.get_mut(context) {
+            cluster.api_server = api_server;
+        }
         Ok(client)
     }
 
+    /// Per-replica requests and limits of every container in the
+    /// cluster's deployments and statefulsets.
+    pub async fn list_containers(&self, cluster_id: &ClusterId) -> Result<Vec<ContainerResources>> {
+        let Some(cluster) = self.get_cluster(cluster_id).await else {
+            anyhow::bail!("cluster {cluster_id} is not registered");
+        };
+        let client = self.get_client(&cluster.context).await?;
+        let params = kube::api::ListParams::default();
+
+        let mut containers = Vec::new();
+        for deployment in kube::Api::<Deployment>::all(client.clone())
+            .list(&params)
+            .await?
+        {
+            if let Some(spec) = deployment.spec {
+                containers.extend(workload_containers(
+                    &deployment.metadata,
+                    spec.replicas,
+                    &spec.template,
+                ));
+            }
+        }
+        for statefulset in kube::Api::<StatefulSet>::all(client).list(&params).await? {
+            if let Some(spec) = statefulset.spec {
+                containers.extend(workload_containers(
+                    &statefulset.metadata,
+                    spec.replicas,
+                    &spec.template,
+                ));
+            }
+        }
+        Ok(containers)
+    }
+
     pub async fn query_metrics(
         &self,
         cluster_id: &ClusterId,
@@ -106,6 +301,8 @@ impl ClusterManager {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Failed to get client for cluster {}: {}", cluster_id, e);
+                self.record_poll(cluster_id, ClusterHealth::Unreachable, None)
+                    .await;
                 return Ok(Vec::new()); // Fallback to empty
             }
         };
@@ -130,6 +327,19 @@ impl ClusterManager {
             }
         }
 
+        // Every cluster has nodes; none reported means metrics are missing.
+        let full_poll = query.resource_type.is_none();
+        let reported_nodes = samples
+            .iter()
+            .any(|s| s.resource_type == phenome_domain::ResourceType::Node);
+        let health = if full_poll && !reported_nodes {
+            ClusterHealth::Degraded
+        } else {
+            ClusterHealth::Healthy
+        };
+        self.record_poll(cluster_id, health, full_poll.then_some(samples.as_slice()))
+            .await;
+
         Ok(samples)
     }
 
@@ -169,7 +379,7 @@ impl ClusterManager {
                         metric_type: phenome_domain::MetricType::CpuUsage,
                         timestamp: Utc::now().timestamp_millis(),
                         value: val,
-                        unit: "cores".to_string(),
+                        unit: Unit::Cores.as_str().to_string(),
                     });
                 }
                 if let Some(mem) = usage.get("memory").and_then(|v| v.as_str()) {
@@ -181,7 +391,7 @@ impl ClusterManager {
                         metric_type: phenome_domain::MetricType::MemoryUsage,
                         timestamp: Utc::now().timestamp_millis(),
                         value: val,
-                        unit: "bytes".to_string(),
+                        unit: Unit::Bytes.as_str().to_string(),
                     });
                 }
             }
@@ -213,18 +423,41 @@ impl ClusterManager {
             let namespace = metric.metadata.namespace.unwrap_or_default();
             let resource_id = format!("{}/{}", namespace, name);
 
-            // Pod metrics have containers list
+            // Pod metrics have containers list; each container is sampled
+            // as `namespace/pod/container` alongside the pod's totals.
             if let Some(containers) = metric.data.get("containers").and_then(|c| c.as_array()) {
                 let mut total_cpu = 0.0;
                 let mut total_mem = 0.0;
 
                 for c in containers {
+                    let container = c.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+                    let container_id = format!("{}/{}", resource_id, container);
                     if let Some(usage) = c.get("usage").and_then(|u| u.as_object()) {
                         if let Some(cpu) = usage.get("cpu").and_then(|v| v.as_str()) {
-                            total_cpu += parse_k8s_quantity(cpu);
+                            let cpu = parse_k8s_quantity(cpu);
+                            total_cpu += cpu;
+                            samples.push(MetricSample {
+                                cluster_id: cluster_id.to_string(),
+                                resource_type: phenome_domain::ResourceType::Container,
+                                resource_id: container_id.clone(),
+                                metric_type: phenome_domain::MetricType::CpuUsage,
+                                timestamp: Utc::now().timestamp_millis(),
+                                value: cpu,
+                                unit: Unit::Cores.as_str().to_string(),
+                            });
                         }
                         if let Some(mem) = usage.get("memory").and_then(|v| v.as_str()) {
-                            total_mem += parse_k8s_quantity(mem);
+                            let mem = parse_k8s_quantity(mem);
+                            total_mem += mem;
+                            samples.push(MetricSample {
+                                cluster_id: cluster_id.to_string(),
+                                resource_type: phenome_domain::ResourceType::Container,
+                                resource_id: container_id.clone(),
+                                metric_type: phenome_domain::MetricType::MemoryUsage,
+                                timestamp: Utc::now().timestamp_millis(),
+                                value: mem,
+                                unit: Unit::Bytes.as_str().to_string(),
+                            });
                         }
                     }
                 }
@@ -236,7 +469,7 @@ impl ClusterManager {
                     metric_type: phenome_domain::MetricType::CpuUsage,
                     timestamp: Utc::now().timestamp_millis(),
                     value: total_cpu,
-                    unit: "cores".to_string(),
+                    unit: Unit::Cores.as_str().to_string(),
                 });
                 samples.push(MetricSample {
                     cluster_id: cluster_id.to_string(),
@@ -245,16 +478,13 @@ impl ClusterManager {
                     metric_type: phenome_domain::MetricType::MemoryUsage,
                     timestamp: Utc::now().timestamp_millis(),
                     value: total_mem,
-                    unit: "bytes".to_string(),
+                    unit: Unit::Bytes.as_str().to_string(),
                 });
             }
         }
         Ok(samples)
     }
 
-    // Helper for parsing k8s quantities
-    // fn parse_k8s_quantity... needs to be added or used if available
-
     pub async fn query_all_clusters(
         &self,
         query: MetricsQuery,
@@ -283,32 +513,83 @@ impl ClusterManager {
     }
 }
 
-fn parse_k8s_quantity(q: &str) -> f64 {
-    let q = q.trim();
-    if let Ok(val) = q.parse::<f64>() {
-        return val;
-    }
+/// Distinct resources seen in one poll of a cluster.
+struct ResourceCounts {
+    nodes: u32,
+    pods: u32,
+    namespaces: u32,
+}
 
-    // Simple parsing for m, Ki, Mi, Gi, n
-    if let Some(stripped) = q.strip_suffix('n') {
-        return stripped.parse::<f64>().unwrap_or(0.0) / 1_000_000_000.0;
-    }
-    if let Some(stripped) = q.strip_suffix('u') {
-        return stripped.parse::<f64>().unwrap_or(0.0) / 1_000_000.0;
-    }
-    if let Some(stripped) = q.strip_suffix('m') {
-        return stripped.parse::<f64>().unwrap_or(0.0) / 1000.0;
-    }
-    if let Some(stripped) = q.strip_suffix("Ki") {
-        return stripped.parse::<f64>().unwrap_or(0.0) * 1024.0;
-    }
-    if let Some(stripped) = q.strip_suffix("Mi") {
-        return stripped.parse::<f64>().unwrap_or(0.0) * 1024.0 * 1024.0;
-    }
-    if let Some(stripped) = q.strip_suffix("Gi") {
-        return stripped.parse::<f64>().unwrap_or(0.0) * 1024.0 * 1024.0 * 1024.0;
+impl ResourceCounts {
+    fn from_samples(samples: &[MetricSample]) -> Self {
+        let mut nodes = HashSet::new();
+        let mut pods = HashSet::new();
+        let mut namespaces = HashSet::new();
+        for sample in samples {
+            match sample.resource_type {
+                phenome_domain::ResourceType::Node => {
+                    nodes.insert(sample.resource_id.as_str());
+                }
+                phenome_domain::ResourceType::Pod => {
+                    pods.insert(sample.resource_id.as_str());
+                    if let Some((namespace, _)) = sample.resource_id.split_once('/') {
+                        namespaces.insert(namespace);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self {
+            nodes: nodes.len() as u32,
+            pods: pods.len() as u32,
+            namespaces: namespaces.len() as u32,
+        }
     }
+}
 
-    // Fallback logic could get fancy but strictly implementation of "usage" is usually simple
-    0.0
+/// The containers of a workload's pod template, under the workload's
+/// `namespace/name`. Kubernetes runs one replica when none is set;
+/// quantities that do not parse are left unset.
+pub(crate) fn workload_containers(
+    metadata: &ObjectMeta,
+    replicas: Option<i32>,
+    template: &PodTemplateSpec,
+) -> Vec<ContainerResources> {
+    let Some(spec) = &template.spec else {
+        return Vec::new();
+    };
+    let workload = format!(
+        "{}/{}",
+        metadata.namespace.as_deref().unwrap_or_default(),
+        metadata.name.as_deref().unwrap_or_default()
+    );
+    let quantity = |values: Option<&BTreeMap<String, Quantity>>, name: &str| {
+        values
+            .and_then(|values| values.get(name))
+            .and_then(|quantity| parse_quantity(&quantity.0).ok())
+    };
+    spec.containers
+        .iter()
+        .map(|container| {
+            let resources = container.resources.as_ref();
+            let requests = resources.and_then(|resources| resources.requests.as_ref());
+            let limits = resources.and_then(|resources| resources.limits.as_ref());
+            ContainerResources {
+                workload: workload.clone(),
+                container: container.name.clone(),
+                replicas: replicas.unwrap_or(1).max(0) as u32,
+                cpu_request: quantity(requests, "cpu"),
+                cpu_limit: quantity(limits, "cpu"),
+                memory_request: quantity(requests, "memory"),
+                memory_limit: quantity(limits, "memory"),
+            }
+        })
+        .collect()
+}
+
+fn parse_k8s_quantity(q: &str) -> f64 {
+    parse_quantity(q).unwrap_or_else(|err| {
+        tracing::warn!("Ignoring metric quantity: {}", err);
+        0.0
+    })
 }
@@ -1,10 +1,146 @@
-use crate::cluster_manager::ClusterManager;
+use std::sync::Arc;
+
+use k8s_openapi::api::apps::v1::Deployment;
+
+use crate::cluster_manager::{ClusterEvent, ClusterExists, ClusterManager, workload_containers};
+use crate::storage::sqlite::SqliteStorage;
 
 #[tokio::test]
 async fn adds_and_lists_clusters() {
     let manager = ClusterManager::new();
-    let id = manager.add_cluster("dev".to_string()).await.unwrap();
+    let cluster = manager
+        .add_cluster("dev".to_string(), "dev".to_string())
+        .await
+        .unwrap();
     let clusters = manager.list_clusters().await;
     assert_eq!(clusters.len(), 1);
-    assert_eq!(clusters[0].id, id);
+    assert_eq!(clusters[0].id, cluster.id);
+}
+
+#[tokio::test]
+async fn persists_clusters_and_announces_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = Arc::new(SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap());
+    let manager = ClusterManager::new().with_storage(storage.clone());
+    let mut events = manager.subscribe();
+
+    manager
+        .add_cluster("Production".to_string(), "prod".to_string())
+        .await
+        .unwrap();
+    manager
+        .add_cluster("Staging".to_string(), "staging".to_string())
+        .await
+        .unwrap();
+    let err = manager
+        .add_cluster("Again".to_string(), "prod".to_string())
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<ClusterExists>().is_some());
+    let removed = manager
+        .remove_cluster(&"staging".to_string())
+        .await
+        .unwrap();
+    assert_eq!(removed.map(|c| c.name), Some("Staging".to_string()));
+    assert!(
+        manager
+            .remove_cluster(&"staging".to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClusterEvent::Added("prod".into())
+    );
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClusterEvent::Added("staging".into())
+    );
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClusterEvent::Removed("staging".into())
+    );
+
+    let restarted = ClusterManager::new().with_storage(storage);
+    restarted.restore_clusters().await.unwrap();
+    let names: Vec<_> = restarted
+        .list_clusters()
+        .await
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, vec!["Production"]);
+}
+
+#[tokio::test]
+async fn removed_configured_clusters_stay_removed_after_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = Arc::new(SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap());
+    let manager = ClusterManager::new().with_storage(storage.clone());
+    let added = manager
+        .add_configured_cluster("Production".to_string(), "prod".to_string())
+        .await
+        .unwrap();
+    assert!(added.is_some());
+    manager.remove_cluster(&"prod".to_string()).await.unwrap();
+
+    let restarted = ClusterManager::new().with_storage(storage.clone());
+    restarted.restore_clusters().await.unwrap();
+    let added = restarted
+        .add_configured_cluster("Production".to_string(), "prod".to_string())
+        .await
+        .unwrap();
+    assert!(added.is_none());
+    assert!(restarted.list_clusters().await.is_empty());
+
+    // Adding it back over the API lifts the removal.
+    restarted
+        .add_cluster("Production".to_string(), "prod".to_string())
+        .await
+        .unwrap();
+    let again = ClusterManager::new().with_storage(storage);
+    again.restore_clusters().await.unwrap();
+    assert_eq!(again.list_clusters().await.len(), 1);
+}
+
+#[test]
+fn reads_container_resources_from_workload_templates() {
+    let deployment: Deployment = serde_json::from_value(serde_json::json!({
+        "metadata": { "name": "api", "namespace": "shop" },
+        "spec": {
+            "replicas": 3,
+            "selector": {},
+            "template": { "spec": { "containers": [
+                {
+                    "name": "app",
+                    "resources": {
+                        "requests": { "cpu": "250m", "memory": "256Mi" },
+                        "limits": { "cpu": "1", "memory": "1Gi" }
+                    }
+                },
+                { "name": "sidecar" }
+            ] } }
+        }
+    }))
+    .unwrap();
+    let spec = deployment.spec.unwrap();
+
+    let containers = workload_containers(&deployment.metadata, spec.replicas, &spec.template);
+
+    assert_eq!(containers.len(), 2);
+    let app = &containers[0];
+    assert_eq!(
+        (app.workload.as_str(), app.container.as_str()),
+        ("shop/api", "app")
+    );
+    assert_eq!(app.replicas, 3);
+    assert_eq!(app.cpu_request, Some(0.25));
+    assert_eq!(app.cpu_limit, Some(1.0));
+    assert_eq!(app.memory_request, Some(256.0 * 1024.0 * 1024.0));
+    assert_eq!(app.memory_limit, Some(1024.0 * 1024.0 * 1024.0));
+    assert_eq!(containers[1].cpu_request, None);
 }
@@ -1,22 +1,39 @@
 use anyhow::Result;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Response, Status};
 
 use phenome_domain as domain;
-use phenome_ports::AnalyticsPort;
+use phenome_ports::{AnalyticsPort, SchedulerPort};
 
 use crate::AnalyticsService;
+use crate::cluster_manager::{ClusterExists, ClusterManager};
+use crate::feed::FeedBatch;
+use crate::storage::MetricsPage;
 
 pub mod analytics {
     tonic::include_proto!("analytics");
 }
 
+#[cfg(test)]
+mod tests;
+
 use analytics::analytics_service_server::{
     AnalyticsService as AnalyticsServiceTrait, AnalyticsServiceServer,
 };
+use analytics::cluster_service_server::{
+    ClusterService as ClusterServiceTrait, ClusterServiceServer,
+};
+use analytics::scheduler_service_server::{
+    SchedulerService as SchedulerServiceTrait, SchedulerServiceServer,
+};
 use analytics::*;
 
+type WatchStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
 #[derive(Debug)]
 pub struct GrpcAnalyticsService {
     inner: Arc<AnalyticsService>,
@@ -35,19 +52,42 @@ impl AnalyticsServiceTrait for GrpcAnalyticsService {
         request: Request<RecordMetricsRequest>,
     ) -> Result<Response<RecordMetricsResponse>, Status> {
         let req = request.into_inner();
-        let samples: Vec<domain::MetricSample> = req
-            .samples
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<_, _>>()
-            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+        let mut samples = Vec::with_capacity(req.samples.len());
+        let mut positions = Vec::with_capacity(req.samples.len());
+        let mut rejections = Vec::new();
+        for (index, sample) in req.samples.into_iter().enumerate() {
+            match wire_sample(sample) {
+                Ok(sample) => {
+                    samples.push(sample);
+                    positions.push(index);
+                }
+                Err((reason, message)) => rejections.push(domain::SampleRejection {
+                    index,
+                    reason,
+                    message,
+                }),
+            }
+        }
+        self.inner.record_rejections(&rejections);
 
-        self.inner
+        let report = self
+            .inner
             .record_metrics(samples)
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
-        Ok(Response::new(RecordMetricsResponse {}))
+        // Map indices back onto the request batch.
+        rejections.extend(report.rejections.into_iter().map(|mut rejection| {
+            rejection.index = positions[rejection.index];
+            rejection
+        }));
+        rejections.sort_by_key(|rejection| rejection.index);
+
+        Ok(Response::new(RecordMetricsResponse {
+            accepted: report.accepted as u32,
+            rejected: rejections.len() as u32,
+            rejections: rejections.into_iter().map(Into::into).collect(),
+        }))
     }
 
     async fn query_aggregated(
@@ -82,15 +122,17 @@ impl AnalyticsServiceTrait for GrpcAnalyticsService {
         let range = req
             .time_range
             .ok_or_else(|| Status::invalid_argument("missing time range"))?
-            .into();
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
 
         let series = self
             .inner
-            .get_time_series(req.resource_id, metric_type, range)
+            .get_time_series(req.resource_id, metric_type, range, req.max_points)
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(GetTimeSeriesResponse {
+            method: DownsampleMethod::from(series.downsample).into(),
             series: Some(series.into()),
         }))
     }
@@ -100,7 +142,9 @@ impl AnalyticsServiceTrait for GrpcAnalyticsService {
         request: Request<GetAnomaliesRequest>,
     ) -> Result<Response<GetAnomaliesResponse>, Status> {
         let req = request.into_inner();
-        let filter: domain::AnomalyFilter = req.into();
+        let filter: domain::AnomalyFilter = req
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
 
         let anomalies = self
             .inner
@@ -136,27 +180,441 @@ impl AnalyticsServiceTrait for GrpcAnalyticsService {
         request: Request<QueryMetricsRequest>,
     ) -> Result<Response<QueryMetricsResponse>, Status> {
         let req = request.into_inner();
-        let query: domain::MetricsQuery = req.into();
+        let (limit, cursor) = (req.limit, req.cursor);
+        let query: domain::MetricsQuery = req
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+
+        let page = match limit {
+            Some(limit) => self.inner.query_metrics_page(query, cursor, limit).await,
+            None => self
+                .inner
+                .query_metrics(query)
+                .await
+                .map(|samples| MetricsPage {
+                    samples,
+                    next_cursor: None,
+                }),
+        }
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(QueryMetricsResponse {
+            samples: page.samples.into_iter().map(Into::into).collect(),
+            next_cursor: page.next_cursor,
+        }))
+    }
 
-        let samples = self
+    async fn get_data_quality(
+        &self,
+        request: Request<GetDataQualityRequest>,
+    ) -> Result<Response<GetDataQualityResponse>, Status> {
+        let req = request.into_inner();
+        let filter: domain::DataQualityFilter = req.into();
+
+        let issues = self
             .inner
-            .query_metrics(query)
+            .get_data_quality(filter)
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
-        Ok(Response::new(QueryMetricsResponse {
-            samples: samples.into_iter().map(Into::into).collect(),
+        Ok(Response::new(GetDataQualityResponse {
+            issues: issues.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_incidents(
+        &self,
+        request: Request<GetIncidentsRequest>,
+    ) -> Result<Response<GetIncidentsResponse>, Status> {
+        let req = request.into_inner();
+        let filter: domain::IncidentFilter = req
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+
+        let incidents = self
+            .inner
+            .get_incidents(filter)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetIncidentsResponse {
+            incidents: incidents.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn submit_anomaly_feedback(
+        &self,
+        request: Request<SubmitAnomalyFeedbackRequest>,
+    ) -> Result<Response<SubmitAnomalyFeedbackResponse>, Status> {
+        let req = request.into_inner();
+        let kind = AnomalyFeedbackKind::try_from(req.kind)
+            .map_err(anyhow::Error::from)
+            .and_then(domain::AnomalyFeedbackKind::try_from)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let feedback = domain::AnomalyFeedback {
+            kind,
+            submitted_at: chrono::Utc::now().timestamp_millis(),
+            note: req.note.filter(|note| !note.is_empty()),
+        };
+
+        let anomaly = self
+            .inner
+            .submit_anomaly_feedback(req.anomaly_id.clone(), feedback)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found(format!("anomaly {} not found", req.anomaly_id)))?;
+
+        Ok(Response::new(SubmitAnomalyFeedbackResponse {
+            anomaly: Some(anomaly.into()),
+        }))
+    }
+
+    async fn get_anomaly_feedback(
+        &self,
+        request: Request<GetAnomalyFeedbackRequest>,
+    ) -> Result<Response<GetAnomalyFeedbackResponse>, Status> {
+        let req = request.into_inner();
+        let filter: domain::AnomalyFilter = req
+            .try_into()
+            .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+
+        let anomalies = self
+            .inner
+            .get_anomaly_feedback(filter)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetAnomalyFeedbackResponse {
+            anomalies: anomalies.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn dismiss_recommendation(
+        &self,
+        request: Request<DismissRecommendationRequest>,
+    ) -> Result<Response<RecommendationResponse>, Status> {
+        let req = request.into_inner();
+        let recommendation = self
+            .inner
+            .dismiss_recommendation(&req.recommendation_id, req.reason)
+            .await
+            .map_err(transition_status)?
+            .ok_or_else(|| recommendation_not_found(&req.recommendation_id))?;
+
+        Ok(Response::new(RecommendationResponse {
+            recommendation: Some(recommendation.into()),
+        }))
+    }
+
+    async fn schedule_recommendation(
+        &self,
+        request: Request<ScheduleRecommendationRequest>,
+    ) -> Result<Response<RecommendationResponse>, Status> {
+        let req = request.into_inner();
+        let recommendation = self
+            .inner
+            .schedule_recommendation(&req.recommendation_id, req.execute_at)
+            .await
+            .map_err(transition_status)?
+            .ok_or_else(|| recommendation_not_found(&req.recommendation_id))?;
+
+        Ok(Response::new(RecommendationResponse {
+            recommendation: Some(recommendation.into()),
+        }))
+    }
+
+    async fn apply_recommendation(
+        &self,
+        request: Request<ApplyRecommendationRequest>,
+    ) -> Result<Response<RecommendationResponse>, Status> {
+        let req = request.into_inner();
+        let recommendation = self
+            .inner
+            .apply_recommendation(&req.recommendation_id)
+            .await
+            .map_err(transition_status)?
+            .ok_or_else(|| recommendation_not_found(&req.recommendation_id))?;
+
+        Ok(Response::new(RecommendationResponse {
+            recommendation: Some(recommendation.into()),
+        }))
+    }
+
+    type WatchMetricsStream = WatchStream<WatchMetricsResponse>;
+
+    async fn watch_metrics(
+        &self,
+        request: Request<WatchMetricsRequest>,
+    ) -> Result<Response<Self::WatchMetricsStream>, Status> {
+        let req = request.into_inner();
+        let cursor = req.cursor;
+        let rx = self.inner.watch_metrics(req.into(), cursor);
+        let stream = ReceiverStream::new(rx)
+            .map(WatchMetricsResponse::from)
+            .map(Ok);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    type WatchAnomaliesStream = WatchStream<WatchAnomaliesResponse>;
+
+    async fn watch_anomalies(
+        &self,
+        request: Request<WatchAnomaliesRequest>,
+    ) -> Result<Response<Self::WatchAnomaliesStream>, Status> {
+        let req = request.into_inner();
+        let cursor = req.cursor;
+        let rx = self.inner.watch_anomalies(req.into(), cursor);
+        let stream = ReceiverStream::new(rx)
+            .map(WatchAnomaliesResponse::from)
+            .map(Ok);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    type WatchRecommendationsStream = WatchStream<WatchRecommendationsResponse>;
+
+    async fn watch_recommendations(
+        &self,
+        request: Request<WatchRecommendationsRequest>,
+    ) -> Result<Response<Self::WatchRecommendationsStream>, Status> {
+        let req = request.into_inner();
+        let cursor = req.cursor;
+        let rx = self.inner.watch_recommendations(req.into(), cursor);
+        let stream = ReceiverStream::new(rx)
+            .map(WatchRecommendationsResponse::from)
+            .map(Ok);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Rejected status changes, of recommendations or scheduled actions, are
+/// the caller's to fix; anything else failed on our side.
+fn transition_status(e: anyhow::Error) -> Status {
+    if e.downcast_ref::<domain::InvalidTransition>().is_some()
+        || e.downcast_ref::<domain::ScheduleConflict>().is_some()
+    {
+        Status::failed_precondition(e.to_string())
+    } else {
+        Status::internal(e.to_string())
+    }
+}
+
+fn recommendation_not_found(id: &str) -> Status {
+    Status::not_found(format!("recommendation {id} not found"))
+}
+
+fn schedule_not_found(id: &str) -> Status {
+    Status::not_found(format!("scheduled action {id} not found"))
+}
+
+/// Cancelling and rescheduling go through the analytics service, so the
+/// recommendation waiting on the action follows it.
+pub struct GrpcSchedulerService {
+    inner: Arc<AnalyticsService>,
+}
+
+impl GrpcSchedulerService {
+    pub fn new(inner: Arc<AnalyticsService>) -> Self {
+        Self { inner }
+    }
+}
+
+#[tonic::async_trait]
+impl SchedulerServiceTrait for GrpcSchedulerService {
+    async fn list_scheduled_actions(
+        &self,
+        request: Request<ListScheduledActionsRequest>,
+    ) -> Result<Response<ListScheduledActionsResponse>, Status> {
+        let filter = domain::ScheduleFilter::from(request.into_inner());
+        let mut actions: Vec<_> = self
+            .inner
+            .scheduler()
+            .list_scheduled()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .into_iter()
+            .filter(|action| filter.matches(action))
+            .collect();
+        actions.sort_by_key(|action| action.execute_at);
+        if let Some(limit) = filter.limit {
+            actions.truncate(limit as usize);
+        }
+
+        Ok(Response::new(ListScheduledActionsResponse {
+            actions: actions.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_scheduled_action(
+        &self,
+        request: Request<GetScheduledActionRequest>,
+    ) -> Result<Response<ScheduledActionResponse>, Status> {
+        let req = request.into_inner();
+        let action = self
+            .inner
+            .scheduler()
+            .get_scheduled(req.id.clone())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| schedule_not_found(&req.id))?;
+
+        Ok(Response::new(ScheduledActionResponse {
+            action: Some(action.into()),
+        }))
+    }
+
+    async fn cancel_scheduled_action(
+        &self,
+        request: Request<CancelScheduledActionRequest>,
+    ) -> Result<Response<ScheduledActionResponse>, Status> {
+        let req = request.into_inner();
+        let action = self
+            .inner
+            .cancel_schedule(req.id.clone())
+            .await
+            .map_err(transition_status)?
+            .ok_or_else(|| schedule_not_found(&req.id))?;
+
+        Ok(Response::new(ScheduledActionResponse {
+            action: Some(action.into()),
+        }))
+    }
+
+    async fn reschedule_action(
+        &self,
+        request: Request<RescheduleActionRequest>,
+    ) -> Result<Response<ScheduledActionResponse>, Status> {
+        let req = request.into_inner();
+        let action = self
+            .inner
+            .reschedule(req.id.clone(), req.execute_at)
+            .await
+            .map_err(transition_status)?
+            .ok_or_else(|| schedule_not_found(&req.id))?;
+
+        Ok(Response::new(ScheduledActionResponse {
+            action: Some(action.into()),
+        }))
+    }
+
+    async fn get_execution_history(
+        &self,
+        request: Request<GetExecutionHistoryRequest>,
+    ) -> Result<Response<GetExecutionHistoryResponse>, Status> {
+        let executions = self
+            .inner
+            .scheduler()
+            .execution_history(request.into_inner().into())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetExecutionHistoryResponse {
+            executions: executions.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct GrpcClusterService {
+    inner: ClusterManager,
+}
+
+impl GrpcClusterService {
+    pub fn new(inner: ClusterManager) -> Self {
+        Self { inner }
+    }
+}
+
+#[tonic::async_trait]
+impl ClusterServiceTrait for GrpcClusterService {
+    async fn list_clusters(
+        &self,
+        _request: Request<ListClustersRequest>,
+    ) -> Result<Response<ListClustersResponse>, Status> {
+        let clusters = self.inner.list_clusters().await;
+
+        Ok(Response::new(ListClustersResponse {
+            clusters: clusters.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn add_cluster(
+        &self,
+        request: Request<AddClusterRequest>,
+    ) -> Result<Response<ClusterResponse>, Status> {
+        let req = request.into_inner();
+        if req.context.trim().is_empty() {
+            return Err(Status::invalid_argument("cluster context is required"));
+        }
+        let name = req.name.unwrap_or_else(|| req.context.clone());
+        let cluster = self
+            .inner
+            .add_cluster(name, req.context)
+            .await
+            .map_err(|e| {
+                if e.downcast_ref::<ClusterExists>().is_some() {
+                    Status::already_exists(e.to_string())
+                } else {
+                    Status::internal(e.to_string())
+                }
+            })?;
+
+        Ok(Response::new(ClusterResponse {
+            cluster: Some(cluster.into()),
+        }))
+    }
+
+    async fn remove_cluster(
+        &self,
+        request: Request<RemoveClusterRequest>,
+    ) -> Result<Response<ClusterResponse>, Status> {
+        let req = request.into_inner();
+        let cluster = self
+            .inner
+            .remove_cluster(&req.id)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| cluster_not_found(&req.id))?;
+
+        Ok(Response::new(ClusterResponse {
+            cluster: Some(cluster.into()),
+        }))
+    }
+
+    async fn get_cluster_health(
+        &self,
+        request: Request<GetClusterHealthRequest>,
+    ) -> Result<Response<ClusterResponse>, Status> {
+        let req = request.into_inner();
+        let cluster = self
+            .inner
+            .get_cluster(&req.id)
+            .await
+            .ok_or_else(|| cluster_not_found(&req.id))?;
+
+        Ok(Response::new(ClusterResponse {
+            cluster: Some(cluster.into()),
         }))
     }
 }
 
+fn cluster_not_found(id: &str) -> Status {
+    Status::not_found(format!("cluster {id} not found"))
+}
+
 pub struct GrpcServer;
 
 impl GrpcServer {
-    pub async fn serve(addr: SocketAddr, service: Arc<AnalyticsService>) -> Result<()> {
+    pub async fn serve(
+        addr: SocketAddr,
+        service: Arc<AnalyticsService>,
+        clusters: ClusterManager,
+    ) -> Result<()> {
+        let scheduler = GrpcSchedulerService::new(service.clone());
         let grpc_service = GrpcAnalyticsService::new(service);
         tonic::transport::Server::builder()
             .add_service(AnalyticsServiceServer::new(grpc_service))
+            .add_service(SchedulerServiceServer::new(scheduler))
+            .add_service(ClusterServiceServer::new(GrpcClusterService::new(clusters)))
             .serve(addr)
             .await?;
         Ok(())
@@ -215,11 +673,13 @@ impl MlClient {
             analytics::TimeRange {
                 start_ms: first.timestamp,
                 end_ms: last.timestamp,
+                expression: None,
             }
         } else {
             analytics::TimeRange {
                 start_ms: 0,
                 end_ms: 0,
+                expression: None,
             }
         };
 
@@ -268,13 +728,93 @@ impl MlClient {
                 deviation_sigma: a.deviation_sigma,
                 related_metrics: a.related_metrics,
                 root_cause: a.root_cause.filter(|s| !s.is_empty()),
+                model_id: a.model_id,
+                model_version: a.model_version,
+                kind: analytics::AnomalyKind::try_from(a.kind)
+                    .unwrap_or_default()
+                    .into(),
+                change_point: a.change_point.map(Into::into),
+                feedback: None,
+                evidence: None,
             })
             .collect())
     }
+
+    /// Rightsizing advice for `containers`, from their usage in this
+    /// service's aggregates.
+    pub async fn generate_recommendations(
+        &self,
+        cluster_id: &domain::ClusterId,
+        containers: Vec<domain::ContainerResources>,
+    ) -> Result<Vec<domain::Recommendation>> {
+        let mut client =
+            ml::ml_service_client::MlServiceClient::connect(self.endpoint.clone()).await?;
+        let request = tonic::Request::new(ml::GenerateRecommendationsRequest {
+            cluster_id: cluster_id.clone(),
+            containers: containers.into_iter().map(Into::into).collect(),
+        });
+        client
+            .generate_recommendations(request)
+            .await?
+            .into_inner()
+            .recommendations
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+}
+
+impl From<domain::ContainerResources> for ml::ContainerResources {
+    fn from(val: domain::ContainerResources) -> Self {
+        Self {
+            workload: val.workload,
+            container: val.container,
+            replicas: val.replicas,
+            cpu_request: val.cpu_request,
+            cpu_limit: val.cpu_limit,
+            memory_request: val.memory_request,
+            memory_limit: val.memory_limit,
+        }
+    }
 }
 
 // Conversions
 
+/// Convert a submitted sample, or say why it cannot be: an unset enum is a
+/// missing field, one this server does not know an invalid field.
+fn wire_sample(
+    sample: MetricSample,
+) -> Result<domain::MetricSample, (domain::RejectionReason, String)> {
+    let enums = [
+        (
+            "resource_type",
+            sample.resource_type,
+            ResourceType::try_from(sample.resource_type).is_ok(),
+        ),
+        (
+            "metric_type",
+            sample.metric_type,
+            MetricType::try_from(sample.metric_type).is_ok(),
+        ),
+    ];
+    for (field, value, known) in enums {
+        if value == 0 {
+            return Err((
+                domain::RejectionReason::MissingField,
+                format!("{field} is unset"),
+            ));
+        }
+        if !known {
+            return Err((
+                domain::RejectionReason::InvalidField,
+                format!("{field} {value} is not known"),
+            ));
+        }
+    }
+    domain::MetricSample::try_from(sample)
+        .map_err(|e| (domain::RejectionReason::InvalidField, e.to_string()))
+}
+
 impl TryFrom<MetricSample> for domain::MetricSample {
     type Error = anyhow::Error;
 
@@ -284,7 +824,7 @@ impl TryFrom<MetricSample> for domain::MetricSample {
             analytics::ResourceType::Node => domain::ResourceType::Node,
             analytics::ResourceType::Container => domain::ResourceType::Container,
             analytics::ResourceType::Service => domain::ResourceType::Service,
-            _ => domain::ResourceType::Pod,
+            analytics::ResourceType::Unspecified => anyhow::bail!("unspecified resource type"),
         };
 
         let metric_type = match MetricType::try_from(val.metric_type)? {
@@ -294,7 +834,7 @@ impl TryFrom<MetricSample> for domain::MetricSample {
             analytics::MetricType::NetworkOut => domain::MetricType::NetworkOut,
             analytics::MetricType::DiskRead => domain::MetricType::DiskRead,
             analytics::MetricType::DiskWrite => domain::MetricType::DiskWrite,
-            _ => domain::MetricType::CpuUsage,
+            analytics::MetricType::Unspecified => anyhow::bail!("unspecified metric type"),
         };
 
         Ok(domain::MetricSample {
@@ -389,6 +929,7 @@ impl TryFrom<QueryAggregatedRequest> for domain::AggregatedQuery {
                 .transpose()?
                 .map(|t| t.try_into())
                 .transpose()?,
+            resource_ids: val.resource_ids,
             metric_types: val
                 .metric_types
                 .into_iter()
@@ -399,7 +940,7 @@ impl TryFrom<QueryAggregatedRequest> for domain::AggregatedQuery {
                 })
                 .collect::<Result<_, _>>()?,
             window_duration: std::time::Duration::from_millis(val.window_duration_ms as u64),
-            time_range: val.time_range.map(Into::into),
+            time_range: val.time_range.map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -409,6 +950,7 @@ impl From<domain::AggregatedMetric> for AggregatedMetric {
         Self {
             cluster_id: val.cluster_id,
             resource_type: ResourceType::from(val.resource_type).into(),
+            resource_id: val.resource_id,
             metric_type: MetricType::from(val.metric_type).into(),
             window_start: val.window_start,
             window_duration_ms: val.window_duration.as_millis() as i64,
@@ -424,11 +966,18 @@ impl From<domain::AggregatedMetric> for AggregatedMetric {
     }
 }
 
-impl From<TimeRange> for domain::TimeRange {
-    fn from(val: TimeRange) -> Self {
-        domain::TimeRange {
-            start_ms: val.start_ms,
-            end_ms: val.end_ms,
+impl TryFrom<TimeRange> for domain::TimeRange {
+    type Error = anyhow::Error;
+
+    fn try_from(val: TimeRange) -> Result<Self, Self::Error> {
+        match val.expression.as_deref().map(str::trim) {
+            Some(expr) if !expr.is_empty() => {
+                domain::TimeRange::parse(expr, chrono::Utc::now().timestamp_millis())
+            }
+            _ => Ok(domain::TimeRange {
+                start_ms: val.start_ms,
+                end_ms: val.end_ms,
+            }),
         }
     }
 }
@@ -438,6 +987,7 @@ impl From<domain::TimeRange> for TimeRange {
         TimeRange {
             start_ms: val.start_ms,
             end_ms: val.end_ms,
+            expression: None,
         }
     }
 }
@@ -454,6 +1004,44 @@ impl From<domain::TimeSeries> for TimeSeries {
     }
 }
 
+impl TryFrom<TimeSeries> for domain::TimeSeries {
+    type Error = anyhow::Error;
+
+    fn try_from(val: TimeSeries) -> Result<Self, Self::Error> {
+        Ok(domain::TimeSeries {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type: MetricType::try_from(val.metric_type)?.try_into()?,
+            unit: val.unit,
+            points: val.points.into_iter().map(Into::into).collect(),
+            downsample: domain::DownsampleMethod::Raw,
+        })
+    }
+}
+
+impl From<domain::DownsampleMethod> for DownsampleMethod {
+    fn from(val: domain::DownsampleMethod) -> Self {
+        match val {
+            domain::DownsampleMethod::Raw => DownsampleMethod::Raw,
+            domain::DownsampleMethod::Lttb => DownsampleMethod::Lttb,
+            domain::DownsampleMethod::Rollup => DownsampleMethod::Rollup,
+        }
+    }
+}
+
+impl TryFrom<DownsampleMethod> for domain::DownsampleMethod {
+    type Error = anyhow::Error;
+
+    fn try_from(val: DownsampleMethod) -> Result<Self, Self::Error> {
+        match val {
+            DownsampleMethod::Raw => Ok(domain::DownsampleMethod::Raw),
+            DownsampleMethod::Lttb => Ok(domain::DownsampleMethod::Lttb),
+            DownsampleMethod::Rollup => Ok(domain::DownsampleMethod::Rollup),
+            DownsampleMethod::Unspecified => anyhow::bail!("unspecified downsample method"),
+        }
+    }
+}
+
 impl From<domain::TimeSeriesPoint> for TimeSeriesPoint {
     fn from(val: domain::TimeSeriesPoint) -> Self {
         Self {
@@ -463,20 +1051,31 @@ impl From<domain::TimeSeriesPoint> for TimeSeriesPoint {
     }
 }
 
-impl From<GetAnomaliesRequest> for domain::AnomalyFilter {
-    fn from(val: GetAnomaliesRequest) -> Self {
-        domain::AnomalyFilter {
-            cluster_id: val.cluster_id,
-            resource_id: val.resource_id,
-            metric_type: val
-                .metric_type
+impl From<TimeSeriesPoint> for domain::TimeSeriesPoint {
+    fn from(val: TimeSeriesPoint) -> Self {
+        domain::TimeSeriesPoint {
+            timestamp: val.timestamp,
+            value: val.value,
+        }
+    }
+}
+
+impl TryFrom<GetAnomaliesRequest> for domain::AnomalyFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(val: GetAnomaliesRequest) -> Result<Self, Self::Error> {
+        Ok(domain::AnomalyFilter {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type: val
+                .metric_type
                 .and_then(|t| MetricType::try_from(t).ok().and_then(|t| t.try_into().ok())),
             severity: val
                 .severity
                 .and_then(|s| Severity::try_from(s).ok().and_then(|s| s.try_into().ok())),
-            time_range: val.time_range.map(Into::into),
+            time_range: val.time_range.map(TryInto::try_into).transpose()?,
             limit: val.limit,
-        }
+        })
     }
 }
 
@@ -496,6 +1095,205 @@ impl From<domain::Anomaly> for Anomaly {
             deviation_sigma: val.deviation_sigma,
             related_metrics: val.related_metrics,
             root_cause: val.root_cause,
+            model_id: val.model_id,
+            model_version: val.model_version,
+            kind: AnomalyKind::from(val.kind).into(),
+            change_point: val.change_point.map(Into::into),
+            feedback: val.feedback.map(Into::into),
+            evidence: val.evidence.map(Into::into),
+        }
+    }
+}
+
+impl TryFrom<Anomaly> for domain::Anomaly {
+    type Error = anyhow::Error;
+
+    fn try_from(val: Anomaly) -> Result<Self, Self::Error> {
+        let metric_type = MetricType::try_from(val.metric_type)?.try_into()?;
+        let severity = Severity::try_from(val.severity)?.try_into()?;
+        Ok(domain::Anomaly {
+            id: val.id,
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            detected_at: val.detected_at,
+            metric_type,
+            severity,
+            confidence: val.confidence,
+            description: val.description,
+            baseline_value: val.baseline_value,
+            observed_value: val.observed_value,
+            deviation_sigma: val.deviation_sigma,
+            related_metrics: val.related_metrics,
+            root_cause: val.root_cause,
+            model_id: val.model_id,
+            model_version: val.model_version,
+            kind: AnomalyKind::try_from(val.kind)?.into(),
+            change_point: val.change_point.map(Into::into),
+            feedback: val.feedback.map(TryInto::try_into).transpose()?,
+            evidence: val.evidence.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<AnomalyEvidence> for domain::AnomalyEvidence {
+    type Error = anyhow::Error;
+
+    fn try_from(val: AnomalyEvidence) -> Result<Self, Self::Error> {
+        Ok(Self {
+            baseline_window: val
+                .baseline_window
+                .ok_or_else(|| anyhow::anyhow!("evidence without a baseline window"))?
+                .try_into()?,
+            expected_low: val.expected_low,
+            expected_high: val.expected_high,
+            seasonal_expected: val.seasonal_expected,
+            points: val.points.into_iter().map(Into::into).collect(),
+            contributions: val.contributions.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl From<domain::AnomalyEvidence> for AnomalyEvidence {
+    fn from(val: domain::AnomalyEvidence) -> Self {
+        Self {
+            baseline_window: Some(val.baseline_window.into()),
+            expected_low: val.expected_low,
+            expected_high: val.expected_high,
+            seasonal_expected: val.seasonal_expected,
+            points: val.points.into_iter().map(Into::into).collect(),
+            contributions: val.contributions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<FeatureContribution> for domain::FeatureContribution {
+    fn from(val: FeatureContribution) -> Self {
+        Self {
+            feature: val.feature,
+            value: val.value,
+            expected: val.expected,
+            share: val.share,
+        }
+    }
+}
+
+impl From<domain::FeatureContribution> for FeatureContribution {
+    fn from(val: domain::FeatureContribution) -> Self {
+        Self {
+            feature: val.feature,
+            value: val.value,
+            expected: val.expected,
+            share: val.share,
+        }
+    }
+}
+
+impl TryFrom<AnomalyFeedback> for domain::AnomalyFeedback {
+    type Error = anyhow::Error;
+
+    fn try_from(val: AnomalyFeedback) -> Result<Self, Self::Error> {
+        Ok(Self {
+            kind: AnomalyFeedbackKind::try_from(val.kind)?.try_into()?,
+            submitted_at: val.submitted_at,
+            note: val.note,
+        })
+    }
+}
+
+impl From<domain::AnomalyFeedback> for AnomalyFeedback {
+    fn from(val: domain::AnomalyFeedback) -> Self {
+        Self {
+            kind: AnomalyFeedbackKind::from(val.kind).into(),
+            submitted_at: val.submitted_at,
+            note: val.note,
+        }
+    }
+}
+
+impl TryFrom<AnomalyFeedbackKind> for domain::AnomalyFeedbackKind {
+    type Error = anyhow::Error;
+
+    fn try_from(val: AnomalyFeedbackKind) -> Result<Self, Self::Error> {
+        match val {
+            AnomalyFeedbackKind::Confirmed => Ok(domain::AnomalyFeedbackKind::Confirmed),
+            AnomalyFeedbackKind::FalsePositive => Ok(domain::AnomalyFeedbackKind::FalsePositive),
+            AnomalyFeedbackKind::Expected => Ok(domain::AnomalyFeedbackKind::Expected),
+            AnomalyFeedbackKind::Unspecified => anyhow::bail!("unspecified feedback kind"),
+        }
+    }
+}
+
+impl From<domain::AnomalyFeedbackKind> for AnomalyFeedbackKind {
+    fn from(val: domain::AnomalyFeedbackKind) -> Self {
+        match val {
+            domain::AnomalyFeedbackKind::Confirmed => AnomalyFeedbackKind::Confirmed,
+            domain::AnomalyFeedbackKind::FalsePositive => AnomalyFeedbackKind::FalsePositive,
+            domain::AnomalyFeedbackKind::Expected => AnomalyFeedbackKind::Expected,
+        }
+    }
+}
+
+impl TryFrom<GetAnomalyFeedbackRequest> for domain::AnomalyFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(val: GetAnomalyFeedbackRequest) -> Result<Self, Self::Error> {
+        Ok(domain::AnomalyFilter {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type: val
+                .metric_type
+                .and_then(|t| MetricType::try_from(t).ok().and_then(|t| t.try_into().ok())),
+            severity: None,
+            time_range: val.time_range.map(TryInto::try_into).transpose()?,
+            limit: val.limit,
+        })
+    }
+}
+
+impl From<AnomalyKind> for domain::AnomalyKind {
+    fn from(val: AnomalyKind) -> Self {
+        match val {
+            AnomalyKind::Unspecified | AnomalyKind::Spike => domain::AnomalyKind::Spike,
+            AnomalyKind::LevelShift => domain::AnomalyKind::LevelShift,
+            AnomalyKind::TrendChange => domain::AnomalyKind::TrendChange,
+        }
+    }
+}
+
+impl From<domain::AnomalyKind> for AnomalyKind {
+    fn from(val: domain::AnomalyKind) -> Self {
+        match val {
+            domain::AnomalyKind::Spike => AnomalyKind::Spike,
+            domain::AnomalyKind::LevelShift => AnomalyKind::LevelShift,
+            domain::AnomalyKind::TrendChange => AnomalyKind::TrendChange,
+        }
+    }
+}
+
+impl From<ChangePoint> for domain::ChangePoint {
+    fn from(val: ChangePoint) -> Self {
+        Self {
+            changed_at: val.changed_at,
+            before_mean: val.before_mean,
+            after_mean: val.after_mean,
+            before_stddev: val.before_stddev,
+            after_stddev: val.after_stddev,
+            before_slope: val.before_slope,
+            after_slope: val.after_slope,
+        }
+    }
+}
+
+impl From<domain::ChangePoint> for ChangePoint {
+    fn from(val: domain::ChangePoint) -> Self {
+        Self {
+            changed_at: val.changed_at,
+            before_mean: val.before_mean,
+            after_mean: val.after_mean,
+            before_stddev: val.before_stddev,
+            after_stddev: val.after_stddev,
+            before_slope: val.before_slope,
+            after_slope: val.after_slope,
         }
     }
 }
@@ -556,35 +1354,7 @@ impl From<domain::Recommendation> for Recommendation {
                 daily_change: c.daily_change,
                 currency: c.currency,
             }),
-            action: Some(match val.action {
-                domain::RecommendationAction::ScaleDeployment { name, from, to } => {
-                    RecommendationAction {
-                        action: Some(recommendation_action::Action::ScaleDeployment(
-                            ScaleDeploymentAction { name, from, to },
-                        )),
-                    }
-                }
-                domain::RecommendationAction::UpdateResourceLimits { resource, limits } => {
-                    RecommendationAction {
-                        action: Some(recommendation_action::Action::UpdateLimits(
-                            UpdateResourceLimitsAction {
-                                resource,
-                                limits: Some(ResourceLimits {
-                                    cpu: limits.cpu,
-                                    memory: limits.memory,
-                                }),
-                            },
-                        )),
-                    }
-                }
-                domain::RecommendationAction::ReclaimStorage { volume, size_gb } => {
-                    RecommendationAction {
-                        action: Some(recommendation_action::Action::ReclaimStorage(
-                            ReclaimStorageAction { volume, size_gb },
-                        )),
-                    }
-                }
-            }),
+            action: Some(val.action.into()),
             status: Some(match val.status {
                 domain::RecommendationStatus::Pending => RecommendationStatus {
                     status: Some(recommendation_status::Status::Pending(true)),
@@ -603,6 +1373,117 @@ impl From<domain::Recommendation> for Recommendation {
     }
 }
 
+impl TryFrom<Recommendation> for domain::Recommendation {
+    type Error = anyhow::Error;
+
+    fn try_from(val: Recommendation) -> Result<Self, Self::Error> {
+        let status = match val.status.and_then(|status| status.status) {
+            Some(recommendation_status::Status::ScheduledAt(execute_at)) => {
+                domain::RecommendationStatus::Scheduled { execute_at }
+            }
+            Some(recommendation_status::Status::AppliedAt(applied_at)) => {
+                domain::RecommendationStatus::Applied { applied_at }
+            }
+            Some(recommendation_status::Status::DismissedReason(reason)) => {
+                domain::RecommendationStatus::Dismissed { reason }
+            }
+            Some(recommendation_status::Status::Pending(_)) | None => {
+                domain::RecommendationStatus::Pending
+            }
+        };
+        Ok(domain::Recommendation {
+            id: val.id,
+            cluster_id: val.cluster_id,
+            created_at: val.created_at,
+            recommendation_type: RecommendationType::try_from(val.recommendation_type)?
+                .try_into()?,
+            priority: Priority::try_from(val.priority)?.try_into()?,
+            confidence: val.confidence,
+            title: val.title,
+            description: val.description,
+            impact_estimate: val.impact_estimate,
+            cost_impact: val.cost_impact.map(|c| domain::CostImpact {
+                daily_change: c.daily_change,
+                currency: c.currency,
+            }),
+            action: val
+                .action
+                .ok_or_else(|| anyhow::anyhow!("missing recommendation action"))?
+                .try_into()?,
+            status,
+        })
+    }
+}
+
+impl From<domain::RecommendationAction> for RecommendationAction {
+    fn from(val: domain::RecommendationAction) -> Self {
+        let action = match val {
+            domain::RecommendationAction::ScaleDeployment { name, from, to } => {
+                recommendation_action::Action::ScaleDeployment(ScaleDeploymentAction {
+                    name,
+                    from,
+                    to,
+                })
+            }
+            domain::RecommendationAction::UpdateResourceLimits { resource, limits } => {
+                recommendation_action::Action::UpdateLimits(UpdateResourceLimitsAction {
+                    resource,
+                    limits: Some(ResourceLimits {
+                        cpu: limits.cpu,
+                        memory: limits.memory,
+                        cpu_request: limits.cpu_request,
+                        memory_request: limits.memory_request,
+                    }),
+                })
+            }
+            domain::RecommendationAction::ReclaimStorage { volume, size_gb } => {
+                recommendation_action::Action::ReclaimStorage(ReclaimStorageAction {
+                    volume,
+                    size_gb,
+                })
+            }
+        };
+        Self {
+            action: Some(action),
+        }
+    }
+}
+
+impl TryFrom<RecommendationAction> for domain::RecommendationAction {
+    type Error = anyhow::Error;
+
+    fn try_from(val: RecommendationAction) -> Result<Self, Self::Error> {
+        match val.action {
+            Some(recommendation_action::Action::ScaleDeployment(s)) => {
+                Ok(domain::RecommendationAction::ScaleDeployment {
+                    name: s.name,
+                    from: s.from,
+                    to: s.to,
+                })
+            }
+            Some(recommendation_action::Action::UpdateLimits(u)) => {
+                let limits = u.limits.unwrap_or_default();
+                Ok(domain::RecommendationAction::UpdateResourceLimits {
+                    resource: u.resource,
+                    limits: domain::ResourceLimits {
+                        cpu: limits.cpu,
+                        memory: limits.memory,
+                        cpu_request: limits.cpu_request,
+                        memory_request: limits.memory_request,
+                    },
+                })
+            }
+            Some(recommendation_action::Action::ReclaimStorage(r)) => {
+                Ok(domain::RecommendationAction::ReclaimStorage {
+                    volume: r.volume,
+                    size_gb: r.size_gb,
+                })
+            }
+            None => anyhow::bail!("missing recommendation action"),
+        }
+    }
+}
+
 impl TryFrom<Priority> for domain::Priority {
     type Error = anyhow::Error;
 
@@ -675,8 +1556,205 @@ impl TryFrom<RecommendationStatusKind> for domain::RecommendationStatusKind {
     }
 }
 
-impl From<QueryMetricsRequest> for domain::MetricsQuery {
-    fn from(val: QueryMetricsRequest) -> Self {
+impl From<ListScheduledActionsRequest> for domain::ScheduleFilter {
+    fn from(val: ListScheduledActionsRequest) -> Self {
+        domain::ScheduleFilter {
+            recommendation_id: val.recommendation_id,
+            status: val.status.and_then(|s| {
+                ScheduleStatusKind::try_from(s)
+                    .ok()
+                    .and_then(|s| s.try_into().ok())
+            }),
+            limit: val.limit,
+        }
+    }
+}
+
+impl From<GetExecutionHistoryRequest> for domain::ScheduleExecutionFilter {
+    fn from(val: GetExecutionHistoryRequest) -> Self {
+        domain::ScheduleExecutionFilter {
+            schedule_id: val.schedule_id,
+            recommendation_id: val.recommendation_id,
+            limit: val.limit,
+        }
+    }
+}
+
+impl From<domain::ScheduledAction> for ScheduledAction {
+    fn from(val: domain::ScheduledAction) -> Self {
+        Self {
+            id: val.id,
+            execute_at: val.execute_at,
+            recommendation_id: val.recommendation_id,
+            action: Some(val.action.into()),
+            status: ScheduleStatusKind::from(val.status.kind()).into(),
+            error: match val.status {
+                domain::ScheduleStatus::Failed { error } => Some(error),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl TryFrom<ScheduledAction> for domain::ScheduledAction {
+    type Error = anyhow::Error;
+
+    fn try_from(val: ScheduledAction) -> Result<Self, Self::Error> {
+        let status = match ScheduleStatusKind::try_from(val.status)? {
+            ScheduleStatusKind::Pending => domain::ScheduleStatus::Pending,
+            ScheduleStatusKind::Executing => domain::ScheduleStatus::Executing,
+            ScheduleStatusKind::Completed => domain::ScheduleStatus::Completed,
+            ScheduleStatusKind::Failed => domain::ScheduleStatus::Failed {
+                error: val.error.unwrap_or_default(),
+            },
+            ScheduleStatusKind::Cancelled => domain::ScheduleStatus::Cancelled,
+            ScheduleStatusKind::Unspecified => anyhow::bail!("unspecified schedule status"),
+        };
+        Ok(domain::ScheduledAction {
+            id: val.id,
+            execute_at: val.execute_at,
+            recommendation_id: val.recommendation_id,
+            action: val
+                .action
+                .ok_or_else(|| anyhow::anyhow!("missing scheduled action"))?
+                .try_into()?,
+            status,
+        })
+    }
+}
+
+impl TryFrom<ScheduleStatusKind> for domain::ScheduleStatusKind {
+    type Error = anyhow::Error;
+
+    fn try_from(val: ScheduleStatusKind) -> Result<Self, Self::Error> {
+        match val {
+            ScheduleStatusKind::Pending => Ok(domain::ScheduleStatusKind::Pending),
+            ScheduleStatusKind::Executing => Ok(domain::ScheduleStatusKind::Executing),
+            ScheduleStatusKind::Completed => Ok(domain::ScheduleStatusKind::Completed),
+            ScheduleStatusKind::Failed => Ok(domain::ScheduleStatusKind::Failed),
+            ScheduleStatusKind::Cancelled => Ok(domain::ScheduleStatusKind::Cancelled),
+            ScheduleStatusKind::Unspecified => anyhow::bail!("unspecified schedule status"),
+        }
+    }
+}
+
+impl From<domain::ScheduleStatusKind> for ScheduleStatusKind {
+    fn from(val: domain::ScheduleStatusKind) -> Self {
+        match val {
+            domain::ScheduleStatusKind::Pending => ScheduleStatusKind::Pending,
+            domain::ScheduleStatusKind::Executing => ScheduleStatusKind::Executing,
+            domain::ScheduleStatusKind::Completed => ScheduleStatusKind::Completed,
+            domain::ScheduleStatusKind::Failed => ScheduleStatusKind::Failed,
+            domain::ScheduleStatusKind::Cancelled => ScheduleStatusKind::Cancelled,
+        }
+    }
+}
+
+impl From<domain::ScheduleExecution> for ScheduleExecution {
+    fn from(val: domain::ScheduleExecution) -> Self {
+        Self {
+            schedule_id: val.schedule_id,
+            recommendation_id: val.recommendation_id,
+            started_at: val.started_at,
+            finished_at: val.finished_at,
+            error: val.error,
+        }
+    }
+}
+
+impl From<ScheduleExecution> for domain::ScheduleExecution {
+    fn from(val: ScheduleExecution) -> Self {
+        Self {
+            schedule_id: val.schedule_id,
+            recommendation_id: val.recommendation_id,
+            started_at: val.started_at,
+            finished_at: val.finished_at,
+            error: val.error,
+        }
+    }
+}
+
+impl From<domain::ClusterMetadata> for ClusterMetadata {
+    fn from(val: domain::ClusterMetadata) -> Self {
+        Self {
+            id: val.id,
+            name: val.name,
+            context: val.context,
+            api_server: val.api_server,
+            health_status: ClusterHealth::from(val.health_status).into(),
+            last_seen: val.last_seen,
+            pod_count: val.pod_count,
+            node_count: val.node_count,
+            namespace_count: val.namespace_count,
+        }
+    }
+}
+
+impl TryFrom<ClusterMetadata> for domain::ClusterMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(val: ClusterMetadata) -> Result<Self, Self::Error> {
+        Ok(domain::ClusterMetadata {
+            health_status: ClusterHealth::try_from(val.health_status)?.try_into()?,
+            id: val.id,
+            name: val.name,
+            context: val.context,
+            api_server: val.api_server,
+            last_seen: val.last_seen,
+            pod_count: val.pod_count,
+            node_count: val.node_count,
+            namespace_count: val.namespace_count,
+        })
+    }
+}
+
+impl TryFrom<ClusterHealth> for domain::ClusterHealth {
+    type Error = anyhow::Error;
+
+    fn try_from(val: ClusterHealth) -> Result<Self, Self::Error> {
+        match val {
+            ClusterHealth::Healthy => Ok(domain::ClusterHealth::Healthy),
+            ClusterHealth::Degraded => Ok(domain::ClusterHealth::Degraded),
+            ClusterHealth::Unreachable => Ok(domain::ClusterHealth::Unreachable),
+            ClusterHealth::Unspecified => anyhow::bail!("unspecified cluster health"),
+        }
+    }
+}
+
+impl From<domain::ClusterHealth> for ClusterHealth {
+    fn from(val: domain::ClusterHealth) -> Self {
+        match val {
+            domain::ClusterHealth::Healthy => ClusterHealth::Healthy,
+            domain::ClusterHealth::Degraded => ClusterHealth::Degraded,
+            domain::ClusterHealth::Unreachable => ClusterHealth::Unreachable,
+        }
+    }
+}
+
+impl TryFrom<QueryMetricsRequest> for domain::MetricsQuery {
+    type Error = anyhow::Error;
+
+    fn try_from(val: QueryMetricsRequest) -> Result<Self, Self::Error> {
+        Ok(domain::MetricsQuery {
+            cluster_id: val.cluster_id,
+            resource_type: val.resource_type.and_then(|t| {
+                ResourceType::try_from(t)
+                    .ok()
+                    .and_then(|t| t.try_into().ok())
+            }),
+            resource_ids: val.resource_ids,
+            metric_types: val
+                .metric_types
+                .into_iter()
+                .filter_map(|t| MetricType::try_from(t).ok().and_then(|t| t.try_into().ok()))
+                .collect(),
+            time_range: val.time_range.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl From<WatchMetricsRequest> for domain::MetricsQuery {
+    fn from(val: WatchMetricsRequest) -> Self {
         domain::MetricsQuery {
             cluster_id: val.cluster_id,
             resource_type: val.resource_type.and_then(|t| {
@@ -690,7 +1768,317 @@ impl From<QueryMetricsRequest> for domain::MetricsQuery {
                 .into_iter()
                 .filter_map(|t| MetricType::try_from(t).ok().and_then(|t| t.try_into().ok()))
                 .collect(),
-            time_range: val.time_range.map(Into::into),
+            time_range: None,
+        }
+    }
+}
+
+impl From<WatchAnomaliesRequest> for domain::AnomalyFilter {
+    fn from(val: WatchAnomaliesRequest) -> Self {
+        domain::AnomalyFilter {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type: val
+                .metric_type
+                .and_then(|t| MetricType::try_from(t).ok().and_then(|t| t.try_into().ok())),
+            severity: val
+                .severity
+                .and_then(|s| Severity::try_from(s).ok().and_then(|s| s.try_into().ok())),
+            time_range: None,
+            limit: None,
+        }
+    }
+}
+
+impl From<WatchRecommendationsRequest> for domain::RecommendationFilter {
+    fn from(val: WatchRecommendationsRequest) -> Self {
+        domain::RecommendationFilter {
+            cluster_id: val.cluster_id,
+            priority: val
+                .priority
+                .and_then(|p| Priority::try_from(p).ok().and_then(|p| p.try_into().ok())),
+            status: val.status.and_then(|s| {
+                RecommendationStatusKind::try_from(s)
+                    .ok()
+                    .and_then(|s| s.try_into().ok())
+            }),
+            limit: None,
+        }
+    }
+}
+
+impl From<FeedBatch<domain::MetricSample>> for WatchMetricsResponse {
+    fn from(val: FeedBatch<domain::MetricSample>) -> Self {
+        Self {
+            cursor: val.cursor,
+            samples: val.items.into_iter().map(Into::into).collect(),
+            reset: val.reset,
+        }
+    }
+}
+
+impl From<FeedBatch<domain::Anomaly>> for WatchAnomaliesResponse {
+    fn from(val: FeedBatch<domain::Anomaly>) -> Self {
+        Self {
+            cursor: val.cursor,
+            anomalies: val.items.into_iter().map(Into::into).collect(),
+            reset: val.reset,
+        }
+    }
+}
+
+impl From<FeedBatch<domain::Recommendation>> for WatchRecommendationsResponse {
+    fn from(val: FeedBatch<domain::Recommendation>) -> Self {
+        Self {
+            cursor: val.cursor,
+            recommendations: val.items.into_iter().map(Into::into).collect(),
+            reset: val.reset,
+        }
+    }
+}
+
+impl From<GetDataQualityRequest> for domain::DataQualityFilter {
+    fn from(val: GetDataQualityRequest) -> Self {
+        domain::DataQualityFilter {
+            cluster_id: val.cluster_id,
+            kind: val.kind.and_then(|k| {
+                DataQualityIssueKind::try_from(k)
+                    .ok()
+                    .and_then(|k| k.try_into().ok())
+            }),
+            limit: val.limit,
+        }
+    }
+}
+
+impl From<domain::DataQualityIssue> for DataQualityIssue {
+    fn from(val: domain::DataQualityIssue) -> Self {
+        Self {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type: val.metric_type.map(|t| MetricType::from(t).into()),
+            kind: DataQualityIssueKind::from(val.kind).into(),
+            severity: Severity::from(val.severity).into(),
+            detected_at: val.detected_at,
+            last_sample_at: val.last_sample_at,
+            expected_interval_ms: val.expected_interval_ms,
+            message: val.message,
+        }
+    }
+}
+
+impl TryFrom<DataQualityIssue> for domain::DataQualityIssue {
+    type Error = anyhow::Error;
+
+    fn try_from(val: DataQualityIssue) -> Result<Self, Self::Error> {
+        let metric_type = match val.metric_type {
+            Some(t) => Some(MetricType::try_from(t)?.try_into()?),
+            None => None,
+        };
+        let kind = val.kind().try_into()?;
+        let severity = val.severity().try_into()?;
+        Ok(domain::DataQualityIssue {
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            metric_type,
+            kind,
+            severity,
+            detected_at: val.detected_at,
+            last_sample_at: val.last_sample_at,
+            expected_interval_ms: val.expected_interval_ms,
+            message: val.message,
+        })
+    }
+}
+
+impl TryFrom<DataQualityIssueKind> for domain::DataQualityIssueKind {
+    type Error = anyhow::Error;
+
+    fn try_from(val: DataQualityIssueKind) -> Result<Self, Self::Error> {
+        match val {
+            DataQualityIssueKind::Gap => Ok(domain::DataQualityIssueKind::Gap),
+            DataQualityIssueKind::Stale => Ok(domain::DataQualityIssueKind::Stale),
+            DataQualityIssueKind::DuplicateTimestamp => {
+                Ok(domain::DataQualityIssueKind::DuplicateTimestamp)
+            }
+            DataQualityIssueKind::OutOfOrder => Ok(domain::DataQualityIssueKind::OutOfOrder),
+            DataQualityIssueKind::FutureSample => Ok(domain::DataQualityIssueKind::FutureSample),
+            DataQualityIssueKind::Unspecified => anyhow::bail!("unspecified data quality kind"),
+        }
+    }
+}
+
+impl From<domain::DataQualityIssueKind> for DataQualityIssueKind {
+    fn from(val: domain::DataQualityIssueKind) -> Self {
+        match val {
+            domain::DataQualityIssueKind::Gap => DataQualityIssueKind::Gap,
+            domain::DataQualityIssueKind::Stale => DataQualityIssueKind::Stale,
+            domain::DataQualityIssueKind::DuplicateTimestamp => {
+                DataQualityIssueKind::DuplicateTimestamp
+            }
+            domain::DataQualityIssueKind::OutOfOrder => DataQualityIssueKind::OutOfOrder,
+            domain::DataQualityIssueKind::FutureSample => DataQualityIssueKind::FutureSample,
+        }
+    }
+}
+
+impl TryFrom<GetIncidentsRequest> for domain::IncidentFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(val: GetIncidentsRequest) -> Result<Self, Self::Error> {
+        Ok(domain::IncidentFilter {
+            cluster_id: val.cluster_id,
+            status: val.status.and_then(|s| {
+                IncidentStatus::try_from(s)
+                    .ok()
+                    .and_then(|s| s.try_into().ok())
+            }),
+            resource_id: val.resource_id,
+            time_range: val.time_range.map(TryInto::try_into).transpose()?,
+            limit: val.limit,
+        })
+    }
+}
+
+impl From<domain::Incident> for Incident {
+    fn from(val: domain::Incident) -> Self {
+        Self {
+            id: val.id,
+            cluster_id: val.cluster_id,
+            status: IncidentStatus::from(val.status).into(),
+            severity: Severity::from(val.severity).into(),
+            title: val.title,
+            opened_at: val.opened_at,
+            updated_at: val.updated_at,
+            resolved_at: val.resolved_at,
+            resources: val.resources,
+            anomaly_ids: val.anomaly_ids,
+            timeline: val.timeline.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<Incident> for domain::Incident {
+    type Error = anyhow::Error;
+
+    fn try_from(val: Incident) -> Result<Self, Self::Error> {
+        let status = val.status().try_into()?;
+        let severity = val.severity().try_into()?;
+        Ok(domain::Incident {
+            id: val.id,
+            cluster_id: val.cluster_id,
+            status,
+            severity,
+            title: val.title,
+            opened_at: val.opened_at,
+            updated_at: val.updated_at,
+            resolved_at: val.resolved_at,
+            resources: val.resources,
+            anomaly_ids: val.anomaly_ids,
+            timeline: val
+                .timeline
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<domain::IncidentEvent> for IncidentEvent {
+    fn from(val: domain::IncidentEvent) -> Self {
+        Self {
+            timestamp: val.timestamp,
+            kind: IncidentEventKind::from(val.kind).into(),
+            severity: Severity::from(val.severity).into(),
+            resource_id: val.resource_id,
+            anomaly_id: val.anomaly_id,
+            message: val.message,
+        }
+    }
+}
+
+impl TryFrom<IncidentEvent> for domain::IncidentEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(val: IncidentEvent) -> Result<Self, Self::Error> {
+        let kind = val.kind().try_into()?;
+        let severity = val.severity().try_into()?;
+        Ok(domain::IncidentEvent {
+            timestamp: val.timestamp,
+            kind,
+            severity,
+            resource_id: val.resource_id,
+            anomaly_id: val.anomaly_id,
+            message: val.message,
+        })
+    }
+}
+
+impl TryFrom<IncidentStatus> for domain::IncidentStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(val: IncidentStatus) -> Result<Self, Self::Error> {
+        match val {
+            IncidentStatus::Open => Ok(domain::IncidentStatus::Open),
+            IncidentStatus::Resolved => Ok(domain::IncidentStatus::Resolved),
+            IncidentStatus::Unspecified => anyhow::bail!("unspecified incident status"),
+        }
+    }
+}
+
+impl From<domain::IncidentStatus> for IncidentStatus {
+    fn from(val: domain::IncidentStatus) -> Self {
+        match val {
+            domain::IncidentStatus::Open => IncidentStatus::Open,
+            domain::IncidentStatus::Resolved => IncidentStatus::Resolved,
+        }
+    }
+}
+
+impl TryFrom<IncidentEventKind> for domain::IncidentEventKind {
+    type Error = anyhow::Error;
+
+    fn try_from(val: IncidentEventKind) -> Result<Self, Self::Error> {
+        match val {
+            IncidentEventKind::Opened => Ok(domain::IncidentEventKind::Opened),
+            IncidentEventKind::Anomaly => Ok(domain::IncidentEventKind::Anomaly),
+            IncidentEventKind::Escalated => Ok(domain::IncidentEventKind::Escalated),
+            IncidentEventKind::Resolved => Ok(domain::IncidentEventKind::Resolved),
+            IncidentEventKind::Unspecified => anyhow::bail!("unspecified incident event kind"),
+        }
+    }
+}
+
+impl From<domain::IncidentEventKind> for IncidentEventKind {
+    fn from(val: domain::IncidentEventKind) -> Self {
+        match val {
+            domain::IncidentEventKind::Opened => IncidentEventKind::Opened,
+            domain::IncidentEventKind::Anomaly => IncidentEventKind::Anomaly,
+            domain::IncidentEventKind::Escalated => IncidentEventKind::Escalated,
+            domain::IncidentEventKind::Resolved => IncidentEventKind::Resolved,
+        }
+    }
+}
+
+impl From<domain::SampleRejection> for SampleRejection {
+    fn from(val: domain::SampleRejection) -> Self {
+        Self {
+            index: val.index as u32,
+            reason: RejectionReason::from(val.reason).into(),
+            message: val.message,
+        }
+    }
+}
+
+impl From<domain::RejectionReason> for RejectionReason {
+    fn from(val: domain::RejectionReason) -> Self {
+        match val {
+            domain::RejectionReason::MissingField => RejectionReason::MissingField,
+            domain::RejectionReason::NonFiniteValue => RejectionReason::NonFiniteValue,
+            domain::RejectionReason::NegativeValue => RejectionReason::NegativeValue,
+            domain::RejectionReason::TimestampSkew => RejectionReason::TimestampSkew,
+            domain::RejectionReason::UnknownUnit => RejectionReason::UnknownUnit,
+            domain::RejectionReason::InvalidField => RejectionReason::InvalidField,
         }
     }
 }
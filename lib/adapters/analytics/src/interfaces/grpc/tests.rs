@@ -0,0 +1,29 @@
+use phenome_domain::RejectionReason;
+
+use super::analytics::{MetricSample, MetricType, ResourceType};
+use super::wire_sample;
+
+fn sample(resource_type: i32, metric_type: i32) -> MetricSample {
+    MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type,
+        resource_id: "shop/api-0".to_string(),
+        metric_type,
+        timestamp: 1_000,
+        value: 0.5,
+        unit: "cores".to_string(),
+    }
+}
+
+#[test]
+fn wire_samples_are_rejected_for_unset_or_unknown_enums() {
+    let pod = ResourceType::Pod as i32;
+    let cpu = MetricType::CpuUsage as i32;
+
+    assert!(wire_sample(sample(pod, cpu)).is_ok());
+    let reason = |sample| wire_sample(sample).unwrap_err().0;
+    assert_eq!(reason(sample(0, cpu)), RejectionReason::MissingField);
+    assert_eq!(reason(sample(pod, 0)), RejectionReason::MissingField);
+    assert_eq!(reason(sample(99, cpu)), RejectionReason::InvalidField);
+    assert_eq!(reason(sample(pod, 99)), RejectionReason::InvalidField);
+}
@@ -1,15 +1,19 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tokio::sync::watch;
 use tokio::time::interval;
 
-use phenome_domain::{Notification, NotificationChannel};
+use phenome_domain::{
+    DataQualityFilter, Incident, IncidentFilter, Notification, NotificationChannel, Severity,
+    TimeRange,
+};
 use phenome_ports::{AnalyticsPort, NotificationPort};
 
 const ANOMALY_POLL_INTERVAL: Duration = Duration::from_secs(60);
-const MAX_ANOMALIES_PER_TICK: usize = 50;
+const MAX_INCIDENT_NOTIFICATIONS_PER_TICK: usize = 50;
 
 #[derive(Debug, Clone, Default)]
 pub struct NotificationService {
@@ -71,6 +75,9 @@ impl NotificationService {
     ) {
         let mut interval = interval(ANOMALY_POLL_INTERVAL);
         let mut last_check = chrono::Utc::now().timestamp_millis();
+        let mut notified_incidents = HashMap::new();
+        let mut unsent_incidents = Vec::new();
+        let mut active_quality_issues = HashSet::new();
 
         loop {
             tokio::select! {
@@ -82,51 +89,163 @@ impl NotificationService {
                 _ = interval.tick() => {
                     let now = chrono::Utc::now().timestamp_millis();
 
-                    // Query anomalies detected since last check
-                    let filter = phenome_domain::AnomalyFilter {
-                        time_range: Some(phenome_domain::TimeRange {
-                            start_ms: last_check,
-                            end_ms: now,
-                        }),
-                        ..Default::default()
-                    };
-
-                    match service.get_anomalies(filter).await {
-                        Ok(mut anomalies) => {
-                            if anomalies.len() > MAX_ANOMALIES_PER_TICK {
-                                tracing::warn!(
-                                    "Anomaly notifications capped at {} per tick",
-                                    MAX_ANOMALIES_PER_TICK
-                                );
-                                anomalies.truncate(MAX_ANOMALIES_PER_TICK);
-                            }
-
-                            for anomaly in anomalies {
-                                let notification = phenome_domain::Notification {
-                                    id: uuid::Uuid::new_v4().to_string(),
-                                    title: format!("Anomaly Detected: {:?}", anomaly.metric_type),
-                                    message: anomaly.description.clone(),
-                                    severity: anomaly.severity,
-                                    timestamp: now,
-                                    read: false,
-                                    link: None,
-                                    cluster_id: Some(anomaly.cluster_id.clone()),
-                                    resource_id: Some(anomaly.resource_id.clone()),
-                                };
-
-                                if let Err(e) = self.send_notification(notification).await {
-                                    tracing::error!("Failed to send anomaly notification: {}", e);
-                                }
-                            }
-                        }
-                        Err(err) => {
-                            tracing::error!("Failed to query anomalies: {}", err);
-                        }
+                    // Resolve quiet incidents first so their resolution falls
+                    // inside this tick's window.
+                    if let Err(e) = service.sweep_incidents(now).await {
+                        tracing::error!("Failed to resolve quiet incidents: {}", e);
                     }
+                    self.notify_incidents(
+                        &service,
+                        &mut notified_incidents,
+                        &mut unsent_incidents,
+                        last_check,
+                        now,
+                    )
+                    .await;
+                    self.notify_data_quality(&service, &mut active_quality_issues, now)
+                        .await;
 
                     last_check = now;
                 }
             }
         }
     }
+
+    /// Notify when an incident opens, escalates or resolves rather than for
+    /// every anomaly in it. `notified` holds the severity last reported for
+    /// each open incident; `unsent` holds incidents past the per-tick cap,
+    /// retried before the next tick's.
+    async fn notify_incidents(
+        &self,
+        service: &crate::AnalyticsService,
+        notified: &mut HashMap<String, Severity>,
+        unsent: &mut Vec<Incident>,
+        last_check: i64,
+        now: i64,
+    ) {
+        let filter = IncidentFilter {
+            time_range: Some(TimeRange {
+                start_ms: last_check,
+                end_ms: now,
+            }),
+            ..Default::default()
+        };
+        let fresh = match service.get_incidents(filter).await {
+            Ok(incidents) => incidents,
+            Err(err) => {
+                tracing::error!("Failed to query incidents: {}", err);
+                return;
+            }
+        };
+        // A carried-over incident that changed again is taken as it is now.
+        let mut incidents = std::mem::take(unsent);
+        incidents.retain(|held| !fresh.iter().any(|incident| incident.id == held.id));
+        incidents.extend(fresh);
+
+        let mut sent = 0;
+        for incident in incidents {
+            let reported = notified.get(&incident.id).copied();
+            let (title, severity) = match (incident.is_open(), reported) {
+                (true, None) => (
+                    format!("Incident Opened: {}", incident.title),
+                    incident.severity,
+                ),
+                (true, Some(reported)) if incident.severity.rank() > reported.rank() => (
+                    format!("Incident Escalated: {}", incident.title),
+                    incident.severity,
+                ),
+                (false, Some(_)) => (
+                    format!("Incident Resolved: {}", incident.title),
+                    Severity::Info,
+                ),
+                _ => continue,
+            };
+            if sent == MAX_INCIDENT_NOTIFICATIONS_PER_TICK {
+                unsent.push(incident);
+                continue;
+            }
+            sent += 1;
+
+            let notification = Notification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title,
+                message: incident_summary(&incident),
+                severity,
+                timestamp: now,
+                read: false,
+                link: None,
+                cluster_id: Some(incident.cluster_id.clone()),
+                resource_id: incident.resources.first().cloned(),
+            };
+            if let Err(e) = self.send_notification(notification).await {
+                tracing::error!("Failed to send incident notification: {}", e);
+            }
+            if incident.is_open() {
+                notified.insert(incident.id, incident.severity);
+            } else {
+                notified.remove(&incident.id);
+            }
+        }
+        if !unsent.is_empty() {
+            tracing::warn!(
+                "Incident notifications capped at {} per tick; {} carried over",
+                MAX_INCIDENT_NOTIFICATIONS_PER_TICK,
+                unsent.len()
+            );
+        }
+    }
+
+    /// Notify once per data-quality issue while it stays active. Info-level
+    /// issues are left to the problem list.
+    async fn notify_data_quality(
+        &self,
+        service: &crate::AnalyticsService,
+        active: &mut HashSet<String>,
+        now: i64,
+    ) {
+        let issues = match service.get_data_quality(DataQualityFilter::default()).await {
+            Ok(issues) => issues,
+            Err(err) => {
+                tracing::error!("Failed to query data quality: {}", err);
+                return;
+            }
+        };
+
+        let current: HashSet<String> = issues.iter().map(|issue| issue.key()).collect();
+        for issue in issues {
+            if issue.severity == Severity::Info || active.contains(&issue.key()) {
+                continue;
+            }
+            let notification = Notification {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: format!("Data Quality: {}", issue.kind.as_str()),
+                message: issue.message.clone(),
+                severity: issue.severity,
+                timestamp: now,
+                read: false,
+                link: None,
+                cluster_id: Some(issue.cluster_id.clone()),
+                resource_id: issue.resource_id.clone(),
+            };
+            if let Err(e) = self.send_notification(notification).await {
+                tracing::error!("Failed to send data quality notification: {}", e);
+            }
+        }
+        *active = current;
+    }
+}
+
+/// Latest timeline entry and the incident's extent.
+fn incident_summary(incident: &Incident) -> String {
+    let latest = incident
+        .timeline
+        .last()
+        .map(|event| event.message.as_str())
+        .unwrap_or_default();
+    format!(
+        "{} ({} anomalies across {} resources)",
+        latest,
+        incident.anomaly_ids.len(),
+        incident.resources.len()
+    )
 }
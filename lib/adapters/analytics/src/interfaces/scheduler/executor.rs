@@ -1,7 +1,19 @@
 use anyhow::Result;
 
-use phenome_domain::ScheduledAction;
+use phenome_domain::{RecommendationAction, ScheduledAction};
 
-pub async fn execute_action(_action: &ScheduledAction) -> Result<()> {
-    Ok(())
+/// Whether an action can be carried out against the cluster. None can yet,
+/// so applying or scheduling a recommendation is refused rather than
+/// reported as done without changing anything.
+pub fn can_execute(_action: &RecommendationAction) -> bool {
+    false
+}
+
+pub async fn execute_action(action: &ScheduledAction) -> Result<()> {
+    let kind = match &action.action {
+        RecommendationAction::ScaleDeployment { .. } => "scale deployment",
+        RecommendationAction::UpdateResourceLimits { .. } => "update resource limits",
+        RecommendationAction::ReclaimStorage { .. } => "reclaim storage",
+    };
+    anyhow::bail!("{kind} actions cannot be executed automatically yet")
 }
@@ -2,25 +2,37 @@ use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
 use std::sync::Arc;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch};
 use tokio::time::{Duration, interval};
 
-use phenome_domain::{ScheduleId, ScheduleStatus, ScheduledAction};
+use phenome_domain::{
+    ScheduleExecution, ScheduleExecutionFilter, ScheduleId, ScheduleStatus, ScheduledAction,
+};
 use phenome_ports::SchedulerPort;
 
 use crate::storage::StoragePort;
 
 const SCHEDULER_TICK_INTERVAL: Duration = Duration::from_secs(60);
 const MAX_ACTIONS_PER_TICK: usize = 64;
+const EXECUTED_CAPACITY: usize = 64;
 
 #[derive(Clone)]
 pub struct SchedulerService {
     storage: Arc<dyn StoragePort>,
+    executed: broadcast::Sender<ScheduledAction>,
 }
 
 impl SchedulerService {
     pub fn new(storage: Arc<dyn StoragePort>) -> Self {
-        Self { storage }
+        let (executed, _) = broadcast::channel(EXECUTED_CAPACITY);
+        Self { storage, executed }
+    }
+
+    /// Actions the scheduler loop has run from now on, completed or failed.
+    /// Runs started through `execute_now` are returned to their caller
+    /// instead.
+    pub fn subscribe_executed(&self) -> broadcast::Receiver<ScheduledAction> {
+        self.executed.subscribe()
     }
 
     pub async fn run_minute(storage: Arc<dyn StoragePort>, kube_client: kube::Client) {
@@ -64,7 +76,7 @@ impl SchedulerService {
         let now = Utc::now().timestamp_millis();
 
         let mut executed = 0usize;
-        for mut action in all {
+        for action in all {
             // Check if due and pending
             if action.execute_at <= now && matches!(action.status, ScheduleStatus::Pending) {
                 if executed >= MAX_ACTIONS_PER_TICK {
@@ -73,27 +85,56 @@ impl SchedulerService {
                     );
                     break;
                 }
-                // Execute
-                tracing::info!("Executing scheduled action: {}", action.id);
-                // Mark as Executing
-                action.status = ScheduleStatus::Executing;
-                self.storage.update_schedule(action.clone()).await?;
-
-                let result = crate::scheduler::executor::execute_action(&action).await;
-                if let Err(err) = result {
-                    tracing::error!("Scheduled action {} failed: {}", action.id, err);
-                    action.status = ScheduleStatus::Failed {
-                        error: err.to_string(),
-                    };
-                } else {
-                    action.status = ScheduleStatus::Completed;
-                }
-                self.storage.update_schedule(action).await?;
+                let action = self.execute(action).await?;
+                let _ = self.executed.send(action);
                 executed += 1;
             }
         }
         Ok(())
     }
+
+    /// Record `action` and run it immediately rather than waiting for a
+    /// tick. Returns it completed or failed.
+    pub async fn execute_now(&self, mut action: ScheduledAction) -> Result<ScheduledAction> {
+        if action.id.is_empty() {
+            anyhow::bail!("scheduled action id is required");
+        }
+        action.status = ScheduleStatus::Pending;
+        self.storage.insert_schedule(action.clone()).await?;
+        self.execute(action).await
+    }
+
+    async fn execute(&self, mut action: ScheduledAction) -> Result<ScheduledAction> {
+        tracing::info!("Executing scheduled action: {}", action.id);
+        let started_at = Utc::now().timestamp_millis();
+        // Mark as Executing
+        action.status = ScheduleStatus::Executing;
+        self.storage.update_schedule(action.clone()).await?;
+
+        let result = crate::scheduler::executor::execute_action(&action).await;
+        if let Err(err) = result {
+            tracing::error!("Scheduled action {} failed: {}", action.id, err);
+            action.status = ScheduleStatus::Failed {
+                error: err.to_string(),
+            };
+        } else {
+            action.status = ScheduleStatus::Completed;
+        }
+        self.storage.update_schedule(action.clone()).await?;
+        self.storage
+            .insert_schedule_execution(ScheduleExecution {
+                schedule_id: action.id.clone(),
+                recommendation_id: action.recommendation_id.clone(),
+                started_at,
+                finished_at: Utc::now().timestamp_millis(),
+                error: match &action.status {
+                    ScheduleStatus::Failed { error } => Some(error.clone()),
+                    _ => None,
+                },
+            })
+            .await?;
+        Ok(action)
+    }
 }
 
 #[async_trait]
@@ -106,17 +147,37 @@ impl SchedulerPort for SchedulerService {
         Ok(action.id)
     }
 
-    async fn cancel_schedule(&self, id: ScheduleId) -> Result<()> {
-        // Need to fetch, modify, update
-        let all = self.storage.get_all_schedules().await?;
-        if let Some(mut action) = all.into_iter().find(|a| a.id == id) {
-            action.status = ScheduleStatus::Cancelled;
-            self.storage.update_schedule(action).await?;
-        }
-        Ok(())
+    async fn cancel_schedule(&self, id: ScheduleId) -> Result<Option<ScheduledAction>> {
+        let Some(mut action) = self.get_scheduled(id).await? else {
+            return Ok(None);
+        };
+        action.cancel()?;
+        self.storage.update_schedule(action.clone()).await?;
+        Ok(Some(action))
     }
 
     async fn list_scheduled(&self) -> Result<Vec<ScheduledAction>> {
         self.storage.get_all_schedules().await
     }
+
+    async fn get_scheduled(&self, id: ScheduleId) -> Result<Option<ScheduledAction>> {
+        let all = self.storage.get_all_schedules().await?;
+        Ok(all.into_iter().find(|a| a.id == id))
+    }
+
+    async fn reschedule(&self, id: ScheduleId, execute_at: i64) -> Result<Option<ScheduledAction>> {
+        let Some(mut action) = self.get_scheduled(id).await? else {
+            return Ok(None);
+        };
+        action.reschedule(execute_at, Utc::now().timestamp_millis())?;
+        self.storage.update_schedule(action.clone()).await?;
+        Ok(Some(action))
+    }
+
+    async fn execution_history(
+        &self,
+        filter: ScheduleExecutionFilter,
+    ) -> Result<Vec<ScheduleExecution>> {
+        self.storage.query_schedule_executions(filter).await
+    }
 }
@@ -10,4 +10,7 @@ pub use runtime::analytics_service::AnalyticsService;
 
 pub use infra::{circuit_breaker, cluster_manager};
 pub use interfaces::{grpc, notification, scheduler};
-pub use runtime::{aggregator, analytics_engine, analytics_service, cache, metrics_collector};
+pub use runtime::{
+    aggregator, analytics_engine, analytics_service, cache, downsample, feed, feedback, incidents,
+    metrics_collector, quality, streaming, validation,
+};
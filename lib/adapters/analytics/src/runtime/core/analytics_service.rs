@@ -1,34 +1,78 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, watch};
+
 use phenome_domain::{
-    AggregatedMetric, AggregatedQuery, Anomaly, AnomalyFilter, MetricSample, MetricType,
-    MetricsQuery, Recommendation, RecommendationFilter, TimeRange, TimeSeries, TimeSeriesPoint,
+    AggregatedMetric, AggregatedQuery, Anomaly, AnomalyFeedback, AnomalyFilter, ClusterId,
+    DataQualityFilter, DataQualityIssue, DownsampleMethod, Incident, IncidentFilter,
+    IncidentStatus, IngestReport, IngestStats, InvalidTransition, MetricSample, MetricType,
+    MetricsQuery, Recommendation, RecommendationFilter, RecommendationStatus, SampleRejection,
+    ScheduleId, ScheduleStatus, ScheduledAction, TimeRange, TimeSeries, TimeSeriesPoint, Unit,
+    WatchConfig,
 };
-use phenome_ports::AnalyticsPort;
+use phenome_ports::{AnalyticsPort, SchedulerPort};
 
-use crate::aggregator::Aggregator;
+use crate::aggregator::{AGGREGATE_WINDOW, Aggregator};
+use crate::cluster_manager::ClusterManager;
+use crate::downsample;
+use crate::feed::{ChangeFeed, FeedBatch};
+use crate::feedback::FeedbackTuner;
 use crate::grpc::MlClient;
-use crate::storage::StoragePort;
+use crate::incidents::IncidentTracker;
+use crate::quality::DataQualityMonitor;
+use crate::scheduler::{SchedulerService, executor};
+use crate::streaming::StreamingDetector;
+use crate::validation::IngestValidator;
+use crate::storage::{MetricsPage, StoragePort};
+
+/// How often workloads are rightsized; usage is aggregated hourly, so
+/// asking more often would repeat the same advice.
+const RECOMMENDATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Clone)]
 pub struct AnalyticsService {
     storage: Arc<dyn StoragePort>,
     aggregator: Aggregator,
-    anomalies: Arc<RwLock<Vec<Anomaly>>>,
     recommendations: Arc<RwLock<Vec<Recommendation>>>,
     ml_client: MlClient,
+    quality: DataQualityMonitor,
+    validator: IngestValidator,
+    ingest_stats: Arc<RwLock<IngestStats>>,
+    incidents: IncidentTracker,
+    streaming: StreamingDetector,
+    feedback: FeedbackTuner,
+    feeds: Feeds,
+    watch: WatchConfig,
+    scheduler: SchedulerService,
+    /// Held per recommendation id across a transition's check and action.
+    transitions: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+/// Change feeds behind the `Watch*` RPCs.
+#[derive(Debug, Clone)]
+struct Feeds {
+    metrics: ChangeFeed<MetricSample>,
+    anomalies: ChangeFeed<Anomaly>,
+    recommendations: ChangeFeed<Recommendation>,
+}
+
+impl Feeds {
+    fn new(config: &WatchConfig) -> Self {
+        Self {
+            metrics: ChangeFeed::new(config.metrics_history),
+            anomalies: ChangeFeed::new(config.history),
+            recommendations: ChangeFeed::new(config.history),
+        }
+    }
 }
 
 impl std::fmt::Debug for AnalyticsService {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let anomalies_count = self
-            .anomalies
-            .read()
-            .map(|guard| guard.len())
-            .unwrap_or(0);
         let recommendations_count = self
             .recommendations
             .read()
@@ -37,9 +81,15 @@ impl std::fmt::Debug for AnalyticsService {
         f.debug_struct("AnalyticsService")
             .field("storage", &"StoragePort")
             .field("aggregator", &self.aggregator)
-            .field("anomalies_count", &anomalies_count)
             .field("recommendations_count", &recommendations_count)
             .field("ml_client", &self.ml_client)
+            .field("quality", &self.quality)
+            .field("validator", &self.validator)
+            .field("ingest_stats", &self.ingest_stats())
+            .field("incidents", &self.incidents)
+            .field("streaming", &self.streaming)
+            .field("feedback", &self.feedback)
+            .field("feeds", &self.feeds)
             .finish()
     }
 }
@@ -47,40 +97,630 @@ impl std::fmt::Debug for AnalyticsService {
 impl AnalyticsService {
     pub fn new(storage: Arc<dyn StoragePort>, ml_client: MlClient) -> Self {
         Self {
+            scheduler: SchedulerService::new(storage.clone()),
             storage,
             aggregator: Aggregator::new(),
-            anomalies: Arc::new(RwLock::new(Vec::new())),
             recommendations: Arc::new(RwLock::new(Vec::new())),
             ml_client,
+            quality: DataQualityMonitor::default(),
+            validator: IngestValidator::default(),
+            ingest_stats: Arc::new(RwLock::new(IngestStats::default())),
+            incidents: IncidentTracker::default(),
+            streaming: StreamingDetector::default(),
+            feedback: FeedbackTuner::default(),
+            feeds: Feeds::new(&WatchConfig::default()),
+            watch: WatchConfig::default(),
+            transitions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Share a monitor with the collector so cluster-level staleness is
+    /// reported alongside per-series ingest checks.
+    pub fn with_quality_monitor(mut self, quality: DataQualityMonitor) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn with_ingest_validator(mut self, validator: IngestValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    pub fn with_incident_tracker(mut self, incidents: IncidentTracker) -> Self {
+        self.incidents = incidents;
+        self
+    }
+
+    pub fn with_streaming_detector(mut self, streaming: StreamingDetector) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
+    pub fn with_feedback_tuner(mut self, feedback: FeedbackTuner) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Size the change feeds; call before anything is watched, since the
+    /// feeds are replaced.
+    pub fn with_watch_config(mut self, watch: WatchConfig) -> Result<Self> {
+        watch.validate()?;
+        self.feeds = Feeds::new(&watch);
+        self.watch = watch;
+        Ok(self)
+    }
+
+    /// Stream samples matching `query` as they are stored, from after
+    /// `cursor` or from now.
+    pub fn watch_metrics(
+        &self,
+        query: MetricsQuery,
+        cursor: Option<u64>,
+    ) -> mpsc::Receiver<FeedBatch<MetricSample>> {
+        self.feeds.metrics.watch(
+            cursor,
+            move |sample| sample_matches(&query, sample),
+            self.watch.batch_size,
+            self.watch.send_buffer,
+        )
+    }
+
+    /// Stream anomalies matching `filter` as they are recorded or receive
+    /// feedback; an anomaly seen again replaces the earlier copy.
+    pub fn watch_anomalies(
+        &self,
+        filter: AnomalyFilter,
+        cursor: Option<u64>,
+    ) -> mpsc::Receiver<FeedBatch<Anomaly>> {
+        self.feeds.anomalies.watch(
+            cursor,
+            move |anomaly| filter.matches(anomaly),
+            self.watch.batch_size,
+            self.watch.send_buffer,
+        )
+    }
+
+    /// Stream recommendations matching `filter` as they are added or
+    /// change status.
+    pub fn watch_recommendations(
+        &self,
+        filter: RecommendationFilter,
+        cursor: Option<u64>,
+    ) -> mpsc::Receiver<FeedBatch<Recommendation>> {
+        self.feeds.recommendations.watch(
+            cursor,
+            move |recommendation| filter.matches(recommendation),
+            self.watch.batch_size,
+            self.watch.send_buffer,
+        )
+    }
+
+    /// Retune detection from feedback given before a restart.
+    pub async fn restore_feedback(&self) -> Result<usize> {
+        let anomalies = self
+            .storage
+            .query_anomaly_feedback(AnomalyFilter::default())
+            .await?;
+        for anomaly in &anomalies {
+            self.feedback.learn(anomaly);
+        }
+        Ok(anomalies.len())
+    }
+
+    /// Reload recommendations, with their status, stored by a previous run.
+    pub async fn restore_recommendations(&self) -> Result<usize> {
+        let stored = self
+            .storage
+            .query_recommendations(RecommendationFilter::default())
+            .await?;
+        let count = stored.len();
+        if let Ok(mut store) = self.recommendations.write() {
+            *store = stored;
+        } else {
+            tracing::error!("recommendations lock poisoned");
+        }
+        Ok(count)
+    }
+
+    /// Resume tracking incidents left open in storage by a previous run.
+    pub async fn restore_incidents(&self) -> Result<usize> {
+        let open = self
+            .storage
+            .query_incidents(IncidentFilter {
+                status: Some(IncidentStatus::Open),
+                ..Default::default()
+            })
+            .await?;
+        let count = open.len();
+        self.incidents.restore(open);
+        Ok(count)
+    }
+
+    /// Persist newly detected anomalies that feedback does not suppress
+    /// and fold them into incidents. Returns the incidents they opened or
+    /// updated.
+    pub async fn record_anomalies(&self, anomalies: Vec<Anomaly>) -> Result<Vec<Incident>> {
+        let anomalies = self.feedback.filter(anomalies);
+        if anomalies.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.storage.insert_anomalies(anomalies.clone()).await?;
+        let incidents = self.incidents.observe(&anomalies);
+        self.feeds.anomalies.publish(anomalies);
+        self.storage.upsert_incidents(incidents.clone()).await?;
+        Ok(incidents)
+    }
+
+    /// Raw samples a page at a time, for callers reading more history
+    /// than fits in one response.
+    pub async fn query_metrics_page(
+        &self,
+        query: MetricsQuery,
+        cursor: Option<i64>,
+        limit: u32,
+    ) -> Result<MetricsPage> {
+        self.storage.query_metrics_page(query, cursor, limit).await
+    }
+
+    /// Resolve incidents that have gone quiet by `now_ms` and persist them.
+    /// Returns the resolved incidents, each resolved no later than
+    /// `now_ms`.
+    pub async fn sweep_incidents(&self, now_ms: i64) -> Result<Vec<Incident>> {
+        let resolved = self.incidents.sweep(now_ms);
+        self.storage.upsert_incidents(resolved.clone()).await?;
+        Ok(resolved)
+    }
+
+    /// The scheduler running actions for this service's recommendations.
+    pub fn scheduler(&self) -> &SchedulerService {
+        &self.scheduler
+    }
+
+    pub fn ingest_stats(&self) -> IngestStats {
+        match self.ingest_stats.read() {
+            Ok(stats) => stats.clone(),
+            Err(_) => {
+                tracing::error!("ingest stats lock poisoned");
+                IngestStats::default()
+            }
         }
     }
 
-    pub fn add_anomalies(&self, anomalies: Vec<Anomaly>) {
-        if let Ok(mut store) = self.anomalies.write() {
-            store.extend(anomalies);
+    /// Count samples rejected before they reached `record_metrics`, such as
+    /// those that failed wire conversion.
+    pub fn record_rejections(&self, rejections: &[SampleRejection]) {
+        if let Ok(mut stats) = self.ingest_stats.write() {
+            stats.record_rejections(rejections);
         } else {
-            tracing::error!("anomalies lock poisoned");
+            tracing::error!("ingest stats lock poisoned");
         }
     }
 
     pub fn add_recommendations(&self, recommendations: Vec<Recommendation>) {
+        self.feeds
+            .recommendations
+            .publish(recommendations.iter().cloned());
         if let Ok(mut store) = self.recommendations.write() {
             store.extend(recommendations);
         } else {
             tracing::error!("recommendations lock poisoned");
         }
     }
+
+    /// Persist generated recommendations, hold them and publish them to
+    /// watchers, skipping advice already given: a resource with a
+    /// scheduled recommendation, or with the same action pending or
+    /// dismissed. A pending recommendation for the same resource with a
+    /// different action is dismissed as superseded. Returns those recorded.
+    pub async fn record_recommendations(
+        &self,
+        generated: Vec<Recommendation>,
+    ) -> Result<Vec<Recommendation>> {
+        let mut recorded = Vec::new();
+        let mut superseded = Vec::new();
+        {
+            let held = self
+                .recommendations
+                .read()
+                .map_err(|_| anyhow::anyhow!("recommendations lock poisoned"))?;
+            for recommendation in generated {
+                let same_target: Vec<&Recommendation> = held
+                    .iter()
+                    .filter(|rec| {
+                        rec.cluster_id == recommendation.cluster_id
+                            && rec.action.target() == recommendation.action.target()
+                    })
+                    .collect();
+                let repeated = same_target.iter().any(|rec| match rec.status {
+                    RecommendationStatus::Scheduled { .. } => true,
+                    RecommendationStatus::Pending | RecommendationStatus::Dismissed { .. } => {
+                        rec.action == recommendation.action
+                    }
+                    RecommendationStatus::Applied { .. } => false,
+                });
+                // A batch recommends one action per target; the first wins.
+                let batched = recorded.iter().any(|rec: &Recommendation| {
+                    rec.cluster_id == recommendation.cluster_id
+                        && rec.action.target() == recommendation.action.target()
+                });
+                if repeated || batched {
+                    continue;
+                }
+                superseded.extend(
+                    same_target
+                        .iter()
+                        .filter(|rec| matches!(rec.status, RecommendationStatus::Pending))
+                        .map(|rec| (rec.id.clone(), recommendation.id.clone())),
+                );
+                recorded.push(recommendation);
+            }
+        }
+
+        for (id, by) in superseded {
+            self.dismiss_recommendation(&id, format!("superseded by {by}"))
+                .await?;
+        }
+        if !recorded.is_empty() {
+            self.storage
+                .upsert_recommendations(recorded.clone())
+                .await?;
+            self.add_recommendations(recorded.clone());
+        }
+        Ok(recorded)
+    }
+
+    /// Ask the ML service to rightsize the workloads `clusters` reports for
+    /// `cluster_id`, and record the advice.
+    pub async fn recommend(
+        &self,
+        clusters: &ClusterManager,
+        cluster_id: &ClusterId,
+    ) -> Result<Vec<Recommendation>> {
+        let containers = clusters.list_containers(cluster_id).await?;
+        if containers.is_empty() {
+            return Ok(Vec::new());
+        }
+        let generated = self
+            .ml_client
+            .generate_recommendations(cluster_id, containers)
+            .await?;
+        self.record_recommendations(generated).await
+    }
+
+    /// Dismiss a recommendation, cancelling any pending schedule for it.
+    /// `None` if no recommendation has that id; an error wrapping
+    /// `InvalidTransition` if its status does not allow it.
+    pub async fn dismiss_recommendation(
+        &self,
+        id: &str,
+        reason: String,
+    ) -> Result<Option<Recommendation>> {
+        self.transition_recommendation(id, RecommendationStatus::Dismissed { reason })
+            .await
+    }
+
+    /// Schedule a recommendation's action to run at `execute_at`, replacing
+    /// any pending schedule for it. Refused while its action cannot be
+    /// executed; once the run finishes the recommendation is settled by
+    /// [`Self::follow_schedules_with_shutdown`].
+    pub async fn schedule_recommendation(
+        &self,
+        id: &str,
+        execute_at: i64,
+    ) -> Result<Option<Recommendation>> {
+        self.transition_recommendation(id, RecommendationStatus::Scheduled { execute_at })
+            .await
+    }
+
+    /// Run a recommendation's action now. It is marked applied only if the
+    /// action succeeds; either way the run is recorded as a scheduled
+    /// action. Refused while its action cannot be executed.
+    pub async fn apply_recommendation(&self, id: &str) -> Result<Option<Recommendation>> {
+        let applied_at = chrono::Utc::now().timestamp_millis();
+        self.transition_recommendation(id, RecommendationStatus::Applied { applied_at })
+            .await
+    }
+
+    /// Move a recommendation to `next`, one transition per recommendation
+    /// at a time so two callers cannot both run its action.
+    async fn transition_recommendation(
+        &self,
+        id: &str,
+        next: RecommendationStatus,
+    ) -> Result<Option<Recommendation>> {
+        let lock = self
+            .transitions
+            .lock()
+            .map_err(|_| anyhow::anyhow!("recommendation transitions lock poisoned"))?
+            .entry(id.to_string())
+            .or_default()
+            .clone();
+        let result = {
+            let _held = lock.lock().await;
+            self.transition_locked(id, next).await
+        };
+        if let Ok(mut transitions) = self.transitions.lock() {
+            // Only the map and this call still hold it: nobody is waiting.
+            if Arc::strong_count(&lock) == 2 {
+                transitions.remove(id);
+            }
+        }
+        result
+    }
+
+    async fn transition_locked(
+        &self,
+        id: &str,
+        next: RecommendationStatus,
+    ) -> Result<Option<Recommendation>> {
+        let current = match self.recommendations.read() {
+            Ok(store) => store.iter().find(|rec| rec.id == id).cloned(),
+            Err(_) => anyhow::bail!("recommendations lock poisoned"),
+        };
+        let Some(mut recommendation) = current else {
+            return Ok(None);
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        recommendation.status.check_transition(&next, now)?;
+        let runs_action = matches!(
+            next,
+            RecommendationStatus::Applied { .. } | RecommendationStatus::Scheduled { .. }
+        );
+        if runs_action && !executor::can_execute(&recommendation.action) {
+            return Err(InvalidTransition {
+                from: recommendation.status.kind(),
+                to: next.kind(),
+                reason: "its action cannot be executed automatically yet".to_string(),
+            }
+            .into());
+        }
+
+        let scheduled = |execute_at| ScheduledAction {
+            id: uuid::Uuid::new_v4().to_string(),
+            execute_at,
+            recommendation_id: recommendation.id.clone(),
+            action: recommendation.action.clone(),
+            status: ScheduleStatus::Pending,
+        };
+        match &next {
+            RecommendationStatus::Applied { applied_at } => {
+                let run = self.scheduler.execute_now(scheduled(*applied_at)).await?;
+                if let ScheduleStatus::Failed { error } = run.status {
+                    anyhow::bail!("recommendation {id} action failed: {error}");
+                }
+                self.cancel_schedules(id).await?;
+            }
+            RecommendationStatus::Scheduled { execute_at } => {
+                self.cancel_schedules(id).await?;
+                self.scheduler
+                    .schedule_action(scheduled(*execute_at))
+                    .await?;
+            }
+            RecommendationStatus::Dismissed { .. } => self.cancel_schedules(id).await?,
+            RecommendationStatus::Pending => {}
+        }
+
+        recommendation.transition(next, now)?;
+        self.store_recommendation(&recommendation).await?;
+        Ok(Some(recommendation))
+    }
+
+    /// Recommend for every registered cluster each
+    /// `RECOMMENDATION_INTERVAL`, until shutdown.
+    pub async fn recommend_with_shutdown(
+        &self,
+        clusters: ClusterManager,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut tick = tokio::time::interval(RECOMMENDATION_INTERVAL);
+        loop {
+            tokio::select! {
+                result = shutdown.changed() => {
+                    if result.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                }
+                _ = tick.tick() => {
+                    for cluster in clusters.list_clusters().await {
+                        match self.recommend(&clusters, &cluster.id).await {
+                            Ok(recorded) if !recorded.is_empty() => tracing::info!(
+                                "Recorded {} recommendations for {}",
+                                recorded.len(),
+                                cluster.id
+                            ),
+                            Ok(_) => {}
+                            Err(e) => {
+                                tracing::warn!("Failed to recommend for {}: {}", cluster.id, e)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Follow the actions the scheduler loop runs, settling the
+    /// recommendations they were scheduled for.
+    pub async fn follow_schedules_with_shutdown(&self, mut shutdown: watch::Receiver<bool>) {
+        let mut executed = self.scheduler.subscribe_executed();
+        loop {
+            tokio::select! {
+                result = shutdown.changed() => {
+                    if result.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                }
+                action = executed.recv() => match action {
+                    Ok(action) => {
+                        if let Err(e) = self.settle_schedule(&action).await {
+                            tracing::error!("Failed to settle schedule {}: {}", action.id, e);
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(
+                            "Missed {} scheduler runs; their recommendations stay scheduled",
+                            missed
+                        );
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+
+    /// Move the recommendation a finished run was scheduled for: applied if
+    /// it completed, back to pending if it failed so it can be scheduled
+    /// or applied again (the error is in the execution history). Runs of a
+    /// schedule the recommendation no longer waits on are ignored.
+    pub(crate) async fn settle_schedule(
+        &self,
+        action: &ScheduledAction,
+    ) -> Result<Option<Recommendation>> {
+        let Some(mut recommendation) = self.awaiting(action)? else {
+            return Ok(None);
+        };
+
+        let now = chrono::Utc::now().timestamp_millis();
+        match action.status {
+            ScheduleStatus::Completed => {
+                recommendation.transition(RecommendationStatus::Applied { applied_at: now }, now)?
+            }
+            // Not a transition a caller may make, so set directly.
+            ScheduleStatus::Failed { .. } => recommendation.status = RecommendationStatus::Pending,
+            _ => return Ok(None),
+        }
+        self.store_recommendation(&recommendation).await?;
+        Ok(Some(recommendation))
+    }
+
+    /// Cancel a pending scheduled action. The recommendation waiting on it
+    /// returns to pending.
+    pub async fn cancel_schedule(&self, id: ScheduleId) -> Result<Option<ScheduledAction>> {
+        let Some(action) = self.scheduler.cancel_schedule(id).await? else {
+            return Ok(None);
+        };
+        if let Some(mut recommendation) = self.awaiting(&action)? {
+            // Not a transition a caller may make, so set directly.
+            recommendation.status = RecommendationStatus::Pending;
+            self.store_recommendation(&recommendation).await?;
+        }
+        Ok(Some(action))
+    }
+
+    /// Move a pending scheduled action to `execute_at`, along with the
+    /// recommendation waiting on it.
+    pub async fn reschedule(
+        &self,
+        id: ScheduleId,
+        execute_at: i64,
+    ) -> Result<Option<ScheduledAction>> {
+        let Some(before) = self.scheduler.get_scheduled(id.clone()).await? else {
+            return Ok(None);
+        };
+        let waiting = self.awaiting(&before)?;
+        let next = RecommendationStatus::Scheduled { execute_at };
+        let now = chrono::Utc::now().timestamp_millis();
+        if let Some(recommendation) = &waiting {
+            recommendation.status.check_transition(&next, now)?;
+        }
+
+        let Some(action) = self.scheduler.reschedule(id, execute_at).await? else {
+            return Ok(None);
+        };
+        if let Some(mut recommendation) = waiting {
+            recommendation.transition(next, now)?;
+            self.store_recommendation(&recommendation).await?;
+        }
+        Ok(Some(action))
+    }
+
+    /// The recommendation `action` was scheduled for, if it is still
+    /// scheduled for that run.
+    fn awaiting(&self, action: &ScheduledAction) -> Result<Option<Recommendation>> {
+        let store = self
+            .recommendations
+            .read()
+            .map_err(|_| anyhow::anyhow!("recommendations lock poisoned"))?;
+        Ok(store
+            .iter()
+            .find(|rec| rec.id == action.recommendation_id)
+            .filter(|rec| {
+                matches!(
+                    rec.status,
+                    RecommendationStatus::Scheduled { execute_at } if execute_at == action.execute_at
+                )
+            })
+            .cloned())
+    }
+
+    /// Persist a recommendation's new status, replace the held copy and
+    /// publish it to watchers.
+    async fn store_recommendation(&self, recommendation: &Recommendation) -> Result<()> {
+        self.storage
+            .upsert_recommendations(vec![recommendation.clone()])
+            .await?;
+        if let Ok(mut store) = self.recommendations.write() {
+            if let Some(held) = store.iter_mut().find(|rec| rec.id == recommendation.id) {
+                *held = recommendation.clone();
+            }
+        } else {
+            tracing::error!("recommendations lock poisoned");
+        }
+        self.feeds.recommendations.publish([recommendation.clone()]);
+        Ok(())
+    }
+
+    /// Cancel the schedules still waiting to run a recommendation.
+    async fn cancel_schedules(&self, recommendation_id: &str) -> Result<()> {
+        for action in self.scheduler.list_scheduled().await? {
+            if action.recommendation_id == recommendation_id
+                && matches!(action.status, ScheduleStatus::Pending)
+            {
+                self.scheduler.cancel_schedule(action.id).await?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl AnalyticsPort for AnalyticsService {
-    async fn record_metrics(&self, samples: Vec<MetricSample>) -> Result<()> {
-        self.storage.insert_metrics(samples.clone()).await?;
-        let aggregates = self
-            .aggregator
-            .aggregate_window(&samples, Duration::from_secs(3600))?;
-        self.storage.insert_aggregated(aggregates).await?;
-        Ok(())
+    async fn record_metrics(&self, samples: Vec<MetricSample>) -> Result<IngestReport> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let (samples, rejections) = self.validator.validate(samples, now);
+        if !rejections.is_empty() {
+            tracing::debug!("Rejected {} samples at ingest", rejections.len());
+        }
+        // Rejected samples must not move cadence or ordering state.
+        self.quality.observe(&samples, now);
+
+        if !samples.is_empty() {
+            self.storage.insert_metrics(samples.clone()).await?;
+            self.feeds.metrics.publish(samples.iter().cloned());
+            let aggregates = self
+                .aggregator
+                .aggregate_window(&samples, AGGREGATE_WINDOW)?;
+            self.storage.insert_aggregated(aggregates).await?;
+
+            // The samples are stored; failing to record what they raised
+            // should not fail the ingest.
+            let detected = self.streaming.observe(&samples);
+            if let Err(e) = self.record_anomalies(detected).await {
+                tracing::error!("Failed to persist streaming anomalies: {}", e);
+            }
+        }
+
+        if let Ok(mut stats) = self.ingest_stats.write() {
+            stats.record_accepted(samples.len());
+            stats.record_rejections(&rejections);
+        } else {
+            tracing::error!("ingest stats lock poisoned");
+        }
+
+        Ok(IngestReport {
+            accepted: samples.len(),
+            rejections,
+        })
     }
 
     async fn query_aggregated(&self, query: AggregatedQuery) -> Result<Vec<AggregatedMetric>> {
@@ -92,7 +732,37 @@ impl AnalyticsPort for AnalyticsService {
         resource_id: String,
         metric_type: MetricType,
         range: TimeRange,
+        max_points: Option<u32>,
     ) -> Result<TimeSeries> {
+        let max_points = max_points.filter(|&max| max > 0);
+        if let Some(max) = max_points.filter(|&max| downsample::wants_rollup(&range, max)) {
+            let window_ms = AGGREGATE_WINDOW.as_millis() as i64;
+            let windows = self
+                .storage
+                .query_aggregated(AggregatedQuery {
+                    cluster_id: None,
+                    resource_type: None,
+                    resource_ids: vec![resource_id.clone()],
+                    metric_types: vec![metric_type],
+                    window_duration: AGGREGATE_WINDOW,
+                    time_range: Some(TimeRange {
+                        start_ms: range.start_ms - range.start_ms.rem_euclid(window_ms),
+                        end_ms: range.end_ms,
+                    }),
+                })
+                .await?;
+            if let Some(first) = windows.first() {
+                return Ok(TimeSeries {
+                    cluster_id: first.cluster_id.clone(),
+                    resource_id,
+                    metric_type,
+                    unit: Unit::for_metric(metric_type).as_str().to_string(),
+                    points: downsample::rollup_windows(&windows, max),
+                    downsample: DownsampleMethod::Rollup,
+                });
+            }
+        }
+
         let samples = self
             .storage
             .query_metrics(MetricsQuery {
@@ -112,6 +782,10 @@ impl AnalyticsPort for AnalyticsService {
             })
             .collect();
         points.sort_by_key(|point| point.timestamp);
+        let (points, downsample) = match max_points {
+            Some(max) => downsample::downsample(points, &range, max),
+            None => (points, DownsampleMethod::Raw),
+        };
 
         let (cluster_id, unit) = samples
             .first()
@@ -124,6 +798,7 @@ impl AnalyticsPort for AnalyticsService {
             metric_type,
             unit,
             points,
+            downsample,
         })
     }
 
@@ -133,54 +808,18 @@ impl AnalyticsPort for AnalyticsService {
             (&filter.resource_id, filter.metric_type, filter.time_range)
         {
             if let Ok(series) = self
-                .get_time_series(resource_id.clone(), metric_type, range)
+                .get_time_series(resource_id.clone(), metric_type, range, None)
                 .await
             {
                 if let Ok(detected) = self.ml_client.detect_anomalies(&series).await {
-                    // Persist anomalies
-                    if let Err(e) = self.storage.insert_anomalies(detected.clone()).await {
+                    if let Err(e) = self.record_anomalies(detected).await {
                         tracing::error!("Failed to persist anomalies: {}", e);
                     }
-                    self.add_anomalies(detected);
                 }
             }
         }
 
-        let store = self
-            .anomalies
-            .read()
-            .map_err(|_| anyhow::anyhow!("anomalies lock poisoned"))?;
-        let mut filtered: Vec<Anomaly> = store
-            .iter()
-            .filter(|anomaly| {
-                filter
-                    .cluster_id
-                    .as_ref()
-                    .map_or(true, |id| id == &anomaly.cluster_id)
-                    && filter
-                        .resource_id
-                        .as_ref()
-                        .map_or(true, |resource_id| resource_id == &anomaly.resource_id)
-                    && filter
-                        .metric_type
-                        .as_ref()
-                        .map_or(true, |metric_type| metric_type == &anomaly.metric_type)
-                    && filter
-                        .severity
-                        .as_ref()
-                        .map_or(true, |severity| severity == &anomaly.severity)
-                    && filter.time_range.as_ref().map_or(true, |range| {
-                        anomaly.detected_at >= range.start_ms && anomaly.detected_at <= range.end_ms
-                    })
-            })
-            .cloned()
-            .collect();
-
-        if let Some(limit) = filter.limit {
-            filtered.truncate(limit as usize);
-        }
-
-        Ok(filtered)
+        self.storage.query_anomalies(filter).await
     }
 
     async fn get_recommendations(
@@ -193,20 +832,7 @@ impl AnalyticsPort for AnalyticsService {
             .map_err(|_| anyhow::anyhow!("recommendations lock poisoned"))?;
         let mut filtered: Vec<Recommendation> = store
             .iter()
-            .filter(|rec| {
-                filter
-                    .cluster_id
-                    .as_ref()
-                    .map_or(true, |id| id == &rec.cluster_id)
-                    && filter
-                        .priority
-                        .as_ref()
-                        .map_or(true, |priority| priority == &rec.priority)
-                    && filter
-                        .status
-                        .as_ref()
-                        .map_or(true, |status| rec.status.kind() == *status)
-            })
+            .filter(|rec| filter.matches(rec))
             .cloned()
             .collect();
 
@@ -220,4 +846,48 @@ impl AnalyticsPort for AnalyticsService {
     async fn query_metrics(&self, query: MetricsQuery) -> Result<Vec<MetricSample>> {
         self.storage.query_metrics(query).await
     }
+
+    async fn get_data_quality(&self, filter: DataQualityFilter) -> Result<Vec<DataQualityIssue>> {
+        Ok(self.quality.issues(&filter, chrono::Utc::now().timestamp_millis()))
+    }
+
+    async fn get_incidents(&self, filter: IncidentFilter) -> Result<Vec<Incident>> {
+        self.storage.query_incidents(filter).await
+    }
+
+    async fn submit_anomaly_feedback(
+        &self,
+        anomaly_id: String,
+        feedback: AnomalyFeedback,
+    ) -> Result<Option<Anomaly>> {
+        let Some(anomaly) = self
+            .storage
+            .set_anomaly_feedback(anomaly_id, feedback)
+            .await?
+        else {
+            return Ok(None);
+        };
+        self.feedback.learn(&anomaly);
+        self.feeds.anomalies.publish([anomaly.clone()]);
+        Ok(Some(anomaly))
+    }
+
+    async fn get_anomaly_feedback(&self, filter: AnomalyFilter) -> Result<Vec<Anomaly>> {
+        self.storage.query_anomaly_feedback(filter).await
+    }
+}
+
+fn sample_matches(query: &MetricsQuery, sample: &MetricSample) -> bool {
+    query
+        .cluster_id
+        .as_ref()
+        .is_none_or(|id| id == &sample.cluster_id)
+        && query
+            .resource_type
+            .is_none_or(|resource_type| resource_type == sample.resource_type)
+        && (query.resource_ids.is_empty() || query.resource_ids.contains(&sample.resource_id))
+        && (query.metric_types.is_empty() || query.metric_types.contains(&sample.metric_type))
+        && query.time_range.as_ref().is_none_or(|range| {
+            sample.timestamp >= range.start_ms && sample.timestamp <= range.end_ms
+        })
 }
@@ -1,2 +1,5 @@
 pub mod analytics_engine;
 pub mod analytics_service;
+
+#[cfg(test)]
+mod tests;
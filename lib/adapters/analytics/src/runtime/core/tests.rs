@@ -0,0 +1,349 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use phenome_domain::{
+    AggregatedQuery, Anomaly, AnomalyKind, DownsampleMethod, IncidentFilter, IncidentStatus,
+    InvalidTransition, MetricSample, MetricType, Priority, Recommendation, RecommendationAction,
+    RecommendationFilter, RecommendationStatus, RecommendationType, ResourceType, ScheduleId,
+    ScheduleStatus, ScheduledAction, Severity, TimeRange,
+};
+use phenome_ports::{AnalyticsPort, SchedulerPort};
+use tempfile::TempDir;
+
+use crate::grpc::MlClient;
+use crate::storage::sqlite::SqliteStorage;
+
+use super::analytics_service::AnalyticsService;
+
+const EXECUTE_AT: i64 = 4_000_000_000_000;
+
+async fn service() -> (AnalyticsService, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = Arc::new(SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap());
+    let ml_client = MlClient::connect("http://127.0.0.1:1").await.unwrap();
+    (AnalyticsService::new(storage, ml_client), dir)
+}
+
+fn recommendation(id: &str, status: RecommendationStatus) -> Recommendation {
+    Recommendation {
+        id: id.to_string(),
+        cluster_id: "cluster-1".to_string(),
+        created_at: 1_000,
+        recommendation_type: RecommendationType::ScaleDown,
+        priority: Priority::Low,
+        confidence: 0.7,
+        title: format!("Scale down {id}"),
+        description: String::new(),
+        impact_estimate: "-1 replica".to_string(),
+        cost_impact: None,
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 3,
+            to: 2,
+        },
+        status,
+    }
+}
+
+fn run(recommendation_id: &str, execute_at: i64, status: ScheduleStatus) -> ScheduledAction {
+    ScheduledAction {
+        id: format!("run-{recommendation_id}"),
+        execute_at,
+        recommendation_id: recommendation_id.to_string(),
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 3,
+            to: 2,
+        },
+        status,
+    }
+}
+
+async fn status_of(service: &AnalyticsService, id: &str) -> RecommendationStatus {
+    service
+        .get_recommendations(RecommendationFilter::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|rec| rec.id == id)
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn finished_runs_settle_their_recommendation() {
+    let (service, _dir) = service().await;
+    let scheduled = RecommendationStatus::Scheduled {
+        execute_at: EXECUTE_AT,
+    };
+    service.add_recommendations(vec![
+        recommendation("done", scheduled.clone()),
+        recommendation("failed", scheduled.clone()),
+        recommendation("moved", scheduled),
+    ]);
+
+    service
+        .settle_schedule(&run("done", EXECUTE_AT, ScheduleStatus::Completed))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        status_of(&service, "done").await,
+        RecommendationStatus::Applied { .. }
+    ));
+
+    let failed = ScheduleStatus::Failed {
+        error: "forbidden".to_string(),
+    };
+    service
+        .settle_schedule(&run("failed", EXECUTE_AT, failed))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        status_of(&service, "failed").await,
+        RecommendationStatus::Pending
+    ));
+
+    // A run of a schedule that has since moved leaves the recommendation alone.
+    let stale = run("moved", EXECUTE_AT - 60_000, ScheduleStatus::Completed);
+    assert!(service.settle_schedule(&stale).await.unwrap().is_none());
+    assert!(matches!(
+        status_of(&service, "moved").await,
+        RecommendationStatus::Scheduled { .. }
+    ));
+}
+
+#[tokio::test]
+async fn unexecutable_actions_are_not_applied_or_scheduled() {
+    let (service, _dir) = service().await;
+    service.add_recommendations(vec![recommendation("rec", RecommendationStatus::Pending)]);
+
+    let err = service.apply_recommendation("rec").await.unwrap_err();
+    assert!(err.downcast_ref::<InvalidTransition>().is_some());
+    let err = service
+        .schedule_recommendation("rec", EXECUTE_AT)
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<InvalidTransition>().is_some());
+
+    assert!(matches!(
+        status_of(&service, "rec").await,
+        RecommendationStatus::Pending
+    ));
+}
+
+/// Store a pending scheduled action for `recommendation_id`, bypassing the
+/// executability check so the schedule endpoints can be exercised.
+async fn scheduled(service: &AnalyticsService, recommendation_id: &str) -> ScheduleId {
+    service
+        .scheduler()
+        .schedule_action(run(recommendation_id, EXECUTE_AT, ScheduleStatus::Pending))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cancelling_a_schedule_returns_its_recommendation_to_pending() {
+    let (service, _dir) = service().await;
+    service.add_recommendations(vec![recommendation(
+        "rec",
+        RecommendationStatus::Scheduled {
+            execute_at: EXECUTE_AT,
+        },
+    )]);
+    let mut feed = service.watch_recommendations(RecommendationFilter::default(), None);
+    let id = scheduled(&service, "rec").await;
+
+    let cancelled = service.cancel_schedule(id).await.unwrap().unwrap();
+    assert!(matches!(cancelled.status, ScheduleStatus::Cancelled));
+    assert!(matches!(
+        status_of(&service, "rec").await,
+        RecommendationStatus::Pending
+    ));
+    let batch = tokio::time::timeout(Duration::from_secs(1), feed.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        batch.items[0].status,
+        RecommendationStatus::Pending
+    ));
+}
+
+#[tokio::test]
+async fn rescheduling_moves_its_recommendation() {
+    let (service, _dir) = service().await;
+    service.add_recommendations(vec![recommendation(
+        "rec",
+        RecommendationStatus::Scheduled {
+            execute_at: EXECUTE_AT,
+        },
+    )]);
+    let id = scheduled(&service, "rec").await;
+
+    let later = EXECUTE_AT + 3_600_000;
+    service.reschedule(id, later).await.unwrap().unwrap();
+    assert!(matches!(
+        status_of(&service, "rec").await,
+        RecommendationStatus::Scheduled { execute_at } if execute_at == later
+    ));
+}
+
+#[tokio::test]
+async fn sweeping_resolves_quiet_incidents() {
+    let (service, _dir) = service().await;
+    let detected_at = chrono::Utc::now().timestamp_millis() - 2 * 3_600_000;
+    service
+        .record_anomalies(vec![Anomaly {
+            id: "spike".to_string(),
+            cluster_id: "cluster-1".to_string(),
+            resource_id: "pod-a".to_string(),
+            detected_at,
+            metric_type: MetricType::CpuUsage,
+            severity: Severity::Warning,
+            confidence: 0.8,
+            description: "4.00 sigma from running baseline 1.00".to_string(),
+            baseline_value: 1.0,
+            observed_value: 4.0,
+            deviation_sigma: 4.0,
+            related_metrics: Vec::new(),
+            root_cause: None,
+            model_id: None,
+            model_version: None,
+            kind: AnomalyKind::Spike,
+            change_point: None,
+            feedback: None,
+            evidence: None,
+        }])
+        .await
+        .unwrap();
+    let open = IncidentFilter {
+        status: Some(IncidentStatus::Open),
+        ..Default::default()
+    };
+    assert_eq!(service.get_incidents(open.clone()).await.unwrap().len(), 1);
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let resolved = service.sweep_incidents(now).await.unwrap();
+
+    assert_eq!(resolved.len(), 1);
+    assert!(resolved[0].resolved_at.is_some_and(|at| at <= now));
+    assert!(service.get_incidents(open).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn generated_recommendations_skip_repeats_and_supersede_stale_advice() {
+    let (service, _dir) = service().await;
+    let scale_to = |id: &str, to: u32| Recommendation {
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 3,
+            to,
+        },
+        ..recommendation(id, RecommendationStatus::Pending)
+    };
+
+    // One action per target within a batch too.
+    let recorded = service
+        .record_recommendations(vec![scale_to("first", 2), scale_to("twin", 2)])
+        .await
+        .unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].id, "first");
+
+    // The same advice again is not repeated.
+    let recorded = service
+        .record_recommendations(vec![scale_to("again", 2)])
+        .await
+        .unwrap();
+    assert!(recorded.is_empty());
+
+    // Changed advice replaces the pending one.
+    let recorded = service
+        .record_recommendations(vec![scale_to("fresher", 1)])
+        .await
+        .unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert!(matches!(
+        status_of(&service, "first").await,
+        RecommendationStatus::Dismissed { reason } if reason == "superseded by fresher"
+    ));
+    assert!(matches!(
+        status_of(&service, "fresher").await,
+        RecommendationStatus::Pending
+    ));
+
+    // A dismissal holds while the advice is unchanged.
+    service
+        .dismiss_recommendation("fresher", "expected load".to_string())
+        .await
+        .unwrap();
+    let recorded = service
+        .record_recommendations(vec![scale_to("latest", 1)])
+        .await
+        .unwrap();
+    assert!(recorded.is_empty());
+}
+
+#[tokio::test]
+async fn coarse_series_are_served_from_one_aggregate_per_window() {
+    let (service, _dir) = service().await;
+    let hour = 3_600_000;
+    let now = chrono::Utc::now().timestamp_millis();
+    let start = now - now.rem_euclid(hour) - 48 * hour;
+    let sample = |timestamp, value| MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type: ResourceType::Pod,
+        resource_id: "pod-a".to_string(),
+        metric_type: MetricType::CpuUsage,
+        timestamp,
+        value,
+        unit: "cores".to_string(),
+    };
+    // Each ingest batch carries part of both hours.
+    for batch in 0..2 {
+        let offset = batch * 60_000;
+        service
+            .record_metrics(vec![
+                sample(start + offset, 1.0 + batch as f64),
+                sample(start + hour + offset, 3.0),
+            ])
+            .await
+            .unwrap();
+    }
+
+    let range = TimeRange {
+        start_ms: now - 30 * 24 * hour,
+        end_ms: now,
+    };
+    let windows = service
+        .query_aggregated(AggregatedQuery {
+            cluster_id: None,
+            resource_type: None,
+            resource_ids: vec!["pod-a".to_string()],
+            metric_types: vec![MetricType::CpuUsage],
+            window_duration: Duration::from_secs(3600),
+            time_range: Some(range),
+        })
+        .await
+        .unwrap();
+    let counts: Vec<_> = windows
+        .iter()
+        .map(|window| (window.window_start, window.count))
+        .collect();
+    assert_eq!(counts, [(start, 2), (start + hour, 2)]);
+
+    let series = service
+        .get_time_series("pod-a".to_string(), MetricType::CpuUsage, range, Some(100))
+        .await
+        .unwrap();
+    assert_eq!(series.downsample, DownsampleMethod::Rollup);
+    let points: Vec<_> = series
+        .points
+        .iter()
+        .map(|point| (point.timestamp, point.value))
+        .collect();
+    assert_eq!(points, [(start, 1.5), (start + hour, 3.0)]);
+}
@@ -2,4 +2,7 @@ pub mod core;
 pub mod pipeline;
 
 pub use core::{analytics_engine, analytics_service};
-pub use pipeline::{aggregator, cache, metrics_collector};
+pub use pipeline::{
+    aggregator, cache, downsample, feed, feedback, incidents, metrics_collector, quality, streaming,
+    validation,
+};
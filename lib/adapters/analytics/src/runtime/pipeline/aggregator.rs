@@ -8,6 +8,9 @@ use tokio::time::{interval, timeout};
 
 use crate::storage::StoragePort;
 
+/// Window of the aggregates written at ingest.
+pub const AGGREGATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Debug, Clone, Default)]
 pub struct Aggregator;
 
@@ -64,9 +67,9 @@ impl Aggregator {
             return Ok(Vec::new());
         }
 
-        // Key: (ClusterId, ResourceType(json), MetricType(json), window_start)
+        // Key: (ClusterId, ResourceType(json), resource_id, MetricType(json), window_start)
         // Using Strings for JSON-encoded enums to implement Hash
-        type GroupKey = (String, String, String, i64);
+        type GroupKey = (String, String, String, String, i64);
         let mut groups: HashMap<GroupKey, Vec<f64>> = HashMap::new();
 
         for s in samples {
@@ -78,6 +81,7 @@ impl Aggregator {
             let key = (
                 s.cluster_id.clone(),
                 serde_json::to_string(&s.resource_type)?,
+                s.resource_id.clone(),
                 serde_json::to_string(&s.metric_type)?,
                 window_start,
             );
@@ -85,7 +89,7 @@ impl Aggregator {
         }
 
         let mut results = Vec::new();
-        for ((cluster_id, r_type_str, m_type_str, window_start), values) in groups {
+        for ((cluster_id, r_type_str, resource_id, m_type_str, window_start), values) in groups {
             let count = values.len() as u64;
             let sum: f64 = values.iter().sum();
 
@@ -106,6 +110,7 @@ impl Aggregator {
             results.push(AggregatedMetric {
                 cluster_id,
                 resource_type,
+                resource_id,
                 metric_type,
                 window_start,
                 window_duration,
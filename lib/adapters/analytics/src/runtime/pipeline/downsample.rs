@@ -0,0 +1,118 @@
+use phenome_domain::{AggregatedMetric, DownsampleMethod, TimeRange, TimeSeriesPoint};
+
+use crate::aggregator::AGGREGATE_WINDOW;
+
+/// Smallest budget LTTB can honour: the first, last and one chosen point.
+const MIN_POINTS: usize = 3;
+
+fn rollup_window_ms() -> i64 {
+    AGGREGATE_WINDOW.as_millis() as i64
+}
+
+/// Whether a budget is too coarse to resolve anything finer than the
+/// aggregate window, so the range is better served from stored aggregates
+/// than from raw samples.
+pub fn wants_rollup(range: &TimeRange, max_points: u32) -> bool {
+    range.auto_step(max_points) >= rollup_window_ms()
+}
+
+/// Reduce time-ordered raw `points` to at most `max_points`, preserving
+/// the visual shape of the series. Ranges that want a rollup are averaged
+/// per aggregate window first; this covers series with no stored
+/// aggregates. Returns the points and the method used.
+pub fn downsample(
+    points: Vec<TimeSeriesPoint>,
+    range: &TimeRange,
+    max_points: u32,
+) -> (Vec<TimeSeriesPoint>, DownsampleMethod) {
+    let budget = (max_points as usize).max(MIN_POINTS);
+    if points.len() <= budget {
+        return (points, DownsampleMethod::Raw);
+    }
+
+    if wants_rollup(range, max_points) {
+        let rolled = rollup(&points, rollup_window_ms());
+        return (lttb(&rolled, budget), DownsampleMethod::Rollup);
+    }
+    (lttb(&points, budget), DownsampleMethod::Lttb)
+}
+
+/// Window averages from stored aggregates, thinned to at most
+/// `max_points`.
+pub fn rollup_windows(windows: &[AggregatedMetric], max_points: u32) -> Vec<TimeSeriesPoint> {
+    let mut points: Vec<TimeSeriesPoint> = windows
+        .iter()
+        .map(|window| TimeSeriesPoint {
+            timestamp: window.window_start,
+            value: window.avg,
+        })
+        .collect();
+    points.sort_by_key(|point| point.timestamp);
+    lttb(&points, (max_points as usize).max(MIN_POINTS))
+}
+
+/// Largest-Triangle-Three-Buckets: keep the first and last points and, from
+/// each bucket in between, the point forming the largest triangle with the
+/// previously kept point and the average of the next bucket.
+pub fn lttb(points: &[TimeSeriesPoint], threshold: usize) -> Vec<TimeSeriesPoint> {
+    if threshold >= points.len() || threshold < MIN_POINTS {
+        return points.to_vec();
+    }
+
+    let every = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(points[0].clone());
+
+    let mut anchor = 0;
+    for bucket in 0..threshold - 2 {
+        let next_start = ((bucket + 1) as f64 * every) as usize + 1;
+        let next_end = (((bucket + 2) as f64 * every) as usize + 1).min(points.len());
+        let next = &points[next_start..next_end];
+        let avg_x = next.iter().map(|p| p.timestamp as f64).sum::<f64>() / next.len() as f64;
+        let avg_y = next.iter().map(|p| p.value).sum::<f64>() / next.len() as f64;
+
+        let start = (bucket as f64 * every) as usize + 1;
+        let (ax, ay) = (points[anchor].timestamp as f64, points[anchor].value);
+        let mut chosen = start;
+        let mut max_area = -1.0;
+        for (index, point) in points.iter().enumerate().take(next_start).skip(start) {
+            let area = ((ax - avg_x) * (point.value - ay)
+                - (ax - point.timestamp as f64) * (avg_y - ay))
+                .abs();
+            if area > max_area {
+                max_area = area;
+                chosen = index;
+            }
+        }
+
+        sampled.push(points[chosen].clone());
+        anchor = chosen;
+    }
+
+    sampled.push(points[points.len() - 1].clone());
+    sampled
+}
+
+/// Average time-ordered points into fixed windows aligned to `window_ms`,
+/// stamped with the window start.
+pub fn rollup(points: &[TimeSeriesPoint], window_ms: i64) -> Vec<TimeSeriesPoint> {
+    let mut rolled: Vec<TimeSeriesPoint> = Vec::new();
+    let mut count = 0usize;
+    for point in points {
+        let window_start = point.timestamp.div_euclid(window_ms) * window_ms;
+        match rolled.last_mut() {
+            Some(last) if last.timestamp == window_start => {
+                count += 1;
+                last.value += (point.value - last.value) / count as f64;
+            }
+            _ => {
+                rolled.push(TimeSeriesPoint {
+                    timestamp: window_start,
+                    value: point.value,
+                });
+                count = 1;
+            }
+        }
+    }
+    rolled
+}
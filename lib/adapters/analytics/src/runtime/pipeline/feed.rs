@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, watch};
+
+/// Changes sent to a watcher in one message. `cursor` is the sequence
+/// number of the last change read, whether or not the watcher's filter
+/// kept it; passing it back resumes after it. `reset` means changes were
+/// missed, because the cursor fell out of the retained history or came
+/// from an earlier run, and the watcher should re-query before applying
+/// `items`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedBatch<T> {
+    pub cursor: u64,
+    pub items: Vec<T>,
+    pub reset: bool,
+}
+
+struct History<T> {
+    changes: VecDeque<(u64, T)>,
+    last: u64,
+}
+
+/// The latest changes to one kind of record, numbered in order. Watchers
+/// each follow it at their own pace: one that falls too far behind is
+/// reset rather than slowing the publisher down.
+#[derive(Clone)]
+pub struct ChangeFeed<T> {
+    history: Arc<Mutex<History<T>>>,
+    latest: Arc<watch::Sender<u64>>,
+    capacity: usize,
+}
+
+impl<T> std::fmt::Debug for ChangeFeed<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeFeed")
+            .field("cursor", &*self.latest.borrow())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<T: Clone + Send + 'static> ChangeFeed<T> {
+    /// A feed keeping the latest `capacity` changes. Sequence numbers
+    /// start from the current time in microseconds, so a cursor from an
+    /// earlier run always falls before them.
+    pub fn new(capacity: usize) -> Self {
+        let last = chrono::Utc::now().timestamp_micros().max(0) as u64;
+        Self {
+            history: Arc::new(Mutex::new(History {
+                changes: VecDeque::new(),
+                last,
+            })),
+            latest: Arc::new(watch::Sender::new(last)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Sequence number of the latest change.
+    pub fn cursor(&self) -> u64 {
+        *self.latest.borrow()
+    }
+
+    pub fn publish(&self, items: impl IntoIterator<Item = T>) {
+        let Ok(mut history) = self.history.lock() else {
+            tracing::error!("change feed lock poisoned");
+            return;
+        };
+        let before = history.last;
+        for item in items {
+            history.last += 1;
+            let seq = history.last;
+            history.changes.push_back((seq, item));
+        }
+        while history.changes.len() > self.capacity {
+            history.changes.pop_front();
+        }
+        if history.last != before {
+            self.latest.send_replace(history.last);
+        }
+    }
+
+    /// Changes after `cursor`, at most `limit` of them; `None` when there
+    /// are none yet.
+    fn read_after(&self, cursor: u64, limit: usize) -> Option<FeedBatch<T>> {
+        let history = self.history.lock().ok()?;
+        if cursor == history.last {
+            return None;
+        }
+        let oldest = history
+            .changes
+            .front()
+            .map_or(history.last + 1, |(seq, _)| *seq);
+        // Ahead of the feed means the cursor is from another run.
+        let reset = cursor + 1 < oldest || cursor > history.last;
+        let from = if reset { oldest - 1 } else { cursor };
+        let items: Vec<(u64, T)> = history
+            .changes
+            .iter()
+            .filter(|(seq, _)| *seq > from)
+            .take(limit)
+            .cloned()
+            .collect();
+        Some(FeedBatch {
+            cursor: items.last().map_or(history.last, |(seq, _)| *seq),
+            items: items.into_iter().map(|(_, item)| item).collect(),
+            reset,
+        })
+    }
+
+    /// Follow the feed from after `cursor`, or from now without one,
+    /// keeping changes `filter` accepts. At most `send_buffer` batches of
+    /// up to `batch_size` changes queue for the receiver; the watcher
+    /// stops reading while they do and ends when the receiver is dropped.
+    pub fn watch(
+        &self,
+        cursor: Option<u64>,
+        filter: impl Fn(&T) -> bool + Send + 'static,
+        batch_size: usize,
+        send_buffer: usize,
+    ) -> mpsc::Receiver<FeedBatch<T>> {
+        let (tx, rx) = mpsc::channel(send_buffer.max(1));
+        let mut latest = self.latest.subscribe();
+        let mut cursor = cursor.unwrap_or_else(|| self.cursor());
+        let feed = self.clone();
+        tokio::spawn(async move {
+            loop {
+                latest.borrow_and_update();
+                if let Some(mut batch) = feed.read_after(cursor, batch_size.max(1)) {
+                    cursor = batch.cursor;
+                    batch.items.retain(|item| filter(item));
+                    if (batch.reset || !batch.items.is_empty()) && tx.send(batch).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                tokio::select! {
+                    changed = latest.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                    }
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+        rx
+    }
+}
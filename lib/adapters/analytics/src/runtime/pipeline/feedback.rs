@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use phenome_domain::{
+    Anomaly, AnomalyFeedbackConfig, AnomalyFeedbackKind, AnomalyKind, ClusterId, MetricType,
+};
+
+const DAY_MS: i64 = 86_400_000;
+
+/// A series and the model that scored it.
+type TuningKey = (ClusterId, String, MetricType, Option<String>);
+
+/// An anomaly shape an operator marked expected: its kind, whether the
+/// series was above or below its baseline, and the UTC time of day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KnownPattern {
+    kind: AnomalyKind,
+    above: bool,
+    time_of_day: i64,
+}
+
+impl KnownPattern {
+    fn of(anomaly: &Anomaly) -> Self {
+        Self {
+            kind: anomaly.kind,
+            above: anomaly.observed_value >= anomaly.baseline_value,
+            time_of_day: anomaly.detected_at.rem_euclid(DAY_MS),
+        }
+    }
+
+    fn matches(&self, other: &KnownPattern, window_ms: i64) -> bool {
+        let apart = (self.time_of_day - other.time_of_day).rem_euclid(DAY_MS);
+        self.kind == other.kind
+            && self.above == other.above
+            && apart.min(DAY_MS - apart) <= window_ms
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tuning {
+    /// Sigma an anomaly must reach to be reported.
+    floor: f64,
+    known: Vec<KnownPattern>,
+}
+
+/// Tunes detection per series and model from operator feedback: false
+/// positives raise the sigma later anomalies must exceed, confirmations
+/// lower it again, and anomalies marked expected become known patterns
+/// that suppress their daily recurrences.
+#[derive(Debug, Clone)]
+pub struct FeedbackTuner {
+    enabled: bool,
+    margin: f64,
+    expected_window_ms: i64,
+    series: Arc<RwLock<HashMap<TuningKey, Tuning>>>,
+}
+
+impl Default for FeedbackTuner {
+    fn default() -> Self {
+        Self::from_config(&AnomalyFeedbackConfig::default())
+            .expect("default feedback config is valid")
+    }
+}
+
+impl FeedbackTuner {
+    pub fn from_config(config: &AnomalyFeedbackConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            enabled: config.enabled,
+            margin: config.margin,
+            expected_window_ms: config.expected_window_ms()?,
+            series: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Fold the feedback on `anomaly` into its series' tuning. Feedback is
+    /// applied in the order given, so a later verdict overrides an earlier
+    /// one.
+    pub fn learn(&self, anomaly: &Anomaly) {
+        let Some(feedback) = &anomaly.feedback else {
+            return;
+        };
+        let Ok(mut series) = self.series.write() else {
+            tracing::error!("feedback lock poisoned");
+            return;
+        };
+        let tuning = series.entry(key(anomaly)).or_default();
+        let pattern = KnownPattern::of(anomaly);
+        match feedback.kind {
+            AnomalyFeedbackKind::FalsePositive => {
+                tuning.floor = tuning.floor.max(anomaly.deviation_sigma * self.margin);
+            }
+            AnomalyFeedbackKind::Confirmed => {
+                tuning.floor = tuning.floor.min(anomaly.deviation_sigma);
+                tuning
+                    .known
+                    .retain(|known| !known.matches(&pattern, self.expected_window_ms));
+            }
+            AnomalyFeedbackKind::Expected => {
+                if !tuning
+                    .known
+                    .iter()
+                    .any(|known| known.matches(&pattern, self.expected_window_ms))
+                {
+                    tuning.known.push(pattern);
+                }
+            }
+        }
+    }
+
+    /// Drop anomalies that feedback has marked as noise: those below
+    /// their series' raised floor and recurrences of known patterns.
+    pub fn filter(&self, anomalies: Vec<Anomaly>) -> Vec<Anomaly> {
+        if !self.enabled {
+            return anomalies;
+        }
+        let Ok(series) = self.series.read() else {
+            tracing::error!("feedback lock poisoned");
+            return anomalies;
+        };
+        let before = anomalies.len();
+        let kept: Vec<Anomaly> = anomalies
+            .into_iter()
+            .filter(|anomaly| {
+                series.get(&key(anomaly)).is_none_or(|tuning| {
+                    let pattern = KnownPattern::of(anomaly);
+                    anomaly.deviation_sigma >= tuning.floor
+                        && !tuning
+                            .known
+                            .iter()
+                            .any(|known| known.matches(&pattern, self.expected_window_ms))
+                })
+            })
+            .collect();
+        if kept.len() < before {
+            tracing::debug!("Suppressed {} anomalies by feedback", before - kept.len());
+        }
+        kept
+    }
+}
+
+fn key(anomaly: &Anomaly) -> TuningKey {
+    (
+        anomaly.cluster_id.clone(),
+        anomaly.resource_id.clone(),
+        anomaly.metric_type,
+        anomaly.model_id.clone(),
+    )
+}
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use phenome_domain::{
+    Anomaly, Incident, IncidentConfig, IncidentEvent, IncidentEventKind, IncidentStatus, Severity,
+};
+
+#[derive(Debug)]
+struct Tracked {
+    incident: Incident,
+    /// Resources named in the `related_metrics` of member anomalies.
+    related: HashSet<String>,
+}
+
+impl Tracked {
+    fn accepts(&self, anomaly: &Anomaly, merge_window_ms: i64, correlate: bool) -> bool {
+        let incident = &self.incident;
+        if incident.cluster_id != anomaly.cluster_id
+            || (anomaly.detected_at - incident.updated_at).abs() > merge_window_ms
+        {
+            return false;
+        }
+        if incident.resources.contains(&anomaly.resource_id) {
+            return true;
+        }
+        correlate
+            && (self.related.contains(&anomaly.resource_id)
+                || related_resources(anomaly)
+                    .any(|resource| incident.resources.contains(&resource)))
+    }
+}
+
+/// Merges anomalies into open incidents by resource, time proximity and
+/// correlated resources, and resolves incidents that have gone quiet.
+/// Only open incidents are held; callers persist what changes.
+#[derive(Debug, Clone)]
+pub struct IncidentTracker {
+    merge_window_ms: i64,
+    resolve_after_ms: i64,
+    correlate: bool,
+    open: Arc<RwLock<Vec<Tracked>>>,
+}
+
+impl Default for IncidentTracker {
+    fn default() -> Self {
+        Self::from_config(&IncidentConfig::default()).expect("default incident config is valid")
+    }
+}
+
+impl IncidentTracker {
+    pub fn from_config(config: &IncidentConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            merge_window_ms: config.merge_window_ms()?,
+            resolve_after_ms: config.resolve_after_ms()?,
+            correlate: config.correlate,
+            open: Arc::new(RwLock::new(Vec::new())),
+        })
+    }
+
+    /// Resume tracking incidents that were open when the service stopped.
+    pub fn restore(&self, incidents: Vec<Incident>) {
+        let Ok(mut open) = self.open.write() else {
+            tracing::error!("incidents lock poisoned");
+            return;
+        };
+        for incident in incidents.into_iter().filter(Incident::is_open) {
+            if open
+                .iter()
+                .all(|tracked| tracked.incident.id != incident.id)
+            {
+                open.push(Tracked {
+                    incident,
+                    related: HashSet::new(),
+                });
+            }
+        }
+    }
+
+    /// Add anomalies to the incidents they belong to, opening new ones as
+    /// needed. Returns every incident that changed.
+    pub fn observe(&self, anomalies: &[Anomaly]) -> Vec<Incident> {
+        let Ok(mut open) = self.open.write() else {
+            tracing::error!("incidents lock poisoned");
+            return Vec::new();
+        };
+
+        let mut ordered: Vec<&Anomaly> = anomalies.iter().collect();
+        ordered.sort_by_key(|anomaly| anomaly.detected_at);
+
+        let mut changed: Vec<String> = Vec::new();
+        for anomaly in ordered {
+            if open
+                .iter()
+                .any(|tracked| tracked.incident.anomaly_ids.contains(&anomaly.id))
+            {
+                continue;
+            }
+            let target = open
+                .iter_mut()
+                .filter(|tracked| tracked.accepts(anomaly, self.merge_window_ms, self.correlate))
+                .max_by_key(|tracked| tracked.incident.updated_at);
+            let id = match target {
+                Some(tracked) => {
+                    join(tracked, anomaly);
+                    tracked.incident.id.clone()
+                }
+                None => {
+                    let tracked = open_incident(anomaly);
+                    let id = tracked.incident.id.clone();
+                    open.push(tracked);
+                    id
+                }
+            };
+            if !changed.contains(&id) {
+                changed.push(id);
+            }
+        }
+
+        open.iter()
+            .filter(|tracked| changed.contains(&tracked.incident.id))
+            .map(|tracked| tracked.incident.clone())
+            .collect()
+    }
+
+    /// Resolve incidents without an anomaly for `resolve_after` and stop
+    /// tracking them. Returns the resolved incidents.
+    pub fn sweep(&self, now_ms: i64) -> Vec<Incident> {
+        let Ok(mut open) = self.open.write() else {
+            tracing::error!("incidents lock poisoned");
+            return Vec::new();
+        };
+
+        let (quiet, active): (Vec<Tracked>, Vec<Tracked>) = open
+            .drain(..)
+            .partition(|tracked| tracked.incident.updated_at + self.resolve_after_ms <= now_ms);
+        *open = active;
+
+        quiet
+            .into_iter()
+            .map(|tracked| {
+                let mut incident = tracked.incident;
+                let resolved_at = incident.updated_at + self.resolve_after_ms;
+                incident.status = IncidentStatus::Resolved;
+                incident.resolved_at = Some(resolved_at);
+                incident.timeline.push(IncidentEvent {
+                    timestamp: resolved_at,
+                    kind: IncidentEventKind::Resolved,
+                    severity: Severity::Info,
+                    resource_id: None,
+                    anomaly_id: None,
+                    message: format!("no anomalies for {}m", self.resolve_after_ms / 60_000),
+                });
+                incident
+            })
+            .collect()
+    }
+}
+
+fn open_incident(anomaly: &Anomaly) -> Tracked {
+    let incident = Incident {
+        id: uuid::Uuid::new_v4().to_string(),
+        cluster_id: anomaly.cluster_id.clone(),
+        status: IncidentStatus::Open,
+        severity: anomaly.severity,
+        title: format!(
+            "{} anomaly on {}",
+            anomaly.metric_type.as_str(),
+            anomaly.resource_id
+        ),
+        opened_at: anomaly.detected_at,
+        updated_at: anomaly.detected_at,
+        resolved_at: None,
+        resources: vec![anomaly.resource_id.clone()],
+        anomaly_ids: vec![anomaly.id.clone()],
+        timeline: vec![event(anomaly, IncidentEventKind::Opened)],
+    };
+    Tracked {
+        incident,
+        related: related_resources(anomaly).collect(),
+    }
+}
+
+fn join(tracked: &mut Tracked, anomaly: &Anomaly) {
+    let incident = &mut tracked.incident;
+    if !incident.resources.contains(&anomaly.resource_id) {
+        incident.resources.push(anomaly.resource_id.clone());
+    }
+    incident.anomaly_ids.push(anomaly.id.clone());
+    incident.updated_at = incident.updated_at.max(anomaly.detected_at);
+    incident
+        .timeline
+        .push(event(anomaly, IncidentEventKind::Anomaly));
+    if anomaly.severity.rank() > incident.severity.rank() {
+        incident.severity = anomaly.severity;
+        incident
+            .timeline
+            .push(event(anomaly, IncidentEventKind::Escalated));
+    }
+    tracked.related.extend(related_resources(anomaly));
+}
+
+fn event(anomaly: &Anomaly, kind: IncidentEventKind) -> IncidentEvent {
+    IncidentEvent {
+        timestamp: anomaly.detected_at,
+        kind,
+        severity: anomaly.severity,
+        resource_id: Some(anomaly.resource_id.clone()),
+        anomaly_id: Some(anomaly.id.clone()),
+        message: format!(
+            "{} {}: {}",
+            anomaly.resource_id,
+            anomaly.metric_type.as_str(),
+            anomaly.description
+        ),
+    }
+}
+
+/// Resources from `resource:metric` keys in `related_metrics`.
+fn related_resources(anomaly: &Anomaly) -> impl Iterator<Item = String> + '_ {
+    anomaly
+        .related_metrics
+        .iter()
+        .filter_map(|key| key.rsplit_once(':'))
+        .map(|(resource, _)| resource.to_string())
+}
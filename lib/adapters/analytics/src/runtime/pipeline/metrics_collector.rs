@@ -1,16 +1,19 @@
 use anyhow::Result;
 use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::watch;
 use tokio::time::{interval, timeout};
 
-use phenome_domain::{MetricSample, MetricsQuery};
+use phenome_domain::{ClusterId, MetricSample, MetricsQuery};
 
-use crate::cluster_manager::ClusterManager;
+use crate::cluster_manager::{ClusterEvent, ClusterManager};
+use crate::quality::DataQualityMonitor;
 
 #[derive(Debug, Clone)]
 pub struct MetricsCollector {
     cluster_manager: ClusterManager,
     interval: Duration,
+    quality: Option<DataQualityMonitor>,
 }
 
 const MAX_COLLECTION_DURATION: Duration = Duration::from_secs(30);
@@ -20,28 +23,58 @@ impl MetricsCollector {
         Self {
             cluster_manager,
             interval,
+            quality: None,
         }
     }
 
+    /// Feed polled samples to the monitor, so a cluster that stops
+    /// returning samples, or never does, is reported as stale.
+    pub fn with_quality_monitor(mut self, quality: DataQualityMonitor) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
     pub async fn collect_once(&self) -> Result<Vec<MetricSample>> {
         let query = MetricsQuery::default();
         let results = self.cluster_manager.query_all_clusters(query).await;
+        self.observe(&results, chrono::Utc::now().timestamp_millis());
         Ok(results
             .into_iter()
             .flat_map(|(_, result)| result.unwrap_or_default())
             .collect())
     }
 
+    /// Check each cluster's polled samples against its cadence. A cluster
+    /// whose poll failed or came back empty is only tracked, so it goes
+    /// stale once it has been silent too long.
+    pub(crate) fn observe(&self, results: &[(ClusterId, Result<Vec<MetricSample>>)], now_ms: i64) {
+        let Some(quality) = &self.quality else {
+            return;
+        };
+        for (cluster_id, result) in results {
+            match result {
+                Ok(samples) if !samples.is_empty() => {
+                    quality.observe(samples, now_ms);
+                }
+                _ => quality.track_cluster(cluster_id, now_ms),
+            }
+        }
+    }
+
     pub async fn run_polling_loop(&self) -> Result<()> {
         let (_tx, rx) = watch::channel(false);
         self.run_polling_loop_with_shutdown(rx).await
     }
 
+    /// Poll every interval, and at once when a cluster is added rather
+    /// than waiting for the next tick. A removed cluster is dropped from
+    /// the quality monitor so it is not reported as stale.
     pub async fn run_polling_loop_with_shutdown(
         &self,
         mut shutdown: watch::Receiver<bool>,
     ) -> Result<()> {
         let mut tick = interval(self.interval);
+        let mut clusters = self.cluster_manager.subscribe();
         loop {
             tokio::select! {
                 result = shutdown.changed() => {
@@ -49,24 +82,36 @@ impl MetricsCollector {
                         break;
                     }
                 }
-                _ = tick.tick() => {
-                    match timeout(MAX_COLLECTION_DURATION, self.collect_once()).await {
-                        Ok(Ok(_)) => {}
-                        Ok(Err(err)) => {
-                            tracing::error!("Metrics poll failed: {}", err);
-                        }
-                        Err(_) => {
-                            tracing::warn!(
-                                "Metrics poll exceeded {:?} budget",
-                                MAX_COLLECTION_DURATION
-                            );
-                        }
-                    }
-                }
+                _ = tick.tick() => self.poll().await,
+                event = clusters.recv() => match event {
+                    // Missed events may include additions, so poll.
+                    Ok(ClusterEvent::Added(_)) | Err(RecvError::Lagged(_)) => self.poll().await,
+                    Ok(ClusterEvent::Removed(cluster_id)) => self.forget(&cluster_id),
+                    // Never closes: the manager held here keeps the sender.
+                    Err(RecvError::Closed) => {}
+                },
             }
         }
         Ok(())
     }
+
+    fn forget(&self, cluster_id: &ClusterId) {
+        if let Some(quality) = &self.quality {
+            quality.untrack_cluster(cluster_id);
+        }
+    }
+
+    async fn poll(&self) {
+        match timeout(MAX_COLLECTION_DURATION, self.collect_once()).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => {
+                tracing::error!("Metrics poll failed: {}", err);
+            }
+            Err(_) => {
+                tracing::warn!("Metrics poll exceeded {:?} budget", MAX_COLLECTION_DURATION);
+            }
+        }
+    }
 }
 
 use async_trait::async_trait;
@@ -1,3 +1,13 @@
 pub mod aggregator;
 pub mod cache;
+pub mod downsample;
+pub mod feed;
+pub mod feedback;
+pub mod incidents;
 pub mod metrics_collector;
+pub mod quality;
+pub mod streaming;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
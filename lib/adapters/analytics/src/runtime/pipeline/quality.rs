@@ -0,0 +1,328 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use phenome_domain::{
+    ClusterId, DataQualityConfig, DataQualityFilter, DataQualityIssue, DataQualityIssueKind,
+    MetricSample, MetricType, Severity,
+};
+
+const CADENCE_WINDOW: usize = 32;
+const MAX_RECENT_ISSUES: usize = 512;
+const RECENT_ISSUE_TTL_MS: i64 = 60 * 60 * 1000;
+
+type SeriesKey = (ClusterId, String, MetricType);
+
+#[derive(Debug, Default)]
+struct SeriesState {
+    last_timestamp: i64,
+    intervals: VecDeque<i64>,
+}
+
+impl SeriesState {
+    fn expected_interval(&self) -> Option<i64> {
+        if self.intervals.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<i64> = self.intervals.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    fn push_interval(&mut self, interval: i64) {
+        self.intervals.push_back(interval);
+        while self.intervals.len() > CADENCE_WINDOW {
+            self.intervals.pop_front();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClusterState {
+    tracked_since: i64,
+    last_sample_at: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct MonitorState {
+    series: HashMap<SeriesKey, SeriesState>,
+    clusters: HashMap<ClusterId, ClusterState>,
+    recent: VecDeque<DataQualityIssue>,
+}
+
+/// Tracks per-series and per-cluster collection cadence and reports gaps,
+/// staleness, duplicate timestamps and out-of-order or future samples.
+#[derive(Debug, Clone, Default)]
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    state: Arc<RwLock<MonitorState>>,
+}
+
+impl DataQualityMonitor {
+    pub fn new(config: DataQualityConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(RwLock::new(MonitorState::default())),
+        }
+    }
+
+    /// Start expecting samples from a cluster so that a cluster which never
+    /// reports is flagged as stale.
+    pub fn track_cluster(&self, cluster_id: &ClusterId, now_ms: i64) {
+        let Ok(mut state) = self.state.write() else {
+            tracing::error!("data quality lock poisoned");
+            return;
+        };
+        state
+            .clusters
+            .entry(cluster_id.clone())
+            .or_insert(ClusterState {
+                tracked_since: now_ms,
+                last_sample_at: None,
+            });
+    }
+
+    pub fn untrack_cluster(&self, cluster_id: &ClusterId) {
+        let Ok(mut state) = self.state.write() else {
+            tracing::error!("data quality lock poisoned");
+            return;
+        };
+        state.clusters.remove(cluster_id);
+        state.series.retain(|(cluster, _, _), _| cluster != cluster_id);
+        state.recent.retain(|issue| &issue.cluster_id != cluster_id);
+    }
+
+    /// Check an incoming batch against the known cadence. Returns the issues
+    /// raised by this batch; they are also retained for `issues`.
+    pub fn observe(&self, samples: &[MetricSample], now_ms: i64) -> Vec<DataQualityIssue> {
+        let Ok(mut state) = self.state.write() else {
+            tracing::error!("data quality lock poisoned");
+            return Vec::new();
+        };
+
+        let mut raised = Vec::new();
+        for sample in samples {
+            let expected_default = self.config.default_interval_ms;
+            if sample.timestamp > now_ms + self.config.max_future_skew_ms {
+                raised.push(series_issue(
+                    sample,
+                    DataQualityIssueKind::FutureSample,
+                    Severity::Warning,
+                    now_ms,
+                    None,
+                    expected_default,
+                    format!(
+                        "sample is {}s in the future",
+                        (sample.timestamp - now_ms) / 1000
+                    ),
+                ));
+                continue;
+            }
+
+            let cluster = state
+                .clusters
+                .entry(sample.cluster_id.clone())
+                .or_insert(ClusterState {
+                    tracked_since: now_ms,
+                    last_sample_at: None,
+                });
+            cluster.last_sample_at = Some(
+                cluster
+                    .last_sample_at
+                    .map_or(sample.timestamp, |last| last.max(sample.timestamp)),
+            );
+
+            let key = (
+                sample.cluster_id.clone(),
+                sample.resource_id.clone(),
+                sample.metric_type,
+            );
+            let Some(series) = state.series.get_mut(&key) else {
+                state.series.insert(
+                    key,
+                    SeriesState {
+                        last_timestamp: sample.timestamp,
+                        intervals: VecDeque::new(),
+                    },
+                );
+                continue;
+            };
+
+            let last = series.last_timestamp;
+            let expected = series.expected_interval().unwrap_or(expected_default);
+            if sample.timestamp == last {
+                raised.push(series_issue(
+                    sample,
+                    DataQualityIssueKind::DuplicateTimestamp,
+                    Severity::Info,
+                    now_ms,
+                    Some(last),
+                    expected,
+                    format!("duplicate sample at {}", sample.timestamp),
+                ));
+                continue;
+            }
+            if sample.timestamp < last {
+                raised.push(series_issue(
+                    sample,
+                    DataQualityIssueKind::OutOfOrder,
+                    Severity::Info,
+                    now_ms,
+                    Some(last),
+                    expected,
+                    format!("sample {}ms older than the latest one", last - sample.timestamp),
+                ));
+                continue;
+            }
+
+            let interval = sample.timestamp - last;
+            if series.expected_interval().is_some()
+                && interval as f64 > expected as f64 * self.config.gap_factor
+            {
+                raised.push(series_issue(
+                    sample,
+                    DataQualityIssueKind::Gap,
+                    Severity::Warning,
+                    now_ms,
+                    Some(last),
+                    expected,
+                    format!(
+                        "no samples for {}s, expected every {}s",
+                        interval / 1000,
+                        expected / 1000
+                    ),
+                ));
+            } else {
+                // Gaps are excluded so an outage does not stretch the cadence.
+                series.push_interval(interval);
+            }
+            series.last_timestamp = sample.timestamp;
+        }
+
+        for issue in &raised {
+            state.recent.push_back(issue.clone());
+        }
+        while state.recent.len() > MAX_RECENT_ISSUES {
+            state.recent.pop_front();
+        }
+        raised
+    }
+
+    /// Series and clusters that have stopped reporting as of `now_ms`.
+    /// Series silent past the forget threshold are dropped instead, so pods
+    /// that were deleted or rescheduled stop being reported.
+    pub fn stale(&self, now_ms: i64) -> Vec<DataQualityIssue> {
+        let Ok(mut state) = self.state.write() else {
+            tracing::error!("data quality lock poisoned");
+            return Vec::new();
+        };
+
+        let forget_factor = self.config.forget_factor;
+        let default_interval = self.config.default_interval_ms;
+        state.series.retain(|_, series| {
+            let expected = series.expected_interval().unwrap_or(default_interval);
+            (now_ms - series.last_timestamp) as f64 <= expected as f64 * forget_factor
+        });
+
+        let mut issues = Vec::new();
+        let mut cluster_expected: HashMap<&ClusterId, i64> = HashMap::new();
+        for ((cluster_id, resource_id, metric_type), series) in &state.series {
+            let expected = series
+                .expected_interval()
+                .unwrap_or(self.config.default_interval_ms);
+            let slot = cluster_expected.entry(cluster_id).or_insert(expected);
+            *slot = (*slot).min(expected);
+
+            let silent_for = now_ms - series.last_timestamp;
+            if silent_for as f64 > expected as f64 * self.config.stale_factor {
+                issues.push(DataQualityIssue {
+                    cluster_id: cluster_id.clone(),
+                    resource_id: Some(resource_id.clone()),
+                    metric_type: Some(*metric_type),
+                    kind: DataQualityIssueKind::Stale,
+                    severity: Severity::Warning,
+                    detected_at: now_ms,
+                    last_sample_at: Some(series.last_timestamp),
+                    expected_interval_ms: expected,
+                    message: format!("series silent for {}s", silent_for / 1000),
+                });
+            }
+        }
+
+        for (cluster_id, cluster) in &state.clusters {
+            let expected = cluster_expected
+                .get(cluster_id)
+                .copied()
+                .unwrap_or(self.config.default_interval_ms);
+            let since = cluster.last_sample_at.unwrap_or(cluster.tracked_since);
+            let silent_for = now_ms - since;
+            if silent_for as f64 > expected as f64 * self.config.stale_factor {
+                let message = match cluster.last_sample_at {
+                    Some(_) => format!("no samples from cluster for {}s", silent_for / 1000),
+                    None => format!(
+                        "no samples received since tracking started {}s ago",
+                        silent_for / 1000
+                    ),
+                };
+                issues.push(DataQualityIssue {
+                    cluster_id: cluster_id.clone(),
+                    resource_id: None,
+                    metric_type: None,
+                    kind: DataQualityIssueKind::Stale,
+                    severity: Severity::Critical,
+                    detected_at: now_ms,
+                    last_sample_at: cluster.last_sample_at,
+                    expected_interval_ms: expected,
+                    message,
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Current staleness plus recently raised batch issues, newest first.
+    pub fn issues(&self, filter: &DataQualityFilter, now_ms: i64) -> Vec<DataQualityIssue> {
+        let mut issues = self.stale(now_ms);
+        if let Ok(mut state) = self.state.write() {
+            let cutoff = now_ms - RECENT_ISSUE_TTL_MS;
+            state.recent.retain(|issue| issue.detected_at >= cutoff);
+            issues.extend(state.recent.iter().rev().cloned());
+        } else {
+            tracing::error!("data quality lock poisoned");
+        }
+
+        issues.retain(|issue| {
+            filter
+                .cluster_id
+                .as_ref()
+                .is_none_or(|id| id == &issue.cluster_id)
+                && filter.kind.is_none_or(|kind| kind == issue.kind)
+        });
+        if let Some(limit) = filter.limit {
+            issues.truncate(limit as usize);
+        }
+        issues
+    }
+}
+
+fn series_issue(
+    sample: &MetricSample,
+    kind: DataQualityIssueKind,
+    severity: Severity,
+    now_ms: i64,
+    last_sample_at: Option<i64>,
+    expected_interval_ms: i64,
+    message: String,
+) -> DataQualityIssue {
+    DataQualityIssue {
+        cluster_id: sample.cluster_id.clone(),
+        resource_id: Some(sample.resource_id.clone()),
+        metric_type: Some(sample.metric_type),
+        kind,
+        severity,
+        detected_at: now_ms,
+        last_sample_at,
+        expected_interval_ms,
+        message,
+    }
+}
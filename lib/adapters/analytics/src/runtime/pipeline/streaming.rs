@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use phenome_domain::{
+    Anomaly, AnomalyEvidence, AnomalyKind, ClusterId, MetricSample, MetricType, MlThresholdsConfig,
+    StreamingDetectionConfig, TimeRange, TimeSeriesPoint,
+};
+
+const HOUR_MS: i64 = 3_600_000;
+
+/// A gap this long since an hour bucket's last sample means it is being
+/// visited on a new day.
+const NEW_DAY_GAP_MS: i64 = 12 * HOUR_MS;
+
+/// Days an hour bucket must have been seen on before it replaces the
+/// series-wide statistics.
+const SEASONAL_MIN_DAYS: u32 = 2;
+
+/// Exponentially weighted mean and variance. Until `count` is large the
+/// weight of a new sample is `1 / count`, so early estimates are plain
+/// averages rather than biased toward the first sample.
+#[derive(Debug, Clone, Copy, Default)]
+struct Moments {
+    count: u64,
+    mean: f64,
+    variance: f64,
+    first_seen: i64,
+    last_seen: i64,
+}
+
+impl Moments {
+    fn update(&mut self, timestamp: i64, value: f64, half_life_ms: i64) {
+        self.count += 1;
+        let elapsed = (timestamp - self.last_seen).max(0) as f64;
+        let decay = 1.0 - 0.5f64.powf(elapsed / half_life_ms as f64);
+        let weight = if self.count == 1 {
+            self.first_seen = timestamp;
+            1.0
+        } else {
+            decay.max(1.0 / self.count as f64)
+        };
+        let diff = value - self.mean;
+        self.mean += weight * diff;
+        self.variance = (1.0 - weight) * (self.variance + weight * diff * diff);
+        self.last_seen = timestamp;
+    }
+
+    /// `value` pulled to within `sigmas` standard deviations of the mean,
+    /// so that one outlier does not inflate the variance it is judged by.
+    fn clamp(&self, value: f64, sigmas: f64) -> f64 {
+        let reach = sigmas * self.variance.sqrt();
+        value.clamp(self.mean - reach, self.mean + reach)
+    }
+
+    fn deviation(&self, value: f64) -> Option<f64> {
+        let stddev = self.variance.sqrt();
+        (stddev > f64::EPSILON).then(|| (value - self.mean).abs() / stddev)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct HourBucket {
+    moments: Moments,
+    days: u32,
+}
+
+#[derive(Debug, Clone)]
+struct SeriesState {
+    overall: Moments,
+    hours: Vec<HourBucket>,
+}
+
+type SeriesKey = (ClusterId, String, MetricType);
+
+/// One sample scored against its series' statistics before they learn it.
+struct Scored<'a> {
+    sample: &'a MetricSample,
+    baseline: f64,
+    stddev: f64,
+    deviation: f64,
+    seasonal: bool,
+    window: TimeRange,
+}
+
+/// Scores every ingested sample against incremental per-series statistics,
+/// so anomalies are found as data arrives rather than when a client asks.
+/// A series reports at most one anomaly per batch, for its most deviant
+/// sample.
+#[derive(Debug, Clone)]
+pub struct StreamingDetector {
+    enabled: bool,
+    half_life_ms: i64,
+    seasonal: bool,
+    seasonal_half_life_ms: i64,
+    warmup: u64,
+    sigma_threshold: f64,
+    forget_after_ms: i64,
+    thresholds: MlThresholdsConfig,
+    series: Arc<RwLock<HashMap<SeriesKey, SeriesState>>>,
+}
+
+impl Default for StreamingDetector {
+    fn default() -> Self {
+        Self::from_config(&StreamingDetectionConfig::default())
+            .expect("default streaming config is valid")
+    }
+}
+
+impl StreamingDetector {
+    pub fn from_config(config: &StreamingDetectionConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            enabled: config.enabled,
+            half_life_ms: config.half_life_ms()?,
+            seasonal: config.seasonal,
+            seasonal_half_life_ms: config.seasonal_half_life_ms()?,
+            warmup: config.warmup as u64,
+            sigma_threshold: config.sigma_threshold,
+            forget_after_ms: config.forget_after_ms()?,
+            thresholds: MlThresholdsConfig::default(),
+            series: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Grade anomalies by the confidence thresholds in `ml.thresholds`.
+    /// Which samples are anomalies still follows `sigma_threshold`.
+    pub fn with_thresholds(mut self, thresholds: MlThresholdsConfig) -> Result<Self> {
+        thresholds.validate()?;
+        self.thresholds = thresholds;
+        Ok(self)
+    }
+
+    /// Score `samples` in time order, learning each after it is scored,
+    /// and return the anomalies they raise.
+    pub fn observe(&self, samples: &[MetricSample]) -> Vec<Anomaly> {
+        if !self.enabled || samples.is_empty() {
+            return Vec::new();
+        }
+        let Ok(mut series) = self.series.write() else {
+            tracing::error!("streaming detector lock poisoned");
+            return Vec::new();
+        };
+
+        let mut ordered: Vec<&MetricSample> = samples
+            .iter()
+            .filter(|sample| sample.value.is_finite())
+            .collect();
+        ordered.sort_by_key(|sample| sample.timestamp);
+
+        let mut worst: HashMap<SeriesKey, Scored> = HashMap::new();
+        for sample in ordered {
+            let key = (
+                sample.cluster_id.clone(),
+                sample.resource_id.clone(),
+                sample.metric_type,
+            );
+            let state = series.entry(key.clone()).or_insert_with(|| SeriesState {
+                overall: Moments::default(),
+                hours: vec![HourBucket::default(); if self.seasonal { 24 } else { 0 }],
+            });
+            if let Some(scored) = self.score(state, sample) {
+                let current = worst.get(&key).map(|current| current.deviation);
+                if current.is_none_or(|current| scored.deviation > current) {
+                    worst.insert(key, scored);
+                }
+            }
+            self.learn(state, sample);
+        }
+
+        let Some(newest) = samples.iter().map(|sample| sample.timestamp).max() else {
+            return Vec::new();
+        };
+        series.retain(|_, state| newest - state.overall.last_seen <= self.forget_after_ms);
+
+        let mut anomalies: Vec<Anomaly> = worst
+            .into_values()
+            .map(|scored| self.anomaly(scored))
+            .collect();
+        anomalies.sort_by_key(|anomaly| anomaly.detected_at);
+        anomalies
+    }
+
+    fn score<'a>(&self, state: &SeriesState, sample: &'a MetricSample) -> Option<Scored<'a>> {
+        if state.overall.count < self.warmup {
+            return None;
+        }
+        let hour = state
+            .hours
+            .get(hour_of_day(sample.timestamp))
+            .filter(|bucket| {
+                bucket.days >= SEASONAL_MIN_DAYS && bucket.moments.count >= self.warmup
+            });
+        let moments = hour.map_or(&state.overall, |bucket| &bucket.moments);
+        let deviation = moments.deviation(sample.value)?;
+        (deviation >= self.sigma_threshold).then_some(Scored {
+            sample,
+            baseline: moments.mean,
+            stddev: moments.variance.sqrt(),
+            deviation,
+            seasonal: hour.is_some(),
+            window: TimeRange {
+                start_ms: moments.first_seen,
+                end_ms: moments.last_seen,
+            },
+        })
+    }
+
+    /// Fold `sample` into the statistics, clamped to the threshold once
+    /// they have warmed up.
+    fn learn(&self, state: &mut SeriesState, sample: &MetricSample) {
+        let warm = |moments: &Moments| {
+            if moments.count >= self.warmup {
+                moments.clamp(sample.value, self.sigma_threshold)
+            } else {
+                sample.value
+            }
+        };
+        let value = warm(&state.overall);
+        state
+            .overall
+            .update(sample.timestamp, value, self.half_life_ms);
+        if let Some(bucket) = state.hours.get_mut(hour_of_day(sample.timestamp)) {
+            if bucket.moments.count == 0
+                || sample.timestamp - bucket.moments.last_seen >= NEW_DAY_GAP_MS
+            {
+                bucket.days += 1;
+            }
+            let value = warm(&bucket.moments);
+            bucket
+                .moments
+                .update(sample.timestamp, value, self.seasonal_half_life_ms);
+        }
+    }
+
+    fn anomaly(&self, scored: Scored) -> Anomaly {
+        let sample = scored.sample;
+        let confidence = (scored.deviation / (self.sigma_threshold * 1.5)).min(0.99);
+        let basis = if scored.seasonal { "hourly" } else { "running" };
+        Anomaly {
+            id: format!(
+                "{}-{}-{}",
+                sample.resource_id,
+                sample.metric_type.as_str(),
+                sample.timestamp
+            ),
+            cluster_id: sample.cluster_id.clone(),
+            resource_id: sample.resource_id.clone(),
+            detected_at: sample.timestamp,
+            metric_type: sample.metric_type,
+            severity: self
+                .thresholds
+                .for_metric(sample.metric_type)
+                .severity(confidence),
+            confidence,
+            description: format!(
+                "{:.2} sigma from {basis} baseline {:.2}",
+                scored.deviation, scored.baseline
+            ),
+            baseline_value: scored.baseline,
+            observed_value: sample.value,
+            deviation_sigma: scored.deviation,
+            related_metrics: Vec::new(),
+            root_cause: None,
+            model_id: Some("streaming".to_string()),
+            model_version: None,
+            kind: AnomalyKind::Spike,
+            change_point: None,
+            feedback: None,
+            evidence: Some(AnomalyEvidence {
+                baseline_window: scored.window,
+                expected_low: scored.baseline - self.sigma_threshold * scored.stddev,
+                expected_high: scored.baseline + self.sigma_threshold * scored.stddev,
+                seasonal_expected: scored.seasonal.then_some(scored.baseline),
+                points: vec![TimeSeriesPoint {
+                    timestamp: sample.timestamp,
+                    value: sample.value,
+                }],
+                contributions: Vec::new(),
+            }),
+        }
+    }
+}
+
+fn hour_of_day(timestamp: i64) -> usize {
+    (timestamp.rem_euclid(24 * HOUR_MS) / HOUR_MS) as usize
+}
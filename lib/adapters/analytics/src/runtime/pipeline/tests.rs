@@ -0,0 +1,601 @@
+use std::time::Duration;
+
+use phenome_domain::{
+    Anomaly, AnomalyFeedback, AnomalyFeedbackKind, AnomalyKind, DataQualityConfig,
+    DataQualityFilter, DataQualityIssueKind, DownsampleMethod, IncidentEventKind, IncidentStatus,
+    IngestValidationConfig, MetricSample, MetricType, RejectionReason, ResourceType, Severity,
+    TimeRange, TimeSeriesPoint,
+};
+
+use crate::cluster_manager::ClusterManager;
+
+use super::downsample::{downsample, lttb, rollup};
+use super::feed::ChangeFeed;
+use super::feedback::FeedbackTuner;
+use super::incidents::IncidentTracker;
+use super::metrics_collector::MetricsCollector;
+use super::quality::DataQualityMonitor;
+use super::streaming::StreamingDetector;
+use super::validation::IngestValidator;
+
+fn sample(resource_id: &str, timestamp: i64) -> MetricSample {
+    MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type: ResourceType::Pod,
+        resource_id: resource_id.to_string(),
+        metric_type: MetricType::CpuUsage,
+        timestamp,
+        value: 0.5,
+        unit: "cores".to_string(),
+    }
+}
+
+fn monitor() -> DataQualityMonitor {
+    DataQualityMonitor::new(DataQualityConfig::default())
+}
+
+#[test]
+fn flags_duplicate_and_out_of_order_samples() {
+    let monitor = monitor();
+    monitor.observe(&[sample("pod-a", 10_000), sample("pod-a", 20_000)], 20_000);
+
+    let issues = monitor.observe(&[sample("pod-a", 20_000), sample("pod-a", 15_000)], 20_000);
+
+    let kinds: Vec<_> = issues.iter().map(|issue| issue.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DataQualityIssueKind::DuplicateTimestamp,
+            DataQualityIssueKind::OutOfOrder
+        ]
+    );
+}
+
+#[test]
+fn flags_future_samples() {
+    let monitor = monitor();
+    let issues = monitor.observe(&[sample("pod-a", 500_000)], 0);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, DataQualityIssueKind::FutureSample);
+    assert_eq!(issues[0].severity, Severity::Warning);
+}
+
+#[test]
+fn flags_gap_against_learned_cadence() {
+    let monitor = monitor();
+    let batch: Vec<_> = (0..5).map(|i| sample("pod-a", i * 10_000)).collect();
+    assert!(monitor.observe(&batch, 40_000).is_empty());
+
+    let issues = monitor.observe(&[sample("pod-a", 100_000)], 100_000);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, DataQualityIssueKind::Gap);
+    assert_eq!(issues[0].expected_interval_ms, 10_000);
+    assert_eq!(issues[0].last_sample_at, Some(40_000));
+}
+
+#[test]
+fn reports_stale_series_and_cluster() {
+    let monitor = monitor();
+    let batch: Vec<_> = (0..3).map(|i| sample("pod-a", i * 10_000)).collect();
+    monitor.observe(&batch, 20_000);
+    assert!(monitor.stale(30_000).is_empty());
+
+    let stale = monitor.stale(200_000);
+
+    assert!(
+        stale
+            .iter()
+            .any(|issue| issue.resource_id.as_deref() == Some("pod-a"))
+    );
+    let cluster = stale
+        .iter()
+        .find(|issue| issue.is_cluster_level())
+        .expect("cluster-level issue");
+    assert_eq!(cluster.severity, Severity::Critical);
+}
+
+#[test]
+fn reports_tracked_cluster_that_never_reported() {
+    let monitor = monitor();
+    monitor.track_cluster(&"cluster-2".to_string(), 0);
+
+    let stale = monitor.stale(1_000_000);
+
+    assert_eq!(stale.len(), 1);
+    assert!(stale[0].is_cluster_level());
+    assert_eq!(stale[0].last_sample_at, None);
+
+    monitor.untrack_cluster(&"cluster-2".to_string());
+    assert!(monitor.stale(1_000_000).is_empty());
+}
+
+#[test]
+fn forgets_series_silent_past_the_forget_threshold() {
+    let monitor = monitor();
+    for now in (0..=120_000).step_by(30_000) {
+        monitor.observe(&[sample("pod-a", now), sample("pod-b", now)], now);
+    }
+    for now in (150_000..=2_400_000).step_by(30_000) {
+        monitor.observe(&[sample("pod-a", now)], now);
+    }
+
+    let stale = monitor.stale(400_000);
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].resource_id.as_deref(), Some("pod-b"));
+
+    assert!(monitor.stale(2_400_000).is_empty());
+    assert!(monitor.stale(400_000).is_empty());
+}
+
+#[test]
+fn collector_reports_only_clusters_that_stop_returning_samples() {
+    let quality = monitor();
+    let collector = MetricsCollector::new(ClusterManager::new(), Duration::from_secs(30))
+        .with_quality_monitor(quality.clone());
+
+    for now in (0..=300_000).step_by(30_000) {
+        let polled = vec![
+            ("cluster-1".to_string(), Ok(vec![sample("pod-a", now)])),
+            ("cluster-2".to_string(), Ok(Vec::new())),
+        ];
+        collector.observe(&polled, now);
+    }
+
+    let stale = quality.stale(300_000);
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].cluster_id, "cluster-2");
+    assert!(stale[0].is_cluster_level());
+}
+
+#[test]
+fn filters_issues_by_kind_and_limit() {
+    let monitor = monitor();
+    monitor.observe(&[sample("pod-a", 10_000)], 10_000);
+    monitor.observe(
+        &[
+            sample("pod-a", 10_000),
+            sample("pod-a", 10_000),
+            sample("pod-b", 900_000),
+        ],
+        10_000,
+    );
+
+    let duplicates = monitor.issues(
+        &DataQualityFilter {
+            kind: Some(DataQualityIssueKind::DuplicateTimestamp),
+            ..Default::default()
+        },
+        10_000,
+    );
+    assert_eq!(duplicates.len(), 2);
+
+    let limited = monitor.issues(
+        &DataQualityFilter {
+            limit: Some(1),
+            ..Default::default()
+        },
+        10_000,
+    );
+    assert_eq!(limited.len(), 1);
+}
+
+const NOW_MS: i64 = 1_700_000_000_000;
+
+#[test]
+fn validator_rejects_invalid_samples_with_reasons() {
+    let validator = IngestValidator::new(IngestValidationConfig::default());
+    let mut missing_cluster = sample("pod-a", NOW_MS);
+    missing_cluster.cluster_id.clear();
+    let mut nan = sample("pod-a", NOW_MS);
+    nan.value = f64::NAN;
+    let mut negative = sample("pod-a", NOW_MS);
+    negative.value = -1.0;
+    let seconds = sample("pod-a", NOW_MS / 1000);
+    let mut bad_unit = sample("pod-a", NOW_MS);
+    bad_unit.unit = "furlongs".to_string();
+
+    let (accepted, rejections) = validator.validate(
+        vec![
+            sample("pod-a", NOW_MS),
+            missing_cluster,
+            nan,
+            negative,
+            seconds,
+            bad_unit,
+        ],
+        NOW_MS,
+    );
+
+    assert_eq!(accepted.len(), 1);
+    let reasons: Vec<_> = rejections.iter().map(|r| (r.index, r.reason)).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            (1, RejectionReason::MissingField),
+            (2, RejectionReason::NonFiniteValue),
+            (3, RejectionReason::NegativeValue),
+            (4, RejectionReason::TimestampSkew),
+            (5, RejectionReason::UnknownUnit),
+        ]
+    );
+    assert!(rejections[3].message.contains("seconds"));
+}
+
+#[test]
+fn validator_rejects_byte_units_on_cpu() {
+    let validator = IngestValidator::new(IngestValidationConfig::default());
+    let mut cpu = sample("pod-a", NOW_MS);
+    cpu.value = 2.0;
+    cpu.unit = "Gi".to_string();
+
+    let (accepted, rejections) = validator.validate(vec![cpu], NOW_MS);
+
+    assert!(accepted.is_empty());
+    assert_eq!(rejections[0].reason, RejectionReason::UnknownUnit);
+}
+
+#[test]
+fn validator_normalizes_units() {
+    let validator = IngestValidator::new(IngestValidationConfig::default());
+    let mut cpu = sample("pod-a", NOW_MS);
+    cpu.value = 250.0;
+    cpu.unit = "m".to_string();
+    let mut memory = sample("pod-a", NOW_MS);
+    memory.metric_type = MetricType::MemoryUsage;
+    memory.value = 2.0;
+    memory.unit = "Mi".to_string();
+
+    let (accepted, rejections) = validator.validate(vec![cpu, memory], NOW_MS);
+
+    assert!(rejections.is_empty());
+    assert_eq!(accepted[0].unit, "cores");
+    assert!((accepted[0].value - 0.25).abs() < 1e-9);
+    assert_eq!(accepted[1].unit, "bytes");
+    assert_eq!(accepted[1].value, 2.0 * 1024.0 * 1024.0);
+}
+
+fn points(count: i64, step_ms: i64) -> Vec<TimeSeriesPoint> {
+    (0..count)
+        .map(|i| TimeSeriesPoint {
+            timestamp: i * step_ms,
+            value: (i % 10) as f64,
+        })
+        .collect()
+}
+
+#[test]
+fn lttb_keeps_endpoints_and_peaks() {
+    let mut series = points(1_000, 1_000);
+    series[500].value = 100.0;
+
+    let sampled = lttb(&series, 50);
+
+    assert_eq!(sampled.len(), 50);
+    assert_eq!(sampled[0].timestamp, 0);
+    assert_eq!(sampled[49].timestamp, 999_000);
+    assert!(sampled.iter().any(|point| point.value == 100.0));
+    assert!(
+        sampled
+            .windows(2)
+            .all(|pair| pair[0].timestamp < pair[1].timestamp)
+    );
+}
+
+#[test]
+fn rollup_averages_per_window() {
+    let rolled = rollup(&points(20, 1_000), 10_000);
+
+    assert_eq!(rolled.len(), 2);
+    assert_eq!(rolled[1].timestamp, 10_000);
+    assert!((rolled[0].value - 4.5).abs() < 1e-9);
+}
+
+#[test]
+fn downsample_picks_method_from_budget() {
+    let hour = TimeRange {
+        start_ms: 0,
+        end_ms: 3_600_000,
+    };
+    let (raw, method) = downsample(points(60, 60_000), &hour, 100);
+    assert_eq!((raw.len(), method), (60, DownsampleMethod::Raw));
+
+    let (sampled, method) = downsample(points(3_600, 1_000), &hour, 100);
+    assert_eq!((sampled.len(), method), (100, DownsampleMethod::Lttb));
+
+    let month = TimeRange {
+        start_ms: 0,
+        end_ms: 30 * 24 * 3_600_000,
+    };
+    let (rolled, method) = downsample(points(43_200, 60_000), &month, 100);
+    assert_eq!(method, DownsampleMethod::Rollup);
+    assert_eq!(rolled.len(), 100);
+}
+
+const MINUTE_MS: i64 = 60_000;
+
+fn anomaly(resource_id: &str, detected_at: i64, severity: Severity) -> Anomaly {
+    Anomaly {
+        id: format!("{resource_id}-{detected_at}"),
+        cluster_id: "cluster-1".to_string(),
+        resource_id: resource_id.to_string(),
+        detected_at,
+        metric_type: MetricType::MemoryUsage,
+        severity,
+        confidence: 0.9,
+        description: "3.50 sigma deviation".to_string(),
+        baseline_value: 1.0,
+        observed_value: 2.0,
+        deviation_sigma: 3.5,
+        related_metrics: Vec::new(),
+        root_cause: None,
+        model_id: None,
+        model_version: None,
+        kind: AnomalyKind::Spike,
+        change_point: None,
+        feedback: None,
+        evidence: None,
+    }
+}
+
+#[test]
+fn merges_repeated_anomalies_on_a_resource_into_one_incident() {
+    let tracker = IncidentTracker::default();
+    let leak: Vec<_> = (0..10)
+        .map(|minute| anomaly("shop/api-0", minute * MINUTE_MS, Severity::Warning))
+        .collect();
+
+    let incidents = tracker.observe(&leak);
+    // Re-detecting the same anomalies changes nothing.
+    tracker.observe(&leak);
+    let later = tracker.observe(&[anomaly("shop/api-0", 60 * MINUTE_MS, Severity::Warning)]);
+
+    assert_eq!(incidents.len(), 1);
+    assert_eq!(incidents[0].anomaly_ids.len(), 10);
+    assert_eq!(incidents[0].title, "memory_usage anomaly on shop/api-0");
+    assert_eq!(incidents[0].timeline[0].kind, IncidentEventKind::Opened);
+    assert_eq!(incidents[0].updated_at, 9 * MINUTE_MS);
+    assert_eq!(later.len(), 1);
+    assert_ne!(later[0].id, incidents[0].id);
+}
+
+#[test]
+fn merges_correlated_resources_and_escalates() {
+    let tracker = IncidentTracker::default();
+    tracker.observe(&[anomaly("shop/db-0", 0, Severity::Warning)]);
+    let mut upstream = anomaly("shop/api-0", 2 * MINUTE_MS, Severity::Critical);
+    upstream.related_metrics = vec!["shop/db-0:memory_usage".to_string()];
+
+    let incidents = tracker.observe(&[upstream, anomaly("shop/web-0", MINUTE_MS, Severity::Info)]);
+
+    assert_eq!(incidents.len(), 2);
+    let merged = incidents
+        .iter()
+        .find(|incident| incident.resources.len() == 2)
+        .unwrap();
+    assert_eq!(merged.resources, vec!["shop/db-0", "shop/api-0"]);
+    assert_eq!(merged.severity, Severity::Critical);
+    assert_eq!(
+        merged.timeline.last().unwrap().kind,
+        IncidentEventKind::Escalated
+    );
+}
+
+#[test]
+fn resolves_incidents_after_quiet_period() {
+    let tracker = IncidentTracker::default();
+    tracker.observe(&[anomaly("shop/api-0", 0, Severity::Warning)]);
+    assert!(tracker.sweep(29 * MINUTE_MS).is_empty());
+
+    let resolved = tracker.sweep(45 * MINUTE_MS);
+    let reopened = tracker.observe(&[anomaly("shop/api-0", 46 * MINUTE_MS, Severity::Warning)]);
+
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].status, IncidentStatus::Resolved);
+    assert_eq!(resolved[0].resolved_at, Some(30 * MINUTE_MS));
+    assert_eq!(
+        resolved[0].timeline.last().unwrap().kind,
+        IncidentEventKind::Resolved
+    );
+    assert_ne!(reopened[0].id, resolved[0].id);
+}
+
+/// CPU readings every `step_ms` from midnight: one core, or four during
+/// the 02:00 batch hour, with small deterministic noise.
+fn readings(count: usize, step_ms: i64) -> Vec<MetricSample> {
+    (0..count)
+        .map(|i| {
+            let timestamp = i as i64 * step_ms;
+            let batch = (2..3).contains(&(timestamp / (60 * MINUTE_MS) % 24));
+            MetricSample {
+                value: if batch { 4.0 } else { 1.0 } + 0.05 * ((i * 7 % 5) as f64 - 2.0),
+                ..sample("shop/api-0", timestamp)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn streaming_flags_most_deviant_sample_per_batch_after_warmup() {
+    let mut early = readings(20, MINUTE_MS);
+    early[10].value = 9.0;
+    assert!(StreamingDetector::default().observe(&early).is_empty());
+
+    let detector = StreamingDetector::default();
+    assert!(detector.observe(&readings(60, MINUTE_MS)).is_empty());
+    let mut batch = readings(63, MINUTE_MS).split_off(60);
+    batch[0].value = 2.0;
+    batch[1].value = 3.0;
+    let anomalies = detector.observe(&batch);
+
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].detected_at, 61 * MINUTE_MS);
+    assert_eq!(anomalies[0].model_id.as_deref(), Some("streaming"));
+    assert!(anomalies[0].description.contains("running baseline"));
+}
+
+#[test]
+fn streaming_learns_hour_of_day_pattern() {
+    let step_ms = 5 * MINUTE_MS;
+    let day = (24 * 60 * MINUTE_MS / step_ms) as usize;
+    let detector = StreamingDetector::default();
+    let history = readings(3 * day, step_ms);
+    detector.observe(&history);
+
+    let batch_hour = readings(3 * day + 25, step_ms).split_off(3 * day + 24);
+    let mut off_hour = readings(3 * day + 121, step_ms).split_off(3 * day + 120);
+    off_hour[0].value = 4.0;
+
+    assert!(detector.observe(&batch_hour).is_empty());
+    let anomalies = detector.observe(&off_hour);
+    assert_eq!(anomalies.len(), 1);
+    assert!(anomalies[0].description.contains("hourly baseline"));
+}
+
+fn with_feedback(mut anomaly: Anomaly, kind: AnomalyFeedbackKind) -> Anomaly {
+    anomaly.feedback = Some(AnomalyFeedback {
+        kind,
+        submitted_at: anomaly.detected_at,
+        note: None,
+    });
+    anomaly
+}
+
+fn deviating(resource_id: &str, detected_at: i64, deviation_sigma: f64) -> Anomaly {
+    Anomaly {
+        deviation_sigma,
+        ..anomaly(resource_id, detected_at, Severity::Warning)
+    }
+}
+
+#[test]
+fn feedback_tunes_per_series_sensitivity() {
+    let tuner = FeedbackTuner::default();
+    tuner.learn(&with_feedback(
+        deviating("shop/api-0", 0, 3.5),
+        AnomalyFeedbackKind::FalsePositive,
+    ));
+
+    let ids = |kept: Vec<Anomaly>| -> Vec<String> {
+        kept.into_iter().map(|anomaly| anomaly.id).collect()
+    };
+    let batch = vec![
+        deviating("shop/api-0", MINUTE_MS, 3.8),
+        deviating("shop/api-0", 2 * MINUTE_MS, 4.5),
+        deviating("shop/api-1", 3 * MINUTE_MS, 3.8),
+    ];
+    assert_eq!(
+        ids(tuner.filter(batch.clone())),
+        vec!["shop/api-0-120000", "shop/api-1-180000"]
+    );
+
+    tuner.learn(&with_feedback(
+        deviating("shop/api-0", 4 * MINUTE_MS, 3.6),
+        AnomalyFeedbackKind::Confirmed,
+    ));
+    assert_eq!(tuner.filter(batch).len(), 3);
+}
+
+#[test]
+fn confirmed_sigma_is_reported_again_after_a_false_positive() {
+    let tuner = FeedbackTuner::default();
+    tuner.learn(&with_feedback(
+        deviating("shop/api-0", 0, 3.5),
+        AnomalyFeedbackKind::FalsePositive,
+    ));
+    tuner.learn(&with_feedback(
+        deviating("shop/api-0", MINUTE_MS, 3.6),
+        AnomalyFeedbackKind::Confirmed,
+    ));
+
+    let recurrence = deviating("shop/api-0", 2 * MINUTE_MS, 3.6);
+    assert_eq!(tuner.filter(vec![recurrence]).len(), 1);
+}
+
+#[test]
+fn feedback_suppresses_expected_daily_pattern() {
+    const DAY_MS: i64 = 24 * 60 * MINUTE_MS;
+    let tuner = FeedbackTuner::default();
+    let nightly = deviating("shop/batch-0", 2 * 60 * MINUTE_MS, 5.0);
+    tuner.learn(&with_feedback(nightly, AnomalyFeedbackKind::Expected));
+
+    let next_night = deviating(
+        "shop/batch-0",
+        DAY_MS + 2 * 60 * MINUTE_MS + 10 * MINUTE_MS,
+        6.0,
+    );
+    let morning = deviating("shop/batch-0", DAY_MS + 8 * 60 * MINUTE_MS, 6.0);
+    let drop = Anomaly {
+        observed_value: 0.0,
+        ..next_night.clone()
+    };
+    let kept: Vec<_> = tuner
+        .filter(vec![next_night.clone(), morning.clone(), drop])
+        .into_iter()
+        .map(|anomaly| (anomaly.detected_at, anomaly.observed_value))
+        .collect();
+    assert_eq!(
+        kept,
+        vec![(morning.detected_at, 2.0), (next_night.detected_at, 0.0)]
+    );
+
+    // Confirming a recurrence means the pattern no longer explains it.
+    tuner.learn(&with_feedback(
+        next_night.clone(),
+        AnomalyFeedbackKind::Confirmed,
+    ));
+    assert_eq!(tuner.filter(vec![next_night]).len(), 1);
+}
+
+fn stamps(samples: &[MetricSample]) -> Vec<(&str, i64)> {
+    samples
+        .iter()
+        .map(|sample| (sample.resource_id.as_str(), sample.timestamp))
+        .collect()
+}
+
+#[tokio::test]
+async fn watch_resumes_after_cursor_and_filters() {
+    let feed = ChangeFeed::new(100);
+    let mut live = feed.watch(
+        None,
+        |sample: &MetricSample| sample.resource_id == "pod-a",
+        10,
+        4,
+    );
+
+    feed.publish([sample("pod-a", 1_000), sample("pod-b", 1_000)]);
+    let first = live.recv().await.unwrap();
+    assert!(!first.reset);
+    assert_eq!(stamps(&first.items), vec![("pod-a", 1_000)]);
+    assert_eq!(first.cursor, feed.cursor());
+
+    feed.publish([sample("pod-a", 2_000), sample("pod-a", 3_000)]);
+    drop(live);
+    let mut resumed = feed.watch(Some(first.cursor), |_: &MetricSample| true, 1, 4);
+    let next = resumed.recv().await.unwrap();
+    assert_eq!(stamps(&next.items), vec![("pod-a", 2_000)]);
+    let last = resumed.recv().await.unwrap();
+    assert_eq!(stamps(&last.items), vec![("pod-a", 3_000)]);
+    assert_eq!(last.cursor, feed.cursor());
+}
+
+#[tokio::test]
+async fn watch_resets_when_cursor_is_lost() {
+    let feed = ChangeFeed::new(2);
+    let start = feed.cursor();
+    feed.publish((1..=5).map(|ts| sample("pod-a", ts)));
+
+    let mut behind = feed.watch(Some(start), |_: &MetricSample| true, 10, 4);
+    let batch = behind.recv().await.unwrap();
+    assert!(batch.reset);
+    assert_eq!(stamps(&batch.items), vec![("pod-a", 4), ("pod-a", 5)]);
+
+    // A cursor from a later run of the feed is not trusted either.
+    let mut stale = feed.watch(Some(feed.cursor() + 1_000), |_: &MetricSample| false, 10, 4);
+    let batch = stale.recv().await.unwrap();
+    assert!(batch.reset);
+    assert!(batch.items.is_empty());
+    assert_eq!(batch.cursor, feed.cursor());
+}
@@ -0,0 +1,102 @@
+use phenome_domain::{
+    IngestValidationConfig, MetricSample, RejectionReason, SampleRejection, Unit,
+};
+
+/// Timestamps below this are almost certainly seconds rather than
+/// milliseconds (it is early 1970 in ms, 2001 in seconds).
+const SECONDS_TIMESTAMP_CEILING: i64 = 10_000_000_000;
+
+/// Checks samples before they are stored and rewrites units to the
+/// canonical unit of their metric type.
+#[derive(Debug, Clone, Default)]
+pub struct IngestValidator {
+    config: IngestValidationConfig,
+}
+
+impl IngestValidator {
+    pub fn new(config: IngestValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Split a batch into samples that may be stored and rejections keyed by
+    /// the sample's position in `samples`.
+    pub fn validate(
+        &self,
+        samples: Vec<MetricSample>,
+        now_ms: i64,
+    ) -> (Vec<MetricSample>, Vec<SampleRejection>) {
+        let mut accepted = Vec::with_capacity(samples.len());
+        let mut rejections = Vec::new();
+        for (index, sample) in samples.into_iter().enumerate() {
+            match self.check(sample, now_ms) {
+                Ok(sample) => accepted.push(sample),
+                Err((reason, message)) => rejections.push(SampleRejection {
+                    index,
+                    reason,
+                    message,
+                }),
+            }
+        }
+        (accepted, rejections)
+    }
+
+    fn check(
+        &self,
+        mut sample: MetricSample,
+        now_ms: i64,
+    ) -> Result<MetricSample, (RejectionReason, String)> {
+        if sample.cluster_id.trim().is_empty() {
+            return Err((RejectionReason::MissingField, "cluster_id is empty".into()));
+        }
+        if sample.resource_id.trim().is_empty() {
+            return Err((RejectionReason::MissingField, "resource_id is empty".into()));
+        }
+        if !sample.value.is_finite() {
+            return Err((
+                RejectionReason::NonFiniteValue,
+                format!("value {} is not finite", sample.value),
+            ));
+        }
+        if self.config.reject_negative && sample.value < 0.0 {
+            return Err((
+                RejectionReason::NegativeValue,
+                format!("value {} is negative", sample.value),
+            ));
+        }
+
+        if sample.timestamp > now_ms + self.config.max_future_skew_ms {
+            return Err((
+                RejectionReason::TimestampSkew,
+                format!("timestamp {} is ahead of server time", sample.timestamp),
+            ));
+        }
+        if sample.timestamp < now_ms - self.config.max_past_skew_ms {
+            let hint = if sample.timestamp > 0 && sample.timestamp < SECONDS_TIMESTAMP_CEILING {
+                " (looks like seconds, expected milliseconds)"
+            } else {
+                ""
+            };
+            return Err((
+                RejectionReason::TimestampSkew,
+                format!("timestamp {} is too old{hint}", sample.timestamp),
+            ));
+        }
+
+        if self.config.normalize_units {
+            let unit = Unit::for_metric(sample.metric_type);
+            let Some(value) = unit.normalize(sample.value, &sample.unit) else {
+                return Err((
+                    RejectionReason::UnknownUnit,
+                    format!(
+                        "unit {:?} is not valid for {:?}",
+                        sample.unit, sample.metric_type
+                    ),
+                ));
+            };
+            sample.value = value;
+            sample.unit = unit.as_str().to_string();
+        }
+
+        Ok(sample)
+    }
+}
@@ -6,7 +6,7 @@ pub mod sqlite;
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
-pub use port::StoragePort;
+pub use port::{MetricsPage, StoragePort};
 
 #[cfg(test)]
 mod sqlite_test;
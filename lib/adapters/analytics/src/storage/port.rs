@@ -3,17 +3,84 @@ use async_trait::async_trait;
 
 use phenome_domain::{AggregatedMetric, AggregatedQuery, MetricSample, MetricsQuery};
 
+/// A page of raw samples and the cursor to fetch the next one from, if any
+/// remain.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsPage {
+    pub samples: Vec<MetricSample>,
+    pub next_cursor: Option<i64>,
+}
+
 #[async_trait]
 pub trait StoragePort: Send + Sync {
     async fn insert_metrics(&self, samples: Vec<MetricSample>) -> Result<()>;
     async fn query_metrics(&self, query: MetricsQuery) -> Result<Vec<MetricSample>>;
+    /// Up to `limit` matching samples stored after `cursor`, in storage
+    /// order.
+    async fn query_metrics_page(
+        &self,
+        query: MetricsQuery,
+        cursor: Option<i64>,
+        limit: u32,
+    ) -> Result<MetricsPage>;
     async fn insert_aggregated(&self, metrics: Vec<AggregatedMetric>) -> Result<()>;
     async fn query_aggregated(&self, query: AggregatedQuery) -> Result<Vec<AggregatedMetric>>;
     async fn insert_anomalies(&self, anomalies: Vec<phenome_domain::Anomaly>) -> Result<()>;
+    /// Record feedback on a stored anomaly and return it updated, or `None`
+    /// if no anomaly has that id.
+    async fn set_anomaly_feedback(
+        &self,
+        anomaly_id: String,
+        feedback: phenome_domain::AnomalyFeedback,
+    ) -> Result<Option<phenome_domain::Anomaly>>;
+    /// Matching anomalies, most recently detected first.
+    async fn query_anomalies(
+        &self,
+        filter: phenome_domain::AnomalyFilter,
+    ) -> Result<Vec<phenome_domain::Anomaly>>;
+    /// Matching anomalies that have feedback, oldest verdict first.
+    async fn query_anomaly_feedback(
+        &self,
+        filter: phenome_domain::AnomalyFilter,
+    ) -> Result<Vec<phenome_domain::Anomaly>>;
+    async fn upsert_incidents(&self, incidents: Vec<phenome_domain::Incident>) -> Result<()>;
+    /// Matching incidents, most recently updated first.
+    async fn query_incidents(
+        &self,
+        filter: phenome_domain::IncidentFilter,
+    ) -> Result<Vec<phenome_domain::Incident>>;
+    /// Insert recommendations or replace them by id, status included.
+    async fn upsert_recommendations(
+        &self,
+        recommendations: Vec<phenome_domain::Recommendation>,
+    ) -> Result<()>;
+    /// Matching recommendations, newest first.
+    async fn query_recommendations(
+        &self,
+        filter: phenome_domain::RecommendationFilter,
+    ) -> Result<Vec<phenome_domain::Recommendation>>;
     async fn cleanup_retention(&self) -> Result<()>;
 
     // Scheduler methods
     async fn insert_schedule(&self, action: phenome_domain::ScheduledAction) -> Result<()>;
     async fn update_schedule(&self, action: phenome_domain::ScheduledAction) -> Result<()>;
     async fn get_all_schedules(&self) -> Result<Vec<phenome_domain::ScheduledAction>>;
+    async fn insert_schedule_execution(
+        &self,
+        execution: phenome_domain::ScheduleExecution,
+    ) -> Result<()>;
+    /// Matching runs of scheduled actions, most recent first.
+    async fn query_schedule_executions(
+        &self,
+        filter: phenome_domain::ScheduleExecutionFilter,
+    ) -> Result<Vec<phenome_domain::ScheduleExecution>>;
+
+    // Cluster methods
+    /// Insert a cluster or replace it by id, clearing any removal record.
+    async fn upsert_cluster(&self, cluster: phenome_domain::ClusterMetadata) -> Result<()>;
+    /// Delete a cluster and record that it was removed.
+    async fn delete_cluster(&self, id: phenome_domain::ClusterId) -> Result<()>;
+    async fn list_clusters(&self) -> Result<Vec<phenome_domain::ClusterMetadata>>;
+    /// Ids of clusters removed and not added back since.
+    async fn removed_clusters(&self) -> Result<Vec<phenome_domain::ClusterId>>;
 }
@@ -2,13 +2,14 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{Connection, params};
+use rusqlite::types::Value;
+use rusqlite::{Connection, params, params_from_iter};
 use serde::{Serialize, de::DeserializeOwned};
 use std::time::Duration;
 
 use phenome_domain::{AggregatedMetric, AggregatedQuery, MetricSample, MetricsQuery, TimeRange};
 
-use super::port::StoragePort;
+use super::port::{MetricsPage, StoragePort};
 
 const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS metrics_raw (
@@ -28,6 +29,7 @@ CREATE TABLE IF NOT EXISTS metrics_aggregated (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     cluster_id TEXT NOT NULL,
     resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL DEFAULT '',
     metric_type TEXT NOT NULL,
     window_start INTEGER NOT NULL,
     window_duration INTEGER NOT NULL,
@@ -56,11 +58,33 @@ CREATE TABLE IF NOT EXISTS anomalies (
     observed_value REAL NOT NULL,
     deviation_sigma REAL NOT NULL,
     related_metrics TEXT,
-    root_cause TEXT
+    root_cause TEXT,
+    model_id TEXT,
+    model_version INTEGER,
+    kind TEXT,
+    change_point TEXT,
+    feedback TEXT,
+    evidence TEXT
 );
 CREATE INDEX IF NOT EXISTS idx_anomalies_cluster_time
     ON anomalies (cluster_id, detected_at);
 
+CREATE TABLE IF NOT EXISTS incidents (
+    id TEXT PRIMARY KEY,
+    cluster_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    title TEXT NOT NULL,
+    opened_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    resolved_at INTEGER,
+    resources TEXT NOT NULL,
+    anomaly_ids TEXT NOT NULL,
+    timeline TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_incidents_cluster_status
+    ON incidents (cluster_id, status);
+
 CREATE TABLE IF NOT EXISTS recommendations (
     id TEXT PRIMARY KEY,
     cluster_id TEXT NOT NULL,
@@ -92,6 +116,11 @@ CREATE TABLE IF NOT EXISTS clusters (
     namespace_count INTEGER NOT NULL
 );
 
+CREATE TABLE IF NOT EXISTS removed_clusters (
+    id TEXT PRIMARY KEY,
+    removed_at INTEGER NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS scheduled_actions (
     id TEXT PRIMARY KEY,
     execute_at INTEGER NOT NULL,
@@ -102,6 +131,16 @@ CREATE TABLE IF NOT EXISTS scheduled_actions (
 );
 CREATE INDEX IF NOT EXISTS idx_scheduled_actions_execute_at
     ON scheduled_actions (execute_at);
+
+CREATE TABLE IF NOT EXISTS schedule_executions (
+    schedule_id TEXT NOT NULL,
+    recommendation_id TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    finished_at INTEGER NOT NULL,
+    error TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_schedule_executions_schedule
+    ON schedule_executions (schedule_id, finished_at);
 "#;
 
 #[derive(Debug, Clone)]
@@ -161,10 +200,84 @@ impl SqliteStorage {
         configure_sqlite(&conn)?;
         conn.execute_batch(SCHEMA)
             .context("failed to apply sqlite schema")?;
-        Ok(())
+        migrate(&conn)
     }
 }
 
+/// Columns added after a table first shipped; `CREATE TABLE IF NOT EXISTS`
+/// leaves existing databases without them.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("anomalies", "model_id", "TEXT"),
+    ("anomalies", "model_version", "INTEGER"),
+    ("anomalies", "kind", "TEXT"),
+    ("anomalies", "change_point", "TEXT"),
+    ("anomalies", "feedback", "TEXT"),
+    ("anomalies", "evidence", "TEXT"),
+    (
+        "metrics_aggregated",
+        "resource_id",
+        "TEXT NOT NULL DEFAULT ''",
+    ),
+];
+
+const ANOMALY_COLUMNS: &str = "id, cluster_id, resource_id, detected_at, metric_type, severity, confidence, description, baseline_value, observed_value, deviation_sigma, related_metrics, root_cause, model_id, model_version, kind, change_point, feedback, evidence";
+
+fn migrate(conn: &Connection) -> Result<()> {
+    for (table, column, kind) in ADDED_COLUMNS {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .iter()
+            .any(|name| name == column);
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {kind}"))
+                .with_context(|| format!("failed to add {table}.{column}"))?;
+        }
+    }
+    unique_aggregate_windows(conn)
+}
+
+/// Aggregates used to be inserted once per ingest batch, leaving several
+/// partial rows per window. Merge them, then index windows so inserts
+/// merge into the existing row.
+fn unique_aggregate_windows(conn: &Connection) -> Result<()> {
+    let indexed: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'idx_metrics_agg_series_window')",
+        [],
+        |row| row.get(0),
+    )?;
+    if indexed {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "BEGIN;
+         CREATE TEMP TABLE merged_aggregates AS
+             SELECT cluster_id, resource_type, resource_id, metric_type, window_start, window_duration,
+                 SUM(count) AS count, SUM(sum) AS sum, MIN(min) AS min, MAX(max) AS max,
+                 SUM(sum) / SUM(count) AS avg,
+                 SUM(p50 * count) / SUM(count) AS p50,
+                 SUM(p95 * count) / SUM(count) AS p95,
+                 SUM(p99 * count) / SUM(count) AS p99
+             FROM metrics_aggregated
+             GROUP BY cluster_id, resource_type, resource_id, metric_type, window_duration, window_start
+             HAVING COUNT(*) > 1;
+         DELETE FROM metrics_aggregated
+             WHERE (cluster_id, resource_type, resource_id, metric_type, window_duration, window_start) IN
+                 (SELECT cluster_id, resource_type, resource_id, metric_type, window_duration, window_start
+                  FROM merged_aggregates);
+         INSERT INTO metrics_aggregated
+             (cluster_id, resource_type, resource_id, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99)
+             SELECT cluster_id, resource_type, resource_id, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99
+             FROM merged_aggregates;
+         DROP TABLE merged_aggregates;
+         CREATE UNIQUE INDEX idx_metrics_agg_series_window ON metrics_aggregated
+             (cluster_id, resource_type, resource_id, metric_type, window_duration, window_start);
+         COMMIT;",
+    )
+    .context("failed to merge duplicate aggregate windows")
+}
+
 #[async_trait]
 impl StoragePort for SqliteStorage {
     async fn insert_metrics(&self, samples: Vec<MetricSample>) -> Result<()> {
@@ -232,6 +345,73 @@ impl StoragePort for SqliteStorage {
         Ok(filter_metrics(samples, &query))
     }
 
+    async fn query_metrics_page(
+        &self,
+        query: MetricsQuery,
+        cursor: Option<i64>,
+        limit: u32,
+    ) -> Result<MetricsPage> {
+        let mut conditions = Conditions::default();
+        if let Some(cursor) = cursor {
+            conditions.push("id >", cursor);
+        }
+        if let Some(cluster_id) = query.cluster_id {
+            conditions.push("cluster_id =", cluster_id);
+        }
+        if let Some(resource_type) = query.resource_type {
+            conditions.push("resource_type =", encode_enum(&resource_type)?);
+        }
+        conditions.push_any("resource_id", query.resource_ids);
+        conditions.push_any(
+            "metric_type",
+            query
+                .metric_types
+                .iter()
+                .map(encode_enum)
+                .collect::<Result<Vec<_>>>()?,
+        );
+        if let Some(range) = query.time_range {
+            conditions.push("timestamp >=", range.start_ms);
+            conditions.push("timestamp <=", range.end_ms);
+        }
+
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, cluster_id, resource_type, resource_id, metric_type, timestamp, value, unit
+             FROM metrics_raw{} ORDER BY id{}",
+            conditions.where_clause(),
+            limit_clause(Some(limit)),
+        ))?;
+        let rows = stmt.query_map(params_from_iter(conditions.params), |row| {
+            let resource_type_str: String = row.get(2)?;
+            let metric_type_str: String = row.get(4)?;
+            let sample = MetricSample {
+                cluster_id: row.get(1)?,
+                resource_type: decode_enum(&resource_type_str)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                resource_id: row.get(3)?,
+                metric_type: decode_enum(&metric_type_str)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                timestamp: row.get(5)?,
+                value: row.get(6)?,
+                unit: row.get(7)?,
+            };
+            Ok((row.get::<_, i64>(0)?, sample))
+        })?;
+
+        let mut page = MetricsPage::default();
+        let mut last = None;
+        for row in rows {
+            let (id, sample) = row?;
+            last = Some(id);
+            page.samples.push(sample);
+        }
+        if page.samples.len() == limit as usize {
+            page.next_cursor = last;
+        }
+        Ok(page)
+    }
+
     async fn insert_aggregated(&self, metrics: Vec<AggregatedMetric>) -> Result<()> {
         if metrics.is_empty() {
             return Ok(());
@@ -240,15 +420,29 @@ impl StoragePort for SqliteStorage {
         let mut conn = self.pool.get().context("failed to get sqlite connection")?;
         let tx = conn.transaction().context("failed to begin transaction")?;
         {
+            // Each ingest batch carries part of a window; merge it into the
+            // window's row. Percentiles merge as count-weighted means, an
+            // approximation.
             let mut stmt = tx.prepare(
                 "INSERT INTO metrics_aggregated
-                (cluster_id, resource_type, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                (cluster_id, resource_type, resource_id, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                 ON CONFLICT (cluster_id, resource_type, resource_id, metric_type, window_duration, window_start)
+                 DO UPDATE SET
+                    count = count + excluded.count,
+                    sum = sum + excluded.sum,
+                    min = MIN(min, excluded.min),
+                    max = MAX(max, excluded.max),
+                    avg = (sum + excluded.sum) / (count + excluded.count),
+                    p50 = (p50 * count + excluded.p50 * excluded.count) / (count + excluded.count),
+                    p95 = (p95 * count + excluded.p95 * excluded.count) / (count + excluded.count),
+                    p99 = (p99 * count + excluded.p99 * excluded.count) / (count + excluded.count)",
             )?;
             for metric in metrics {
                 stmt.execute(params![
                     metric.cluster_id,
                     encode_enum(&metric.resource_type)?,
+                    metric.resource_id,
                     encode_enum(&metric.metric_type)?,
                     metric.window_start,
                     metric.window_duration.as_millis() as i64,
@@ -268,32 +462,55 @@ impl StoragePort for SqliteStorage {
     }
 
     async fn query_aggregated(&self, query: AggregatedQuery) -> Result<Vec<AggregatedMetric>> {
+        let mut conditions = Conditions::default();
+        if let Some(cluster_id) = query.cluster_id {
+            conditions.push("cluster_id =", cluster_id);
+        }
+        if let Some(resource_type) = query.resource_type {
+            conditions.push("resource_type =", encode_enum(&resource_type)?);
+        }
+        conditions.push_any("resource_id", query.resource_ids);
+        conditions.push_any(
+            "metric_type",
+            query
+                .metric_types
+                .iter()
+                .map(encode_enum)
+                .collect::<Result<Vec<_>>>()?,
+        );
+        if let Some(range) = query.time_range {
+            conditions.push("window_start >=", range.start_ms);
+            conditions.push("window_start <=", range.end_ms);
+        }
+
         let conn = self.pool.get().context("failed to get sqlite connection")?;
-        let mut stmt = conn.prepare(
-            "SELECT cluster_id, resource_type, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99
-             FROM metrics_aggregated",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            let duration_ms: i64 = row.get(4)?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT cluster_id, resource_type, resource_id, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99
+             FROM metrics_aggregated{} ORDER BY window_start, id",
+            conditions.where_clause(),
+        ))?;
+        let rows = stmt.query_map(params_from_iter(conditions.params), |row| {
+            let duration_ms: i64 = row.get(5)?;
             let resource_type_str: String = row.get(1)?;
-            let metric_type_str: String = row.get(2)?;
+            let metric_type_str: String = row.get(3)?;
 
             Ok(AggregatedMetric {
                 cluster_id: row.get(0)?,
                 resource_type: decode_enum(&resource_type_str)
                     .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                resource_id: row.get(2)?,
                 metric_type: decode_enum(&metric_type_str)
                     .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
-                window_start: row.get(3)?,
+                window_start: row.get(4)?,
                 window_duration: Duration::from_millis(duration_ms.max(0) as u64),
-                count: row.get::<_, i64>(5)? as u64,
-                sum: row.get(6)?,
-                min: row.get(7)?,
-                max: row.get(8)?,
-                avg: row.get(9)?,
-                p50: row.get(10)?,
-                p95: row.get(11)?,
-                p99: row.get(12)?,
+                count: row.get::<_, i64>(6)? as u64,
+                sum: row.get(7)?,
+                min: row.get(8)?,
+                max: row.get(9)?,
+                avg: row.get(10)?,
+                p50: row.get(11)?,
+                p95: row.get(12)?,
+                p99: row.get(13)?,
             })
         })?;
 
@@ -301,8 +518,7 @@ impl StoragePort for SqliteStorage {
         for row in rows {
             metrics.push(row?);
         }
-
-        Ok(filter_aggregated(metrics, &query))
+        Ok(metrics)
     }
 
     async fn insert_anomalies(&self, anomalies: Vec<phenome_domain::Anomaly>) -> Result<()> {
@@ -314,9 +530,12 @@ impl StoragePort for SqliteStorage {
         let tx = conn.transaction().context("failed to begin transaction")?;
         {
             let mut stmt = tx.prepare(
+                // Detection reruns rewrite an anomaly; keep the feedback it
+                // was given.
                 "INSERT OR REPLACE INTO anomalies 
-                 (id, cluster_id, resource_id, detected_at, metric_type, severity, confidence, description, baseline_value, observed_value, deviation_sigma, related_metrics, root_cause)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                 (id, cluster_id, resource_id, detected_at, metric_type, severity, confidence, description, baseline_value, observed_value, deviation_sigma, related_metrics, root_cause, model_id, model_version, kind, change_point, feedback, evidence)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                         COALESCE(?18, (SELECT feedback FROM anomalies WHERE id = ?1)), ?19)",
             )?;
             for anomaly in anomalies {
                 stmt.execute(params![
@@ -332,7 +551,22 @@ impl StoragePort for SqliteStorage {
                     anomaly.observed_value,
                     anomaly.deviation_sigma,
                     serde_json::to_string(&anomaly.related_metrics).unwrap_or_default(),
-                    anomaly.root_cause
+                    anomaly.root_cause,
+                    anomaly.model_id,
+                    anomaly.model_version,
+                    encode_enum(&anomaly.kind)?,
+                    anomaly
+                        .change_point
+                        .map(|change| serde_json::to_string(&change))
+                        .transpose()?,
+                    anomaly
+                        .feedback
+                        .map(|feedback| serde_json::to_string(&feedback))
+                        .transpose()?,
+                    anomaly
+                        .evidence
+                        .map(|evidence| serde_json::to_string(&evidence))
+                        .transpose()?
                 ])?;
             }
         }
@@ -340,6 +574,277 @@ impl StoragePort for SqliteStorage {
         Ok(())
     }
 
+    async fn set_anomaly_feedback(
+        &self,
+        anomaly_id: String,
+        feedback: phenome_domain::AnomalyFeedback,
+    ) -> Result<Option<phenome_domain::Anomaly>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let updated = conn.execute(
+            "UPDATE anomalies SET feedback = ?2 WHERE id = ?1",
+            params![anomaly_id, serde_json::to_string(&feedback)?],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        let anomaly = conn.query_row(
+            &format!("SELECT {ANOMALY_COLUMNS} FROM anomalies WHERE id = ?1"),
+            params![anomaly_id],
+            anomaly_from_row,
+        )?;
+        Ok(Some(anomaly))
+    }
+
+    async fn query_anomalies(
+        &self,
+        filter: phenome_domain::AnomalyFilter,
+    ) -> Result<Vec<phenome_domain::Anomaly>> {
+        let mut conditions = Conditions::default();
+        if let Some(cluster_id) = filter.cluster_id {
+            conditions.push("cluster_id =", cluster_id);
+        }
+        if let Some(resource_id) = filter.resource_id {
+            conditions.push("resource_id =", resource_id);
+        }
+        if let Some(metric_type) = filter.metric_type {
+            conditions.push("metric_type =", encode_enum(&metric_type)?);
+        }
+        if let Some(severity) = filter.severity {
+            conditions.push("severity =", encode_enum(&severity)?);
+        }
+        if let Some(range) = filter.time_range {
+            conditions.push("detected_at >=", range.start_ms);
+            conditions.push("detected_at <=", range.end_ms);
+        }
+
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ANOMALY_COLUMNS} FROM anomalies{} ORDER BY detected_at DESC{}",
+            conditions.where_clause(),
+            limit_clause(filter.limit),
+        ))?;
+        let anomalies = stmt
+            .query_map(params_from_iter(conditions.params), anomaly_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(anomalies)
+    }
+
+    async fn query_anomaly_feedback(
+        &self,
+        filter: phenome_domain::AnomalyFilter,
+    ) -> Result<Vec<phenome_domain::Anomaly>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ANOMALY_COLUMNS} FROM anomalies WHERE feedback IS NOT NULL"
+        ))?;
+        let rows = stmt.query_map([], anomaly_from_row)?;
+
+        let mut anomalies = Vec::new();
+        for row in rows {
+            let anomaly = row?;
+            if filter.matches(&anomaly) {
+                anomalies.push(anomaly);
+            }
+        }
+        anomalies.sort_by_key(|anomaly| {
+            anomaly
+                .feedback
+                .as_ref()
+                .map_or(0, |feedback| feedback.submitted_at)
+        });
+        if let Some(limit) = filter.limit {
+            anomalies.truncate(limit as usize);
+        }
+        Ok(anomalies)
+    }
+
+    async fn upsert_incidents(&self, incidents: Vec<phenome_domain::Incident>) -> Result<()> {
+        if incidents.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.pool.get().context("failed to get sqlite connection")?;
+        let tx = conn.transaction().context("failed to begin transaction")?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO incidents
+                 (id, cluster_id, status, severity, title, opened_at, updated_at, resolved_at, resources, anomaly_ids, timeline)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            )?;
+            for incident in incidents {
+                stmt.execute(params![
+                    incident.id,
+                    incident.cluster_id,
+                    encode_enum(&incident.status)?,
+                    encode_enum(&incident.severity)?,
+                    incident.title,
+                    incident.opened_at,
+                    incident.updated_at,
+                    incident.resolved_at,
+                    serde_json::to_string(&incident.resources)?,
+                    serde_json::to_string(&incident.anomaly_ids)?,
+                    serde_json::to_string(&incident.timeline)?
+                ])?;
+            }
+        }
+        tx.commit().context("failed to commit incidents")?;
+        Ok(())
+    }
+
+    async fn query_incidents(
+        &self,
+        filter: phenome_domain::IncidentFilter,
+    ) -> Result<Vec<phenome_domain::Incident>> {
+        let mut conditions = Conditions::default();
+        if let Some(cluster_id) = filter.cluster_id {
+            conditions.push("cluster_id =", cluster_id);
+        }
+        if let Some(status) = filter.status {
+            conditions.push("status =", encode_enum(&status)?);
+        }
+        if let Some(resource_id) = filter.resource_id {
+            let resource_id = conditions.param(resource_id);
+            conditions.clauses.push(format!(
+                "EXISTS (SELECT 1 FROM json_each(resources) WHERE value = {resource_id})"
+            ));
+        }
+        if let Some(range) = filter.time_range {
+            conditions.push("opened_at <=", range.end_ms);
+            let start = conditions.param(range.start_ms);
+            conditions
+                .clauses
+                .push(format!("(resolved_at IS NULL OR resolved_at >= {start})"));
+        }
+
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, cluster_id, status, severity, title, opened_at, updated_at, resolved_at, resources, anomaly_ids, timeline
+             FROM incidents{} ORDER BY updated_at DESC{}",
+            conditions.where_clause(),
+            limit_clause(filter.limit),
+        ))?;
+        let rows = stmt.query_map(params_from_iter(conditions.params), |row| {
+            let status_str: String = row.get(2)?;
+            let severity_str: String = row.get(3)?;
+            let resources: String = row.get(8)?;
+            let anomaly_ids: String = row.get(9)?;
+            let timeline: String = row.get(10)?;
+            Ok(phenome_domain::Incident {
+                id: row.get(0)?,
+                cluster_id: row.get(1)?,
+                status: decode_enum(&status_str)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                severity: decode_enum(&severity_str)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                title: row.get(4)?,
+                opened_at: row.get(5)?,
+                updated_at: row.get(6)?,
+                resolved_at: row.get(7)?,
+                resources: serde_json::from_str(&resources)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                anomaly_ids: serde_json::from_str(&anomaly_ids)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                timeline: serde_json::from_str(&timeline)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+            })
+        })?;
+        let incidents = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(incidents)
+    }
+
+    async fn upsert_recommendations(
+        &self,
+        recommendations: Vec<phenome_domain::Recommendation>,
+    ) -> Result<()> {
+        if recommendations.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.pool.get().context("failed to get sqlite connection")?;
+        let tx = conn.transaction().context("failed to begin transaction")?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO recommendations
+                 (id, cluster_id, created_at, recommendation_type, priority, confidence, title, description, impact_estimate, cost_impact_daily, cost_impact_currency, action, status, status_data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            )?;
+            for rec in recommendations {
+                stmt.execute(params![
+                    rec.id,
+                    rec.cluster_id,
+                    rec.created_at,
+                    encode_enum(&rec.recommendation_type)?,
+                    encode_enum(&rec.priority)?,
+                    rec.confidence,
+                    rec.title,
+                    rec.description,
+                    rec.impact_estimate,
+                    rec.cost_impact.as_ref().map(|cost| cost.daily_change),
+                    rec.cost_impact.as_ref().map(|cost| cost.currency.clone()),
+                    serde_json::to_string(&rec.action)?,
+                    encode_enum(&rec.status.kind())?,
+                    serde_json::to_string(&rec.status)?
+                ])?;
+            }
+        }
+        tx.commit().context("failed to commit recommendations")?;
+        Ok(())
+    }
+
+    async fn query_recommendations(
+        &self,
+        filter: phenome_domain::RecommendationFilter,
+    ) -> Result<Vec<phenome_domain::Recommendation>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, cluster_id, created_at, recommendation_type, priority, confidence, title, description, impact_estimate, cost_impact_daily, cost_impact_currency, action, status_data
+             FROM recommendations ORDER BY created_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let type_str: String = row.get(3)?;
+            let priority_str: String = row.get(4)?;
+            let daily_change: Option<f64> = row.get(9)?;
+            let currency: Option<String> = row.get(10)?;
+            let action: String = row.get(11)?;
+            let status: Option<String> = row.get(12)?;
+            let conversion = |e: anyhow::Error| rusqlite::Error::ToSqlConversionFailure(e.into());
+            let json = |e: serde_json::Error| rusqlite::Error::ToSqlConversionFailure(e.into());
+            Ok(phenome_domain::Recommendation {
+                id: row.get(0)?,
+                cluster_id: row.get(1)?,
+                created_at: row.get(2)?,
+                recommendation_type: decode_enum(&type_str).map_err(conversion)?,
+                priority: decode_enum(&priority_str).map_err(conversion)?,
+                confidence: row.get(5)?,
+                title: row.get(6)?,
+                description: row.get(7)?,
+                impact_estimate: row.get(8)?,
+                cost_impact: daily_change.map(|daily_change| phenome_domain::CostImpact {
+                    daily_change,
+                    currency: currency.unwrap_or_default(),
+                }),
+                action: serde_json::from_str(&action).map_err(json)?,
+                status: status
+                    .map(|status| serde_json::from_str(&status))
+                    .transpose()
+                    .map_err(json)?
+                    .unwrap_or(phenome_domain::RecommendationStatus::Pending),
+            })
+        })?;
+
+        let mut recommendations = Vec::new();
+        for row in rows {
+            let rec = row?;
+            if filter.matches(&rec) {
+                recommendations.push(rec);
+            }
+        }
+        if let Some(limit) = filter.limit {
+            recommendations.truncate(limit as usize);
+        }
+        Ok(recommendations)
+    }
+
     async fn cleanup_retention(&self) -> Result<()> {
         self.run_retention_cleanup(chrono::Utc::now().timestamp_millis())
     }
@@ -401,6 +906,185 @@ impl StoragePort for SqliteStorage {
         }
         Ok(actions)
     }
+
+    async fn insert_schedule_execution(
+        &self,
+        execution: phenome_domain::ScheduleExecution,
+    ) -> Result<()> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        conn.execute(
+            "INSERT INTO schedule_executions (schedule_id, recommendation_id, started_at, finished_at, error)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                execution.schedule_id,
+                execution.recommendation_id,
+                execution.started_at,
+                execution.finished_at,
+                execution.error,
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn query_schedule_executions(
+        &self,
+        filter: phenome_domain::ScheduleExecutionFilter,
+    ) -> Result<Vec<phenome_domain::ScheduleExecution>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT schedule_id, recommendation_id, started_at, finished_at, error
+             FROM schedule_executions ORDER BY finished_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(phenome_domain::ScheduleExecution {
+                schedule_id: row.get(0)?,
+                recommendation_id: row.get(1)?,
+                started_at: row.get(2)?,
+                finished_at: row.get(3)?,
+                error: row.get(4)?,
+            })
+        })?;
+
+        let mut executions = Vec::new();
+        for row in rows {
+            let execution = row?;
+            if filter.matches(&execution) {
+                executions.push(execution);
+            }
+        }
+        if let Some(limit) = filter.limit {
+            executions.truncate(limit as usize);
+        }
+        Ok(executions)
+    }
+
+    async fn upsert_cluster(&self, cluster: phenome_domain::ClusterMetadata) -> Result<()> {
+        let mut conn = self.pool.get().context("failed to get sqlite connection")?;
+        let tx = conn.transaction().context("failed to begin transaction")?;
+        tx.execute(
+            "DELETE FROM removed_clusters WHERE id = ?1",
+            params![cluster.id],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO clusters
+             (id, name, context, api_server, health_status, last_seen, pod_count, node_count, namespace_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                cluster.id,
+                cluster.name,
+                cluster.context,
+                cluster.api_server,
+                encode_enum(&cluster.health_status)?,
+                cluster.last_seen,
+                cluster.pod_count,
+                cluster.node_count,
+                cluster.namespace_count,
+            ],
+        )?;
+        tx.commit().context("failed to commit cluster")?;
+        Ok(())
+    }
+
+    async fn delete_cluster(&self, id: phenome_domain::ClusterId) -> Result<()> {
+        let mut conn = self.pool.get().context("failed to get sqlite connection")?;
+        let tx = conn.transaction().context("failed to begin transaction")?;
+        tx.execute("DELETE FROM clusters WHERE id = ?1", params![id])?;
+        tx.execute(
+            "INSERT OR REPLACE INTO removed_clusters (id, removed_at) VALUES (?1, ?2)",
+            params![id, chrono::Utc::now().timestamp_millis()],
+        )?;
+        tx.commit().context("failed to commit cluster removal")?;
+        Ok(())
+    }
+
+    async fn removed_clusters(&self) -> Result<Vec<phenome_domain::ClusterId>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare("SELECT id FROM removed_clusters")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ids)
+    }
+
+    async fn list_clusters(&self) -> Result<Vec<phenome_domain::ClusterMetadata>> {
+        let conn = self.pool.get().context("failed to get sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, context, api_server, health_status, last_seen, pod_count, node_count, namespace_count
+             FROM clusters ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let health_str: String = row.get(4)?;
+            Ok(phenome_domain::ClusterMetadata {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                context: row.get(2)?,
+                api_server: row.get(3)?,
+                health_status: decode_enum(&health_str)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?,
+                last_seen: row.get(5)?,
+                pod_count: row.get(6)?,
+                node_count: row.get(7)?,
+                namespace_count: row.get(8)?,
+            })
+        })?;
+
+        let mut clusters = Vec::new();
+        for row in rows {
+            clusters.push(row?);
+        }
+        Ok(clusters)
+    }
+}
+
+fn anomaly_from_row(row: &rusqlite::Row) -> rusqlite::Result<phenome_domain::Anomaly> {
+    let metric_type_str: String = row.get(4)?;
+    let severity_str: String = row.get(5)?;
+    let related_metrics: Option<String> = row.get(11)?;
+    let kind_str: Option<String> = row.get(15)?;
+    let change_point: Option<String> = row.get(16)?;
+    let feedback: Option<String> = row.get(17)?;
+    let evidence: Option<String> = row.get(18)?;
+    let conversion = |e: anyhow::Error| rusqlite::Error::ToSqlConversionFailure(e.into());
+    let json = |e: serde_json::Error| rusqlite::Error::ToSqlConversionFailure(e.into());
+    Ok(phenome_domain::Anomaly {
+        id: row.get(0)?,
+        cluster_id: row.get(1)?,
+        resource_id: row.get(2)?,
+        detected_at: row.get(3)?,
+        metric_type: decode_enum(&metric_type_str).map_err(conversion)?,
+        severity: decode_enum(&severity_str).map_err(conversion)?,
+        confidence: row.get(6)?,
+        description: row.get(7)?,
+        baseline_value: row.get(8)?,
+        observed_value: row.get(9)?,
+        deviation_sigma: row.get(10)?,
+        related_metrics: related_metrics
+            .filter(|related| !related.is_empty())
+            .map(|related| serde_json::from_str(&related))
+            .transpose()
+            .map_err(json)?
+            .unwrap_or_default(),
+        root_cause: row.get(12)?,
+        model_id: row.get(13)?,
+        model_version: row.get(14)?,
+        kind: kind_str
+            .map(|kind| decode_enum(&kind))
+            .transpose()
+            .map_err(conversion)?
+            .unwrap_or_default(),
+        change_point: change_point
+            .map(|change| serde_json::from_str(&change))
+            .transpose()
+            .map_err(json)?,
+        feedback: feedback
+            .map(|feedback| serde_json::from_str(&feedback))
+            .transpose()
+            .map_err(json)?,
+        evidence: evidence
+            .map(|evidence| serde_json::from_str(&evidence))
+            .transpose()
+            .map_err(json)?,
+    })
 }
 
 fn configure_sqlite(conn: &Connection) -> Result<()> {
@@ -410,6 +1094,53 @@ fn configure_sqlite(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Conditions of a filtered query, ANDed together, with their parameters.
+#[derive(Default)]
+struct Conditions {
+    clauses: Vec<String>,
+    params: Vec<Value>,
+}
+
+impl Conditions {
+    /// Add `test ?n`, where `test` is a column and comparison such as
+    /// `"cluster_id ="`.
+    fn push(&mut self, test: &str, value: impl Into<Value>) {
+        let param = self.param(value);
+        self.clauses.push(format!("{test} {param}"));
+    }
+
+    /// Bind `value` and return its placeholder, for clauses `push` cannot
+    /// express.
+    fn param(&mut self, value: impl Into<Value>) -> String {
+        self.params.push(value.into());
+        format!("?{}", self.params.len())
+    }
+
+    /// Add `column IN (...)` over `values`; an empty list adds nothing.
+    fn push_any(&mut self, column: &str, values: Vec<String>) {
+        if values.is_empty() {
+            return;
+        }
+        let placeholders: Vec<_> = values.into_iter().map(|value| self.param(value)).collect();
+        self.clauses
+            .push(format!("{column} IN ({})", placeholders.join(", ")));
+    }
+
+    fn where_clause(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+fn limit_clause(limit: Option<u32>) -> String {
+    limit
+        .map(|limit| format!(" LIMIT {limit}"))
+        .unwrap_or_default()
+}
+
 fn encode_enum<T: Serialize>(value: &T) -> Result<String> {
     let json = serde_json::to_value(value)?;
     match json {
@@ -453,32 +1184,6 @@ fn matches_metrics_query(sample: &MetricSample, query: &MetricsQuery) -> bool {
             .map_or(true, |range| timestamp_in_range(sample.timestamp, range))
 }
 
-fn filter_aggregated(
-    mut metrics: Vec<AggregatedMetric>,
-    query: &AggregatedQuery,
-) -> Vec<AggregatedMetric> {
-    metrics.retain(|metric| {
-        query
-            .cluster_id
-            .as_ref()
-            .map_or(true, |id| id == &metric.cluster_id)
-            && query
-                .resource_type
-                .as_ref()
-                .map_or(true, |resource_type| resource_type == &metric.resource_type)
-            && (query.metric_types.is_empty()
-                || query
-                    .metric_types
-                    .iter()
-                    .any(|metric_type| metric_type == &metric.metric_type))
-            && query
-                .time_range
-                .as_ref()
-                .map_or(true, |range| timestamp_in_range(metric.window_start, range))
-    });
-    metrics
-}
-
 fn timestamp_in_range(timestamp: i64, range: &TimeRange) -> bool {
     if range.end_ms < range.start_ms {
         return false;
@@ -1,5 +1,15 @@
-use phenome_domain::{MetricSample, MetricType, MetricsQuery, ResourceType};
+use std::time::Duration;
 
+use phenome_domain::{
+    AggregatedQuery, Anomaly, AnomalyEvidence, AnomalyFeedback, AnomalyFeedbackKind, AnomalyFilter,
+    AnomalyKind, ChangePoint, ClusterHealth, ClusterMetadata, FeatureContribution, Incident,
+    IncidentEvent, IncidentEventKind, IncidentFilter, IncidentStatus, MetricSample, MetricType,
+    MetricsQuery, Priority, Recommendation, RecommendationAction, RecommendationFilter,
+    RecommendationStatus, RecommendationStatusKind, RecommendationType, ResourceType,
+    ScheduleExecution, ScheduleExecutionFilter, Severity, TimeRange, TimeSeriesPoint,
+};
+
+use crate::aggregator::Aggregator;
 use crate::storage::port::StoragePort;
 use crate::storage::sqlite::SqliteStorage;
 
@@ -36,3 +46,612 @@ async fn sqlite_inserts_and_queries_metrics() {
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].resource_id, "pod-a");
 }
+
+#[tokio::test]
+async fn sqlite_pages_through_matching_metrics() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+    let sample = |resource_id: &str, metric_type, timestamp| MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type: ResourceType::Pod,
+        resource_id: resource_id.to_string(),
+        metric_type,
+        timestamp,
+        value: 0.5,
+        unit: "cores".to_string(),
+    };
+    storage
+        .insert_metrics(vec![
+            sample("pod-a", MetricType::CpuUsage, 1_000),
+            sample("pod-b", MetricType::CpuUsage, 2_000),
+            sample("pod-a", MetricType::MemoryUsage, 3_000),
+            sample("pod-a", MetricType::CpuUsage, 4_000),
+            sample("pod-a", MetricType::CpuUsage, 5_000),
+            sample("pod-a", MetricType::CpuUsage, 9_000),
+        ])
+        .await
+        .unwrap();
+    let query = MetricsQuery {
+        cluster_id: Some("cluster-1".to_string()),
+        resource_type: None,
+        resource_ids: vec!["pod-a".to_string()],
+        metric_types: vec![MetricType::CpuUsage],
+        time_range: Some(TimeRange {
+            start_ms: 0,
+            end_ms: 6_000,
+        }),
+    };
+
+    let mut timestamps = Vec::new();
+    let mut cursor = None;
+    let mut pages = 0;
+    loop {
+        let page = storage
+            .query_metrics_page(query.clone(), cursor, 2)
+            .await
+            .unwrap();
+        pages += 1;
+        timestamps.extend(page.samples.iter().map(|sample| sample.timestamp));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(timestamps, [1_000, 4_000, 5_000]);
+    assert_eq!(pages, 2);
+}
+
+#[tokio::test]
+async fn sqlite_queries_aggregates_per_resource() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+    let sample = |resource_id: &str, timestamp, value| MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type: ResourceType::Pod,
+        resource_id: resource_id.to_string(),
+        metric_type: MetricType::CpuUsage,
+        timestamp,
+        value,
+        unit: "cores".to_string(),
+    };
+    let samples = vec![
+        sample("pod-a", 1_000, 0.5),
+        sample("pod-a", 2_000, 1.5),
+        sample("pod-b", 1_000, 4.0),
+        sample("pod-a", 3_601_000, 2.0),
+    ];
+    let aggregates = Aggregator::new()
+        .aggregate_window(&samples, Duration::from_secs(3600))
+        .unwrap();
+    storage.insert_aggregated(aggregates).await.unwrap();
+
+    let query = |resource_ids: Vec<String>, time_range| AggregatedQuery {
+        cluster_id: Some("cluster-1".to_string()),
+        resource_type: Some(ResourceType::Pod),
+        resource_ids,
+        metric_types: vec![MetricType::CpuUsage],
+        window_duration: Duration::from_secs(3600),
+        time_range,
+    };
+    let windows = storage
+        .query_aggregated(query(vec!["pod-a".to_string()], None))
+        .await
+        .unwrap();
+    let stats: Vec<_> = windows
+        .iter()
+        .map(|window| {
+            (
+                window.resource_id.as_str(),
+                window.window_start,
+                window.count,
+                window.max,
+            )
+        })
+        .collect();
+    assert_eq!(stats, [("pod-a", 0, 2, 1.5), ("pod-a", 3_600_000, 1, 2.0)]);
+
+    let first_hour = TimeRange {
+        start_ms: 0,
+        end_ms: 3_599_999,
+    };
+    let windows = storage
+        .query_aggregated(query(Vec::new(), Some(first_hour)))
+        .await
+        .unwrap();
+    assert_eq!(windows.len(), 2);
+}
+
+#[tokio::test]
+async fn sqlite_merges_aggregates_into_one_row_per_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let aggregate = "INSERT INTO metrics_aggregated
+        (cluster_id, resource_type, metric_type, window_start, window_duration, count, sum, min, max, avg, p50, p95, p99)
+        VALUES ('cluster-1', 'pod', 'cpu_usage', 0, 3600000, 1, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0)";
+    rusqlite::Connection::open(&db_path)
+        .unwrap()
+        .execute_batch(&format!(
+            "CREATE TABLE metrics_aggregated (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, cluster_id TEXT NOT NULL,
+                resource_type TEXT NOT NULL, metric_type TEXT NOT NULL,
+                window_start INTEGER NOT NULL, window_duration INTEGER NOT NULL,
+                count INTEGER NOT NULL, sum REAL NOT NULL, min REAL NOT NULL, max REAL NOT NULL,
+                avg REAL NOT NULL, p50 REAL NOT NULL, p95 REAL NOT NULL, p99 REAL NOT NULL
+            );
+            {aggregate}; {aggregate};"
+        ))
+        .unwrap();
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    let sample = |timestamp, value| MetricSample {
+        cluster_id: "cluster-1".to_string(),
+        resource_type: ResourceType::Pod,
+        resource_id: String::new(),
+        metric_type: MetricType::CpuUsage,
+        timestamp,
+        value,
+        unit: "cores".to_string(),
+    };
+    for batch in [
+        vec![sample(1_000, 3.0)],
+        vec![sample(2_000, 5.0), sample(3_000, 5.0)],
+    ] {
+        let aggregates = Aggregator::new()
+            .aggregate_window(&batch, Duration::from_secs(3600))
+            .unwrap();
+        storage.insert_aggregated(aggregates).await.unwrap();
+    }
+
+    let windows = storage
+        .query_aggregated(AggregatedQuery {
+            cluster_id: None,
+            resource_type: None,
+            resource_ids: Vec::new(),
+            metric_types: Vec::new(),
+            window_duration: Duration::from_secs(3600),
+            time_range: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(windows.len(), 1);
+    let window = &windows[0];
+    assert_eq!((window.count, window.min, window.max), (5, 1.0, 5.0));
+    assert!((window.sum - 15.0).abs() < 1e-9);
+    assert!((window.avg - 3.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn sqlite_adds_model_columns_to_existing_anomalies_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    rusqlite::Connection::open(&db_path)
+        .unwrap()
+        .execute_batch(
+            "CREATE TABLE anomalies (
+                id TEXT PRIMARY KEY, cluster_id TEXT NOT NULL, resource_id TEXT NOT NULL,
+                detected_at INTEGER NOT NULL, metric_type TEXT NOT NULL, severity TEXT NOT NULL,
+                confidence REAL NOT NULL, description TEXT NOT NULL, baseline_value REAL NOT NULL,
+                observed_value REAL NOT NULL, deviation_sigma REAL NOT NULL,
+                related_metrics TEXT, root_cause TEXT
+            );",
+        )
+        .unwrap();
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    storage
+        .insert_anomalies(vec![Anomaly {
+            id: "pod-a-1000".to_string(),
+            cluster_id: "cluster-1".to_string(),
+            resource_id: "pod-a".to_string(),
+            detected_at: 1_000,
+            metric_type: MetricType::CpuUsage,
+            severity: Severity::Critical,
+            confidence: 0.95,
+            description: "4.00 sigma deviation".to_string(),
+            baseline_value: 1.0,
+            observed_value: 4.0,
+            deviation_sigma: 4.0,
+            related_metrics: Vec::new(),
+            root_cause: None,
+            model_id: Some("anomaly:z_score:cpu_usage:pod-a".to_string()),
+            model_version: Some(2),
+            kind: AnomalyKind::LevelShift,
+            change_point: Some(ChangePoint {
+                changed_at: 1_000,
+                before_mean: 1.0,
+                after_mean: 2.0,
+                before_stddev: 0.1,
+                after_stddev: 0.1,
+                before_slope: 0.0,
+                after_slope: 0.0,
+            }),
+            feedback: None,
+            evidence: None,
+        }])
+        .await
+        .unwrap();
+
+    let (model_id, model_version, kind, change_point): (String, u32, String, String) =
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .query_row(
+                "SELECT model_id, model_version, kind, change_point FROM anomalies",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+    assert_eq!(model_id, "anomaly:z_score:cpu_usage:pod-a");
+    assert_eq!(model_version, 2);
+    assert_eq!(kind, "level_shift");
+    let change_point: ChangePoint = serde_json::from_str(&change_point).unwrap();
+    assert_eq!(change_point.after_mean, 2.0);
+}
+
+fn incident(id: &str, status: IncidentStatus, updated_at: i64) -> Incident {
+    Incident {
+        id: id.to_string(),
+        cluster_id: "cluster-1".to_string(),
+        status,
+        severity: Severity::Warning,
+        title: "memory_usage anomaly on pod-a".to_string(),
+        opened_at: 0,
+        updated_at,
+        resolved_at: (status == IncidentStatus::Resolved).then_some(updated_at + 1_000),
+        resources: vec!["pod-a".to_string()],
+        anomaly_ids: vec![format!("pod-a-{updated_at}")],
+        timeline: vec![IncidentEvent {
+            timestamp: 0,
+            kind: IncidentEventKind::Opened,
+            severity: Severity::Warning,
+            resource_id: Some("pod-a".to_string()),
+            anomaly_id: Some("pod-a-0".to_string()),
+            message: "pod-a memory_usage: 3.50 sigma deviation".to_string(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn sqlite_upserts_and_filters_incidents() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    storage
+        .upsert_incidents(vec![
+            incident("old", IncidentStatus::Resolved, 1_000),
+            incident("new", IncidentStatus::Open, 5_000),
+        ])
+        .await
+        .unwrap();
+    storage
+        .upsert_incidents(vec![incident("new", IncidentStatus::Open, 9_000)])
+        .await
+        .unwrap();
+
+    let all = storage
+        .query_incidents(IncidentFilter::default())
+        .await
+        .unwrap();
+    let ids: Vec<_> = all.iter().map(|incident| incident.id.as_str()).collect();
+    assert_eq!(ids, vec!["new", "old"]);
+    assert_eq!(all[0], incident("new", IncidentStatus::Open, 9_000));
+
+    let active_later = storage
+        .query_incidents(IncidentFilter {
+            resource_id: Some("pod-a".to_string()),
+            time_range: Some(TimeRange {
+                start_ms: 3_000,
+                end_ms: 10_000,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(active_later.len(), 1);
+    assert_eq!(active_later[0].id, "new");
+
+    let resolved = storage
+        .query_incidents(IncidentFilter {
+            cluster_id: Some("cluster-1".to_string()),
+            status: Some(IncidentStatus::Resolved),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].id, "old");
+    let newest = storage
+        .query_incidents(IncidentFilter {
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(newest.len(), 1);
+    assert_eq!(newest[0].id, "new");
+    for unmatched in [
+        IncidentFilter {
+            resource_id: Some("pod-b".to_string()),
+            ..Default::default()
+        },
+        IncidentFilter {
+            cluster_id: Some("cluster-2".to_string()),
+            ..Default::default()
+        },
+    ] {
+        assert!(storage.query_incidents(unmatched).await.unwrap().is_empty());
+    }
+}
+
+fn spike(id: &str, detected_at: i64) -> Anomaly {
+    Anomaly {
+        id: id.to_string(),
+        cluster_id: "cluster-1".to_string(),
+        resource_id: "pod-a".to_string(),
+        detected_at,
+        metric_type: MetricType::CpuUsage,
+        severity: Severity::Warning,
+        confidence: 0.8,
+        description: "4.00 sigma from running baseline 1.00".to_string(),
+        baseline_value: 1.0,
+        observed_value: 4.0,
+        deviation_sigma: 4.0,
+        related_metrics: vec!["pod-a:memory_usage".to_string()],
+        root_cause: None,
+        model_id: Some("streaming".to_string()),
+        model_version: None,
+        kind: AnomalyKind::Spike,
+        change_point: None,
+        feedback: None,
+        evidence: Some(AnomalyEvidence {
+            baseline_window: TimeRange {
+                start_ms: 0,
+                end_ms: detected_at,
+            },
+            expected_low: 0.7,
+            expected_high: 1.3,
+            seasonal_expected: None,
+            points: vec![TimeSeriesPoint {
+                timestamp: detected_at,
+                value: 4.0,
+            }],
+            contributions: vec![FeatureContribution {
+                feature: "value".to_string(),
+                value: 4.0,
+                expected: 1.0,
+                share: 1.0,
+            }],
+        }),
+    }
+}
+
+#[tokio::test]
+async fn sqlite_filters_anomalies_newest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+    let mut other = spike("other", 3_000);
+    other.resource_id = "pod-b".to_string();
+    storage
+        .insert_anomalies(vec![spike("a", 1_000), spike("b", 2_000), other])
+        .await
+        .unwrap();
+    // Redetection replaces the anomaly rather than adding a second row.
+    let mut redetected = spike("a", 1_000);
+    redetected.severity = Severity::Critical;
+    storage.insert_anomalies(vec![redetected]).await.unwrap();
+
+    let all = storage
+        .query_anomalies(AnomalyFilter::default())
+        .await
+        .unwrap();
+    let ids: Vec<_> = all.iter().map(|anomaly| anomaly.id.as_str()).collect();
+    assert_eq!(ids, ["other", "b", "a"]);
+    assert_eq!(all[2].severity, Severity::Critical);
+
+    let filtered = storage
+        .query_anomalies(AnomalyFilter {
+            resource_id: Some("pod-a".to_string()),
+            time_range: Some(TimeRange {
+                start_ms: 0,
+                end_ms: 2_500,
+            }),
+            limit: Some(1),
+            ..AnomalyFilter::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].id, "b");
+}
+
+#[tokio::test]
+async fn sqlite_keeps_anomaly_feedback_across_redetection() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+    storage
+        .insert_anomalies(vec![spike("a", 1_000), spike("b", 2_000)])
+        .await
+        .unwrap();
+
+    let feedback = AnomalyFeedback {
+        kind: AnomalyFeedbackKind::FalsePositive,
+        submitted_at: 5_000,
+        note: Some("deploy".to_string()),
+    };
+    let updated = storage
+        .set_anomaly_feedback("a".to_string(), feedback.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.feedback.as_ref(), Some(&feedback));
+    assert_eq!(updated.related_metrics, vec!["pod-a:memory_usage"]);
+    let evidence = updated.evidence.unwrap();
+    assert_eq!(evidence.baseline_window.end_ms, 1_000);
+    assert_eq!(evidence.contributions[0].feature, "value");
+    assert!(
+        storage
+            .set_anomaly_feedback("missing".to_string(), feedback.clone())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    storage
+        .insert_anomalies(vec![spike("a", 1_000)])
+        .await
+        .unwrap();
+    let with_feedback = storage
+        .query_anomaly_feedback(AnomalyFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(with_feedback.len(), 1);
+    assert_eq!(with_feedback[0].id, "a");
+    assert_eq!(with_feedback[0].feedback, Some(feedback));
+}
+
+fn recommendation(id: &str, created_at: i64) -> Recommendation {
+    Recommendation {
+        id: id.to_string(),
+        cluster_id: "cluster-1".to_string(),
+        created_at,
+        recommendation_type: RecommendationType::ScaleDown,
+        priority: Priority::Low,
+        confidence: 0.7,
+        title: format!("Scale down {id}"),
+        description: String::new(),
+        impact_estimate: "-1 replica".to_string(),
+        cost_impact: None,
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 3,
+            to: 2,
+        },
+        status: RecommendationStatus::Pending,
+    }
+}
+
+#[tokio::test]
+async fn sqlite_persists_recommendation_status() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    storage
+        .upsert_recommendations(vec![recommendation("a", 1_000), recommendation("b", 2_000)])
+        .await
+        .unwrap();
+    let mut dismissed = recommendation("a", 1_000);
+    dismissed.status = RecommendationStatus::Dismissed {
+        reason: "batch job".to_string(),
+    };
+    storage
+        .upsert_recommendations(vec![dismissed])
+        .await
+        .unwrap();
+
+    let all = storage
+        .query_recommendations(RecommendationFilter::default())
+        .await
+        .unwrap();
+    let ids: Vec<_> = all.iter().map(|rec| rec.id.as_str()).collect();
+    assert_eq!(ids, vec!["b", "a"]);
+    assert!(matches!(
+        &all[1].status,
+        RecommendationStatus::Dismissed { reason } if reason == "batch job"
+    ));
+
+    let pending = storage
+        .query_recommendations(RecommendationFilter {
+            status: Some(RecommendationStatusKind::Pending),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, "b");
+}
+
+fn execution(schedule_id: &str, finished_at: i64, error: Option<&str>) -> ScheduleExecution {
+    ScheduleExecution {
+        schedule_id: schedule_id.to_string(),
+        recommendation_id: format!("rec-{schedule_id}"),
+        started_at: finished_at - 500,
+        finished_at,
+        error: error.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn sqlite_records_schedule_executions() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    for run in [
+        execution("a", 1_000, Some("deployment not found")),
+        execution("b", 2_000, None),
+        execution("a", 3_000, None),
+    ] {
+        storage.insert_schedule_execution(run).await.unwrap();
+    }
+
+    let all = storage
+        .query_schedule_executions(ScheduleExecutionFilter::default())
+        .await
+        .unwrap();
+    let finished: Vec<_> = all.iter().map(|run| run.finished_at).collect();
+    assert_eq!(finished, vec![3_000, 2_000, 1_000]);
+
+    let for_a = storage
+        .query_schedule_executions(ScheduleExecutionFilter {
+            schedule_id: Some("a".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(for_a, vec![execution("a", 3_000, None)]);
+}
+
+#[tokio::test]
+async fn sqlite_persists_clusters() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+    let mut prod = ClusterMetadata {
+        id: "prod".to_string(),
+        name: "Production".to_string(),
+        context: "prod".to_string(),
+        api_server: String::new(),
+        health_status: ClusterHealth::Healthy,
+        last_seen: 1_000,
+        pod_count: 0,
+        node_count: 0,
+        namespace_count: 0,
+    };
+    let dev = ClusterMetadata {
+        id: "dev".to_string(),
+        name: "Development".to_string(),
+        context: "dev".to_string(),
+        ..prod.clone()
+    };
+    storage.upsert_cluster(prod.clone()).await.unwrap();
+    storage.upsert_cluster(dev).await.unwrap();
+    prod.health_status = ClusterHealth::Degraded;
+    prod.pod_count = 12;
+    storage.upsert_cluster(prod).await.unwrap();
+    storage.delete_cluster("dev".to_string()).await.unwrap();
+
+    let clusters = storage.list_clusters().await.unwrap();
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].name, "Production");
+    assert_eq!(clusters[0].health_status, ClusterHealth::Degraded);
+    assert_eq!(clusters[0].pod_count, 12);
+    assert_eq!(storage.removed_clusters().await.unwrap(), vec!["dev"]);
+}
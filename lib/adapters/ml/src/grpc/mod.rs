@@ -70,20 +70,15 @@ impl MlServiceTrait for MlGrpcService {
         let prediction = self
             .inner
             .predict_scaling_needs(
-                req.resource_id.clone(),
+                req.cluster_id,
+                req.resource_id,
                 std::time::Duration::from_millis(req.horizon_ms as u64),
             )
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(PredictScalingNeedsResponse {
-            prediction: Some(ml::ScalingPrediction {
-                resource_id: req.resource_id,
-                generated_at: prediction.generated_at,
-                horizon: prediction.horizon.as_millis() as i64,
-                predicted_value: prediction.predicted_value,
-                unit: prediction.unit,
-            }),
+            prediction: Some(prediction.into()),
         }))
     }
 
@@ -94,7 +89,10 @@ impl MlServiceTrait for MlGrpcService {
         let req = request.into_inner();
         let recs = self
             .inner
-            .generate_recommendations(req.cluster_id)
+            .generate_recommendations(
+                req.cluster_id,
+                req.containers.into_iter().map(Into::into).collect(),
+            )
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
@@ -102,6 +100,30 @@ impl MlServiceTrait for MlGrpcService {
             recommendations: recs.into_iter().map(Into::into).collect(),
         }))
     }
+
+    async fn analyze_root_cause(
+        &self,
+        request: Request<AnalyzeRootCauseRequest>,
+    ) -> Result<Response<AnalyzeRootCauseResponse>, Status> {
+        let req = request.into_inner();
+        if req.anomaly_id.is_empty() {
+            return Err(Status::invalid_argument("missing anomaly id"));
+        }
+        let (analysis, anomaly) = self
+            .inner
+            .analyze_root_cause(
+                req.cluster_id,
+                req.anomaly_id,
+                req.dependencies.into_iter().map(Into::into).collect(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(AnalyzeRootCauseResponse {
+            analysis: Some(analysis.into()),
+            anomaly: Some(anomaly.into()),
+        }))
+    }
 }
 
 pub struct GrpcServer;
@@ -117,6 +139,9 @@ impl GrpcServer {
     }
 }
 
+/// Samples per `QueryMetrics` page, a few megabytes at most on the wire.
+const HISTORY_PAGE_SIZE: u32 = 20_000;
+
 #[derive(Debug, Clone)]
 pub struct AnalyticsClient {
     client: analytics::analytics_service_client::AnalyticsServiceClient<tonic::transport::Channel>,
@@ -129,54 +154,103 @@ impl AnalyticsClient {
         Ok(Self { client })
     }
 
+    /// Connect on first request, so the ML service can start before
+    /// analytics-service is reachable.
+    pub fn connect_lazy(endpoint: String) -> Result<Self> {
+        let channel = tonic::transport::Endpoint::from_shared(endpoint)?.connect_lazy();
+        Ok(Self {
+            client: analytics::analytics_service_client::AnalyticsServiceClient::new(channel),
+        })
+    }
+
     pub async fn query_time_series(
         &mut self,
         resource_id: String,
-    ) -> Result<Vec<domain::MetricSample>> {
-        let now = chrono::Utc::now().timestamp_millis();
-        let start = now - 3600 * 1000;
-
+        metric_type: domain::MetricType,
+        range: domain::TimeRange,
+        max_points: Option<u32>,
+    ) -> Result<domain::TimeSeries> {
         let req = analytics::GetTimeSeriesRequest {
             resource_id,
-            metric_type: 1, // MetricType::CpuUsage as i32 (1)? Or use analytics::MetricType::CpuUsage.into()
-            time_range: Some(analytics::TimeRange {
-                start_ms: start,
-                end_ms: now,
-            }),
+            metric_type: i32::from(analytics::MetricType::from(metric_type)),
+            time_range: Some(range.into()),
+            max_points,
         };
 
-        let resp = self.client.get_time_series(req).await?;
-        let inner = resp.into_inner();
+        let inner = self.client.get_time_series(req).await?.into_inner();
+        let mut series: domain::TimeSeries = inner
+            .series
+            .ok_or_else(|| anyhow::anyhow!("missing series"))?
+            .try_into()?;
+        series.downsample = match analytics::DownsampleMethod::try_from(inner.method) {
+            Ok(analytics::DownsampleMethod::Lttb) => domain::DownsampleMethod::Lttb,
+            Ok(analytics::DownsampleMethod::Rollup) => domain::DownsampleMethod::Rollup,
+            _ => domain::DownsampleMethod::Raw,
+        };
+        Ok(series)
+    }
 
-        let mut samples = Vec::new();
-        if let Some(ts) = inner.series {
-            for p in ts.points {
-                samples.push(domain::MetricSample {
-                    cluster_id: ts.cluster_id.clone(),
-                    resource_type: domain::ResourceType::Node, // Infer
-                    resource_id: ts.resource_id.clone(),
-                    metric_type: domain::MetricType::CpuUsage, // Infer
-                    timestamp: p.timestamp,
-                    value: p.value,
-                    unit: ts.unit.clone(), // Use unit from TimeSeries
-                });
-            }
-        }
+    pub async fn get_anomalies(
+        &mut self,
+        filter: domain::AnomalyFilter,
+    ) -> Result<Vec<domain::Anomaly>> {
+        let req = analytics::GetAnomaliesRequest {
+            cluster_id: filter.cluster_id,
+            resource_id: filter.resource_id,
+            metric_type: filter
+                .metric_type
+                .map(|m| i32::from(analytics::MetricType::from(m))),
+            severity: filter
+                .severity
+                .map(|s| i32::from(analytics::Severity::from(s))),
+            time_range: filter.time_range.map(Into::into),
+            limit: filter.limit,
+        };
 
-        Ok(samples)
+        let resp = self.client.get_anomalies(req).await?;
+        resp.into_inner()
+            .anomalies
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
+    pub async fn query_aggregated(
+        &mut self,
+        query: domain::AggregatedQuery,
+    ) -> Result<Vec<domain::AggregatedMetric>> {
+        let req = analytics::QueryAggregatedRequest {
+            cluster_id: query.cluster_id,
+            resource_type: query
+                .resource_type
+                .map(|r| i32::from(analytics::ResourceType::from(r))),
+            metric_types: query
+                .metric_types
+                .into_iter()
+                .map(|m| i32::from(analytics::MetricType::from(m)))
+                .collect(),
+            window_duration_ms: query.window_duration.as_millis() as i64,
+            time_range: query.time_range.map(Into::into),
+            resource_ids: query.resource_ids,
+        };
+
+        let resp = self.client.query_aggregated(req).await?;
+        resp.into_inner()
+            .metrics
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
     }
 
     pub async fn fetch_historical(
         &mut self,
         req: domain::MetricsQuery,
     ) -> Result<Vec<domain::MetricSample>> {
-        let range = req.time_range.map(|r| analytics::TimeRange {
-            start_ms: r.start_ms,
-            end_ms: r.end_ms,
-        });
+        let range = req.time_range.map(Into::into);
 
-        // Use QueryMetrics because GetTimeSeries is singular
-        let proto_req = analytics::QueryMetricsRequest {
+        // Use QueryMetrics because GetTimeSeries is singular, a page at a
+        // time so no response outgrows the message size limit.
+        let mut proto_req = analytics::QueryMetricsRequest {
             resource_type: req
                 .resource_type
                 .map(|r| i32::from(analytics::ResourceType::from(r))),
@@ -188,10 +262,23 @@ impl AnalyticsClient {
                 .map(|m| i32::from(analytics::MetricType::from(m)))
                 .collect(),
             time_range: range,
+            limit: Some(HISTORY_PAGE_SIZE),
+            cursor: None,
         };
 
-        let resp = self.client.query_metrics(proto_req).await?;
-        let samples = resp.into_inner().samples;
+        let mut samples = Vec::new();
+        loop {
+            let page = self
+                .client
+                .query_metrics(proto_req.clone())
+                .await?
+                .into_inner();
+            samples.extend(page.samples);
+            match page.next_cursor {
+                Some(cursor) => proto_req.cursor = Some(cursor),
+                None => break,
+            }
+        }
 
         let domain_samples = samples
             .into_iter()
@@ -261,6 +348,7 @@ impl TryFrom<analytics::TimeSeries> for domain::TimeSeries {
                 .try_into()?,
             unit: val.unit,
             points: val.points.into_iter().map(Into::into).collect(),
+            downsample: domain::DownsampleMethod::Raw,
         })
     }
 }
@@ -283,6 +371,16 @@ impl From<analytics::TimeRange> for domain::TimeRange {
     }
 }
 
+impl From<domain::TimeRange> for analytics::TimeRange {
+    fn from(val: domain::TimeRange) -> Self {
+        Self {
+            start_ms: val.start_ms,
+            end_ms: val.end_ms,
+            expression: None,
+        }
+    }
+}
+
 impl From<domain::ResourceType> for analytics::ResourceType {
     fn from(val: domain::ResourceType) -> Self {
         match val {
@@ -294,6 +392,21 @@ impl From<domain::ResourceType> for analytics::ResourceType {
     }
 }
 
+impl TryFrom<analytics::ResourceType> for domain::ResourceType {
+    type Error = anyhow::Error;
+    fn try_from(val: analytics::ResourceType) -> Result<Self, Self::Error> {
+        match val {
+            analytics::ResourceType::Pod => Ok(domain::ResourceType::Pod),
+            analytics::ResourceType::Node => Ok(domain::ResourceType::Node),
+            analytics::ResourceType::Container => Ok(domain::ResourceType::Container),
+            analytics::ResourceType::Service => Ok(domain::ResourceType::Service),
+            analytics::ResourceType::Unspecified => {
+                Err(anyhow::anyhow!("unspecified resource type"))
+            }
+        }
+    }
+}
+
 impl From<domain::MetricType> for analytics::MetricType {
     fn from(val: domain::MetricType) -> Self {
         match val {
@@ -312,7 +425,65 @@ impl TryFrom<analytics::MetricType> for domain::MetricType {
     fn try_from(val: analytics::MetricType) -> Result<Self, Self::Error> {
         match val {
             analytics::MetricType::CpuUsage => Ok(domain::MetricType::CpuUsage),
-            _ => Err(anyhow::anyhow!("unsupported metric type")),
+            analytics::MetricType::MemoryUsage => Ok(domain::MetricType::MemoryUsage),
+            analytics::MetricType::NetworkIn => Ok(domain::MetricType::NetworkIn),
+            analytics::MetricType::NetworkOut => Ok(domain::MetricType::NetworkOut),
+            analytics::MetricType::DiskRead => Ok(domain::MetricType::DiskRead),
+            analytics::MetricType::DiskWrite => Ok(domain::MetricType::DiskWrite),
+            analytics::MetricType::Unspecified => Err(anyhow::anyhow!("unspecified metric type")),
+        }
+    }
+}
+
+impl TryFrom<analytics::AggregatedMetric> for domain::AggregatedMetric {
+    type Error = anyhow::Error;
+    fn try_from(val: analytics::AggregatedMetric) -> Result<Self, Self::Error> {
+        Ok(domain::AggregatedMetric {
+            cluster_id: val.cluster_id,
+            resource_type: analytics::ResourceType::try_from(val.resource_type)
+                .map_err(|_| anyhow::anyhow!("invalid resource type"))?
+                .try_into()?,
+            resource_id: val.resource_id,
+            metric_type: analytics::MetricType::try_from(val.metric_type)
+                .map_err(|_| anyhow::anyhow!("invalid metric type"))?
+                .try_into()?,
+            window_start: val.window_start,
+            window_duration: std::time::Duration::from_millis(val.window_duration_ms.max(0) as u64),
+            count: val.count,
+            sum: val.sum,
+            min: val.min,
+            max: val.max,
+            avg: val.avg,
+            p50: val.p50,
+            p95: val.p95,
+            p99: val.p99,
+        })
+    }
+}
+
+impl From<domain::ScalingPrediction> for ml::ScalingPrediction {
+    fn from(val: domain::ScalingPrediction) -> Self {
+        Self {
+            resource_id: val.resource_id,
+            generated_at: val.generated_at,
+            horizon: val.horizon.as_millis() as i64,
+            predicted_value: val.predicted_value,
+            unit: val.unit,
+            lower_bound: val.lower_bound,
+            upper_bound: val.upper_bound,
+            model: ml::ScalingModel::from(val.model).into(),
+            model_id: val.model_id,
+            model_version: val.model_version,
+        }
+    }
+}
+
+impl From<domain::ScalingModel> for ml::ScalingModel {
+    fn from(val: domain::ScalingModel) -> Self {
+        match val {
+            domain::ScalingModel::MovingAverage => ml::ScalingModel::MovingAverage,
+            domain::ScalingModel::HoltWinters => ml::ScalingModel::HoltWinters,
+            domain::ScalingModel::Arima => ml::ScalingModel::Arima,
         }
     }
 }
@@ -326,18 +497,355 @@ impl From<domain::Anomaly> for analytics::Anomaly {
             cluster_id: val.cluster_id,
             resource_id: val.resource_id,
             detected_at: val.detected_at,
-            // ... map fields
-            ..Default::default()
+            metric_type: analytics::MetricType::from(val.metric_type).into(),
+            severity: analytics::Severity::from(val.severity).into(),
+            confidence: val.confidence,
+            description: val.description,
+            baseline_value: val.baseline_value,
+            observed_value: val.observed_value,
+            deviation_sigma: val.deviation_sigma,
+            related_metrics: val.related_metrics,
+            root_cause: val.root_cause,
+            model_id: val.model_id,
+            model_version: val.model_version,
+            kind: analytics::AnomalyKind::from(val.kind).into(),
+            change_point: val.change_point.map(Into::into),
+            feedback: val.feedback.map(Into::into),
+            evidence: val.evidence.map(Into::into),
+        }
+    }
+}
+
+impl TryFrom<analytics::Anomaly> for domain::Anomaly {
+    type Error = anyhow::Error;
+
+    fn try_from(val: analytics::Anomaly) -> Result<Self, Self::Error> {
+        Ok(domain::Anomaly {
+            id: val.id,
+            cluster_id: val.cluster_id,
+            resource_id: val.resource_id,
+            detected_at: val.detected_at,
+            metric_type: analytics::MetricType::try_from(val.metric_type)?.try_into()?,
+            severity: analytics::Severity::try_from(val.severity)?.try_into()?,
+            confidence: val.confidence,
+            description: val.description,
+            baseline_value: val.baseline_value,
+            observed_value: val.observed_value,
+            deviation_sigma: val.deviation_sigma,
+            related_metrics: val.related_metrics,
+            root_cause: val.root_cause,
+            model_id: val.model_id,
+            model_version: val.model_version,
+            kind: analytics::AnomalyKind::try_from(val.kind)?.into(),
+            change_point: val.change_point.map(Into::into),
+            feedback: val.feedback.map(TryInto::try_into).transpose()?,
+            evidence: val.evidence.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<analytics::AnomalyEvidence> for domain::AnomalyEvidence {
+    type Error = anyhow::Error;
+
+    fn try_from(val: analytics::AnomalyEvidence) -> Result<Self, Self::Error> {
+        Ok(Self {
+            baseline_window: val
+                .baseline_window
+                .ok_or_else(|| anyhow::anyhow!("evidence without a baseline window"))?
+                .into(),
+            expected_low: val.expected_low,
+            expected_high: val.expected_high,
+            seasonal_expected: val.seasonal_expected,
+            points: val.points.into_iter().map(Into::into).collect(),
+            contributions: val
+                .contributions
+                .into_iter()
+                .map(|contribution| domain::FeatureContribution {
+                    feature: contribution.feature,
+                    value: contribution.value,
+                    expected: contribution.expected,
+                    share: contribution.share,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<domain::AnomalyEvidence> for analytics::AnomalyEvidence {
+    fn from(val: domain::AnomalyEvidence) -> Self {
+        Self {
+            baseline_window: Some(val.baseline_window.into()),
+            expected_low: val.expected_low,
+            expected_high: val.expected_high,
+            seasonal_expected: val.seasonal_expected,
+            points: val
+                .points
+                .into_iter()
+                .map(|point| analytics::TimeSeriesPoint {
+                    timestamp: point.timestamp,
+                    value: point.value,
+                })
+                .collect(),
+            contributions: val
+                .contributions
+                .into_iter()
+                .map(|contribution| analytics::FeatureContribution {
+                    feature: contribution.feature,
+                    value: contribution.value,
+                    expected: contribution.expected,
+                    share: contribution.share,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<analytics::AnomalyKind> for domain::AnomalyKind {
+    fn from(val: analytics::AnomalyKind) -> Self {
+        match val {
+            analytics::AnomalyKind::Unspecified | analytics::AnomalyKind::Spike => {
+                domain::AnomalyKind::Spike
+            }
+            analytics::AnomalyKind::LevelShift => domain::AnomalyKind::LevelShift,
+            analytics::AnomalyKind::TrendChange => domain::AnomalyKind::TrendChange,
+        }
+    }
+}
+
+impl From<domain::AnomalyKind> for analytics::AnomalyKind {
+    fn from(val: domain::AnomalyKind) -> Self {
+        match val {
+            domain::AnomalyKind::Spike => analytics::AnomalyKind::Spike,
+            domain::AnomalyKind::LevelShift => analytics::AnomalyKind::LevelShift,
+            domain::AnomalyKind::TrendChange => analytics::AnomalyKind::TrendChange,
+        }
+    }
+}
+
+impl From<analytics::ChangePoint> for domain::ChangePoint {
+    fn from(val: analytics::ChangePoint) -> Self {
+        Self {
+            changed_at: val.changed_at,
+            before_mean: val.before_mean,
+            after_mean: val.after_mean,
+            before_stddev: val.before_stddev,
+            after_stddev: val.after_stddev,
+            before_slope: val.before_slope,
+            after_slope: val.after_slope,
+        }
+    }
+}
+
+impl From<domain::ChangePoint> for analytics::ChangePoint {
+    fn from(val: domain::ChangePoint) -> Self {
+        Self {
+            changed_at: val.changed_at,
+            before_mean: val.before_mean,
+            after_mean: val.after_mean,
+            before_stddev: val.before_stddev,
+            after_stddev: val.after_stddev,
+            before_slope: val.before_slope,
+            after_slope: val.after_slope,
+        }
+    }
+}
+
+impl TryFrom<analytics::AnomalyFeedback> for domain::AnomalyFeedback {
+    type Error = anyhow::Error;
+
+    fn try_from(val: analytics::AnomalyFeedback) -> Result<Self, Self::Error> {
+        let kind = match analytics::AnomalyFeedbackKind::try_from(val.kind)? {
+            analytics::AnomalyFeedbackKind::Confirmed => domain::AnomalyFeedbackKind::Confirmed,
+            analytics::AnomalyFeedbackKind::FalsePositive => {
+                domain::AnomalyFeedbackKind::FalsePositive
+            }
+            analytics::AnomalyFeedbackKind::Expected => domain::AnomalyFeedbackKind::Expected,
+            analytics::AnomalyFeedbackKind::Unspecified => {
+                anyhow::bail!("unspecified feedback kind")
+            }
+        };
+        Ok(Self {
+            kind,
+            submitted_at: val.submitted_at,
+            note: val.note,
+        })
+    }
+}
+
+impl From<domain::AnomalyFeedback> for analytics::AnomalyFeedback {
+    fn from(val: domain::AnomalyFeedback) -> Self {
+        let kind = match val.kind {
+            domain::AnomalyFeedbackKind::Confirmed => analytics::AnomalyFeedbackKind::Confirmed,
+            domain::AnomalyFeedbackKind::FalsePositive => {
+                analytics::AnomalyFeedbackKind::FalsePositive
+            }
+            domain::AnomalyFeedbackKind::Expected => analytics::AnomalyFeedbackKind::Expected,
+        };
+        Self {
+            kind: kind.into(),
+            submitted_at: val.submitted_at,
+            note: val.note,
+        }
+    }
+}
+
+impl From<domain::Severity> for analytics::Severity {
+    fn from(val: domain::Severity) -> Self {
+        match val {
+            domain::Severity::Critical => analytics::Severity::Critical,
+            domain::Severity::Warning => analytics::Severity::Warning,
+            domain::Severity::Info => analytics::Severity::Info,
+        }
+    }
+}
+
+impl TryFrom<analytics::Severity> for domain::Severity {
+    type Error = anyhow::Error;
+
+    fn try_from(val: analytics::Severity) -> Result<Self, Self::Error> {
+        match val {
+            analytics::Severity::Critical => Ok(domain::Severity::Critical),
+            analytics::Severity::Warning => Ok(domain::Severity::Warning),
+            analytics::Severity::Info => Ok(domain::Severity::Info),
+            analytics::Severity::Unspecified => anyhow::bail!("unspecified severity"),
+        }
+    }
+}
+
+impl From<ml::ContainerResources> for domain::ContainerResources {
+    fn from(val: ml::ContainerResources) -> Self {
+        Self {
+            workload: val.workload,
+            container: val.container,
+            replicas: val.replicas,
+            cpu_request: val.cpu_request,
+            cpu_limit: val.cpu_limit,
+            memory_request: val.memory_request,
+            memory_limit: val.memory_limit,
+        }
+    }
+}
+
+impl From<ml::StepDependency> for domain::StepDependency {
+    fn from(val: ml::StepDependency) -> Self {
+        Self {
+            step_id: val.step_id,
+            pod: val.pod,
+            depends_on: val.depends_on,
+        }
+    }
+}
+
+impl From<domain::RootCauseAnalysis> for ml::RootCauseAnalysis {
+    fn from(val: domain::RootCauseAnalysis) -> Self {
+        Self {
+            summary: val.summary,
+            confidence: val.confidence,
+            related_metrics: val.related_metrics,
+            causes: val.causes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<domain::ProbableCause> for ml::ProbableCause {
+    fn from(val: domain::ProbableCause) -> Self {
+        Self {
+            resource_id: val.resource_id,
+            metric_type: analytics::MetricType::from(val.metric_type).into(),
+            score: val.score,
+            evidence: val.evidence,
         }
     }
 }
 
 impl From<domain::Recommendation> for analytics::Recommendation {
     fn from(val: domain::Recommendation) -> Self {
+        use analytics::{recommendation_action, recommendation_status};
+
         Self {
             id: val.id,
-            // ... map fields
-            ..Default::default()
+            cluster_id: val.cluster_id,
+            created_at: val.created_at,
+            recommendation_type: analytics::RecommendationType::from(val.recommendation_type)
+                .into(),
+            priority: analytics::Priority::from(val.priority).into(),
+            confidence: val.confidence,
+            title: val.title,
+            description: val.description,
+            impact_estimate: val.impact_estimate,
+            cost_impact: val.cost_impact.map(|c| analytics::CostImpact {
+                daily_change: c.daily_change,
+                currency: c.currency,
+            }),
+            action: Some(analytics::RecommendationAction {
+                action: Some(match val.action {
+                    domain::RecommendationAction::ScaleDeployment { name, from, to } => {
+                        recommendation_action::Action::ScaleDeployment(
+                            analytics::ScaleDeploymentAction { name, from, to },
+                        )
+                    }
+                    domain::RecommendationAction::UpdateResourceLimits { resource, limits } => {
+                        recommendation_action::Action::UpdateLimits(
+                            analytics::UpdateResourceLimitsAction {
+                                resource,
+                                limits: Some(analytics::ResourceLimits {
+                                    cpu: limits.cpu,
+                                    memory: limits.memory,
+                                    cpu_request: limits.cpu_request,
+                                    memory_request: limits.memory_request,
+                                }),
+                            },
+                        )
+                    }
+                    domain::RecommendationAction::ReclaimStorage { volume, size_gb } => {
+                        recommendation_action::Action::ReclaimStorage(
+                            analytics::ReclaimStorageAction { volume, size_gb },
+                        )
+                    }
+                }),
+            }),
+            status: Some(analytics::RecommendationStatus {
+                status: Some(match val.status {
+                    domain::RecommendationStatus::Pending => {
+                        recommendation_status::Status::Pending(true)
+                    }
+                    domain::RecommendationStatus::Scheduled { execute_at } => {
+                        recommendation_status::Status::ScheduledAt(execute_at)
+                    }
+                    domain::RecommendationStatus::Applied { applied_at } => {
+                        recommendation_status::Status::AppliedAt(applied_at)
+                    }
+                    domain::RecommendationStatus::Dismissed { reason } => {
+                        recommendation_status::Status::DismissedReason(reason)
+                    }
+                }),
+            }),
+        }
+    }
+}
+
+impl From<domain::Priority> for analytics::Priority {
+    fn from(val: domain::Priority) -> Self {
+        match val {
+            domain::Priority::High => analytics::Priority::High,
+            domain::Priority::Medium => analytics::Priority::Medium,
+            domain::Priority::Low => analytics::Priority::Low,
+        }
+    }
+}
+
+impl From<domain::RecommendationType> for analytics::RecommendationType {
+    fn from(val: domain::RecommendationType) -> Self {
+        match val {
+            domain::RecommendationType::ScaleUp => analytics::RecommendationType::ScaleUp,
+            domain::RecommendationType::ScaleDown => analytics::RecommendationType::ScaleDown,
+            domain::RecommendationType::OptimizeResources => {
+                analytics::RecommendationType::OptimizeResources
+            }
+            domain::RecommendationType::AdjustLimits => analytics::RecommendationType::AdjustLimits,
+            domain::RecommendationType::StorageOptimization => {
+                analytics::RecommendationType::StorageOptimizations
+            }
         }
     }
 }
@@ -4,3 +4,6 @@ pub mod grpc;
 pub mod ml_service;
 
 pub use ml_service::MlService;
+
+#[cfg(test)]
+mod tests;
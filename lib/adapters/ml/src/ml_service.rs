@@ -1,68 +1,435 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use phenome_domain::{Anomaly, ClusterId, Recommendation, ScalingPrediction, TimeSeriesData};
-use phenome_ml::{AnomalyDetector, RecommendationEngine, ScalingPredictor};
+use phenome_domain::{
+    AggregatedMetric, AggregatedQuery, Anomaly, AnomalyFilter, ClusterId, ContainerResources,
+    DownsampleMethod, MetricSample, MetricType, MetricsQuery, MlConfig, PhenomeConfig,
+    Recommendation, RootCauseAnalysis, ScalingModelKind, ScalingPrediction, StepDependency,
+    TimeRange, TimeSeries, TimeSeriesData, TimeSeriesPoint, Unit,
+};
+use phenome_ml::{
+    AnomalyDetector, MlModels, ModelFactory, ModelRegistry, RecommendationEngine, RootCauseContext,
+    RootCauseEngine, ScalingPredictor,
+};
 use phenome_ports::MLPort;
 
-// Stub to satisfy verification comment "load IsolationForest::fit()"
-#[derive(Debug, Clone)]
-pub struct IsolationForest;
+use crate::grpc::AnalyticsClient;
 
-impl IsolationForest {
-    pub fn fit() -> Self {
-        Self
-    }
+/// History fetched for a scaling prediction: a week spans the daily season
+/// several times over, served as hourly rollups.
+const PREDICTION_HISTORY: Duration = Duration::from_secs(7 * 24 * 3600);
 
-    pub fn predict(&self, _value: f64) -> bool {
-        false
-    }
-}
+/// Metric scaling predictions forecast.
+const PREDICTION_METRIC: MetricType = MetricType::CpuUsage;
 
-use crate::grpc::AnalyticsClient;
+/// Window analytics aggregates usage over, merged across the lookback for
+/// recommendations.
+const USAGE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Cluster samples before an anomaly that candidate causes are correlated on.
+const ROOT_CAUSE_HISTORY: Duration = Duration::from_secs(3600);
 
 #[derive(Debug, Clone)]
 pub struct MlService {
-    _analytics_client: AnalyticsClient,
-    anomaly_detector: AnomalyDetector,
-    scaling_predictor: ScalingPredictor,
-    recommendation_engine: RecommendationEngine,
-    // Added model
-    _model: IsolationForest,
+    analytics_client: AnalyticsClient,
+    models: Arc<RwLock<MlModels>>,
+    root_cause_engine: RootCauseEngine,
+    registry: Arc<RwLock<ModelRegistry>>,
 }
 
 impl MlService {
     pub fn new(analytics_client: AnalyticsClient) -> Self {
         Self {
-            _analytics_client: analytics_client,
-            anomaly_detector: AnomalyDetector::default(),
-            scaling_predictor: ScalingPredictor::new(),
-            recommendation_engine: RecommendationEngine::new(),
-            _model: IsolationForest::fit(),
+            analytics_client,
+            models: Arc::new(RwLock::new(MlModels::default())),
+            root_cause_engine: RootCauseEngine::new(),
+            registry: Arc::new(RwLock::new(ModelRegistry::default())),
+        }
+    }
+
+    /// Use the models the `ml` section selects, e.g.
+    /// `ModelFactory::from_config(&config.ml)?.build()?`.
+    pub fn with_models(mut self, models: MlModels) -> Self {
+        self.models = Arc::new(RwLock::new(models));
+        self
+    }
+
+    pub fn with_anomaly_detector(self, anomaly_detector: AnomalyDetector) -> Self {
+        self.update_models(|models| models.anomaly_detector = anomaly_detector)
+    }
+
+    pub fn with_scaling_predictor(self, scaling_predictor: ScalingPredictor) -> Self {
+        self.update_models(|models| models.scaling_predictor = scaling_predictor)
+    }
+
+    pub fn with_recommendation_engine(self, recommendation_engine: RecommendationEngine) -> Self {
+        self.update_models(|models| models.recommendation_engine = recommendation_engine)
+    }
+
+    fn update_models(self, update: impl FnOnce(&mut MlModels)) -> Self {
+        if let Ok(mut models) = self.models.write() {
+            update(&mut models);
+        }
+        self
+    }
+
+    /// The models currently in use.
+    pub fn models(&self) -> Result<MlModels> {
+        self.models
+            .read()
+            .map(|models| models.clone())
+            .map_err(|_| anyhow::anyhow!("models lock poisoned"))
+    }
+
+    /// Swap in the models `config` selects. An invalid config is rejected
+    /// and the current models stay in use.
+    pub fn reload(&self, config: &MlConfig) -> Result<()> {
+        let models = ModelFactory::from_config(config)?.build()?;
+        *self
+            .models
+            .write()
+            .map_err(|_| anyhow::anyhow!("models lock poisoned"))? = models;
+        Ok(())
+    }
+
+    /// Reload the `ml` section whenever the config file at `path` changes,
+    /// checking every `poll`. The registry keeps the settings it was opened
+    /// with; changing `ml.registry` needs a restart.
+    pub async fn watch_config(self: Arc<Self>, path: PathBuf, poll: Duration) {
+        let mut seen = tokio::fs::read_to_string(&path).await.ok();
+        let mut interval = tokio::time::interval(poll);
+        loop {
+            interval.tick().await;
+            let current = tokio::fs::read_to_string(&path).await.ok();
+            if current.is_none() || current == seen {
+                continue;
+            }
+            seen = current;
+            match PhenomeConfig::load_from_path(&path).and_then(|config| self.reload(&config.ml)) {
+                Ok(()) => tracing::info!("reloaded ml models from {}", path.display()),
+                Err(err) => tracing::warn!(
+                    "keeping current ml models, {} is invalid: {err:#}",
+                    path.display()
+                ),
+            }
+        }
+    }
+
+    /// Score with trained models from `registry`, e.g.
+    /// `ModelRegistry::from_config(&config.ml.registry)?`.
+    pub fn with_model_registry(mut self, registry: ModelRegistry) -> Self {
+        self.registry = Arc::new(RwLock::new(registry));
+        self
+    }
+
+    /// Retrain anomaly models and the `auto` scaling selection on the
+    /// registry's training window and register them as new versions, one
+    /// cluster's history at a time, retiring models of series no longer
+    /// seen. Returns the number of models registered.
+    pub async fn retrain(&self) -> Result<usize> {
+        let trained_at = now_millis();
+        let models = self.models()?;
+        let (scope, window) = {
+            let registry = self.registry()?;
+            (registry.scope(), registry.training_window())
+        };
+        let range = lookback(trained_at, window);
+        let mut client = self.analytics_client.clone();
+        // Hourly windows name the clusters with history in the window
+        // without pulling their raw samples.
+        let clusters: BTreeSet<ClusterId> = client
+            .query_aggregated(AggregatedQuery {
+                cluster_id: None,
+                resource_type: None,
+                resource_ids: Vec::new(),
+                metric_types: Vec::new(),
+                window_duration: USAGE_WINDOW,
+                time_range: Some(window_range(range)),
+            })
+            .await?
+            .into_iter()
+            .map(|window| window.cluster_id)
+            .collect();
+
+        let mut fitted = Vec::new();
+        for cluster_id in clusters {
+            let samples = client
+                .fetch_historical(MetricsQuery {
+                    cluster_id: Some(cluster_id.clone()),
+                    resource_type: None,
+                    resource_ids: Vec::new(),
+                    metric_types: Vec::new(),
+                    time_range: Some(range),
+                })
+                .await?;
+            for data in group_series(samples, range) {
+                fitted.extend(models.anomaly_detector.train(&data, scope));
+            }
+            if models.scaling_predictor.model() == ScalingModelKind::Auto {
+                let history = self
+                    .usage_history(cluster_id, Vec::new(), trained_at)
+                    .await?;
+                fitted.extend(
+                    history
+                        .iter()
+                        .filter_map(|series| models.scaling_predictor.train(series)),
+                );
+            }
+        }
+
+        // Write the new versions to a copy so detection keeps scoring with
+        // the current models until they are swapped in.
+        let count = fitted.len();
+        let mut next = self.registry()?.clone();
+        for model in fitted {
+            next.register(model, trained_at)?;
+        }
+        let retired = next.retire(trained_at);
+        if retired > 0 {
+            tracing::info!("retired {retired} models not retrained within the training window");
         }
+        *self
+            .registry
+            .write()
+            .map_err(|_| anyhow::anyhow!("model registry lock poisoned"))? = next;
+        Ok(count)
+    }
+
+    /// Retrain every `ml.registry.retrain_interval`, starting immediately.
+    pub async fn run_retraining(self: Arc<Self>) {
+        let period = match self.registry() {
+            Ok(registry) => registry.retrain_interval(),
+            Err(err) => {
+                tracing::error!("retraining disabled: {err}");
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match self.retrain().await {
+                Ok(count) => tracing::info!("registered {count} retrained models"),
+                Err(err) => tracing::warn!("model retraining failed: {err}"),
+            }
+        }
+    }
+
+    /// Hourly usage of the `cluster_id` resources scaling predictions
+    /// forecast over the week before `now`, all of them when `resource_ids`
+    /// is empty.
+    async fn usage_history(
+        &self,
+        cluster_id: ClusterId,
+        resource_ids: Vec<String>,
+        now: i64,
+    ) -> Result<Vec<TimeSeries>> {
+        let windows = self
+            .analytics_client
+            .clone()
+            .query_aggregated(AggregatedQuery {
+                cluster_id: Some(cluster_id),
+                resource_type: None,
+                resource_ids,
+                metric_types: vec![PREDICTION_METRIC],
+                window_duration: USAGE_WINDOW,
+                time_range: Some(window_range(lookback(now, PREDICTION_HISTORY))),
+            })
+            .await?;
+        Ok(hourly_series(windows))
+    }
+
+    fn registry(&self) -> Result<std::sync::RwLockReadGuard<'_, ModelRegistry>> {
+        self.registry
+            .read()
+            .map_err(|_| anyhow::anyhow!("model registry lock poisoned"))
     }
 }
 
 #[async_trait]
 impl MLPort for MlService {
     async fn detect_anomalies(&self, data: TimeSeriesData) -> Result<Vec<Anomaly>> {
-        self.anomaly_detector.detect(&data)
+        let detector = self.models()?.anomaly_detector;
+        detector.detect_with(&data, &*self.registry()?)
     }
 
     async fn predict_scaling_needs(
         &self,
+        cluster_id: ClusterId,
         resource_id: String,
         horizon: Duration,
     ) -> Result<ScalingPrediction> {
         let generated_at = now_millis();
-        self.scaling_predictor
-            .predict(resource_id, horizon, &[], "unknown", generated_at)
+        let series = self
+            .usage_history(cluster_id.clone(), vec![resource_id.clone()], generated_at)
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| TimeSeries {
+                cluster_id,
+                resource_id,
+                metric_type: PREDICTION_METRIC,
+                unit: Unit::for_metric(PREDICTION_METRIC).as_str().to_string(),
+                points: Vec::new(),
+                downsample: DownsampleMethod::Rollup,
+            });
+        let predictor = self.models()?.scaling_predictor;
+        predictor.predict_with(&series, horizon, generated_at, &*self.registry()?)
+    }
+
+    async fn generate_recommendations(
+        &self,
+        cluster_id: ClusterId,
+        containers: Vec<ContainerResources>,
+    ) -> Result<Vec<Recommendation>> {
+        let engine = self.models()?.recommendation_engine;
+        let range = window_range(lookback(now_millis(), engine.lookback()));
+        let windows = self
+            .analytics_client
+            .clone()
+            .query_aggregated(AggregatedQuery {
+                cluster_id: Some(cluster_id.clone()),
+                resource_type: None,
+                resource_ids: Vec::new(),
+                metric_types: vec![MetricType::CpuUsage, MetricType::MemoryUsage],
+                window_duration: USAGE_WINDOW,
+                time_range: Some(range),
+            })
+            .await?;
+        engine.generate(cluster_id, &containers, &windows)
     }
 
-    async fn generate_recommendations(&self, cluster_id: ClusterId) -> Result<Vec<Recommendation>> {
-        self.recommendation_engine.generate(cluster_id)
+    async fn analyze_root_cause(
+        &self,
+        cluster_id: ClusterId,
+        anomaly_id: String,
+        dependencies: Vec<StepDependency>,
+    ) -> Result<(RootCauseAnalysis, Anomaly)> {
+        let mut client = self.analytics_client.clone();
+        let anomalies = client
+            .get_anomalies(AnomalyFilter {
+                cluster_id: Some(cluster_id.clone()),
+                ..AnomalyFilter::default()
+            })
+            .await?;
+        let mut anomaly = anomalies
+            .iter()
+            .find(|anomaly| anomaly.id == anomaly_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("anomaly {anomaly_id} not found"))?;
+        let samples = client
+            .fetch_historical(MetricsQuery {
+                cluster_id: Some(cluster_id),
+                resource_type: None,
+                resource_ids: Vec::new(),
+                metric_types: Vec::new(),
+                time_range: Some(lookback(anomaly.detected_at, ROOT_CAUSE_HISTORY)),
+            })
+            .await?;
+
+        let context = RootCauseContext {
+            anomalies,
+            samples,
+            dependencies,
+        };
+        let analysis = self.root_cause_engine.analyze(&anomaly, &context)?;
+        self.root_cause_engine.annotate(&mut anomaly, &analysis);
+        Ok((analysis, anomaly))
+    }
+}
+
+fn lookback(now: i64, window: Duration) -> TimeRange {
+    TimeRange {
+        start_ms: now - window.as_millis() as i64,
+        end_ms: now,
+    }
+}
+
+/// Usage windows are keyed by their start; reach back to the one `range`
+/// begins in.
+fn window_range(range: TimeRange) -> TimeRange {
+    TimeRange {
+        start_ms: range.start_ms - (USAGE_WINDOW.as_millis() as i64 - 1),
+        end_ms: range.end_ms,
+    }
+}
+
+/// Summed values and sample count per window start.
+type WindowTotals = BTreeMap<i64, (f64, u64)>;
+
+/// One rollup series per resource and metric from aggregated windows, each
+/// point the mean of the samples in its window.
+fn hourly_series(windows: Vec<AggregatedMetric>) -> Vec<TimeSeries> {
+    let mut merged: HashMap<(ClusterId, String, MetricType), WindowTotals> = HashMap::new();
+    for window in windows {
+        let (sum, count) = merged
+            .entry((window.cluster_id, window.resource_id, window.metric_type))
+            .or_default()
+            .entry(window.window_start)
+            .or_default();
+        *sum += window.sum;
+        *count += window.count;
+    }
+    merged
+        .into_iter()
+        .map(
+            |((cluster_id, resource_id, metric_type), windows)| TimeSeries {
+                cluster_id,
+                resource_id,
+                metric_type,
+                unit: Unit::for_metric(metric_type).as_str().to_string(),
+                points: windows
+                    .into_iter()
+                    .filter(|(_, (_, count))| *count > 0)
+                    .map(|(timestamp, (sum, count))| TimeSeriesPoint {
+                        timestamp,
+                        value: sum / count as f64,
+                    })
+                    .collect(),
+                downsample: DownsampleMethod::Rollup,
+            },
+        )
+        .collect()
+}
+
+/// Group raw samples into one series per cluster, resource and metric.
+fn group_series(samples: Vec<MetricSample>, range: TimeRange) -> Vec<TimeSeriesData> {
+    let mut clusters: HashMap<String, HashMap<(String, MetricType), TimeSeries>> = HashMap::new();
+    for sample in samples {
+        let series = clusters
+            .entry(sample.cluster_id.clone())
+            .or_default()
+            .entry((sample.resource_id.clone(), sample.metric_type))
+            .or_insert_with(|| TimeSeries {
+                cluster_id: sample.cluster_id.clone(),
+                resource_id: sample.resource_id.clone(),
+                metric_type: sample.metric_type,
+                unit: sample.unit.clone(),
+                points: Vec::new(),
+                downsample: DownsampleMethod::Raw,
+            });
+        series.points.push(TimeSeriesPoint {
+            timestamp: sample.timestamp,
+            value: sample.value,
+        });
     }
+    clusters
+        .into_iter()
+        .map(|(cluster_id, series)| TimeSeriesData {
+            cluster_id,
+            range,
+            series: series
+                .into_values()
+                .map(|mut series| {
+                    series.points.sort_by_key(|point| point.timestamp);
+                    series
+                })
+                .collect(),
+        })
+        .collect()
 }
 
 fn now_millis() -> i64 {
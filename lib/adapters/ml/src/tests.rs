@@ -0,0 +1,278 @@
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use phenome_adapter_analytics::grpc::{GrpcServer, MlClient};
+use phenome_adapter_analytics::storage::sqlite::SqliteStorage;
+use phenome_adapter_analytics::{AnalyticsService, ClusterManager};
+use phenome_domain::{
+    Anomaly, AnomalyKind, ContainerResources, MetricSample, MetricType, RecommendationType,
+    ResourceType, ScalingModel, ScalingModelKind, Severity, StepDependency,
+};
+use phenome_ml::ScalingPredictor;
+use phenome_ports::{AnalyticsPort, MLPort};
+use tempfile::TempDir;
+
+use crate::MlService;
+use crate::grpc::AnalyticsClient;
+
+const HOUR_MS: i64 = 3_600_000;
+const MINUTE_MS: i64 = 60_000;
+
+/// Serve a SQLite-backed analytics service on a free local port.
+async fn analytics_server() -> (Arc<AnalyticsService>, AnalyticsClient, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("analytics.db");
+    let storage = Arc::new(SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap());
+    let ml_client = MlClient::connect("http://127.0.0.1:1").await.unwrap();
+    let service = Arc::new(AnalyticsService::new(storage, ml_client));
+
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(GrpcServer::serve(
+        addr,
+        service.clone(),
+        ClusterManager::new(),
+    ));
+
+    for _ in 0..50 {
+        if let Ok(client) = AnalyticsClient::connect(format!("http://{addr}")).await {
+            return (service, client, dir);
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("analytics server did not start on {addr}");
+}
+
+/// Hourly CPU samples for `pod-a` over the last three days, rising steadily,
+/// stamped on the half hour so window edges never fall on a sample.
+fn rising_cpu(now: i64) -> Vec<MetricSample> {
+    (0..72)
+        .map(|hour| MetricSample {
+            cluster_id: "cluster-1".to_string(),
+            resource_type: ResourceType::Pod,
+            resource_id: "pod-a".to_string(),
+            metric_type: MetricType::CpuUsage,
+            timestamp: now - (72 - hour) * HOUR_MS + HOUR_MS / 2,
+            value: 1.0 + 0.02 * hour as f64,
+            unit: "cores".to_string(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn predicts_from_history_served_by_analytics() {
+    let (analytics, client, _dir) = analytics_server().await;
+    let now = chrono::Utc::now().timestamp_millis();
+    let report = analytics.record_metrics(rising_cpu(now)).await.unwrap();
+    assert_eq!(report.accepted, 72);
+    // The same pod name in another cluster must not leak into the forecast.
+    let elsewhere = rising_cpu(now)
+        .into_iter()
+        .map(|sample| MetricSample {
+            cluster_id: "cluster-2".to_string(),
+            value: 100.0,
+            ..sample
+        })
+        .collect();
+    analytics.record_metrics(elsewhere).await.unwrap();
+
+    let service = MlService::new(client)
+        .with_scaling_predictor(ScalingPredictor::new().with_model(ScalingModelKind::Arima));
+    let prediction = service
+        .predict_scaling_needs(
+            "cluster-1".to_string(),
+            "pod-a".to_string(),
+            Duration::from_secs(6 * 3600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(prediction.model, ScalingModel::Arima);
+    assert_eq!(prediction.unit, "cores");
+    assert!(prediction.predicted_value > 1.0 + 0.02 * 71.0);
+    assert!(prediction.predicted_value < 10.0);
+    assert!(prediction.lower_bound <= prediction.predicted_value);
+    assert!(prediction.upper_bound >= prediction.predicted_value);
+}
+
+#[tokio::test]
+async fn recommends_from_cluster_history_served_by_analytics() {
+    let (analytics, client, _dir) = analytics_server().await;
+    let now = chrono::Utc::now().timestamp_millis();
+    // Aligned to the hour, so each sample has an hourly window to itself.
+    let hour = now - now.rem_euclid(HOUR_MS);
+    analytics.record_metrics(rising_cpu(hour)).await.unwrap();
+
+    let containers = vec![ContainerResources {
+        workload: "pod-a".to_string(),
+        container: "app".to_string(),
+        replicas: 2,
+        cpu_request: Some(1.0),
+        ..ContainerResources::default()
+    }];
+    let recommendations = MlService::new(client)
+        .generate_recommendations("cluster-1".to_string(), containers)
+        .await
+        .unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    assert_eq!(recommendations[0].cluster_id, "cluster-1");
+    assert_eq!(
+        recommendations[0].recommendation_type,
+        RecommendationType::ScaleUp
+    );
+    assert!(recommendations[0].description.contains("(24 samples)"));
+}
+
+/// Per-minute CPU for `resource_id` over the hour before `end`, following
+/// `load` shifted `lead` minutes earlier.
+fn minute_cpu(resource_id: &str, end: i64, lead: usize) -> Vec<MetricSample> {
+    (0..60)
+        .map(|minute| {
+            let t = (minute + lead) as f64;
+            MetricSample {
+                cluster_id: "cluster-1".to_string(),
+                resource_type: ResourceType::Pod,
+                resource_id: resource_id.to_string(),
+                metric_type: MetricType::CpuUsage,
+                timestamp: end - (60 - minute as i64) * MINUTE_MS,
+                value: 1.0 + (t / 3.0).sin() + 0.3 * (t / 7.0).cos(),
+                unit: "cores".to_string(),
+            }
+        })
+        .collect()
+}
+
+fn cpu_anomaly(resource_id: &str, detected_at: i64) -> Anomaly {
+    Anomaly {
+        id: format!("{resource_id}-{detected_at}"),
+        cluster_id: "cluster-1".to_string(),
+        resource_id: resource_id.to_string(),
+        detected_at,
+        metric_type: MetricType::CpuUsage,
+        severity: Severity::Critical,
+        confidence: 0.95,
+        description: "4.00 sigma deviation".to_string(),
+        baseline_value: 1.0,
+        observed_value: 4.0,
+        deviation_sigma: 4.0,
+        related_metrics: Vec::new(),
+        root_cause: None,
+        model_id: None,
+        model_version: None,
+        kind: AnomalyKind::Spike,
+        change_point: None,
+        feedback: None,
+        evidence: None,
+    }
+}
+
+#[tokio::test]
+async fn analyzes_root_cause_from_analytics_history() {
+    let (analytics, client, _dir) = analytics_server().await;
+    let now = chrono::Utc::now().timestamp_millis() - MINUTE_MS;
+    let mut samples = minute_cpu("shop/api-7d9f", now, 0);
+    samples.extend(minute_cpu("shop/db-0", now, 2));
+    analytics.record_metrics(samples).await.unwrap();
+    let target = cpu_anomaly("shop/api-7d9f", now);
+    analytics
+        .record_anomalies(vec![
+            target.clone(),
+            cpu_anomaly("shop/db-0", now - 3 * MINUTE_MS),
+        ])
+        .await
+        .unwrap();
+
+    let dependencies = vec![
+        StepDependency {
+            step_id: "api".to_string(),
+            pod: Some("api".to_string()),
+            depends_on: vec!["db".to_string()],
+        },
+        StepDependency {
+            step_id: "db".to_string(),
+            pod: Some("db".to_string()),
+            depends_on: Vec::new(),
+        },
+    ];
+    let (analysis, anomaly) = MlService::new(client)
+        .analyze_root_cause("cluster-1".to_string(), target.id.clone(), dependencies)
+        .await
+        .unwrap();
+
+    assert_eq!(analysis.causes[0].resource_id, "shop/db-0");
+    assert!(
+        analysis.causes[0]
+            .evidence
+            .iter()
+            .any(|e| e == "anomalous 3m before")
+    );
+    assert_eq!(anomaly.id, target.id);
+    assert_eq!(
+        anomaly.root_cause.as_deref(),
+        Some(analysis.summary.as_str())
+    );
+    assert_eq!(anomaly.related_metrics[0], "shop/db-0:cpu_usage");
+}
+
+#[tokio::test]
+async fn retrains_models_from_analytics_history() {
+    let (analytics, client, _dir) = analytics_server().await;
+    let now = chrono::Utc::now().timestamp_millis();
+    analytics.record_metrics(rising_cpu(now)).await.unwrap();
+
+    let service = MlService::new(client)
+        .with_scaling_predictor(ScalingPredictor::new().with_model(ScalingModelKind::Auto));
+    let registered = service.retrain().await.unwrap();
+    let prediction = service
+        .predict_scaling_needs(
+            "cluster-1".to_string(),
+            "pod-a".to_string(),
+            Duration::from_secs(6 * 3600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(registered, 2);
+    assert_eq!(
+        prediction.model_id.as_deref(),
+        Some("scaling:auto:cpu_usage:pod-a")
+    );
+    assert_eq!(prediction.model_version, Some(1));
+}
+
+#[tokio::test]
+async fn reloads_models_when_config_changes() {
+    let sample = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../phenome-config.yaml"),
+    )
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yaml");
+    std::fs::write(&path, &sample).unwrap();
+
+    let client = AnalyticsClient::connect_lazy("http://127.0.0.1:1".to_string()).unwrap();
+    let service = Arc::new(MlService::new(client));
+    tokio::spawn(
+        service
+            .clone()
+            .watch_config(path.clone(), Duration::from_millis(10)),
+    );
+    let model = || service.models().unwrap().scaling_predictor.model();
+    let settle = || tokio::time::sleep(Duration::from_millis(200));
+    settle().await;
+    assert_eq!(model(), ScalingModelKind::MovingAverage);
+
+    let arima = sample.replace("scaling_prediction: auto", "scaling_prediction: arima");
+    std::fs::write(&path, &arima).unwrap();
+    settle().await;
+    assert_eq!(model(), ScalingModelKind::Arima);
+
+    let invalid = arima.replace("warning_confidence: 0.80", "warning_confidence: 0.95");
+    std::fs::write(&path, invalid).unwrap();
+    settle().await;
+    assert_eq!(model(), ScalingModelKind::Arima);
+}
@@ -48,6 +48,8 @@ use primer::application::timing::storage::{
 use primer::domain::models::assembly::Assembly;
 use primer::domain::models::module::spec::ModuleSpec;
 
+use phenome_domain::{BootstrapEta, StepProgress, StepTiming};
+use phenome_ml::StepDurationPredictor;
 use phenome_ports::{
     AccessStatus, AccessUrlInfo, BootstrapPort, BootstrapStatus, ComponentState, ComponentStatus,
 };
@@ -78,7 +80,7 @@ const CACHE_TTL: Duration = Duration::from_secs(5);
 pub struct BootstrapAdapter {
     state: Arc<RwLock<HashMap<String, ComponentState>>>,
     assembly: Arc<Assembly>,
-    timing_history: Arc<RwLock<Option<TimingHistory>>>,
+    timing: Arc<RwLock<TimingCache>>,
     command_tx: mpsc::Sender<InteractiveCommand>,
     detailed_cache: Arc<Mutex<DetailedStatusCache>>,
     status: Arc<RwLock<BootstrapStatus>>,
@@ -119,7 +121,7 @@ impl BootstrapAdapter {
 
         let state = Arc::new(RwLock::new(state_map));
         let status = Arc::new(RwLock::new(BootstrapStatus::default()));
-        let timing_history = Arc::new(RwLock::new(None));
+        let timing = Arc::new(RwLock::new(TimingCache::default()));
         let detailed_cache = Arc::new(Mutex::new(DetailedStatusCache::new(CACHE_TTL)));
         let assembly = Arc::new(assembly);
         let access_urls = Arc::new(RwLock::new(Vec::new()));
@@ -127,7 +129,7 @@ impl BootstrapAdapter {
         let adapter = Self {
             state: Arc::clone(&state),
             assembly: Arc::clone(&assembly),
-            timing_history: Arc::clone(&timing_history),
+            timing: Arc::clone(&timing),
             command_tx,
             detailed_cache: Arc::clone(&detailed_cache),
             status: Arc::clone(&status),
@@ -139,7 +141,7 @@ impl BootstrapAdapter {
             event_bus,
             state,
             status,
-            timing_history,
+            timing,
             detailed_cache,
             access_urls,
         );
@@ -152,7 +154,7 @@ impl BootstrapAdapter {
         event_bus: EventBus,
         state: Arc<RwLock<HashMap<String, ComponentState>>>,
         status: Arc<RwLock<BootstrapStatus>>,
-        timing_history: Arc<RwLock<Option<TimingHistory>>>,
+        timing: Arc<RwLock<TimingCache>>,
         detailed_cache: Arc<Mutex<DetailedStatusCache>>,
         access_urls: Arc<RwLock<Vec<AccessUrlInfo>>>,
     ) {
@@ -166,8 +168,8 @@ impl BootstrapAdapter {
             }
             // Initial fetch of timing history
             if let Ok(history) = Self::load_timing_history().await {
-                if let Ok(mut guard) = timing_history.write() {
-                    *guard = Some(history);
+                if let Ok(mut guard) = timing.write() {
+                    *guard = TimingCache::learn(history);
                 }
             }
 
@@ -182,8 +184,8 @@ impl BootstrapAdapter {
                         }
                     }
                     if let Ok(history) = Self::load_timing_history().await {
-                        if let Ok(mut guard) = timing_history.write() {
-                            *guard = Some(history);
+                        if let Ok(mut guard) = timing.write() {
+                            *guard = TimingCache::learn(history);
                         }
                     }
                 }
@@ -414,10 +416,22 @@ impl BootstrapAdapter {
 
 impl BootstrapPort for BootstrapAdapter {
     fn component_states(&self) -> HashMap<String, ComponentState> {
-        self.state
+        let mut states = self
+            .state
             .read()
             .map(|guard| guard.clone())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        if let Ok(timing) = self.timing.read() {
+            for state in states.values_mut() {
+                if matches!(
+                    state.status,
+                    ComponentStatus::Pending | ComponentStatus::Running
+                ) {
+                    state.timing.estimate = timing.durations.estimate(&state.id);
+                }
+            }
+        }
+        states
     }
 
     fn dependency_graph(&self) -> &Assembly {
@@ -425,10 +439,36 @@ impl BootstrapPort for BootstrapAdapter {
     }
 
     fn timing_history(&self) -> Option<TimingHistory> {
-        self.timing_history
+        self.timing
             .read()
             .ok()
-            .and_then(|guard| guard.clone())
+            .and_then(|guard| guard.history.clone())
+    }
+
+    fn eta(&self) -> Option<BootstrapEta> {
+        let progress: HashMap<String, StepProgress> = self
+            .component_states()
+            .into_iter()
+            .map(|(id, state)| {
+                let progress = match state.status {
+                    ComponentStatus::Pending => StepProgress::Pending,
+                    ComponentStatus::Running => StepProgress::Running {
+                        elapsed: state.timing.current_elapsed().unwrap_or_default(),
+                    },
+                    ComponentStatus::Complete
+                    | ComponentStatus::Failed
+                    | ComponentStatus::Deferred => StepProgress::Done,
+                };
+                (id, progress)
+            })
+            .collect();
+        let graph: Vec<(&str, &[String])> = self
+            .assembly
+            .steps
+            .iter()
+            .map(|step| (step.id.as_str(), step.required.as_slice()))
+            .collect();
+        self.timing.read().ok()?.durations.eta(&graph, &progress)
     }
 
     fn bootstrap_status(&self) -> BootstrapStatus {
@@ -500,6 +540,38 @@ impl BootstrapPort for BootstrapAdapter {
     }
 }
 
+/// Timing history and the step durations learned from it, replaced
+/// together whenever the history is reloaded.
+#[derive(Default)]
+struct TimingCache {
+    history: Option<TimingHistory>,
+    durations: StepDurationPredictor,
+}
+
+impl TimingCache {
+    fn learn(history: TimingHistory) -> Self {
+        let runs: Vec<Vec<StepTiming>> = history
+            .entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .steps
+                    .iter()
+                    .map(|step| StepTiming {
+                        step_id: step.step_id.clone(),
+                        duration: Duration::from_millis(step.duration_ms),
+                        success: step.success,
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            durations: StepDurationPredictor::learn(&runs),
+            history: Some(history),
+        }
+    }
+}
+
 struct DetailedStatusCache {
     data: HashMap<String, (DetailedStatus, Instant)>,
     ttl: Duration,
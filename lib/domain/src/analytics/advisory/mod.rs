@@ -1,2 +1,5 @@
 pub mod notification;
 pub mod recommendation;
+
+#[cfg(test)]
+mod tests;
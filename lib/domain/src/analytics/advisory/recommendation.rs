@@ -30,13 +30,33 @@ pub struct CostImpact {
     pub currency: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Container resources as Kubernetes quantities (`500m`, `512Mi`); `cpu`
+/// and `memory` are limits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ResourceLimits {
     pub cpu: Option<String>,
     pub memory: Option<String>,
+    #[serde(default)]
+    pub cpu_request: Option<String>,
+    #[serde(default)]
+    pub memory_request: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Per-replica requests and limits of one container of a workload, in base
+/// units (cores, bytes).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerResources {
+    /// `namespace/name` of the workload; its pods are `namespace/name-...`.
+    pub workload: String,
+    pub container: String,
+    pub replicas: u32,
+    pub cpu_request: Option<f64>,
+    pub cpu_limit: Option<f64>,
+    pub memory_request: Option<f64>,
+    pub memory_limit: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum RecommendationAction {
     ScaleDeployment {
@@ -54,6 +74,17 @@ pub enum RecommendationAction {
     },
 }
 
+impl RecommendationAction {
+    /// The workload, container or volume the action changes.
+    pub fn target(&self) -> &str {
+        match self {
+            RecommendationAction::ScaleDeployment { name, .. } => name,
+            RecommendationAction::UpdateResourceLimits { resource, .. } => resource,
+            RecommendationAction::ReclaimStorage { volume, .. } => volume,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum RecommendationStatus {
@@ -72,6 +103,17 @@ pub enum RecommendationStatusKind {
     Dismissed,
 }
 
+impl RecommendationStatusKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecommendationStatusKind::Pending => "pending",
+            RecommendationStatusKind::Scheduled => "scheduled",
+            RecommendationStatusKind::Applied => "applied",
+            RecommendationStatusKind::Dismissed => "dismissed",
+        }
+    }
+}
+
 impl RecommendationStatus {
     pub fn kind(&self) -> RecommendationStatusKind {
         match self {
@@ -81,8 +123,72 @@ impl RecommendationStatus {
             RecommendationStatus::Dismissed { .. } => RecommendationStatusKind::Dismissed,
         }
     }
+
+    /// Applied and dismissed recommendations are final.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            RecommendationStatus::Applied { .. } | RecommendationStatus::Dismissed { .. }
+        )
+    }
+
+    /// Check that a recommendation in this status may move to `next` at
+    /// `now_ms`. Pending and scheduled recommendations can be scheduled
+    /// (again), applied or dismissed; nothing returns to pending, a
+    /// schedule must lie in the future and a dismissal needs a reason.
+    pub fn check_transition(
+        &self,
+        next: &RecommendationStatus,
+        now_ms: i64,
+    ) -> Result<(), InvalidTransition> {
+        let invalid = |reason: String| InvalidTransition {
+            from: self.kind(),
+            to: next.kind(),
+            reason,
+        };
+        if self.is_final() {
+            return Err(invalid(format!(
+                "recommendation is already {}",
+                self.kind().as_str()
+            )));
+        }
+        match next {
+            RecommendationStatus::Pending => {
+                Err(invalid("recommendations cannot return to pending".into()))
+            }
+            RecommendationStatus::Scheduled { execute_at } if *execute_at <= now_ms => {
+                Err(invalid("execute_at must be in the future".into()))
+            }
+            RecommendationStatus::Dismissed { reason } if reason.trim().is_empty() => {
+                Err(invalid("a dismissal needs a reason".into()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A recommendation status change the transition rules reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: RecommendationStatusKind,
+    pub to: RecommendationStatusKind,
+    pub reason: String,
 }
 
+impl std::fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot move recommendation from {} to {}: {}",
+            self.from.as_str(),
+            self.to.as_str(),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Recommendation {
     pub id: String,
@@ -99,6 +205,19 @@ pub struct Recommendation {
     pub status: RecommendationStatus,
 }
 
+impl Recommendation {
+    /// Move to `next` if the transition rules allow it at `now_ms`.
+    pub fn transition(
+        &mut self,
+        next: RecommendationStatus,
+        now_ms: i64,
+    ) -> Result<(), InvalidTransition> {
+        self.status.check_transition(&next, now_ms)?;
+        self.status = next;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RecommendationFilter {
     pub cluster_id: Option<ClusterId>,
@@ -107,6 +226,20 @@ pub struct RecommendationFilter {
     pub limit: Option<u32>,
 }
 
+impl RecommendationFilter {
+    pub fn matches(&self, recommendation: &Recommendation) -> bool {
+        self.cluster_id
+            .as_ref()
+            .is_none_or(|id| id == &recommendation.cluster_id)
+            && self
+                .priority
+                .is_none_or(|priority| priority == recommendation.priority)
+            && self
+                .status
+                .is_none_or(|status| status == recommendation.status.kind())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledAction {
     pub id: ScheduleId,
@@ -125,3 +258,141 @@ pub enum ScheduleStatus {
     Failed { error: String },
     Cancelled,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleStatusKind {
+    Pending,
+    Executing,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl ScheduleStatusKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScheduleStatusKind::Pending => "pending",
+            ScheduleStatusKind::Executing => "executing",
+            ScheduleStatusKind::Completed => "completed",
+            ScheduleStatusKind::Failed => "failed",
+            ScheduleStatusKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl ScheduleStatus {
+    pub fn kind(&self) -> ScheduleStatusKind {
+        match self {
+            ScheduleStatus::Pending => ScheduleStatusKind::Pending,
+            ScheduleStatus::Executing => ScheduleStatusKind::Executing,
+            ScheduleStatus::Completed => ScheduleStatusKind::Completed,
+            ScheduleStatus::Failed { .. } => ScheduleStatusKind::Failed,
+            ScheduleStatus::Cancelled => ScheduleStatusKind::Cancelled,
+        }
+    }
+}
+
+impl ScheduledAction {
+    /// Move a pending action to run at `execute_at`, which must lie after
+    /// `now_ms`.
+    pub fn reschedule(&mut self, execute_at: i64, now_ms: i64) -> Result<(), ScheduleConflict> {
+        self.check_pending()?;
+        if execute_at <= now_ms {
+            return Err(self.conflict("execute_at must be in the future"));
+        }
+        self.execute_at = execute_at;
+        Ok(())
+    }
+
+    /// Cancel an action that has not started running.
+    pub fn cancel(&mut self) -> Result<(), ScheduleConflict> {
+        self.check_pending()?;
+        self.status = ScheduleStatus::Cancelled;
+        Ok(())
+    }
+
+    fn check_pending(&self) -> Result<(), ScheduleConflict> {
+        match self.status {
+            ScheduleStatus::Pending => Ok(()),
+            _ => Err(self.conflict(&format!("action is {}", self.status.kind().as_str()))),
+        }
+    }
+
+    fn conflict(&self, reason: &str) -> ScheduleConflict {
+        ScheduleConflict {
+            schedule_id: self.id.clone(),
+            status: self.status.kind(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// A change to a scheduled action that its status or timing rules out;
+/// only pending actions can be cancelled or rescheduled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleConflict {
+    pub schedule_id: ScheduleId,
+    pub status: ScheduleStatusKind,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ScheduleConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot change scheduled action {}: {}",
+            self.schedule_id, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ScheduleConflict {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScheduleFilter {
+    pub recommendation_id: Option<String>,
+    pub status: Option<ScheduleStatusKind>,
+    pub limit: Option<u32>,
+}
+
+impl ScheduleFilter {
+    pub fn matches(&self, action: &ScheduledAction) -> bool {
+        self.recommendation_id
+            .as_ref()
+            .is_none_or(|id| id == &action.recommendation_id)
+            && self
+                .status
+                .is_none_or(|status| status == action.status.kind())
+    }
+}
+
+/// One run of a scheduled action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleExecution {
+    pub schedule_id: ScheduleId,
+    pub recommendation_id: String,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// Why the run failed; `None` if it succeeded.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScheduleExecutionFilter {
+    pub schedule_id: Option<ScheduleId>,
+    pub recommendation_id: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl ScheduleExecutionFilter {
+    pub fn matches(&self, execution: &ScheduleExecution) -> bool {
+        self.schedule_id
+            .as_ref()
+            .is_none_or(|id| id == &execution.schedule_id)
+            && self
+                .recommendation_id
+                .as_ref()
+                .is_none_or(|id| id == &execution.recommendation_id)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    InvalidTransition, Priority, Recommendation, RecommendationAction, RecommendationStatus,
+    RecommendationStatusKind, RecommendationType, ScheduleConflict, ScheduleFilter, ScheduleStatus,
+    ScheduleStatusKind, ScheduledAction,
+};
+
+const NOW: i64 = 1_700_000_000_000;
+
+fn recommendation(status: RecommendationStatus) -> Recommendation {
+    Recommendation {
+        id: "rec-1".to_string(),
+        cluster_id: "cluster-1".to_string(),
+        created_at: NOW - 60_000,
+        recommendation_type: RecommendationType::ScaleDown,
+        priority: Priority::Medium,
+        confidence: 0.8,
+        title: "Scale down api".to_string(),
+        description: String::new(),
+        impact_estimate: String::new(),
+        cost_impact: None,
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 4,
+            to: 2,
+        },
+        status,
+    }
+}
+
+#[test]
+fn pending_recommendations_can_be_scheduled_rescheduled_and_applied() {
+    let mut rec = recommendation(RecommendationStatus::Pending);
+
+    rec.transition(
+        RecommendationStatus::Scheduled {
+            execute_at: NOW + 1,
+        },
+        NOW,
+    )
+    .unwrap();
+    rec.transition(
+        RecommendationStatus::Scheduled {
+            execute_at: NOW + 3_600_000,
+        },
+        NOW,
+    )
+    .unwrap();
+    rec.transition(RecommendationStatus::Applied { applied_at: NOW }, NOW)
+        .unwrap();
+
+    assert!(matches!(rec.status, RecommendationStatus::Applied { .. }));
+}
+
+#[test]
+fn rejects_invalid_transitions_and_keeps_status() {
+    let cases = [
+        (
+            RecommendationStatus::Pending,
+            RecommendationStatus::Scheduled { execute_at: NOW },
+        ),
+        (
+            RecommendationStatus::Pending,
+            RecommendationStatus::Dismissed {
+                reason: "  ".to_string(),
+            },
+        ),
+        (
+            RecommendationStatus::Scheduled {
+                execute_at: NOW + 1,
+            },
+            RecommendationStatus::Pending,
+        ),
+        (
+            RecommendationStatus::Applied { applied_at: NOW },
+            RecommendationStatus::Dismissed {
+                reason: "too late".to_string(),
+            },
+        ),
+        (
+            RecommendationStatus::Dismissed {
+                reason: "noisy".to_string(),
+            },
+            RecommendationStatus::Applied { applied_at: NOW },
+        ),
+    ];
+    for (from, to) in cases {
+        let mut rec = recommendation(from.clone());
+        let err = rec.transition(to.clone(), NOW).unwrap_err();
+        assert_eq!(
+            (err.from, err.to),
+            (from.kind(), to.kind()),
+            "{from:?} -> {to:?}"
+        );
+        assert_eq!(rec.status.kind(), from.kind());
+    }
+}
+
+#[test]
+fn invalid_transition_explains_itself() {
+    let err = RecommendationStatus::Applied { applied_at: NOW }
+        .check_transition(
+            &RecommendationStatus::Scheduled {
+                execute_at: NOW + 1,
+            },
+            NOW,
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        InvalidTransition {
+            from: RecommendationStatusKind::Applied,
+            to: RecommendationStatusKind::Scheduled,
+            reason: "recommendation is already applied".to_string(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "cannot move recommendation from applied to scheduled: recommendation is already applied"
+    );
+}
+
+fn scheduled(status: ScheduleStatus) -> ScheduledAction {
+    ScheduledAction {
+        id: "sched-1".to_string(),
+        execute_at: NOW + 60_000,
+        recommendation_id: "rec-1".to_string(),
+        action: RecommendationAction::ScaleDeployment {
+            name: "default/api".to_string(),
+            from: 4,
+            to: 2,
+        },
+        status,
+    }
+}
+
+#[test]
+fn only_pending_scheduled_actions_change() {
+    let mut action = scheduled(ScheduleStatus::Pending);
+    action.reschedule(NOW + 120_000, NOW).unwrap();
+    assert_eq!(action.execute_at, NOW + 120_000);
+
+    let err = action.reschedule(NOW, NOW).unwrap_err();
+    assert_eq!(err.reason, "execute_at must be in the future");
+    assert_eq!(action.execute_at, NOW + 120_000);
+
+    action.cancel().unwrap();
+    assert_eq!(action.status.kind(), ScheduleStatusKind::Cancelled);
+
+    let mut running = scheduled(ScheduleStatus::Executing);
+    assert_eq!(
+        running.cancel().unwrap_err(),
+        ScheduleConflict {
+            schedule_id: "sched-1".to_string(),
+            status: ScheduleStatusKind::Executing,
+            reason: "action is executing".to_string(),
+        }
+    );
+    assert!(running.reschedule(NOW + 1, NOW).is_err());
+    assert_eq!(running.status.kind(), ScheduleStatusKind::Executing);
+}
+
+#[test]
+fn schedule_filter_matches_recommendation_and_status() {
+    let action = scheduled(ScheduleStatus::Failed {
+        error: "boom".to_string(),
+    });
+
+    assert!(ScheduleFilter::default().matches(&action));
+    assert!(
+        ScheduleFilter {
+            recommendation_id: Some("rec-1".to_string()),
+            status: Some(ScheduleStatusKind::Failed),
+            limit: None,
+        }
+        .matches(&action)
+    );
+    assert!(
+        !ScheduleFilter {
+            status: Some(ScheduleStatusKind::Pending),
+            ..Default::default()
+        }
+        .matches(&action)
+    );
+}
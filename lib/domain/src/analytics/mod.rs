@@ -2,4 +2,4 @@ pub mod advisory;
 pub mod signal;
 
 pub use advisory::{notification, recommendation};
-pub use signal::{analytics, anomaly, metrics};
+pub use signal::{analytics, anomaly, evaluation, incident, ingest, metrics, quality, time, units};
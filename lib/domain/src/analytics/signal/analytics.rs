@@ -23,6 +23,29 @@ pub struct TimeSeriesPoint {
     pub value: f64,
 }
 
+/// How the points of a time series were reduced to fit a point budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DownsampleMethod {
+    /// Every stored sample in the range.
+    #[default]
+    Raw,
+    /// Largest-Triangle-Three-Buckets selection over raw samples.
+    Lttb,
+    /// Per-window averages, thinned with LTTB when still over budget.
+    Rollup,
+}
+
+impl DownsampleMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DownsampleMethod::Raw => "raw",
+            DownsampleMethod::Lttb => "lttb",
+            DownsampleMethod::Rollup => "rollup",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TimeSeries {
     pub cluster_id: ClusterId,
@@ -31,6 +54,8 @@ pub struct TimeSeries {
     pub unit: String,
     #[serde(default)]
     pub points: Vec<TimeSeriesPoint>,
+    #[serde(default)]
+    pub downsample: DownsampleMethod,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,10 +66,24 @@ pub struct TimeSeriesData {
     pub series: Vec<TimeSeries>,
 }
 
+/// A configured dashboard panel resolved against the clock: one series per
+/// resource, averaged into `step_ms` buckets across `range`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardPanel {
+    pub title: String,
+    pub range: TimeRange,
+    pub step_ms: i64,
+    #[serde(default)]
+    pub series: Vec<TimeSeries>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedMetric {
     pub cluster_id: ClusterId,
     pub resource_type: ResourceType,
+    /// Empty for windows aggregated before resources were told apart.
+    #[serde(default)]
+    pub resource_id: String,
     pub metric_type: MetricType,
     pub window_start: i64,
     pub window_duration: Duration,
@@ -74,6 +113,8 @@ pub struct AggregatedQuery {
     pub cluster_id: Option<ClusterId>,
     pub resource_type: Option<ResourceType>,
     #[serde(default)]
+    pub resource_ids: Vec<String>,
+    #[serde(default)]
     pub metric_types: Vec<MetricType>,
     pub window_duration: Duration,
     pub time_range: Option<TimeRange>,
@@ -86,4 +127,43 @@ pub struct ScalingPrediction {
     pub horizon: Duration,
     pub predicted_value: f64,
     pub unit: String,
+    /// 95% prediction interval around `predicted_value`.
+    #[serde(default)]
+    pub lower_bound: f64,
+    #[serde(default)]
+    pub upper_bound: f64,
+    #[serde(default)]
+    pub model: ScalingModel,
+    /// Registry entry behind the prediction; the version is set when the
+    /// model selection was trained ahead of time.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub model_version: Option<u32>,
+}
+
+/// Forecasting model behind a scaling prediction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingModel {
+    #[default]
+    MovingAverage,
+    HoltWinters,
+    Arima,
+}
+
+impl ScalingModel {
+    pub const ALL: [ScalingModel; 3] = [
+        ScalingModel::MovingAverage,
+        ScalingModel::HoltWinters,
+        ScalingModel::Arima,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScalingModel::MovingAverage => "moving_average",
+            ScalingModel::HoltWinters => "holt_winters",
+            ScalingModel::Arima => "arima",
+        }
+    }
 }
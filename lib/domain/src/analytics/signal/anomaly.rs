@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::{ClusterId, MetricType, TimeRange};
+use crate::{AssemblyStep, AssemblyStepDef, ClusterId, MetricType, TimeRange, TimeSeriesPoint};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -12,6 +12,121 @@ pub enum Severity {
     Info,
 }
 
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+
+    /// Ordering key, higher is more severe.
+    pub fn rank(self) -> u8 {
+        match self {
+            Severity::Critical => 2,
+            Severity::Warning => 1,
+            Severity::Info => 0,
+        }
+    }
+}
+
+/// What an anomaly describes: an outlying point, or a regime change that
+/// persists after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    #[default]
+    Spike,
+    LevelShift,
+    TrendChange,
+}
+
+impl AnomalyKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyKind::Spike => "spike",
+            AnomalyKind::LevelShift => "level_shift",
+            AnomalyKind::TrendChange => "trend_change",
+        }
+    }
+}
+
+/// Statistics of the segments either side of a detected change point.
+/// Slopes are in metric units per hour.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChangePoint {
+    pub changed_at: i64,
+    pub before_mean: f64,
+    pub after_mean: f64,
+    pub before_stddev: f64,
+    pub after_stddev: f64,
+    pub before_slope: f64,
+    pub after_slope: f64,
+}
+
+/// Why a detector flagged an anomaly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyEvidence {
+    /// History the baseline was learned from.
+    pub baseline_window: TimeRange,
+    /// Range the detector expected the value in; outside it, the value
+    /// crosses the detector's threshold.
+    pub expected_low: f64,
+    pub expected_high: f64,
+    /// Value a seasonal baseline predicted at the anomaly's time.
+    #[serde(default)]
+    pub seasonal_expected: Option<f64>,
+    /// Latest points of the series, the flagged one last.
+    #[serde(default)]
+    pub points: Vec<TimeSeriesPoint>,
+    /// Inputs behind the score, largest share first.
+    #[serde(default)]
+    pub contributions: Vec<FeatureContribution>,
+}
+
+/// One input's part in an anomaly score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureContribution {
+    /// A metric type for joint findings, or a window feature for the
+    /// Isolation Forest.
+    pub feature: String,
+    pub value: f64,
+    pub expected: f64,
+    /// Share of the score. Shares sum to one.
+    pub share: f64,
+}
+
+/// An operator's verdict on an anomaly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyFeedbackKind {
+    /// A real problem.
+    Confirmed,
+    /// Nothing was wrong.
+    FalsePositive,
+    /// A known, recurring pattern such as a nightly batch.
+    Expected,
+}
+
+impl AnomalyFeedbackKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyFeedbackKind::Confirmed => "confirmed",
+            AnomalyFeedbackKind::FalsePositive => "false_positive",
+            AnomalyFeedbackKind::Expected => "expected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyFeedback {
+    pub kind: AnomalyFeedbackKind,
+    pub submitted_at: i64,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Anomaly {
     pub id: String,
@@ -28,6 +143,22 @@ pub struct Anomaly {
     #[serde(default)]
     pub related_metrics: Vec<String>,
     pub root_cause: Option<String>,
+    /// Model that flagged the anomaly; the version is set for models
+    /// trained ahead of time in the model registry.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub model_version: Option<u32>,
+    #[serde(default)]
+    pub kind: AnomalyKind,
+    /// Set for level shifts and trend changes.
+    #[serde(default)]
+    pub change_point: Option<ChangePoint>,
+    /// The latest operator verdict, if any.
+    #[serde(default)]
+    pub feedback: Option<AnomalyFeedback>,
+    #[serde(default)]
+    pub evidence: Option<AnomalyEvidence>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +167,56 @@ pub struct RootCauseAnalysis {
     pub confidence: f64,
     #[serde(default)]
     pub related_metrics: Vec<String>,
+    /// Ranked candidates, most probable first.
+    #[serde(default)]
+    pub causes: Vec<ProbableCause>,
+}
+
+/// A metric that may explain an anomaly, with the evidence behind its score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbableCause {
+    pub resource_id: String,
+    pub metric_type: MetricType,
+    pub score: f64,
+    #[serde(default)]
+    pub evidence: Vec<String>,
+}
+
+impl ProbableCause {
+    /// `resource:metric` key used in `related_metrics`.
+    pub fn metric_key(&self) -> String {
+        format!("{}:{}", self.resource_id, self.metric_type.as_str())
+    }
+}
+
+/// An assembly step reduced to what root cause ranking needs: the pod it
+/// runs as and the steps it depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepDependency {
+    pub step_id: String,
+    pub pod: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl From<&AssemblyStep> for StepDependency {
+    fn from(step: &AssemblyStep) -> Self {
+        Self {
+            step_id: step.id.clone(),
+            pod: step.pod.clone(),
+            depends_on: step.depends_on.clone(),
+        }
+    }
+}
+
+impl From<&AssemblyStepDef> for StepDependency {
+    fn from(step: &AssemblyStepDef) -> Self {
+        Self {
+            step_id: step.id.clone(),
+            pod: step.pod.clone(),
+            depends_on: step.depends_on.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -47,3 +228,27 @@ pub struct AnomalyFilter {
     pub time_range: Option<TimeRange>,
     pub limit: Option<u32>,
 }
+
+impl AnomalyFilter {
+    pub fn matches(&self, anomaly: &Anomaly) -> bool {
+        self.cluster_id
+            .as_ref()
+            .is_none_or(|id| id == &anomaly.cluster_id)
+            && self
+                .resource_id
+                .as_ref()
+                .is_none_or(|resource_id| resource_id == &anomaly.resource_id)
+            && self
+                .metric_type
+                .is_none_or(|metric_type| metric_type == anomaly.metric_type)
+            && self
+                .severity
+                .is_none_or(|severity| severity == anomaly.severity)
+            && self
+                .time_range
+                .as_ref()
+                .is_none_or(|range| {
+                    anomaly.detected_at >= range.start_ms && anomaly.detected_at <= range.end_ms
+                })
+    }
+}
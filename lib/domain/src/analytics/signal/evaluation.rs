@@ -0,0 +1,67 @@
+//! Offline evaluation models: labeled series and the scores detectors and
+//! predictors reach on them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::TimeSeries;
+
+/// Ground-truth anomaly window, inclusive on both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnomalyWindow {
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+impl AnomalyWindow {
+    pub fn contains(&self, timestamp: i64) -> bool {
+        (self.start_ms..=self.end_ms).contains(&timestamp)
+    }
+}
+
+/// A series with the windows in which it is known to be anomalous.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledSeries {
+    pub series: TimeSeries,
+    #[serde(default)]
+    pub windows: Vec<AnomalyWindow>,
+}
+
+/// How well one detector configuration found the labeled windows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionScore {
+    pub candidate: String,
+    /// Points flagged during replay.
+    pub flagged: usize,
+    /// Flagged points outside every window.
+    pub false_positives: usize,
+    pub windows: usize,
+    /// Windows with at least one flagged point.
+    pub detected_windows: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    /// Mean time from window start to its first flag, over detected windows.
+    pub mean_delay_ms: Option<f64>,
+}
+
+/// Forecast error of one predictor configuration over rolling origins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastScore {
+    pub candidate: String,
+    pub forecasts: usize,
+    /// Mean absolute percentage error, skipping zero actuals.
+    pub mape: Option<f64>,
+    /// Mean absolute error scaled by the in-sample one-step naive error.
+    pub mase: Option<f64>,
+}
+
+/// Side-by-side scores of every candidate on one dataset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub dataset: String,
+    pub series: usize,
+    #[serde(default)]
+    pub detection: Vec<DetectionScore>,
+    #[serde(default)]
+    pub forecast: Vec<ForecastScore>,
+}
@@ -0,0 +1,116 @@
+//! Incident domain models: related anomalies merged into one problem with
+//! a timeline.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClusterId, Severity, TimeRange};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    Resolved,
+}
+
+impl IncidentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IncidentStatus::Open => "open",
+            IncidentStatus::Resolved => "resolved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentEventKind {
+    Opened,
+    Anomaly,
+    /// The incident's severity rose.
+    Escalated,
+    Resolved,
+}
+
+impl IncidentEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IncidentEventKind::Opened => "opened",
+            IncidentEventKind::Anomaly => "anomaly",
+            IncidentEventKind::Escalated => "escalated",
+            IncidentEventKind::Resolved => "resolved",
+        }
+    }
+}
+
+/// One entry of an incident timeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentEvent {
+    pub timestamp: i64,
+    pub kind: IncidentEventKind,
+    pub severity: Severity,
+    pub resource_id: Option<String>,
+    pub anomaly_id: Option<String>,
+    pub message: String,
+}
+
+/// Anomalies on one resource, or on resources correlated with it, that
+/// arrived close enough together to be one problem. `severity` is the
+/// highest seen so far and `updated_at` the time of the latest anomaly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    pub cluster_id: ClusterId,
+    pub status: IncidentStatus,
+    pub severity: Severity,
+    pub title: String,
+    pub opened_at: i64,
+    pub updated_at: i64,
+    pub resolved_at: Option<i64>,
+    #[serde(default)]
+    pub resources: Vec<String>,
+    #[serde(default)]
+    pub anomaly_ids: Vec<String>,
+    #[serde(default)]
+    pub timeline: Vec<IncidentEvent>,
+}
+
+impl Incident {
+    pub fn is_open(&self) -> bool {
+        self.status == IncidentStatus::Open
+    }
+
+    /// Whether the incident was open at any point in `range`.
+    pub fn overlaps(&self, range: &TimeRange) -> bool {
+        self.opened_at <= range.end_ms
+            && self
+                .resolved_at
+                .is_none_or(|resolved_at| resolved_at >= range.start_ms)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IncidentFilter {
+    pub cluster_id: Option<ClusterId>,
+    pub status: Option<IncidentStatus>,
+    pub resource_id: Option<String>,
+    /// Incidents open at any point in the range.
+    pub time_range: Option<TimeRange>,
+    pub limit: Option<u32>,
+}
+
+impl IncidentFilter {
+    pub fn matches(&self, incident: &Incident) -> bool {
+        self.cluster_id
+            .as_ref()
+            .is_none_or(|id| id == &incident.cluster_id)
+            && self.status.is_none_or(|status| status == incident.status)
+            && self
+                .resource_id
+                .as_ref()
+                .is_none_or(|resource_id| incident.resources.contains(resource_id))
+            && self
+                .time_range
+                .as_ref()
+                .is_none_or(|range| incident.overlaps(range))
+    }
+}
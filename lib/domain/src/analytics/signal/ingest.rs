@@ -0,0 +1,70 @@
+//! Metric ingest validation models.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    MissingField,
+    NonFiniteValue,
+    NegativeValue,
+    TimestampSkew,
+    UnknownUnit,
+    InvalidField,
+}
+
+impl RejectionReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectionReason::MissingField => "missing-field",
+            RejectionReason::NonFiniteValue => "non-finite",
+            RejectionReason::NegativeValue => "negative",
+            RejectionReason::TimestampSkew => "timestamp-skew",
+            RejectionReason::UnknownUnit => "unknown-unit",
+            RejectionReason::InvalidField => "invalid-field",
+        }
+    }
+}
+
+/// A sample dropped at ingest; `index` is its position in the submitted batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleRejection {
+    pub index: usize,
+    pub reason: RejectionReason,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestReport {
+    pub accepted: usize,
+    pub rejections: Vec<SampleRejection>,
+}
+
+impl IngestReport {
+    pub fn rejected(&self) -> usize {
+        self.rejections.len()
+    }
+}
+
+/// Running ingest totals since the service started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestStats {
+    pub accepted: u64,
+    pub rejected: u64,
+    pub rejected_by_reason: BTreeMap<RejectionReason, u64>,
+}
+
+impl IngestStats {
+    pub fn record_accepted(&mut self, count: usize) {
+        self.accepted += count as u64;
+    }
+
+    pub fn record_rejections(&mut self, rejections: &[SampleRejection]) {
+        self.rejected += rejections.len() as u64;
+        for rejection in rejections {
+            *self.rejected_by_reason.entry(rejection.reason).or_default() += 1;
+        }
+    }
+}
@@ -26,6 +26,28 @@ pub enum MetricType {
     DiskWrite,
 }
 
+impl MetricType {
+    pub const ALL: [MetricType; 6] = [
+        MetricType::CpuUsage,
+        MetricType::MemoryUsage,
+        MetricType::NetworkIn,
+        MetricType::NetworkOut,
+        MetricType::DiskRead,
+        MetricType::DiskWrite,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetricType::CpuUsage => "cpu_usage",
+            MetricType::MemoryUsage => "memory_usage",
+            MetricType::NetworkIn => "network_in",
+            MetricType::NetworkOut => "network_out",
+            MetricType::DiskRead => "disk_read",
+            MetricType::DiskWrite => "disk_write",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricSample {
     pub cluster_id: ClusterId,
@@ -1,3 +1,12 @@
 pub mod analytics;
 pub mod anomaly;
+pub mod evaluation;
+pub mod incident;
+pub mod ingest;
 pub mod metrics;
+pub mod quality;
+pub mod time;
+pub mod units;
+
+#[cfg(test)]
+mod tests;
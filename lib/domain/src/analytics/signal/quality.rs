@@ -0,0 +1,68 @@
+//! Data-quality domain models.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClusterId, MetricType, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataQualityIssueKind {
+    Gap,
+    Stale,
+    DuplicateTimestamp,
+    OutOfOrder,
+    FutureSample,
+}
+
+impl DataQualityIssueKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DataQualityIssueKind::Gap => "gap",
+            DataQualityIssueKind::Stale => "stale",
+            DataQualityIssueKind::DuplicateTimestamp => "duplicate",
+            DataQualityIssueKind::OutOfOrder => "out-of-order",
+            DataQualityIssueKind::FutureSample => "future",
+        }
+    }
+}
+
+/// A collection problem observed for a series, or for a whole cluster when
+/// `resource_id` and `metric_type` are unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataQualityIssue {
+    pub cluster_id: ClusterId,
+    pub resource_id: Option<String>,
+    pub metric_type: Option<MetricType>,
+    pub kind: DataQualityIssueKind,
+    pub severity: Severity,
+    pub detected_at: i64,
+    pub last_sample_at: Option<i64>,
+    pub expected_interval_ms: i64,
+    pub message: String,
+}
+
+impl DataQualityIssue {
+    /// Stable identity used to deduplicate repeated reports of the same issue.
+    pub fn key(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.cluster_id,
+            self.resource_id.as_deref().unwrap_or("*"),
+            self.metric_type
+                .map(|metric| format!("{metric:?}"))
+                .unwrap_or_else(|| "*".to_string()),
+            self.kind.as_str()
+        )
+    }
+
+    pub fn is_cluster_level(&self) -> bool {
+        self.resource_id.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DataQualityFilter {
+    pub cluster_id: Option<ClusterId>,
+    pub kind: Option<DataQualityIssueKind>,
+    pub limit: Option<u32>,
+}
@@ -0,0 +1,171 @@
+use crate::{
+    MetricType, TimeRange, Unit, format_quantity, parse_duration, parse_instant, parse_quantity,
+};
+
+#[test]
+fn parses_kubernetes_quantities() {
+    let cases = [
+        ("250m", 0.25),
+        ("123456789n", 0.123456789),
+        ("2", 2.0),
+        ("1.5", 1.5),
+        ("1k", 1e3),
+        ("2G", 2e9),
+        ("3M", 3e6),
+        ("1Ki", 1024.0),
+        ("1.5Gi", 1.5 * 1024.0 * 1024.0 * 1024.0),
+        ("2Ti", 2.0 * 1024f64.powi(4)),
+        ("1e3", 1e3),
+        ("12E-1", 1.2),
+        ("1E", 1e18),
+        ("1Ei", 1024f64.powi(6)),
+    ];
+    for (raw, expected) in cases {
+        let parsed = parse_quantity(raw).unwrap();
+        assert!(
+            (parsed - expected).abs() <= expected.abs() * 1e-12,
+            "{raw}: {parsed} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_quantities() {
+    for raw in ["", "Gi", "12Q", "1.2.3", "5mi"] {
+        assert!(parse_quantity(raw).is_err(), "{raw} should not parse");
+    }
+}
+
+#[test]
+fn formats_kubernetes_quantities() {
+    assert_eq!(format_quantity(0.25, Unit::Cores), "250m");
+    assert_eq!(format_quantity(2.0, Unit::Cores), "2");
+    assert_eq!(
+        format_quantity(512.0 * 1024.0 * 1024.0, Unit::Bytes),
+        "512Mi"
+    );
+    assert_eq!(
+        format_quantity(1.5 * 1024f64.powi(3), Unit::Bytes),
+        "1536Mi"
+    );
+    assert_eq!(format_quantity(1000.0, Unit::Bytes), "1000");
+}
+
+#[test]
+fn normalizes_labelled_units_to_base() {
+    let cpu = Unit::for_metric(MetricType::CpuUsage);
+    assert_eq!(cpu.normalize(500.0, "millicores"), Some(0.5));
+    assert_eq!(cpu.normalize(500.0, "m"), Some(0.5));
+    assert_eq!(cpu.normalize(2.0, "k"), Some(2000.0));
+    assert_eq!(cpu.normalize(2.0, "Gi"), None);
+    assert_eq!(cpu.normalize(2.0, "M"), None);
+
+    let memory = Unit::for_metric(MetricType::MemoryUsage);
+    assert_eq!(memory.normalize(2.0, "MiB"), Some(2.0 * 1024.0 * 1024.0));
+    assert_eq!(memory.normalize(2.0, "GB"), Some(2e9));
+    assert_eq!(memory.normalize(2.0, "cores"), None);
+
+    let rate = Unit::for_metric(MetricType::NetworkIn);
+    assert_eq!(rate.normalize(1.0, "KiB/s"), Some(1024.0));
+    assert_eq!(rate.normalize(1.0, "KiB"), None);
+}
+
+#[test]
+fn formats_human_units() {
+    assert_eq!(Unit::Cores.format_human(0.25), "250 millicores");
+    assert_eq!(Unit::Cores.format_human(3.5), "3.50 cores");
+    assert_eq!(
+        Unit::Bytes.format_human(1536.0 * 1024.0 * 1024.0),
+        "1.50 GiB"
+    );
+    assert_eq!(Unit::BytesPerSecond.format_human(2048.0), "2.00 KiB/s");
+}
+
+// 2024-03-15T10:30:00Z
+const NOW_MS: i64 = 1_710_498_600_000;
+const HOUR_MS: i64 = 3_600_000;
+
+#[test]
+fn parses_durations() {
+    assert_eq!(parse_duration("90s").unwrap(), 90_000);
+    assert_eq!(parse_duration("1h30m").unwrap(), 90 * 60_000);
+    assert_eq!(parse_duration("2d").unwrap(), 48 * HOUR_MS);
+    assert_eq!(parse_duration("250ms").unwrap(), 250);
+    assert!(parse_duration("5").is_err());
+    assert!(parse_duration("h").is_err());
+    assert!(parse_duration("3y").is_err());
+}
+
+#[test]
+fn parses_relative_ranges() {
+    let range = TimeRange::parse("last 24h", NOW_MS).unwrap();
+    assert_eq!(range.start_ms, NOW_MS - 24 * HOUR_MS);
+    assert_eq!(range.end_ms, NOW_MS);
+
+    let range = TimeRange::parse("now-1h", NOW_MS).unwrap();
+    assert_eq!(range.duration_ms(), HOUR_MS);
+
+    let range = TimeRange::parse("now-2h..now-1h", NOW_MS).unwrap();
+    assert_eq!(range.start_ms, NOW_MS - 2 * HOUR_MS);
+    assert_eq!(range.end_ms, NOW_MS - HOUR_MS);
+
+    assert!(TimeRange::parse("now-1h..now-2h", NOW_MS).is_err());
+    assert!(TimeRange::parse("sometime", NOW_MS).is_err());
+}
+
+#[test]
+fn rejects_relative_times_that_overflow() {
+    assert!(parse_instant("now+9223372036854775000ms", NOW_MS).is_err());
+    assert!(TimeRange::parse("now-1h..now+9223372036854775000ms", NOW_MS).is_err());
+    assert!(TimeRange::parse("last 9223372036854775000ms", -NOW_MS).is_err());
+}
+
+#[test]
+fn parses_calendar_ranges_in_offset() {
+    let midnight_utc = NOW_MS - 10 * HOUR_MS - 30 * 60_000;
+
+    let today = TimeRange::parse("today", NOW_MS).unwrap();
+    assert_eq!(today.start_ms, midnight_utc);
+    assert_eq!(today.end_ms, NOW_MS);
+
+    let yesterday = TimeRange::parse("yesterday", NOW_MS).unwrap();
+    assert_eq!(yesterday.start_ms, midnight_utc - 24 * HOUR_MS);
+    assert_eq!(yesterday.end_ms, midnight_utc);
+
+    // At UTC-11 it is still 23:30 on the 14th, so the local day began at
+    // 11:00 UTC on the 14th.
+    let today = TimeRange::parse_with_offset("today", NOW_MS, -11 * 3600).unwrap();
+    assert_eq!(today.start_ms, midnight_utc - 13 * HOUR_MS);
+}
+
+#[test]
+fn parses_iso_instants_with_zone() {
+    let range =
+        TimeRange::parse("2024-03-15T09:00:00+01:00 to 2024-03-15T10:00:00Z", NOW_MS).unwrap();
+    assert_eq!(range.start_ms, NOW_MS - 2 * HOUR_MS - 30 * 60_000);
+    assert_eq!(range.end_ms, NOW_MS - 30 * 60_000);
+
+    assert_eq!(
+        parse_instant("2024-03-15", NOW_MS).unwrap(),
+        NOW_MS - 10 * HOUR_MS - 30 * 60_000
+    );
+    assert_eq!(parse_instant("1710498600000", 0).unwrap(), NOW_MS);
+}
+
+#[test]
+fn aligns_and_steps_ranges() {
+    let range = TimeRange {
+        start_ms: 61_000,
+        end_ms: 179_000,
+    };
+    let aligned = range.align(60_000);
+    assert_eq!((aligned.start_ms, aligned.end_ms), (60_000, 180_000));
+    assert_eq!(
+        range.steps(60_000).collect::<Vec<_>>(),
+        vec![60_000, 120_000]
+    );
+
+    let day = TimeRange::parse("last 24h", NOW_MS).unwrap();
+    assert_eq!(day.auto_step(100), 15 * 60_000);
+    assert_eq!(day.auto_step(10_000), 15_000);
+}
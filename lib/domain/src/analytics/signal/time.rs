@@ -0,0 +1,229 @@
+//! Relative and calendar time range expressions.
+//!
+//! Accepted range forms:
+//! - `last 24h`, `last 1d12h`: trailing window ending now.
+//! - `today`, `yesterday`: calendar days in the given UTC offset.
+//! - `now-1h`, an ISO-8601 instant or epoch milliseconds: from that point
+//!   until now.
+//! - `<instant>..<instant>` or `<instant> to <instant>`: explicit bounds,
+//!   where each side is any instant form above.
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, FixedOffset, NaiveDate, Offset, TimeZone, Utc};
+
+use crate::TimeRange;
+
+const SECOND_MS: i64 = 1000;
+const MINUTE_MS: i64 = 60 * SECOND_MS;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+const DAY_MS: i64 = 24 * HOUR_MS;
+const WEEK_MS: i64 = 7 * DAY_MS;
+
+/// Steps `auto_step` chooses from, smallest first.
+const NICE_STEPS_MS: [i64; 14] = [
+    SECOND_MS,
+    5 * SECOND_MS,
+    15 * SECOND_MS,
+    30 * SECOND_MS,
+    MINUTE_MS,
+    5 * MINUTE_MS,
+    15 * MINUTE_MS,
+    30 * MINUTE_MS,
+    HOUR_MS,
+    3 * HOUR_MS,
+    6 * HOUR_MS,
+    12 * HOUR_MS,
+    DAY_MS,
+    WEEK_MS,
+];
+
+impl TimeRange {
+    /// Resolve a range expression against `now_ms`, with calendar days in UTC.
+    pub fn parse(expr: &str, now_ms: i64) -> Result<Self> {
+        Self::parse_with_offset(expr, now_ms, 0)
+    }
+
+    /// Resolve a range expression against `now_ms`, with calendar days
+    /// (`today`, `yesterday`, date-only ISO values) in the zone `utc_offset_secs`
+    /// east of UTC.
+    pub fn parse_with_offset(expr: &str, now_ms: i64, utc_offset_secs: i32) -> Result<Self> {
+        let zone = FixedOffset::east_opt(utc_offset_secs)
+            .ok_or_else(|| anyhow!("invalid UTC offset {utc_offset_secs}s"))?;
+        let expr = expr.trim();
+        let lowered = expr.to_ascii_lowercase();
+
+        let range = if let Some(window) = lowered.strip_prefix("last ") {
+            let window = parse_duration(window)?;
+            TimeRange {
+                start_ms: now_ms
+                    .checked_sub(window)
+                    .ok_or_else(|| anyhow!("time range {expr:?} is out of range"))?,
+                end_ms: now_ms,
+            }
+        } else if lowered == "today" {
+            let start_ms = day_start(now_ms, zone)?;
+            TimeRange {
+                start_ms,
+                end_ms: now_ms,
+            }
+        } else if lowered == "yesterday" {
+            let end_ms = day_start(now_ms, zone)?;
+            TimeRange {
+                start_ms: day_start(end_ms - 1, zone)?,
+                end_ms,
+            }
+        } else if let Some((start, end)) = split_bounds(expr) {
+            TimeRange {
+                start_ms: parse_instant_in(start, now_ms, zone)?,
+                end_ms: parse_instant_in(end, now_ms, zone)?,
+            }
+        } else {
+            TimeRange {
+                start_ms: parse_instant_in(expr, now_ms, zone)?,
+                end_ms: now_ms,
+            }
+        };
+
+        if range.start_ms > range.end_ms {
+            bail!("time range {expr:?} ends before it starts");
+        }
+        Ok(range)
+    }
+
+    /// Widen the range outward to whole multiples of `step_ms`.
+    pub fn align(&self, step_ms: i64) -> Self {
+        if step_ms <= 0 {
+            return *self;
+        }
+        let start_ms = self.start_ms.div_euclid(step_ms) * step_ms;
+        let end_ms = match self.end_ms.rem_euclid(step_ms) {
+            0 => self.end_ms,
+            rem => self.end_ms - rem + step_ms,
+        };
+        TimeRange { start_ms, end_ms }
+    }
+
+    /// Bucket start times from the aligned start up to the end.
+    pub fn steps(&self, step_ms: i64) -> impl Iterator<Item = i64> {
+        let aligned = self.align(step_ms);
+        let step = step_ms.max(1) as usize;
+        (aligned.start_ms..aligned.end_ms).step_by(step)
+    }
+
+    /// The smallest conventional step that keeps the range within
+    /// `max_points` buckets.
+    pub fn auto_step(&self, max_points: u32) -> i64 {
+        let max_points = i64::from(max_points.max(1));
+        let duration = self.duration_ms();
+        NICE_STEPS_MS
+            .iter()
+            .copied()
+            .find(|step| duration / step <= max_points)
+            .unwrap_or_else(|| ((duration + max_points - 1) / max_points).max(WEEK_MS))
+    }
+}
+
+/// Parse a duration such as `90s`, `5m`, `1h30m`, `2d` or `1w` into
+/// milliseconds. Units: `ms`, `s`, `m`, `h`, `d`, `w`.
+pub fn parse_duration(raw: &str) -> Result<i64> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        bail!("empty duration");
+    }
+
+    let mut total = 0i64;
+    let mut rest = raw;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| anyhow!("duration {raw:?} is missing a unit"))?;
+        if digits == 0 {
+            bail!("invalid duration {raw:?}");
+        }
+        let value: i64 = rest[..digits].parse()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => SECOND_MS,
+            "m" => MINUTE_MS,
+            "h" => HOUR_MS,
+            "d" => DAY_MS,
+            "w" => WEEK_MS,
+            other => bail!("unknown duration unit {other:?} in {raw:?}"),
+        };
+        total = value
+            .checked_mul(unit)
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| anyhow!("duration {raw:?} is too large"))?;
+        rest = &rest[unit_len..];
+    }
+    Ok(total)
+}
+
+/// Parse a single instant (`now`, `now-1h`, `now+5m`, ISO-8601 or epoch
+/// milliseconds) into epoch milliseconds. Date-only values are UTC midnight.
+pub fn parse_instant(raw: &str, now_ms: i64) -> Result<i64> {
+    parse_instant_in(raw, now_ms, Utc.fix())
+}
+
+fn parse_instant_in(raw: &str, now_ms: i64, zone: FixedOffset) -> Result<i64> {
+    let raw = raw.trim();
+    if let Some(rest) = raw.strip_prefix("now") {
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(now_ms);
+        }
+        let (sign, offset) = match rest.split_at(1) {
+            ("-", offset) => (-1, offset),
+            ("+", offset) => (1, offset),
+            _ => bail!("invalid relative time {raw:?}"),
+        };
+        return parse_duration(offset)?
+            .checked_mul(sign)
+            .and_then(|offset| now_ms.checked_add(offset))
+            .ok_or_else(|| anyhow!("relative time {raw:?} is out of range"));
+    }
+
+    if !raw.is_empty() && raw.chars().all(|c| c.is_ascii_digit()) {
+        return raw
+            .parse()
+            .with_context(|| format!("invalid epoch milliseconds {raw:?}"));
+    }
+
+    if let Ok(instant) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(instant.timestamp_millis());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("valid midnight");
+        return zone
+            .from_local_datetime(&midnight)
+            .single()
+            .map(|instant| instant.timestamp_millis())
+            .ok_or_else(|| anyhow!("ambiguous local date {raw:?}"));
+    }
+    bail!("unrecognized time {raw:?}")
+}
+
+fn split_bounds(expr: &str) -> Option<(&str, &str)> {
+    expr.split_once("..").or_else(|| expr.split_once(" to "))
+}
+
+fn day_start(at_ms: i64, zone: FixedOffset) -> Result<i64> {
+    let local = Utc
+        .timestamp_millis_opt(at_ms)
+        .single()
+        .ok_or_else(|| anyhow!("timestamp {at_ms} out of range"))?
+        .with_timezone(&zone);
+    let midnight = local
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("valid midnight");
+    let start = zone
+        .from_local_datetime(&midnight)
+        .single()
+        .ok_or_else(|| anyhow!("ambiguous local midnight"))?;
+    Ok(start.timestamp_millis())
+}
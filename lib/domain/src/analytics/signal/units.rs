@@ -0,0 +1,176 @@
+//! Metric unit models and Kubernetes quantity conversion.
+
+use serde::{Deserialize, Serialize};
+
+use crate::MetricType;
+
+const KI: f64 = 1024.0;
+
+/// Canonical base unit a metric is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Cores,
+    Bytes,
+    BytesPerSecond,
+}
+
+impl Unit {
+    pub fn for_metric(metric_type: MetricType) -> Self {
+        match metric_type {
+            MetricType::CpuUsage => Unit::Cores,
+            MetricType::MemoryUsage => Unit::Bytes,
+            MetricType::NetworkIn
+            | MetricType::NetworkOut
+            | MetricType::DiskRead
+            | MetricType::DiskWrite => Unit::BytesPerSecond,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Unit::Cores => "cores",
+            Unit::Bytes => "bytes",
+            Unit::BytesPerSecond => "bytes/s",
+        }
+    }
+
+    /// Factor that converts a value labelled `unit` into this base unit.
+    /// Accepts the base name, common spellings and the Kubernetes quantity
+    /// suffixes that apply: `n`, `u`, `m` and `k` for cores, decimal and
+    /// binary byte suffixes otherwise. An empty label is the base unit
+    /// itself.
+    pub fn factor_from(self, unit: &str) -> Option<f64> {
+        let unit = unit.trim();
+        if unit.is_empty() {
+            return Some(1.0);
+        }
+        let label = match self {
+            Unit::BytesPerSecond => unit.strip_suffix("/s")?,
+            _ => unit,
+        };
+        match (self, label) {
+            (Unit::Cores, "cores" | "core") => Some(1.0),
+            (Unit::Cores, "millicores") => Some(1e-3),
+            (Unit::Cores, "microcores") => Some(1e-6),
+            (Unit::Cores, "nanocores") => Some(1e-9),
+            (Unit::Bytes, "bytes" | "B") => Some(1.0),
+            (Unit::BytesPerSecond, "bytes" | "B") => Some(1.0),
+            (Unit::Bytes | Unit::BytesPerSecond, label) => label
+                .strip_suffix('B')
+                .and_then(suffix_multiplier)
+                .or_else(|| suffix_multiplier(label)),
+            (Unit::Cores, label @ ("n" | "u" | "m" | "k")) => suffix_multiplier(label),
+            (Unit::Cores, _) => None,
+        }
+    }
+
+    /// Convert `value` labelled `unit` into this base unit.
+    pub fn normalize(self, value: f64, unit: &str) -> Option<f64> {
+        self.factor_from(unit).map(|factor| value * factor)
+    }
+
+    /// Render a base-unit value for people: millicores below one core,
+    /// binary prefixes for bytes.
+    pub fn format_human(self, value: f64) -> String {
+        match self {
+            Unit::Cores if value.abs() < 1.0 => format!("{:.0} millicores", value * 1e3),
+            Unit::Cores => format!("{value:.2} cores"),
+            Unit::Bytes => format_binary(value),
+            Unit::BytesPerSecond => format!("{}/s", format_binary(value)),
+        }
+    }
+}
+
+/// Parse a Kubernetes resource quantity (`250m`, `1.5Gi`, `2G`, `1e3`,
+/// `128974848`) into a plain number.
+pub fn parse_quantity(raw: &str) -> anyhow::Result<f64> {
+    let raw = raw.trim();
+    let split = raw
+        .char_indices()
+        .find(|&(index, c)| {
+            c.is_ascii_alphabetic() && !(matches!(c, 'e' | 'E') && is_exponent(&raw[index + 1..]))
+        })
+        .map_or(raw.len(), |(index, _)| index);
+    let (number, suffix) = raw.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid quantity {raw:?}"))?;
+    let multiplier = suffix_multiplier(suffix)
+        .ok_or_else(|| anyhow::anyhow!("unknown quantity suffix {suffix:?} in {raw:?}"))?;
+    Ok(number * multiplier)
+}
+
+/// Format a value in the Kubernetes quantity form used for `unit`:
+/// millicores for CPU, the largest exact binary suffix for bytes.
+pub fn format_quantity(value: f64, unit: Unit) -> String {
+    match unit {
+        Unit::Cores => {
+            let millis = (value * 1e3).round();
+            if millis % 1e3 == 0.0 {
+                format!("{}", millis / 1e3)
+            } else {
+                format!("{millis}m")
+            }
+        }
+        Unit::Bytes | Unit::BytesPerSecond => {
+            let bytes = value.round();
+            for (suffix, factor) in [
+                ("Ei", KI.powi(6)),
+                ("Pi", KI.powi(5)),
+                ("Ti", KI.powi(4)),
+                ("Gi", KI.powi(3)),
+                ("Mi", KI.powi(2)),
+                ("Ki", KI),
+            ] {
+                if bytes != 0.0 && bytes % factor == 0.0 {
+                    return format!("{}{suffix}", bytes / factor);
+                }
+            }
+            format!("{bytes}")
+        }
+    }
+}
+
+fn suffix_multiplier(suffix: &str) -> Option<f64> {
+    let multiplier = match suffix {
+        "" => 1.0,
+        "n" => 1e-9,
+        "u" => 1e-6,
+        "m" => 1e-3,
+        "k" | "K" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "P" => 1e15,
+        "E" => 1e18,
+        "Ki" => KI,
+        "Mi" => KI.powi(2),
+        "Gi" => KI.powi(3),
+        "Ti" => KI.powi(4),
+        "Pi" => KI.powi(5),
+        "Ei" => KI.powi(6),
+        _ => return None,
+    };
+    Some(multiplier)
+}
+
+fn is_exponent(rest: &str) -> bool {
+    let digits = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn format_binary(bytes: f64) -> String {
+    let abs = bytes.abs();
+    if abs >= KI.powi(4) {
+        format!("{:.2} TiB", bytes / KI.powi(4))
+    } else if abs >= KI.powi(3) {
+        format!("{:.2} GiB", bytes / KI.powi(3))
+    } else if abs >= KI.powi(2) {
+        format!("{:.2} MiB", bytes / KI.powi(2))
+    } else if abs >= KI {
+        format!("{:.2} KiB", bytes / KI)
+    } else {
+        format!("{bytes:.0} B")
+    }
+}
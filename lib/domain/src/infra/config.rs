@@ -1,9 +1,13 @@
 //! Phenome configuration schema and loader.
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use crate::{MetricType, ScalingModel, Severity, TimeRange, parse_duration};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhenomeConfig {
     pub deployment: DeploymentConfig,
@@ -12,6 +16,8 @@ pub struct PhenomeConfig {
     pub clusters: Vec<ClusterConfig>,
     pub services: ServicesConfig,
     pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub dashboards: Vec<DashboardConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +31,18 @@ pub struct AnalyticsConfig {
     pub sqlite_path: String,
     pub retention: RetentionConfig,
     pub collection: CollectionConfig,
+    #[serde(default)]
+    pub quality: DataQualityConfig,
+    #[serde(default)]
+    pub ingest: IngestValidationConfig,
+    #[serde(default)]
+    pub incidents: IncidentConfig,
+    #[serde(default)]
+    pub streaming: StreamingDetectionConfig,
+    #[serde(default)]
+    pub feedback: AnomalyFeedbackConfig,
+    #[serde(default)]
+    pub watch: WatchConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,22 +57,737 @@ pub struct CollectionConfig {
     pub batch_size: usize,
 }
 
+/// Thresholds for collection gap and staleness detection. Factors are
+/// multiples of the learned per-series cadence. A series silent for
+/// `forget_factor` cadences is assumed deleted and no longer reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataQualityConfig {
+    pub gap_factor: f64,
+    pub stale_factor: f64,
+    pub forget_factor: f64,
+    pub max_future_skew_ms: i64,
+    pub default_interval_ms: i64,
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            gap_factor: 3.0,
+            stale_factor: 5.0,
+            forget_factor: 60.0,
+            max_future_skew_ms: 60_000,
+            default_interval_ms: 30_000,
+        }
+    }
+}
+
+/// Rules applied to samples before they are stored. Skew bounds are relative
+/// to the service clock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestValidationConfig {
+    pub max_past_skew_ms: i64,
+    pub max_future_skew_ms: i64,
+    pub reject_negative: bool,
+    pub normalize_units: bool,
+}
+
+impl Default for IngestValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_past_skew_ms: 7 * 24 * 60 * 60 * 1000,
+            max_future_skew_ms: 60_000,
+            reject_negative: true,
+            normalize_units: true,
+        }
+    }
+}
+
+/// How anomalies are grouped into incidents. An anomaly joins an open
+/// incident on the same resource, or on a resource it is correlated with,
+/// when it arrives within `merge_window` of the incident's latest anomaly.
+/// Incidents resolve after `resolve_after` without a new anomaly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncidentConfig {
+    pub merge_window: String,
+    pub resolve_after: String,
+    /// Merge across resources linked by `related_metrics`.
+    pub correlate: bool,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        Self {
+            merge_window: "15m".to_string(),
+            resolve_after: "30m".to_string(),
+            correlate: true,
+        }
+    }
+}
+
+impl IncidentConfig {
+    pub fn merge_window_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.merge_window)
+            .with_context(|| format!("incidents.merge_window {:?}", self.merge_window))
+    }
+
+    pub fn resolve_after_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.resolve_after)
+            .with_context(|| format!("incidents.resolve_after {:?}", self.resolve_after))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.merge_window_ms()? <= 0 {
+            anyhow::bail!("incidents.merge_window must be positive");
+        }
+        if self.resolve_after_ms()? <= 0 {
+            anyhow::bail!("incidents.resolve_after must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Detection run on every ingested batch. Each series keeps an
+/// exponentially weighted mean and variance that halve the weight of a
+/// sample every `half_life`, and with `seasonal` the same per UTC hour of
+/// day, halving every `seasonal_half_life`. A sample is scored once its
+/// series has `warmup` samples, against its hour's statistics once that
+/// hour has been seen on two days. Series not seen for `forget_after` are
+/// dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamingDetectionConfig {
+    pub enabled: bool,
+    pub half_life: String,
+    pub seasonal: bool,
+    pub seasonal_half_life: String,
+    pub warmup: usize,
+    pub sigma_threshold: f64,
+    pub forget_after: String,
+}
+
+impl Default for StreamingDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            half_life: "1h".to_string(),
+            seasonal: true,
+            seasonal_half_life: "7d".to_string(),
+            warmup: 30,
+            sigma_threshold: 4.0,
+            forget_after: "24h".to_string(),
+        }
+    }
+}
+
+impl StreamingDetectionConfig {
+    pub fn half_life_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.half_life)
+            .with_context(|| format!("streaming.half_life {:?}", self.half_life))
+    }
+
+    pub fn seasonal_half_life_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.seasonal_half_life)
+            .with_context(|| format!("streaming.seasonal_half_life {:?}", self.seasonal_half_life))
+    }
+
+    pub fn forget_after_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.forget_after)
+            .with_context(|| format!("streaming.forget_after {:?}", self.forget_after))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.half_life_ms()? <= 0 || self.seasonal_half_life_ms()? <= 0 {
+            anyhow::bail!("streaming half lives must be positive");
+        }
+        if self.warmup < 2 {
+            anyhow::bail!("streaming.warmup must be at least 2");
+        }
+        if !(self.sigma_threshold.is_finite() && self.sigma_threshold > 0.0) {
+            anyhow::bail!("streaming.sigma_threshold must be positive");
+        }
+        if self.forget_after_ms()? <= 0 {
+            anyhow::bail!("streaming.forget_after must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// How operator feedback tunes detection, per series and model since
+/// models score deviations on different scales. A false positive raises
+/// the sigma later anomalies must reach to `margin` times its deviation; a
+/// confirmed anomaly lowers it back to at most its own. An anomaly marked
+/// expected suppresses later ones of the same kind and direction within
+/// `expected_window` of the same UTC time of day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyFeedbackConfig {
+    pub enabled: bool,
+    pub margin: f64,
+    pub expected_window: String,
+}
+
+impl Default for AnomalyFeedbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            margin: 1.1,
+            expected_window: "30m".to_string(),
+        }
+    }
+}
+
+impl AnomalyFeedbackConfig {
+    pub fn expected_window_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.expected_window)
+            .with_context(|| format!("feedback.expected_window {:?}", self.expected_window))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(self.margin.is_finite() && self.margin >= 1.0) {
+            anyhow::bail!("feedback.margin must be at least 1.0");
+        }
+        let window = self.expected_window_ms()?;
+        if window <= 0 || window > 12 * 3_600_000 {
+            anyhow::bail!("feedback.expected_window must be between 0 and 12h");
+        }
+        Ok(())
+    }
+}
+
+/// Server-streaming `Watch*` RPCs. Each feed keeps its latest `history`
+/// changes (`metrics_history` samples for metrics) so a client can resume
+/// from its last cursor. A watcher is sent at most `batch_size` changes per
+/// message and stops reading its feed once `send_buffer` messages wait on
+/// a slow client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    pub history: usize,
+    pub metrics_history: usize,
+    pub batch_size: usize,
+    pub send_buffer: usize,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            history: 1_000,
+            metrics_history: 10_000,
+            batch_size: 500,
+            send_buffer: 16,
+        }
+    }
+}
+
+impl WatchConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.history == 0 || self.metrics_history == 0 {
+            anyhow::bail!("watch.history and watch.metrics_history must be positive");
+        }
+        if self.batch_size == 0 || self.send_buffer == 0 {
+            anyhow::bail!("watch.batch_size and watch.send_buffer must be positive");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MlConfig {
     pub models: MlModelsConfig,
     pub thresholds: MlThresholdsConfig,
+    #[serde(default)]
+    pub isolation_forest: IsolationForestConfig,
+    #[serde(default)]
+    pub seasonal: SeasonalConfig,
+    #[serde(default)]
+    pub change_point: ChangePointConfig,
+    #[serde(default)]
+    pub multivariate: MultivariateConfig,
+    #[serde(default)]
+    pub recommendations: RecommendationConfig,
+    #[serde(default)]
+    pub registry: ModelRegistryConfig,
+}
+
+impl MlConfig {
+    /// Reject model settings that would otherwise fail when the detector,
+    /// predictor or recommendation engine is built.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.thresholds.validate()?;
+        self.isolation_forest.validate()?;
+        self.seasonal.period_ms()?;
+        self.change_point.validate()?;
+        self.multivariate.validate()?;
+        self.recommendations.validate()?;
+        self.registry.validate()?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MlModelsConfig {
-    pub anomaly_detection: String,
-    pub scaling_prediction: String,
+    pub anomaly_detection: AnomalyModelKind,
+    /// Per-metric overrides of `anomaly_detection`.
+    #[serde(default)]
+    pub anomaly_detection_by_metric: HashMap<MetricType, AnomalyModelKind>,
+    pub scaling_prediction: ScalingModelKind,
+}
+
+impl MlModelsConfig {
+    pub fn anomaly_model_for(&self, metric_type: MetricType) -> AnomalyModelKind {
+        self.anomaly_detection_by_metric
+            .get(&metric_type)
+            .copied()
+            .unwrap_or(self.anomaly_detection)
+    }
+}
+
+/// Model used to score the latest point of each series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyModelKind {
+    #[default]
+    ZScore,
+    IsolationForest,
+    Seasonal,
+}
+
+impl AnomalyModelKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyModelKind::ZScore => "z_score",
+            AnomalyModelKind::IsolationForest => "isolation_forest",
+            AnomalyModelKind::Seasonal => "seasonal",
+        }
+    }
+}
+
+/// Isolation Forest parameters. Each point is embedded with its trailing
+/// `window` samples; `contamination` is the expected share of anomalous
+/// points and sets the score threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IsolationForestConfig {
+    pub trees: usize,
+    pub sample_size: usize,
+    pub contamination: f64,
+    pub window: usize,
+}
+
+impl Default for IsolationForestConfig {
+    fn default() -> Self {
+        Self {
+            trees: 100,
+            sample_size: 256,
+            contamination: 0.02,
+            window: 12,
+        }
+    }
 }
 
+/// Regime-change detection, run next to the point model. A split is
+/// reported as a level shift when the segment means differ by `shift_sigma`
+/// standard deviations, or as a trend change when the segment slopes differ
+/// by `shift_sigma` standard errors. Both segments need `min_segment`
+/// points, and changes older than `max_age` are no longer reported.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangePointConfig {
+    pub enabled: bool,
+    pub min_segment: usize,
+    pub shift_sigma: f64,
+    pub max_age: String,
+}
+
+impl Default for ChangePointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_segment: 6,
+            shift_sigma: 4.0,
+            max_age: "6h".to_string(),
+        }
+    }
+}
+
+impl ChangePointConfig {
+    pub fn max_age_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.max_age)
+            .with_context(|| format!("change_point.max_age {:?}", self.max_age))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.min_segment < 3 {
+            anyhow::bail!("change_point.min_segment must be at least 3");
+        }
+        if !(self.shift_sigma.is_finite() && self.shift_sigma > 0.0) {
+            anyhow::bail!("change_point.shift_sigma must be positive");
+        }
+        if self.max_age_ms()? <= 0 {
+            anyhow::bail!("change_point.max_age must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Joint detection over the metric series of one resource. The latest
+/// reading they share is scored by its Mahalanobis distance from the
+/// trailing `window` shared readings, once there are `min_points` of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultivariateConfig {
+    pub enabled: bool,
+    pub window: usize,
+    pub min_points: usize,
+}
+
+impl Default for MultivariateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 360,
+            min_points: 30,
+        }
+    }
+}
+
+impl MultivariateConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.min_points < 10 {
+            anyhow::bail!("multivariate.min_points must be at least 10");
+        }
+        if self.window < self.min_points {
+            anyhow::bail!("multivariate.window must be at least min_points");
+        }
+        Ok(())
+    }
+}
+
+/// Forecasting model used for scaling predictions. `auto` backtests every
+/// model on each series and keeps the most accurate one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingModelKind {
+    #[default]
+    MovingAverage,
+    HoltWinters,
+    Arima,
+    Auto,
+}
+
+impl ScalingModelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScalingModelKind::MovingAverage => "moving_average",
+            ScalingModelKind::HoltWinters => "holt_winters",
+            ScalingModelKind::Arima => "arima",
+            ScalingModelKind::Auto => "auto",
+        }
+    }
+
+    /// The fixed model this kind selects, or `None` for `auto`.
+    pub fn model(&self) -> Option<ScalingModel> {
+        match self {
+            ScalingModelKind::MovingAverage => Some(ScalingModel::MovingAverage),
+            ScalingModelKind::HoltWinters => Some(ScalingModel::HoltWinters),
+            ScalingModelKind::Arima => Some(ScalingModel::Arima),
+            ScalingModelKind::Auto => None,
+        }
+    }
+}
+
+/// Seasonal baseline parameters. Candidate periods are durations
+/// (`1d`, `1w`); a period is used when the series covers `min_cycles` of it
+/// and its autocorrelation reaches `min_correlation`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeasonalConfig {
+    pub periods: Vec<String>,
+    pub min_cycles: usize,
+    pub min_correlation: f64,
+}
+
+impl Default for SeasonalConfig {
+    fn default() -> Self {
+        Self {
+            periods: vec!["1d".to_string(), "1w".to_string()],
+            min_cycles: 2,
+            min_correlation: 0.3,
+        }
+    }
+}
+
+impl SeasonalConfig {
+    pub fn period_ms(&self) -> anyhow::Result<Vec<i64>> {
+        self.periods
+            .iter()
+            .map(|period| {
+                parse_duration(period).with_context(|| format!("seasonal period {period:?}"))
+            })
+            .collect()
+    }
+}
+
+/// Rightsizing thresholds, as shares of a container's per-replica request
+/// or limit. Usage is the p95 (requests) and p99 (limits) over `lookback`;
+/// resized requests put the p95 at `target_utilization`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecommendationConfig {
+    pub lookback: String,
+    pub min_samples: usize,
+    pub target_utilization: f64,
+    /// p95 above this share of the request is under-provisioned.
+    pub under_provisioned: f64,
+    /// p95 below this share of the request is over-provisioned.
+    pub over_provisioned: f64,
+    /// p99 above this share of the limit risks throttling or OOM kills.
+    pub limit_pressure: f64,
+    /// Raised limits are the p99 times this factor.
+    pub limit_headroom: f64,
+}
+
+impl Default for RecommendationConfig {
+    fn default() -> Self {
+        Self {
+            lookback: "24h".to_string(),
+            min_samples: 12,
+            target_utilization: 0.7,
+            under_provisioned: 0.9,
+            over_provisioned: 0.4,
+            limit_pressure: 0.9,
+            limit_headroom: 1.5,
+        }
+    }
+}
+
+impl RecommendationConfig {
+    pub fn lookback_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.lookback)
+            .with_context(|| format!("recommendations.lookback {:?}", self.lookback))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.lookback_ms()?;
+        if !(self.target_utilization > 0.0 && self.target_utilization <= 1.0) {
+            anyhow::bail!("recommendations.target_utilization must be in (0, 1]");
+        }
+        if !(self.over_provisioned > 0.0 && self.over_provisioned < self.under_provisioned) {
+            anyhow::bail!(
+                "recommendations.over_provisioned must be positive and below under_provisioned"
+            );
+        }
+        if !(self.limit_pressure > 0.0 && self.limit_pressure <= 1.0) {
+            anyhow::bail!("recommendations.limit_pressure must be in (0, 1]");
+        }
+        if self.limit_headroom < 1.0 {
+            anyhow::bail!("recommendations.limit_headroom must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// Persisted, versioned models. Without a `path` trained models live only
+/// in memory. Models are retrained every `retrain_interval` on the last
+/// `training_window` of history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelRegistryConfig {
+    pub path: Option<String>,
+    pub scope: ModelScope,
+    pub retrain_interval: String,
+    pub training_window: String,
+    /// Versions kept on disk per model, newest first.
+    pub keep_versions: usize,
+}
+
+impl Default for ModelRegistryConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            scope: ModelScope::Series,
+            retrain_interval: "6h".to_string(),
+            training_window: "7d".to_string(),
+            keep_versions: 3,
+        }
+    }
+}
+
+impl ModelRegistryConfig {
+    pub fn retrain_interval_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.retrain_interval)
+            .with_context(|| format!("registry.retrain_interval {:?}", self.retrain_interval))
+    }
+
+    pub fn training_window_ms(&self) -> anyhow::Result<i64> {
+        parse_duration(&self.training_window)
+            .with_context(|| format!("registry.training_window {:?}", self.training_window))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.retrain_interval_ms()? <= 0 {
+            anyhow::bail!("registry.retrain_interval must be positive");
+        }
+        if self.training_window_ms()? <= 0 {
+            anyhow::bail!("registry.training_window must be positive");
+        }
+        if self.keep_versions == 0 {
+            anyhow::bail!("registry.keep_versions must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// What an anomaly model is trained on: each series on its own, or every
+/// series of a metric type pooled. Seasonal baselines are always per series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelScope {
+    #[default]
+    Series,
+    Metric,
+}
+
+impl ModelScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModelScope::Series => "series",
+            ModelScope::Metric => "metric",
+        }
+    }
+}
+
+impl IsolationForestConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.trees == 0 {
+            anyhow::bail!("isolation_forest.trees must be positive");
+        }
+        if self.sample_size < 2 {
+            anyhow::bail!("isolation_forest.sample_size must be at least 2");
+        }
+        if self.window < 2 {
+            anyhow::bail!("isolation_forest.window must be at least 2");
+        }
+        if !(self.contamination > 0.0 && self.contamination <= 0.5) {
+            anyhow::bail!("isolation_forest.contamination must be in (0, 0.5]");
+        }
+        Ok(())
+    }
+}
+
+/// Detection thresholds. A point model reports an anomaly once it is
+/// `sigma_threshold` deviations out and its confidence reaches
+/// `min_confidence`; it is critical from `critical_confidence`, a warning
+/// from `warning_confidence` and informational below that. `by_metric`
+/// overrides any of them for one metric type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MlThresholdsConfig {
+    pub sigma_threshold: f64,
+    pub min_confidence: f64,
+    pub warning_confidence: f64,
     pub critical_confidence: f64,
+    pub by_metric: HashMap<MetricType, MetricThresholdsConfig>,
+}
+
+impl Default for MlThresholdsConfig {
+    fn default() -> Self {
+        Self {
+            sigma_threshold: 3.0,
+            min_confidence: 0.7,
+            warning_confidence: 0.7,
+            critical_confidence: 0.9,
+            by_metric: HashMap::new(),
+        }
+    }
+}
+
+/// Per-metric overrides of `MlThresholdsConfig`; unset fields keep the
+/// shared value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricThresholdsConfig {
+    pub sigma_threshold: Option<f64>,
+    pub min_confidence: Option<f64>,
+    pub warning_confidence: Option<f64>,
+    pub critical_confidence: Option<f64>,
+}
+
+/// Thresholds resolved for one metric type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectionThresholds {
+    pub sigma_threshold: f64,
+    pub min_confidence: f64,
     pub warning_confidence: f64,
+    pub critical_confidence: f64,
+}
+
+impl DetectionThresholds {
+    /// Confidence in a deviation of `deviation_sigma`: two thirds at the
+    /// threshold, capped at 0.99 from one and a half times it.
+    pub fn confidence(&self, deviation_sigma: f64) -> f64 {
+        (deviation_sigma / (self.sigma_threshold * 1.5)).min(0.99)
+    }
+
+    pub fn severity(&self, confidence: f64) -> Severity {
+        if confidence >= self.critical_confidence {
+            Severity::Critical
+        } else if confidence >= self.warning_confidence {
+            Severity::Warning
+        } else {
+            Severity::Info
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if !(self.sigma_threshold.is_finite() && self.sigma_threshold > 0.0) {
+            anyhow::bail!("sigma_threshold must be positive");
+        }
+        let unit = |value: f64| (0.0..=1.0).contains(&value);
+        if !(unit(self.min_confidence)
+            && unit(self.warning_confidence)
+            && unit(self.critical_confidence))
+        {
+            anyhow::bail!("confidence thresholds must be in [0, 1]");
+        }
+        if self.warning_confidence > self.critical_confidence {
+            anyhow::bail!("warning_confidence must not exceed critical_confidence");
+        }
+        Ok(())
+    }
+}
+
+impl MlThresholdsConfig {
+    /// The thresholds for `metric_type`, with its overrides applied.
+    pub fn for_metric(&self, metric_type: MetricType) -> DetectionThresholds {
+        let overrides = self.by_metric.get(&metric_type);
+        let pick = |field: fn(&MetricThresholdsConfig) -> Option<f64>, shared: f64| {
+            overrides.and_then(field).unwrap_or(shared)
+        };
+        DetectionThresholds {
+            sigma_threshold: pick(|o| o.sigma_threshold, self.sigma_threshold),
+            min_confidence: pick(|o| o.min_confidence, self.min_confidence),
+            warning_confidence: pick(|o| o.warning_confidence, self.warning_confidence),
+            critical_confidence: pick(|o| o.critical_confidence, self.critical_confidence),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for metric_type in MetricType::ALL {
+            let scope = if self.by_metric.contains_key(&metric_type) {
+                format!("ml.thresholds.by_metric.{}", metric_type.as_str())
+            } else {
+                "ml.thresholds".to_string()
+            };
+            self.for_metric(metric_type)
+                .validate()
+                .with_context(|| scope)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,10 +813,64 @@ pub enum NotificationChannelConfig {
     Ntfy { url: String, topic: String },
 }
 
+/// A saved set of metric panels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    pub name: String,
+    pub panels: Vec<DashboardPanelConfig>,
+}
+
+/// `range` is any `TimeRange::parse` expression (`last 6h`, `today`, ...)
+/// and is resolved each time the panel is queried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardPanelConfig {
+    pub title: String,
+    pub metric_type: MetricType,
+    #[serde(default)]
+    pub resource_ids: Vec<String>,
+    pub range: String,
+    #[serde(default)]
+    pub step: Option<String>,
+}
+
+impl DashboardPanelConfig {
+    pub fn time_range(&self, now_ms: i64) -> anyhow::Result<TimeRange> {
+        TimeRange::parse(&self.range, now_ms)
+    }
+
+    pub fn step_ms(&self) -> anyhow::Result<Option<i64>> {
+        let step = self.step.as_deref().map(parse_duration).transpose()?;
+        if step == Some(0) {
+            anyhow::bail!("dashboard step must be positive");
+        }
+        Ok(step)
+    }
+}
+
 impl PhenomeConfig {
     pub fn load_from_path(path: &Path) -> anyhow::Result<Self> {
         let contents = fs::read_to_string(path)?;
-        let config = serde_yaml::from_str(&contents)?;
+        let config: Self = serde_yaml::from_str(&contents)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// Reject settings that would otherwise fail at query or training time.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.analytics.incidents.validate()?;
+        self.analytics.streaming.validate()?;
+        self.analytics.feedback.validate()?;
+        self.analytics.watch.validate()?;
+        self.ml.validate()?;
+
+        let now_ms = crate::now_millis() as i64;
+        for dashboard in &self.dashboards {
+            for panel in &dashboard.panels {
+                let context = || format!("dashboard {:?} panel {:?}", dashboard.name, panel.title);
+                panel.time_range(now_ms).with_context(context)?;
+                panel.step_ms().with_context(context)?;
+            }
+        }
+        Ok(())
+    }
 }
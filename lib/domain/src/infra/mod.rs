@@ -1,3 +1,6 @@
 pub mod cluster;
 pub mod config;
 pub mod health;
+
+#[cfg(test)]
+mod tests;
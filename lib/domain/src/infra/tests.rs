@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use crate::{
+    AnomalyModelKind, MetricType, ModelScope, PhenomeConfig, ScalingModel, ScalingModelKind,
+    Severity,
+};
+
+fn sample_config() -> PhenomeConfig {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../phenome-config.yaml");
+    PhenomeConfig::load_from_path(&path).unwrap()
+}
+
+#[test]
+fn loads_dashboards_from_sample_config() {
+    let config = sample_config();
+    let panel = &config.dashboards[0].panels[0];
+    assert_eq!(panel.metric_type, MetricType::CpuUsage);
+    assert_eq!(panel.step_ms().unwrap(), Some(5 * 60_000));
+    assert_eq!(panel.time_range(0).unwrap().duration_ms(), 6 * 3_600_000);
+}
+
+#[test]
+fn rejects_invalid_dashboard_range() {
+    let mut config = sample_config();
+    config.dashboards[0].panels[0].range = "last fortnight".to_string();
+    let err = config.validate().unwrap_err();
+    assert!(format!("{err:#}").contains("CPU (last 6h)"));
+}
+
+#[test]
+fn rejects_zero_dashboard_step() {
+    let mut config = sample_config();
+    config.dashboards[0].panels[0].step = Some("0s".to_string());
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn selects_isolation_forest_from_sample_config() {
+    let mut config = sample_config();
+    assert_eq!(
+        config.ml.models.anomaly_detection,
+        AnomalyModelKind::IsolationForest
+    );
+
+    config.ml.isolation_forest.contamination = 0.9;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn overrides_anomaly_model_per_metric() {
+    let config = sample_config();
+    let models = &config.ml.models;
+    assert_eq!(
+        models.anomaly_model_for(MetricType::NetworkIn),
+        AnomalyModelKind::Seasonal
+    );
+    assert_eq!(
+        models.anomaly_model_for(MetricType::MemoryUsage),
+        AnomalyModelKind::IsolationForest
+    );
+    assert_eq!(
+        config.ml.seasonal.period_ms().unwrap(),
+        vec![86_400_000, 604_800_000]
+    );
+}
+
+#[test]
+fn selects_backtested_scaling_model_from_sample_config() {
+    let config = sample_config();
+    assert_eq!(config.ml.models.scaling_prediction, ScalingModelKind::Auto);
+    assert_eq!(config.ml.models.scaling_prediction.model(), None);
+    assert_eq!(
+        ScalingModelKind::HoltWinters.model(),
+        Some(ScalingModel::HoltWinters)
+    );
+}
+
+#[test]
+fn loads_recommendation_thresholds_from_sample_config() {
+    let mut config = sample_config();
+    let recommendations = &config.ml.recommendations;
+    assert_eq!(recommendations.lookback_ms().unwrap(), 7 * 86_400_000);
+    assert_eq!(recommendations.target_utilization, 0.7);
+
+    config.ml.recommendations.over_provisioned = 0.95;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_model_registry_from_sample_config() {
+    let mut config = sample_config();
+    let registry = &config.ml.registry;
+    assert_eq!(registry.path.as_deref(), Some("~/.phenome/models"));
+    assert_eq!(registry.scope, ModelScope::Series);
+    assert_eq!(registry.retrain_interval_ms().unwrap(), 6 * 3_600_000);
+
+    config.ml.registry.keep_versions = 0;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_incident_grouping_from_sample_config() {
+    let mut config = sample_config();
+    let incidents = &config.analytics.incidents;
+    assert_eq!(incidents.merge_window_ms().unwrap(), 15 * 60_000);
+    assert_eq!(incidents.resolve_after_ms().unwrap(), 30 * 60_000);
+    assert!(incidents.correlate);
+
+    config.analytics.incidents.resolve_after = "soon".to_string();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_change_point_detection_from_sample_config() {
+    let mut config = sample_config();
+    let change_point = &config.ml.change_point;
+    assert!(change_point.enabled);
+    assert_eq!(change_point.min_segment, 6);
+    assert_eq!(change_point.max_age_ms().unwrap(), 6 * 3_600_000);
+
+    config.ml.change_point.min_segment = 2;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_multivariate_detection_from_sample_config() {
+    let mut config = sample_config();
+    assert!(config.ml.multivariate.enabled);
+    assert_eq!(config.ml.multivariate.window, 360);
+
+    config.ml.multivariate.window = config.ml.multivariate.min_points - 1;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_streaming_detection_from_sample_config() {
+    let mut config = sample_config();
+    let streaming = &config.analytics.streaming;
+    assert!(streaming.enabled && streaming.seasonal);
+    assert_eq!(streaming.half_life_ms().unwrap(), 3_600_000);
+    assert_eq!(streaming.seasonal_half_life_ms().unwrap(), 7 * 86_400_000);
+
+    config.analytics.streaming.warmup = 1;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn loads_anomaly_feedback_from_sample_config() {
+    let mut config = sample_config();
+    let feedback = &config.analytics.feedback;
+    assert!(feedback.enabled);
+    assert_eq!(feedback.expected_window_ms().unwrap(), 30 * 60_000);
+
+    config.analytics.feedback.margin = 0.9;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn resolves_detection_thresholds_per_metric() {
+    let mut config = sample_config();
+    let thresholds = &config.ml.thresholds;
+    let cpu = thresholds.for_metric(MetricType::CpuUsage);
+    let network = thresholds.for_metric(MetricType::NetworkIn);
+    assert_eq!(cpu.sigma_threshold, 3.0);
+    assert_eq!(network.sigma_threshold, 4.0);
+    assert_eq!(network.critical_confidence, cpu.critical_confidence);
+    assert_eq!(cpu.severity(0.95), Severity::Critical);
+    assert_eq!(cpu.severity(0.85), Severity::Warning);
+    assert_eq!(cpu.severity(0.75), Severity::Info);
+
+    config
+        .ml
+        .thresholds
+        .by_metric
+        .get_mut(&MetricType::NetworkIn)
+        .unwrap()
+        .warning_confidence = Some(0.95);
+    let err = config.validate().unwrap_err();
+    assert!(format!("{err:#}").contains("by_metric.network_in"));
+}
+
+#[test]
+fn rejects_unknown_model_names() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../phenome-config.yaml");
+    let yaml = std::fs::read_to_string(path).unwrap();
+    let yaml = yaml.replace(
+        "anomaly_detection: isolation_forest",
+        "anomaly_detection: random_forest",
+    );
+    let err = serde_yaml::from_str::<PhenomeConfig>(&yaml).unwrap_err();
+    assert!(err.to_string().contains("random_forest"));
+    assert!(err.to_string().contains("isolation_forest"));
+}
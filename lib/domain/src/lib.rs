@@ -4,33 +4,54 @@ mod analytics;
 mod infra;
 mod ops;
 
-pub use analytics::{anomaly, metrics, notification, recommendation};
+pub use analytics::{
+    anomaly, evaluation, incident, ingest, metrics, notification, quality, recommendation, time,
+    units,
+};
 pub use infra::{cluster, config, health};
-pub use ops::{actions, assembly, events, snapshot};
+pub use ops::{actions, assembly, events, snapshot, timing};
 
 pub use actions::{ActionDefinition, ActionId, ActionRegistry, ActionSafety};
 pub use analytics::analytics::{
-    AggregatedMetric, AggregatedQuery, MetricsQuery, ScalingPrediction, TimeRange, TimeSeries,
-    TimeSeriesData, TimeSeriesPoint,
+    AggregatedMetric, AggregatedQuery, DashboardPanel, DownsampleMethod, MetricsQuery,
+    ScalingModel, ScalingPrediction, TimeRange, TimeSeries, TimeSeriesData, TimeSeriesPoint,
+};
+pub use analytics::anomaly::{
+    Anomaly, AnomalyEvidence, AnomalyFeedback, AnomalyFeedbackKind, AnomalyFilter, AnomalyKind,
+    ChangePoint, FeatureContribution, ProbableCause, RootCauseAnalysis, Severity, StepDependency,
 };
-pub use analytics::anomaly::{Anomaly, AnomalyFilter, RootCauseAnalysis, Severity};
 pub use assembly::{Assembly, AssemblyStepDef};
 pub use cluster::{ClusterHealth, ClusterId, ClusterMetadata};
 pub use config::{
-    AnalyticsConfig, ClusterConfig, CollectionConfig, DeploymentConfig, MlConfig, MlModelsConfig,
-    MlThresholdsConfig, NotificationChannelConfig, NotificationsConfig, RetentionConfig,
-    PhenomeConfig, ServicesConfig,
+    AnalyticsConfig, AnomalyFeedbackConfig, AnomalyModelKind, ChangePointConfig, ClusterConfig,
+    CollectionConfig, DashboardConfig, DashboardPanelConfig, DataQualityConfig, DeploymentConfig,
+    DetectionThresholds, IncidentConfig, IngestValidationConfig, IsolationForestConfig,
+    MetricThresholdsConfig, MlConfig, MlModelsConfig, MlThresholdsConfig, ModelRegistryConfig,
+    ModelScope, MultivariateConfig, NotificationChannelConfig, NotificationsConfig, PhenomeConfig,
+    RecommendationConfig, RetentionConfig, ScalingModelKind, SeasonalConfig, ServicesConfig,
+    StreamingDetectionConfig, WatchConfig,
+};
+pub use evaluation::{
+    AnomalyWindow, DetectionScore, EvaluationReport, ForecastScore, LabeledSeries,
 };
 pub use events::{Event, EventBus, EventLevel};
 pub use health::{ComponentHealthStatus, HealthSnapshot};
+pub use incident::{Incident, IncidentEvent, IncidentEventKind, IncidentFilter, IncidentStatus};
+pub use ingest::{IngestReport, IngestStats, RejectionReason, SampleRejection};
 pub use metrics::{MetricSample, MetricType, ResourceType};
 pub use notification::{Notification, NotificationChannel};
+pub use quality::{DataQualityFilter, DataQualityIssue, DataQualityIssueKind};
 pub use recommendation::{
-    CostImpact, Priority, Recommendation, RecommendationAction, RecommendationFilter,
-    RecommendationStatus, RecommendationStatusKind, RecommendationType, ResourceLimits, ScheduleId,
-    ScheduleStatus, ScheduledAction,
+    ContainerResources, CostImpact, InvalidTransition, Priority, Recommendation,
+    RecommendationAction, RecommendationFilter, RecommendationStatus, RecommendationStatusKind,
+    RecommendationType, ResourceLimits, ScheduleConflict, ScheduleExecution,
+    ScheduleExecutionFilter, ScheduleFilter, ScheduleId, ScheduleStatus, ScheduleStatusKind,
+    ScheduledAction,
 };
 pub use snapshot::{
     ActionStatus, AssemblyStep, AssemblyStepStatus, AssemblySummary, Capability, CapabilityStatus,
     HealthStatus, Snapshot, now_millis,
 };
+pub use time::{parse_duration, parse_instant};
+pub use timing::{BootstrapEta, StepEstimate, StepProgress, StepTiming};
+pub use units::{Unit, format_quantity, parse_quantity};
@@ -2,3 +2,4 @@ pub mod actions;
 pub mod assembly;
 pub mod events;
 pub mod snapshot;
+pub mod timing;
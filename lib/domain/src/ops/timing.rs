@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// One step of a recorded bootstrap run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepTiming {
+    pub step_id: String,
+    pub duration: Duration,
+    pub success: bool,
+}
+
+/// Where a step of the current bootstrap run stands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepProgress {
+    Pending,
+    Running { elapsed: Duration },
+    Done,
+}
+
+/// Duration of a step learned from earlier runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepEstimate {
+    /// Mean duration, at most the p90.
+    pub expected: Duration,
+    /// Duration nine runs in ten finish within.
+    pub p90: Duration,
+    /// Successful runs the estimate was learned from.
+    pub samples: usize,
+}
+
+/// Time left in a bootstrap run, along the chain of dependent steps
+/// expected to finish last.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BootstrapEta {
+    pub remaining: Duration,
+    pub p90_remaining: Duration,
+    /// 0..1: how much of the critical path has history behind it, scaled
+    /// by how tightly that history agrees.
+    pub confidence: f64,
+    /// Unfinished steps on the critical path, first to last.
+    pub critical_path: Vec<String>,
+}
@@ -2,19 +2,21 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use phenome_domain::{
-    AggregatedMetric, AggregatedQuery, Anomaly, AnomalyFilter, MetricSample, MetricType,
-    MetricsQuery, Recommendation, RecommendationFilter, TimeRange, TimeSeries,
+    AggregatedMetric, AggregatedQuery, Anomaly, AnomalyFeedback, AnomalyFilter,
+    DataQualityFilter, DataQualityIssue, Incident, IncidentFilter, IngestReport, MetricSample,
+    MetricType, MetricsQuery, Recommendation, RecommendationFilter, TimeRange, TimeSeries,
 };
 
 #[async_trait]
 pub trait AnalyticsPort: Send + Sync {
-    async fn record_metrics(&self, samples: Vec<MetricSample>) -> Result<()>;
+    async fn record_metrics(&self, samples: Vec<MetricSample>) -> Result<IngestReport>;
     async fn query_aggregated(&self, query: AggregatedQuery) -> Result<Vec<AggregatedMetric>>;
     async fn get_time_series(
         &self,
         resource_id: String,
         metric_type: MetricType,
         range: TimeRange,
+        max_points: Option<u32>,
     ) -> Result<TimeSeries>;
     async fn get_anomalies(&self, filter: AnomalyFilter) -> Result<Vec<Anomaly>>;
     async fn get_recommendations(
@@ -22,4 +24,17 @@ pub trait AnalyticsPort: Send + Sync {
         filter: RecommendationFilter,
     ) -> Result<Vec<Recommendation>>;
     async fn query_metrics(&self, query: MetricsQuery) -> Result<Vec<MetricSample>>;
+    async fn get_data_quality(&self, filter: DataQualityFilter) -> Result<Vec<DataQualityIssue>>;
+    /// Incidents grouping related anomalies, most recently updated first.
+    async fn get_incidents(&self, filter: IncidentFilter) -> Result<Vec<Incident>>;
+    /// Record an operator's verdict on an anomaly so detection on its
+    /// series adapts. Returns the anomaly with the feedback attached, or
+    /// `None` if no anomaly has that id.
+    async fn submit_anomaly_feedback(
+        &self,
+        anomaly_id: String,
+        feedback: AnomalyFeedback,
+    ) -> Result<Option<Anomaly>>;
+    /// Anomalies that have feedback, oldest verdict first.
+    async fn get_anomaly_feedback(&self, filter: AnomalyFilter) -> Result<Vec<Anomaly>>;
 }
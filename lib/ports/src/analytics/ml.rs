@@ -3,19 +3,32 @@ use async_trait::async_trait;
 use std::time::Duration;
 
 use phenome_domain::{
-    Anomaly, ClusterId, Recommendation, ScalingPrediction, TimeSeriesData,
+    Anomaly, ClusterId, ContainerResources, Recommendation, RootCauseAnalysis, ScalingPrediction,
+    StepDependency, TimeSeriesData,
 };
 
 #[async_trait]
 pub trait MLPort: Send + Sync {
     async fn detect_anomalies(&self, data: TimeSeriesData) -> Result<Vec<Anomaly>>;
+    /// Forecast `resource_id` in the cluster `horizon` ahead.
     async fn predict_scaling_needs(
         &self,
+        cluster_id: ClusterId,
         resource_id: String,
         horizon: Duration,
     ) -> Result<ScalingPrediction>;
+    /// Rightsize `containers` against their recent usage in the cluster.
     async fn generate_recommendations(
         &self,
         cluster_id: ClusterId,
+        containers: Vec<ContainerResources>,
     ) -> Result<Vec<Recommendation>>;
+    /// Rank probable causes of a stored anomaly; returns the analysis and
+    /// the anomaly annotated with it.
+    async fn analyze_root_cause(
+        &self,
+        cluster_id: ClusterId,
+        anomaly_id: String,
+        dependencies: Vec<StepDependency>,
+    ) -> Result<(RootCauseAnalysis, Anomaly)>;
 }
@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-use phenome_domain::{Assembly, Event, HealthSnapshot};
+use phenome_domain::{Assembly, BootstrapEta, Event, HealthSnapshot};
 
 use async_trait::async_trait;
 
@@ -138,6 +138,10 @@ impl BootstrapPort for NullBootstrapPort {
         None
     }
 
+    fn eta(&self) -> Option<BootstrapEta> {
+        None
+    }
+
     fn bootstrap_status(&self) -> BootstrapStatus {
         BootstrapStatus::default()
     }
@@ -195,9 +199,12 @@ struct NullAnalyticsPort;
 impl AnalyticsPort for NullAnalyticsPort {
     async fn record_metrics(
         &self,
-        _samples: Vec<phenome_domain::MetricSample>,
-    ) -> anyhow::Result<()> {
-        Ok(())
+        samples: Vec<phenome_domain::MetricSample>,
+    ) -> anyhow::Result<phenome_domain::IngestReport> {
+        Ok(phenome_domain::IngestReport {
+            accepted: samples.len(),
+            rejections: Vec::new(),
+        })
     }
 
     async fn query_aggregated(
@@ -212,6 +219,7 @@ impl AnalyticsPort for NullAnalyticsPort {
         resource_id: String,
         metric_type: phenome_domain::MetricType,
         _range: phenome_domain::TimeRange,
+        _max_points: Option<u32>,
     ) -> anyhow::Result<phenome_domain::TimeSeries> {
         Ok(phenome_domain::TimeSeries {
             cluster_id: String::new(),
@@ -219,6 +227,7 @@ impl AnalyticsPort for NullAnalyticsPort {
             metric_type,
             unit: String::new(),
             points: Vec::new(),
+            downsample: phenome_domain::DownsampleMethod::Raw,
         })
     }
 
@@ -242,6 +251,35 @@ impl AnalyticsPort for NullAnalyticsPort {
     ) -> anyhow::Result<Vec<phenome_domain::MetricSample>> {
         Ok(Vec::new())
     }
+
+    async fn get_data_quality(
+        &self,
+        _filter: phenome_domain::DataQualityFilter,
+    ) -> anyhow::Result<Vec<phenome_domain::DataQualityIssue>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_incidents(
+        &self,
+        _filter: phenome_domain::IncidentFilter,
+    ) -> anyhow::Result<Vec<phenome_domain::Incident>> {
+        Ok(Vec::new())
+    }
+
+    async fn submit_anomaly_feedback(
+        &self,
+        _anomaly_id: String,
+        _feedback: phenome_domain::AnomalyFeedback,
+    ) -> anyhow::Result<Option<phenome_domain::Anomaly>> {
+        Ok(None)
+    }
+
+    async fn get_anomaly_feedback(
+        &self,
+        _filter: phenome_domain::AnomalyFilter,
+    ) -> anyhow::Result<Vec<phenome_domain::Anomaly>> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Clone, Default)]
@@ -258,6 +296,7 @@ impl MLPort for NullMLPort {
 
     async fn predict_scaling_needs(
         &self,
+        _cluster_id: phenome_domain::ClusterId,
         resource_id: String,
         horizon: std::time::Duration,
     ) -> anyhow::Result<phenome_domain::ScalingPrediction> {
@@ -267,15 +306,30 @@ impl MLPort for NullMLPort {
             horizon,
             predicted_value: 0.0,
             unit: String::new(),
+            lower_bound: 0.0,
+            upper_bound: 0.0,
+            model: phenome_domain::ScalingModel::default(),
+            model_id: None,
+            model_version: None,
         })
     }
 
     async fn generate_recommendations(
         &self,
         _cluster_id: phenome_domain::ClusterId,
+        _containers: Vec<phenome_domain::ContainerResources>,
     ) -> anyhow::Result<Vec<phenome_domain::Recommendation>> {
         Ok(Vec::new())
     }
+
+    async fn analyze_root_cause(
+        &self,
+        _cluster_id: phenome_domain::ClusterId,
+        anomaly_id: String,
+        _dependencies: Vec<phenome_domain::StepDependency>,
+    ) -> anyhow::Result<(phenome_domain::RootCauseAnalysis, phenome_domain::Anomaly)> {
+        anyhow::bail!("anomaly {anomaly_id} not found")
+    }
 }
 
 #[derive(Clone, Default)]
@@ -310,11 +364,36 @@ impl SchedulerPort for NullSchedulerPort {
         Ok(String::new())
     }
 
-    async fn cancel_schedule(&self, _id: phenome_domain::ScheduleId) -> anyhow::Result<()> {
-        Ok(())
+    async fn cancel_schedule(
+        &self,
+        _id: phenome_domain::ScheduleId,
+    ) -> anyhow::Result<Option<phenome_domain::ScheduledAction>> {
+        Ok(None)
     }
 
     async fn list_scheduled(&self) -> anyhow::Result<Vec<phenome_domain::ScheduledAction>> {
         Ok(Vec::new())
     }
+
+    async fn get_scheduled(
+        &self,
+        _id: phenome_domain::ScheduleId,
+    ) -> anyhow::Result<Option<phenome_domain::ScheduledAction>> {
+        Ok(None)
+    }
+
+    async fn reschedule(
+        &self,
+        _id: phenome_domain::ScheduleId,
+        _execute_at: i64,
+    ) -> anyhow::Result<Option<phenome_domain::ScheduledAction>> {
+        Ok(None)
+    }
+
+    async fn execution_history(
+        &self,
+        _filter: phenome_domain::ScheduleExecutionFilter,
+    ) -> anyhow::Result<Vec<phenome_domain::ScheduleExecution>> {
+        Ok(Vec::new())
+    }
 }
@@ -3,6 +3,7 @@ use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
+use phenome_domain::{BootstrapEta, StepEstimate};
 pub use primer::application::events::InteractiveCommand;
 use primer::application::readiness::{DetailedStatus, ReadinessStatus};
 use primer::application::timing::TimingHistory;
@@ -73,6 +74,8 @@ pub struct ComponentTiming {
     pub apply_duration: Option<Duration>,
     pub wait_duration: Option<Duration>,
     pub last_elapsed: Option<Duration>,
+    /// Learned from earlier runs, while the component has not finished.
+    pub estimate: Option<StepEstimate>,
 }
 
 impl ComponentTiming {
@@ -124,6 +127,8 @@ pub trait BootstrapPort: Send + Sync {
     fn component_states(&self) -> HashMap<String, ComponentState>;
     fn dependency_graph(&self) -> &Assembly;
     fn timing_history(&self) -> Option<TimingHistory>;
+    /// Time left in the run, predicted from `timing_history`.
+    fn eta(&self) -> Option<BootstrapEta>;
     fn bootstrap_status(&self) -> BootstrapStatus;
     fn access_urls(&self) -> Vec<AccessUrlInfo>;
     fn send_command(&self, cmd: InteractiveCommand) -> Result<()>;
@@ -1,11 +1,22 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use phenome_domain::{ScheduleId, ScheduledAction};
+use phenome_domain::{ScheduleExecution, ScheduleExecutionFilter, ScheduleId, ScheduledAction};
 
 #[async_trait]
 pub trait SchedulerPort: Send + Sync {
     async fn schedule_action(&self, action: ScheduledAction) -> Result<ScheduleId>;
-    async fn cancel_schedule(&self, id: ScheduleId) -> Result<()>;
+    /// Cancel a pending action and return it, or `None` if no action has
+    /// that id.
+    async fn cancel_schedule(&self, id: ScheduleId) -> Result<Option<ScheduledAction>>;
     async fn list_scheduled(&self) -> Result<Vec<ScheduledAction>>;
+    async fn get_scheduled(&self, id: ScheduleId) -> Result<Option<ScheduledAction>>;
+    /// Move a pending action to `execute_at` and return it, or `None` if no
+    /// action has that id.
+    async fn reschedule(&self, id: ScheduleId, execute_at: i64) -> Result<Option<ScheduledAction>>;
+    /// Matching runs of scheduled actions, most recent first.
+    async fn execution_history(
+        &self,
+        filter: ScheduleExecutionFilter,
+    ) -> Result<Vec<ScheduleExecution>>;
 }
@@ -1,92 +1,803 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 
-use phenome_domain::{Anomaly, Severity, TimeSeriesData};
+use phenome_domain::{
+    Anomaly, AnomalyEvidence, AnomalyKind, AnomalyModelKind, ChangePointConfig,
+    DetectionThresholds, FeatureContribution, IsolationForestConfig, MetricType, MlConfig,
+    MlThresholdsConfig, ModelScope, MultivariateConfig, SeasonalConfig, Severity, TimeRange,
+    TimeSeries, TimeSeriesData, TimeSeriesPoint,
+};
+
+use crate::detection::change_point::{ChangePointDetector, DetectedChange};
+use crate::detection::isolation_forest::{FEATURE_NAMES, FEATURES, IsolationForest, Point};
+use crate::detection::multivariate::{MultivariateDetector, MultivariateFinding};
+use crate::detection::seasonal::SeasonalBaseline;
+use crate::registry::model_registry::{
+    FittedModel, ModelRecord, ModelRegistry, TrainedModel, anomaly_model_id,
+};
+
+/// Fewest finite points a series needs before it is scored at all.
+const MIN_POINTS: usize = 10;
+
+/// Latest points of a series kept as an anomaly's evidence.
+const EVIDENCE_POINTS: usize = 10;
 
 #[derive(Debug, Clone)]
 pub struct AnomalyDetector {
-    sigma_threshold: f64,
-    min_confidence: f64,
+    model: AnomalyModelKind,
+    model_by_metric: HashMap<MetricType, AnomalyModelKind>,
+    forest: IsolationForestConfig,
+    seasonal: SeasonalConfig,
+    seasonal_periods: Vec<i64>,
+    change_point: Option<ChangePointDetector>,
+    multivariate: Option<MultivariateDetector>,
+    thresholds: MlThresholdsConfig,
 }
 
 impl Default for AnomalyDetector {
     fn default() -> Self {
+        let seasonal = SeasonalConfig::default();
+        let seasonal_periods = seasonal
+            .period_ms()
+            .expect("default seasonal periods parse");
         Self {
-            sigma_threshold: 3.0,
-            min_confidence: 0.7,
+            model: AnomalyModelKind::ZScore,
+            model_by_metric: HashMap::new(),
+            forest: IsolationForestConfig::default(),
+            seasonal,
+            seasonal_periods,
+            change_point: ChangePointDetector::from_config(&ChangePointConfig::default()).ok(),
+            multivariate: MultivariateDetector::from_config(&MultivariateConfig::default()).ok(),
+            thresholds: MlThresholdsConfig::default(),
         }
     }
 }
 
+struct Finding {
+    severity: Severity,
+    confidence: f64,
+    description: String,
+    baseline: f64,
+    deviation_sigma: f64,
+    expected_low: f64,
+    expected_high: f64,
+    seasonal_expected: Option<f64>,
+    contributions: Vec<FeatureContribution>,
+}
+
+/// A finding with the history its model learned from, and the id and, for
+/// registry models, version of that model.
+struct Attributed {
+    finding: Finding,
+    baseline_window: TimeRange,
+    model_id: String,
+    model_version: Option<u32>,
+}
+
 impl AnomalyDetector {
+    pub fn from_config(config: &MlConfig) -> Result<Self> {
+        Self {
+            model: config.models.anomaly_detection,
+            model_by_metric: config.models.anomaly_detection_by_metric.clone(),
+            forest: config.isolation_forest.clone(),
+            seasonal_periods: config.seasonal.period_ms()?,
+            seasonal: config.seasonal.clone(),
+            ..Self::default()
+        }
+        .with_thresholds(config.thresholds.clone())?
+        .with_change_point(&config.change_point)?
+        .with_multivariate(&config.multivariate)
+    }
+
+    pub fn with_model(mut self, model: AnomalyModelKind) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Score `metric_type` series with `model` instead of the default model.
+    pub fn with_metric_model(mut self, metric_type: MetricType, model: AnomalyModelKind) -> Self {
+        self.model_by_metric.insert(metric_type, model);
+        self
+    }
+
+    pub fn with_seasonal(mut self, seasonal: SeasonalConfig) -> Result<Self> {
+        self.seasonal_periods = seasonal.period_ms()?;
+        self.seasonal = seasonal;
+        Ok(self)
+    }
+
+    /// Report and grade anomalies by `thresholds` rather than the defaults.
+    pub fn with_thresholds(mut self, thresholds: MlThresholdsConfig) -> Result<Self> {
+        thresholds.validate()?;
+        self.thresholds = thresholds;
+        Ok(self)
+    }
+
+    pub fn with_isolation_forest(mut self, forest: IsolationForestConfig) -> Self {
+        self.forest = forest;
+        self
+    }
+
+    /// Also report level shifts and trend changes, unless `config` disables
+    /// them.
+    pub fn with_change_point(mut self, config: &ChangePointConfig) -> Result<Self> {
+        self.change_point = if config.enabled {
+            Some(ChangePointDetector::from_config(config)?)
+        } else {
+            None
+        };
+        Ok(self)
+    }
+
+    /// Also score each resource's metrics jointly, unless `config`
+    /// disables it.
+    pub fn with_multivariate(mut self, config: &MultivariateConfig) -> Result<Self> {
+        self.multivariate = if config.enabled {
+            Some(MultivariateDetector::from_config(config)?)
+        } else {
+            None
+        };
+        Ok(self)
+    }
+
+    pub fn model(&self) -> AnomalyModelKind {
+        self.model
+    }
+
+    /// Model used for series of `metric_type`.
+    pub fn model_for(&self, metric_type: MetricType) -> AnomalyModelKind {
+        self.model_by_metric
+            .get(&metric_type)
+            .copied()
+            .unwrap_or(self.model)
+    }
+
+    /// Score the latest point of each series, fitting the model on the
+    /// series itself.
     pub fn detect(&self, data: &TimeSeriesData) -> Result<Vec<Anomaly>> {
+        self.detect_series(data, None)
+    }
+
+    /// Score the latest point of each series with its trained model in
+    /// `registry`: the series' own model, else its metric's pooled model.
+    /// Series without one are scored as by `detect`.
+    pub fn detect_with(
+        &self,
+        data: &TimeSeriesData,
+        registry: &ModelRegistry,
+    ) -> Result<Vec<Anomaly>> {
+        self.detect_series(data, Some(registry))
+    }
+
+    fn detect_series(
+        &self,
+        data: &TimeSeriesData,
+        registry: Option<&ModelRegistry>,
+    ) -> Result<Vec<Anomaly>> {
         let mut anomalies = Vec::new();
         for series in &data.series {
-            let values: Vec<f64> = series
-                .points
-                .iter()
-                .map(|point| point.value)
-                .filter(|value| value.is_finite())
-                .collect();
-            if values.len() < 10 {
-                // Not enough data for ML, use Z-score or skip
+            let points = finite_points(series);
+            if points.len() < MIN_POINTS {
                 continue;
             }
-
-            // Fallback Z-score for small data or if ML fails/is overkill
-            let mean = values.iter().sum::<f64>() / values.len() as f64;
-            let variance =
-                values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
-            let stddev = variance.sqrt();
-            let latest = if let Some(l) = series.points.last() {
-                l
-            } else {
+            let Some(&latest) = points.last() else {
                 continue;
             };
+            let model = self.model_for(series.metric_type);
+            let limits = self.thresholds.for_metric(series.metric_type);
+            let trained = registry.and_then(|registry| {
+                let record = registry
+                    .get(&anomaly_model_id(
+                        model,
+                        series.metric_type,
+                        Some(&series.resource_id),
+                    ))
+                    .or_else(|| registry.get(&anomaly_model_id(model, series.metric_type, None)))?;
+                let window = registry.training_window().as_millis() as i64;
+                Some((record, window))
+            });
+            let attributed = match trained {
+                Some((record, window)) => self.score_trained(record, window, &points, &limits),
+                None => self.score_fitted(model, &points, &limits),
+            };
 
-            let mut detected_anomaly = None;
-
-            // Isolation Forest integration is deferred; current detector uses Z-score only.
-
-            // Fallback or confirm with Z-score
-            if detected_anomaly.is_none() && stddev > f64::EPSILON {
-                let deviation = (latest.value - mean).abs() / stddev;
-                if deviation >= self.sigma_threshold {
-                    // Z-score anomaly
-                    let confidence = (deviation / (self.sigma_threshold * 1.5)).min(0.99);
-                    if confidence >= self.min_confidence {
-                        let severity = if confidence > 0.9 {
-                            Severity::Critical
-                        } else {
-                            Severity::Warning
-                        };
-                        detected_anomaly = Some((
-                            severity,
-                            confidence,
-                            format!("{:.2} sigma deviation", deviation),
-                        ));
-                    }
-                }
-            }
-
-            if let Some((severity, confidence, desc)) = detected_anomaly {
+            if let Some(attributed) = attributed {
+                let finding = attributed.finding;
                 anomalies.push(Anomaly {
                     id: format!("{}-{}", series.resource_id, latest.timestamp),
                     cluster_id: data.cluster_id.clone(),
                     resource_id: series.resource_id.clone(),
                     detected_at: latest.timestamp,
                     metric_type: series.metric_type,
-                    severity,
-                    confidence,
-                    description: desc,
-                    baseline_value: mean,
+                    severity: finding.severity,
+                    confidence: finding.confidence,
+                    description: finding.description,
+                    baseline_value: finding.baseline,
                     observed_value: latest.value,
-                    deviation_sigma: (latest.value - mean).abs() / stddev.max(f64::EPSILON),
+                    deviation_sigma: finding.deviation_sigma,
                     related_metrics: Vec::new(),
                     root_cause: None,
+                    model_id: Some(attributed.model_id),
+                    model_version: attributed.model_version,
+                    kind: AnomalyKind::Spike,
+                    change_point: None,
+                    feedback: None,
+                    evidence: Some(AnomalyEvidence {
+                        baseline_window: attributed.baseline_window,
+                        expected_low: finding.expected_low,
+                        expected_high: finding.expected_high,
+                        seasonal_expected: finding.seasonal_expected,
+                        points: recent(&points),
+                        contributions: finding.contributions,
+                    }),
+                });
+            }
+
+            if let Some(detector) = &self.change_point {
+                let adjusted = self.seasonally_adjusted(&points);
+                let view: Vec<&TimeSeriesPoint> = match &adjusted {
+                    Some(adjusted) => adjusted.iter().collect(),
+                    None => points.clone(),
+                };
+                if let Some(found) = detector.detect(&view) {
+                    let basis = if adjusted.is_some() {
+                        ", seasonally adjusted"
+                    } else {
+                        ""
+                    };
+                    anomalies
+                        .extend(self.regime_change(data, series, latest, detector, found, basis));
+                }
+            }
+        }
+
+        if let Some(detector) = &self.multivariate {
+            let mut resources: Vec<(&str, Vec<&TimeSeries>)> = Vec::new();
+            for series in &data.series {
+                match resources
+                    .iter_mut()
+                    .find(|(resource_id, _)| *resource_id == series.resource_id)
+                {
+                    Some((_, group)) => group.push(series),
+                    None => resources.push((&series.resource_id, vec![series])),
+                }
+            }
+            for (resource_id, group) in resources {
+                if group.len() < 2 {
+                    continue;
+                }
+                let Some(finding) = detector.score(&group) else {
+                    continue;
+                };
+                // A metric already flagged on its own explains the reading.
+                let flagged = anomalies.iter().any(|anomaly| {
+                    anomaly.resource_id == resource_id
+                        && anomaly.kind == AnomalyKind::Spike
+                        && anomaly.detected_at == finding.timestamp
                 });
+                if !flagged {
+                    anomalies.extend(self.joint(data, resource_id, finding));
+                }
             }
         }
 
         Ok(anomalies)
     }
+
+    /// A resource's metrics far off in combination, attributed to the
+    /// metric with the largest share of the distance. `related_metrics`
+    /// lists every metric that added to it, largest share first.
+    fn joint(
+        &self,
+        data: &TimeSeriesData,
+        resource_id: &str,
+        finding: MultivariateFinding,
+    ) -> Option<Anomaly> {
+        let top = finding.contributions.first()?;
+        let limits = self.thresholds.for_metric(top.metric_type);
+        let deviation = finding.deviation_sigma;
+        let confidence = limits.confidence(deviation);
+        if deviation < limits.sigma_threshold || confidence < limits.min_confidence {
+            return None;
+        }
+        let contributors: Vec<_> = finding
+            .contributions
+            .iter()
+            .filter(|contribution| contribution.share > 0.0)
+            .collect();
+        let shares: Vec<String> = contributors
+            .iter()
+            .map(|contribution| {
+                format!(
+                    "{} {:.0}%",
+                    contribution.metric_type.as_str(),
+                    contribution.share * 100.0
+                )
+            })
+            .collect();
+        Some(Anomaly {
+            id: format!("{resource_id}-multivariate-{}", finding.timestamp),
+            cluster_id: data.cluster_id.clone(),
+            resource_id: resource_id.to_string(),
+            detected_at: finding.timestamp,
+            metric_type: top.metric_type,
+            severity: limits.severity(confidence),
+            confidence,
+            description: format!(
+                "{deviation:.2} sigma joint deviation across {} metrics ({})",
+                finding.contributions.len(),
+                shares.join(", ")
+            ),
+            baseline_value: top.mean,
+            observed_value: top.value,
+            deviation_sigma: deviation,
+            related_metrics: contributors
+                .iter()
+                .map(|contribution| format!("{resource_id}:{}", contribution.metric_type.as_str()))
+                .collect(),
+            root_cause: None,
+            model_id: Some("multivariate".to_string()),
+            model_version: None,
+            kind: AnomalyKind::Spike,
+            change_point: None,
+            feedback: None,
+            evidence: Some(AnomalyEvidence {
+                baseline_window: TimeRange {
+                    start_ms: finding.since,
+                    end_ms: finding.timestamp,
+                },
+                expected_low: top.mean - limits.sigma_threshold * top.stddev,
+                expected_high: top.mean + limits.sigma_threshold * top.stddev,
+                seasonal_expected: None,
+                points: data
+                    .series
+                    .iter()
+                    .find(|series| {
+                        series.resource_id == resource_id && series.metric_type == top.metric_type
+                    })
+                    .map(|series| recent(&finite_points(series)))
+                    .unwrap_or_default(),
+                contributions: finding
+                    .contributions
+                    .iter()
+                    .map(|contribution| FeatureContribution {
+                        feature: contribution.metric_type.as_str().to_string(),
+                        value: contribution.value,
+                        expected: contribution.mean,
+                        share: contribution.share,
+                    })
+                    .collect(),
+            }),
+        })
+    }
+
+    /// `points` with their learned daily or weekly pattern removed, so a
+    /// recurring batch window is not mistaken for a change of regime.
+    /// `None` when no period can be learned.
+    fn seasonally_adjusted(&self, points: &[&TimeSeriesPoint]) -> Option<Vec<TimeSeriesPoint>> {
+        let history: Vec<TimeSeriesPoint> = points.iter().map(|point| (*point).clone()).collect();
+        let baseline = SeasonalBaseline::learn(&history, &self.seasonal_periods, &self.seasonal)?;
+        let level = history.iter().map(|point| point.value).sum::<f64>() / history.len() as f64;
+        Some(
+            history
+                .into_iter()
+                .map(|point| TimeSeriesPoint {
+                    value: point.value - baseline.expected(point.timestamp) + level,
+                    ..point
+                })
+                .collect(),
+        )
+    }
+
+    /// A level shift or trend change, identified by where it happened so
+    /// that re-running detection reports the same anomaly. The baseline is
+    /// the mean before the change. Its size past the shift threshold is
+    /// graded like a deviation past `sigma_threshold`.
+    fn regime_change(
+        &self,
+        data: &TimeSeriesData,
+        series: &TimeSeries,
+        latest: &TimeSeriesPoint,
+        detector: &ChangePointDetector,
+        found: DetectedChange,
+        basis: &str,
+    ) -> Option<Anomaly> {
+        let limits = self.thresholds.for_metric(series.metric_type);
+        let confidence =
+            limits.confidence(found.magnitude / detector.shift_sigma() * limits.sigma_threshold);
+        if confidence < limits.min_confidence {
+            return None;
+        }
+        let change = found.change;
+        let description = match found.kind {
+            AnomalyKind::TrendChange => format!(
+                "trend change from {:+.3}/h to {:+.3}/h ({:.2} sigma{basis})",
+                change.before_slope, change.after_slope, found.magnitude
+            ),
+            _ => format!(
+                "level shift from {:.2} to {:.2} ({:.2} sigma{basis})",
+                change.before_mean, change.after_mean, found.magnitude
+            ),
+        };
+        let points = finite_points(series);
+        let reach = detector.shift_sigma() * change.before_stddev;
+        Some(Anomaly {
+            id: format!(
+                "{}-{}-{}",
+                series.resource_id,
+                found.kind.as_str(),
+                change.changed_at
+            ),
+            cluster_id: data.cluster_id.clone(),
+            resource_id: series.resource_id.clone(),
+            detected_at: latest.timestamp,
+            metric_type: series.metric_type,
+            severity: limits.severity(confidence),
+            confidence,
+            description,
+            baseline_value: change.before_mean,
+            observed_value: latest.value,
+            deviation_sigma: found.magnitude,
+            related_metrics: Vec::new(),
+            root_cause: None,
+            model_id: Some("change_point".to_string()),
+            model_version: None,
+            kind: found.kind,
+            change_point: Some(change),
+            feedback: None,
+            evidence: Some(AnomalyEvidence {
+                baseline_window: TimeRange {
+                    start_ms: points
+                        .first()
+                        .map_or(change.changed_at, |point| point.timestamp),
+                    end_ms: change.changed_at,
+                },
+                expected_low: change.before_mean - reach,
+                expected_high: change.before_mean + reach,
+                seasonal_expected: None,
+                points: recent(&points),
+                contributions: Vec::new(),
+            }),
+        })
+    }
+
+    /// Fit the configured model on `points` and score the latest of them.
+    fn score_fitted(
+        &self,
+        model: AnomalyModelKind,
+        points: &[&TimeSeriesPoint],
+        limits: &DetectionThresholds,
+    ) -> Option<Attributed> {
+        let latest = *points.last()?;
+        let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+        let (mean, stddev) = mean_stddev(&values);
+
+        // The forest needs a full window of history behind every training
+        // point and the seasonal baseline a learnable period; otherwise
+        // both fall back to the z-score.
+        let baseline = match model {
+            AnomalyModelKind::Seasonal => {
+                let history: Vec<TimeSeriesPoint> = points[..points.len() - 1]
+                    .iter()
+                    .map(|point| (*point).clone())
+                    .collect();
+                SeasonalBaseline::learn(&history, &self.seasonal_periods, &self.seasonal)
+            }
+            _ => None,
+        };
+        let window = |end: &TimeSeriesPoint| TimeRange {
+            start_ms: points[0].timestamp,
+            end_ms: end.timestamp,
+        };
+        let (finding, baseline_window, used) = match (model, baseline) {
+            (AnomalyModelKind::IsolationForest, _) if values.len() > 2 * self.forest.window => {
+                let embedded = IsolationForest::embed(&values, self.forest.window);
+                let forest = IsolationForest::fit(&embedded, &self.forest);
+                let finding =
+                    self.isolation_forest(&forest, &embedded, &values, mean, stddev, limits);
+                (finding, window(latest), AnomalyModelKind::IsolationForest)
+            }
+            // The seasonal baseline is learned without the latest point.
+            (_, Some(baseline)) => (
+                self.seasonal(&baseline, latest, limits),
+                window(points[points.len() - 2]),
+                AnomalyModelKind::Seasonal,
+            ),
+            _ => (
+                self.z_score(latest.value, mean, stddev, limits),
+                window(latest),
+                AnomalyModelKind::ZScore,
+            ),
+        };
+        Some(Attributed {
+            finding: finding?,
+            baseline_window,
+            model_id: used.as_str().to_string(),
+            model_version: None,
+        })
+    }
+
+    /// Score the latest of `points` with a model from the registry, whose
+    /// baseline is the training window that ended when it was fitted.
+    fn score_trained(
+        &self,
+        record: &ModelRecord,
+        window_ms: i64,
+        points: &[&TimeSeriesPoint],
+        limits: &DetectionThresholds,
+    ) -> Option<Attributed> {
+        let latest = *points.last()?;
+        let finding = match &record.model {
+            TrainedModel::ZScore { mean, stddev } => {
+                self.z_score(latest.value, *mean, *stddev, limits)
+            }
+            TrainedModel::IsolationForest {
+                window,
+                mean,
+                stddev,
+                forest,
+            } => {
+                // The latest embedded point only needs a window of history;
+                // the rest of the series explains which feature stood out.
+                let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+                let embedded = IsolationForest::embed(&values, *window);
+                self.isolation_forest(forest, &embedded, &values, *mean, *stddev, limits)
+            }
+            TrainedModel::Seasonal { baseline } => self.seasonal(baseline, latest, limits),
+            TrainedModel::ScalingSelection { .. } => None,
+        }?;
+        Some(Attributed {
+            finding,
+            baseline_window: TimeRange {
+                start_ms: record.trained_at - window_ms,
+                end_ms: record.trained_at,
+            },
+            model_id: record.id.clone(),
+            model_version: Some(record.version),
+        })
+    }
+
+    /// Fit the configured model of every series in `data`, one model per
+    /// series or, with `ModelScope::Metric`, one per metric type pooled
+    /// over its series. Seasonal baselines are always fitted per series,
+    /// and series too short for their model get a z-score model instead.
+    pub fn train(&self, data: &TimeSeriesData, scope: ModelScope) -> Vec<FittedModel> {
+        let mut fitted = Vec::new();
+        let mut pooled: HashMap<MetricType, Vec<&TimeSeries>> = HashMap::new();
+        for series in &data.series {
+            let model = self.model_for(series.metric_type);
+            if scope == ModelScope::Metric && model != AnomalyModelKind::Seasonal {
+                pooled.entry(series.metric_type).or_default().push(series);
+                continue;
+            }
+            let points = finite_points(series);
+            if points.len() < MIN_POINTS {
+                continue;
+            }
+            let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+            let trained = match model {
+                AnomalyModelKind::Seasonal => {
+                    let history: Vec<TimeSeriesPoint> =
+                        points.iter().map(|point| (*point).clone()).collect();
+                    SeasonalBaseline::learn(&history, &self.seasonal_periods, &self.seasonal)
+                        .map(|baseline| TrainedModel::Seasonal { baseline })
+                }
+                _ => self.fit_pooled(model, &[values.as_slice()]),
+            };
+            fitted.push(FittedModel {
+                id: anomaly_model_id(model, series.metric_type, Some(&series.resource_id)),
+                samples: values.len(),
+                model: trained.unwrap_or_else(|| z_score_model(&values)),
+            });
+        }
+
+        for (metric_type, series) in pooled {
+            let values: Vec<Vec<f64>> = series
+                .iter()
+                .map(|series| {
+                    finite_points(series)
+                        .iter()
+                        .map(|point| point.value)
+                        .collect()
+                })
+                .collect();
+            let slices: Vec<&[f64]> = values.iter().map(Vec::as_slice).collect();
+            let samples: usize = slices.iter().map(|values| values.len()).sum();
+            if samples < MIN_POINTS {
+                continue;
+            }
+            let model = self.model_for(metric_type);
+            fitted.push(FittedModel {
+                id: anomaly_model_id(model, metric_type, None),
+                samples,
+                model: self
+                    .fit_pooled(model, &slices)
+                    .unwrap_or_else(|| z_score_model(&slices.concat())),
+            });
+        }
+        fitted
+    }
+
+    /// Fit a z-score or forest model on one or more series at once; a
+    /// forest is embedded per series so windows never span two of them.
+    fn fit_pooled(&self, model: AnomalyModelKind, series: &[&[f64]]) -> Option<TrainedModel> {
+        let values = series.concat();
+        match model {
+            AnomalyModelKind::IsolationForest => {
+                let embedded: Vec<_> = series
+                    .iter()
+                    .filter(|values| values.len() > 2 * self.forest.window)
+                    .flat_map(|values| IsolationForest::embed(values, self.forest.window))
+                    .collect();
+                if embedded.is_empty() {
+                    return None;
+                }
+                let (mean, stddev) = mean_stddev(&values);
+                Some(TrainedModel::IsolationForest {
+                    window: self.forest.window,
+                    mean,
+                    stddev,
+                    forest: IsolationForest::fit(&embedded, &self.forest),
+                })
+            }
+            _ => Some(z_score_model(&values)),
+        }
+    }
+
+    /// Score the latest embedded point of `values` with `forest`, fitted
+    /// either on the series itself, latest point included, or on earlier
+    /// history from the registry.
+    fn isolation_forest(
+        &self,
+        forest: &IsolationForest,
+        embedded: &[Point],
+        values: &[f64],
+        mean: f64,
+        stddev: f64,
+        limits: &DetectionThresholds,
+    ) -> Option<Finding> {
+        let latest = embedded.last()?;
+        let score = forest.score(latest);
+        // The threshold reads as `sigma_threshold`, so confidence and
+        // severity line up with the statistical detectors.
+        let confidence = limits.confidence(forest.calibrate(score) * limits.sigma_threshold);
+        if score < forest.threshold() || confidence < limits.min_confidence {
+            return None;
+        }
+        Some(Finding {
+            severity: limits.severity(confidence),
+            confidence,
+            description: format!(
+                "isolation score {score:.2} above threshold {:.2}",
+                forest.threshold()
+            ),
+            baseline: mean,
+            deviation_sigma: (values[values.len() - 1] - mean).abs() / stddev.max(f64::EPSILON),
+            expected_low: mean - limits.sigma_threshold * stddev,
+            expected_high: mean + limits.sigma_threshold * stddev,
+            seasonal_expected: None,
+            contributions: forest_contributions(embedded),
+        })
+    }
+
+    /// Score the latest point against the value its phase in each learned
+    /// period predicts, learned from the history before it.
+    fn seasonal(
+        &self,
+        baseline: &SeasonalBaseline,
+        latest: &TimeSeriesPoint,
+        limits: &DetectionThresholds,
+    ) -> Option<Finding> {
+        let deviation = baseline.deviation(latest.timestamp, latest.value);
+        let confidence = limits.confidence(deviation);
+        if deviation < limits.sigma_threshold || confidence < limits.min_confidence {
+            return None;
+        }
+        let expected = baseline.expected(latest.timestamp);
+        let periods: Vec<String> = baseline
+            .periods_ms()
+            .iter()
+            .map(|period| format!("{}h", period / 3_600_000))
+            .collect();
+        Some(Finding {
+            severity: limits.severity(confidence),
+            confidence,
+            description: format!(
+                "{deviation:.2} sigma from seasonal baseline {expected:.2} (period {})",
+                periods.join(", ")
+            ),
+            baseline: expected,
+            deviation_sigma: deviation,
+            expected_low: expected - limits.sigma_threshold * baseline.scale(),
+            expected_high: expected + limits.sigma_threshold * baseline.scale(),
+            seasonal_expected: Some(expected),
+            contributions: Vec::new(),
+        })
+    }
+
+    fn z_score(
+        &self,
+        value: f64,
+        mean: f64,
+        stddev: f64,
+        limits: &DetectionThresholds,
+    ) -> Option<Finding> {
+        if stddev <= f64::EPSILON {
+            return None;
+        }
+        let deviation = (value - mean).abs() / stddev;
+        let confidence = limits.confidence(deviation);
+        if deviation < limits.sigma_threshold || confidence < limits.min_confidence {
+            return None;
+        }
+        Some(Finding {
+            severity: limits.severity(confidence),
+            confidence,
+            description: format!("{:.2} sigma deviation", deviation),
+            baseline: mean,
+            deviation_sigma: deviation,
+            expected_low: mean - limits.sigma_threshold * stddev,
+            expected_high: mean + limits.sigma_threshold * stddev,
+            seasonal_expected: None,
+            contributions: Vec::new(),
+        })
+    }
+}
+
+/// The last `EVIDENCE_POINTS` of a series, shown next to the anomaly.
+fn recent(points: &[&TimeSeriesPoint]) -> Vec<TimeSeriesPoint> {
+    points[points.len().saturating_sub(EVIDENCE_POINTS)..]
+        .iter()
+        .map(|point| (*point).clone())
+        .collect()
+}
+
+/// How far each feature of the latest embedded point sits from the same
+/// feature over the points before it, as shares of the squared distance.
+fn forest_contributions(embedded: &[Point]) -> Vec<FeatureContribution> {
+    let Some((latest, history)) = embedded.split_last() else {
+        return Vec::new();
+    };
+    let spread: Vec<(f64, f64)> = (0..FEATURES)
+        .map(|feature| {
+            let values: Vec<f64> = history.iter().map(|point| point[feature]).collect();
+            let (mean, stddev) = mean_stddev(&values);
+            let z = (latest[feature] - mean) / stddev.max(f64::EPSILON);
+            (mean, z * z)
+        })
+        .collect();
+    let total: f64 = spread.iter().map(|(_, squared)| squared).sum();
+    let mut contributions: Vec<FeatureContribution> = spread
+        .iter()
+        .enumerate()
+        .map(|(feature, (mean, squared))| FeatureContribution {
+            feature: FEATURE_NAMES[feature].to_string(),
+            value: latest[feature],
+            expected: *mean,
+            share: if total > 0.0 { squared / total } else { 0.0 },
+        })
+        .collect();
+    contributions.sort_by(|a, b| b.share.total_cmp(&a.share));
+    contributions
+}
+
+fn finite_points(series: &TimeSeries) -> Vec<&TimeSeriesPoint> {
+    series
+        .points
+        .iter()
+        .filter(|point| point.value.is_finite())
+        .collect()
+}
+
+pub(crate) fn mean_stddev(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len().max(1) as f64;
+    (mean, variance.sqrt())
+}
+
+fn z_score_model(values: &[f64]) -> TrainedModel {
+    let (mean, stddev) = mean_stddev(values);
+    TrainedModel::ZScore { mean, stddev }
 }
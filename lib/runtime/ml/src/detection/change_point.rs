@@ -0,0 +1,263 @@
+//! Change-point detection for regime shifts.
+//!
+//! A point model stops flagging a new level once its baseline catches up,
+//! so sustained changes are located separately. Level shifts are found with
+//! CUSUM: the split where the cumulative deviation from the overall mean
+//! peaks, confirmed by lines fitted either side jumping there. Trend
+//! changes are found as the split whose two least-squares lines fit the
+//! series best while still meeting at the split.
+
+use anyhow::Result;
+
+use phenome_domain::{AnomalyKind, ChangePoint, ChangePointConfig, TimeSeriesPoint};
+
+const HOUR_MS: f64 = 3_600_000.0;
+
+/// A change located in a series and how far it stands out, in standard
+/// deviations of the noise around the fitted segments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedChange {
+    pub kind: AnomalyKind,
+    pub magnitude: f64,
+    pub change: ChangePoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangePointDetector {
+    min_segment: usize,
+    shift_sigma: f64,
+    max_age_ms: i64,
+}
+
+impl ChangePointDetector {
+    pub fn from_config(config: &ChangePointConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            min_segment: config.min_segment,
+            shift_sigma: config.shift_sigma,
+            max_age_ms: config.max_age_ms()?,
+        })
+    }
+
+    /// Threshold `magnitude` must reach to be reported.
+    pub fn shift_sigma(&self) -> f64 {
+        self.shift_sigma
+    }
+
+    /// The most recent significant change in time-ordered `points`, if it
+    /// happened within `max_age` of the latest point. Level shifts take
+    /// precedence over trend changes.
+    pub fn detect(&self, points: &[&TimeSeriesPoint]) -> Option<DetectedChange> {
+        if points.len() < 2 * self.min_segment {
+            return None;
+        }
+        let sums = Sums::new(points);
+        let found = self
+            .level_shift(&sums)
+            .or_else(|| self.trend_change(&sums))?;
+        let latest = points.last()?.timestamp;
+        (latest - found.change.changed_at <= self.max_age_ms).then_some(found)
+    }
+
+    fn level_shift(&self, sums: &Sums) -> Option<DetectedChange> {
+        let n = sums.len();
+        let mean = sums.y[n] / n as f64;
+        let split = (self.min_segment..=n - self.min_segment).max_by(|&a, &b| {
+            let cusum = |k: usize| (sums.y[k] - k as f64 * mean).abs();
+            cusum(a).total_cmp(&cusum(b))
+        })?;
+
+        let before = sums.flat(0, split);
+        let after = sums.flat(split, n);
+        let noise = before.stddev().max(after.stddev()).max(f64::EPSILON);
+        let magnitude = (after.mean - before.mean).abs() / noise;
+        // A ramp or a bend also separates into a low and a high half; it is
+        // a shift only when lines fitted either side jump at the split.
+        if sums.jump(split) < self.shift_sigma || magnitude < self.shift_sigma {
+            return None;
+        }
+        Some(DetectedChange {
+            kind: AnomalyKind::LevelShift,
+            magnitude,
+            change: sums.change_point(split),
+        })
+    }
+
+    fn trend_change(&self, sums: &Sums) -> Option<DetectedChange> {
+        let n = sums.len();
+        let split = (self.min_segment..=n - self.min_segment).min_by(|&a, &b| {
+            let sse = |k: usize| sums.line(0, k).sse + sums.line(k, n).sse;
+            sse(a).total_cmp(&sse(b))
+        })?;
+        // Lines that do not meet at the split describe a jump, which the
+        // level-shift check already judged too small to report.
+        if sums.jump(split) >= self.shift_sigma {
+            return None;
+        }
+
+        let before = sums.line(0, split);
+        let after = sums.line(split, n);
+        let slope_change = (after.slope - before.slope).abs();
+        // The new slope must be both well estimated and large enough to
+        // carry the series away from the old line by the end.
+        let error = (before.stddev().powi(2) / before.sxx + after.stddev().powi(2) / after.sxx)
+            .sqrt()
+            .max(f64::EPSILON);
+        let noise = before.stddev().max(after.stddev()).max(f64::EPSILON);
+        let span = sums.x_at(n - 1) - sums.x_at(split);
+        let magnitude = (slope_change / error).min(slope_change * span / noise);
+        (magnitude >= self.shift_sigma).then(|| DetectedChange {
+            kind: AnomalyKind::TrendChange,
+            magnitude,
+            change: sums.change_point(split),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Flat {
+    count: usize,
+    mean: f64,
+    sse: f64,
+}
+
+impl Flat {
+    fn stddev(&self) -> f64 {
+        (self.sse / (self.count - 1) as f64).sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    count: usize,
+    slope: f64,
+    intercept: f64,
+    sse: f64,
+    /// Spread of the segment's hours, which bounds how well `slope` is
+    /// known.
+    sxx: f64,
+}
+
+impl Line {
+    fn at(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+
+    fn stddev(&self) -> f64 {
+        (self.sse / (self.count - 2) as f64).sqrt()
+    }
+}
+
+/// Prefix sums over hours since the first point and values centred on the
+/// series mean, so any segment's flat or linear fit costs O(1).
+struct Sums {
+    timestamps: Vec<i64>,
+    offset: f64,
+    x: Vec<f64>,
+    y: Vec<f64>,
+    xx: Vec<f64>,
+    xy: Vec<f64>,
+    yy: Vec<f64>,
+}
+
+impl Sums {
+    fn new(points: &[&TimeSeriesPoint]) -> Self {
+        let start = points[0].timestamp;
+        let offset = points.iter().map(|point| point.value).sum::<f64>() / points.len() as f64;
+        let mut sums = Self {
+            timestamps: points.iter().map(|point| point.timestamp).collect(),
+            offset,
+            x: vec![0.0],
+            y: vec![0.0],
+            xx: vec![0.0],
+            xy: vec![0.0],
+            yy: vec![0.0],
+        };
+        for point in points {
+            let x = (point.timestamp - start) as f64 / HOUR_MS;
+            let y = point.value - offset;
+            sums.x.push(sums.x.last().unwrap_or(&0.0) + x);
+            sums.y.push(sums.y.last().unwrap_or(&0.0) + y);
+            sums.xx.push(sums.xx.last().unwrap_or(&0.0) + x * x);
+            sums.xy.push(sums.xy.last().unwrap_or(&0.0) + x * y);
+            sums.yy.push(sums.yy.last().unwrap_or(&0.0) + y * y);
+        }
+        sums
+    }
+
+    fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    fn x_at(&self, index: usize) -> f64 {
+        self.x[index + 1] - self.x[index]
+    }
+
+    fn range(sums: &[f64], from: usize, to: usize) -> f64 {
+        sums[to] - sums[from]
+    }
+
+    /// Constant fit over points `from..to`, in centred units.
+    fn flat(&self, from: usize, to: usize) -> Flat {
+        let n = (to - from) as f64;
+        let sy = Self::range(&self.y, from, to);
+        let syy = Self::range(&self.yy, from, to);
+        Flat {
+            count: to - from,
+            mean: sy / n,
+            sse: (syy - sy * sy / n).max(0.0),
+        }
+    }
+
+    /// Least-squares line over points `from..to`, in centred units.
+    fn line(&self, from: usize, to: usize) -> Line {
+        let n = (to - from) as f64;
+        let sx = Self::range(&self.x, from, to);
+        let sy = Self::range(&self.y, from, to);
+        let sxx = Self::range(&self.xx, from, to) - sx * sx / n;
+        let sxy = Self::range(&self.xy, from, to) - sx * sy / n;
+        let syy = (Self::range(&self.yy, from, to) - sy * sy / n).max(0.0);
+        if sxx <= f64::EPSILON {
+            return Line {
+                count: to - from,
+                slope: 0.0,
+                intercept: sy / n,
+                sse: syy,
+                sxx: f64::EPSILON,
+            };
+        }
+        let slope = sxy / sxx;
+        Line {
+            count: to - from,
+            slope,
+            intercept: (sy - slope * sx) / n,
+            sse: (syy - slope * sxy).max(0.0),
+            sxx,
+        }
+    }
+
+    /// Gap at `split` between lines fitted either side of it, in standard
+    /// deviations of the noisier side.
+    fn jump(&self, split: usize) -> f64 {
+        let before = self.line(0, split);
+        let after = self.line(split, self.len());
+        let at = self.x_at(split);
+        let noise = before.stddev().max(after.stddev()).max(f64::EPSILON);
+        (after.at(at) - before.at(at)).abs() / noise
+    }
+
+    fn change_point(&self, split: usize) -> ChangePoint {
+        let n = self.len();
+        let before = self.flat(0, split);
+        let after = self.flat(split, n);
+        ChangePoint {
+            changed_at: self.timestamps[split],
+            before_mean: before.mean + self.offset,
+            after_mean: after.mean + self.offset,
+            before_stddev: before.stddev(),
+            after_stddev: after.stddev(),
+            before_slope: self.line(0, split).slope,
+            after_slope: self.line(split, n).slope,
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Isolation Forest over sliding-window embeddings of a series.
+//!
+//! Anomalous points are separated from the rest by fewer random axis-aligned
+//! splits, so their average path length across trees is short. Scores follow
+//! Liu et al.: `2^(-E[h(x)] / c(n))`, close to 1 for anomalies and around 0.5
+//! or below for ordinary points.
+
+use serde::{Deserialize, Serialize};
+
+use phenome_domain::IsolationForestConfig;
+
+/// Fixed seed so the same history always yields the same model.
+const SEED: u64 = 0x5eed_1501_f0e5_7000;
+
+/// Features per embedded point: the value, its step from the previous
+/// sample and its deviation from the trailing window mean.
+pub const FEATURES: usize = 3;
+
+pub type Point = [f64; FEATURES];
+
+/// Names of the embedded features, in order.
+pub const FEATURE_NAMES: [&str; FEATURES] = ["value", "step", "window_deviation"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Node {
+    Leaf {
+        size: usize,
+    },
+    /// Splits `feature` at `value`; `min..=max` is the range of the
+    /// feature among the points the split was grown on.
+    Split {
+        feature: usize,
+        value: f64,
+        min: f64,
+        max: f64,
+        left: Box<Node>,
+        right: Box<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IsolationForest {
+    trees: Vec<Node>,
+    sample_size: usize,
+    threshold: f64,
+    /// Median score of the training points. Forests saved before it was
+    /// kept assume 0.5, where Liu et al. place ordinary points.
+    #[serde(default = "default_median")]
+    median: f64,
+}
+
+fn default_median() -> f64 {
+    0.5
+}
+
+impl IsolationForest {
+    /// Embed each point with its trailing `window` samples. The first
+    /// `window` values only seed the embedding.
+    pub fn embed(values: &[f64], window: usize) -> Vec<Point> {
+        if window == 0 || values.len() <= window {
+            return Vec::new();
+        }
+        (window..values.len())
+            .map(|index| {
+                let trailing = &values[index - window..index];
+                let mean = trailing.iter().sum::<f64>() / window as f64;
+                let value = values[index];
+                [value, value - values[index - 1], value - mean]
+            })
+            .collect()
+    }
+
+    /// Grow `config.trees` trees on random subsamples of `points` and set the
+    /// anomaly threshold at the `1 - contamination` quantile of their scores,
+    /// keeping the median to calibrate scores against.
+    pub fn fit(points: &[Point], config: &IsolationForestConfig) -> Self {
+        let sample_size = config.sample_size.min(points.len()).max(1);
+        let max_depth = (sample_size as f64).log2().ceil() as usize;
+        let mut rng = SplitMix64(SEED);
+
+        let trees = (0..config.trees)
+            .map(|_| {
+                let sample = rng.sample(points, sample_size);
+                grow(sample, 0, max_depth, &mut rng)
+            })
+            .collect();
+        let mut forest = Self {
+            trees,
+            sample_size,
+            threshold: 1.0,
+            median: default_median(),
+        };
+
+        let mut scores: Vec<f64> = points.iter().map(|point| forest.score(point)).collect();
+        scores.sort_by(f64::total_cmp);
+        if let Some(last) = scores.len().checked_sub(1) {
+            let rank = ((1.0 - config.contamination) * last as f64).round() as usize;
+            forest.threshold = scores[rank.min(last)];
+            forest.median = scores[last / 2];
+        }
+        forest
+    }
+
+    /// Anomaly score in `(0, 1]`.
+    pub fn score(&self, point: &Point) -> f64 {
+        if self.trees.is_empty() {
+            return 0.0;
+        }
+        let mean_path = self
+            .trees
+            .iter()
+            .map(|tree| path_length(tree, point, 0))
+            .sum::<f64>()
+            / self.trees.len() as f64;
+        let norm = average_path(self.sample_size);
+        if norm <= 0.0 {
+            return 0.0;
+        }
+        2f64.powf(-mean_path / norm)
+    }
+
+    /// Score at or above which a point is among the expected contamination.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// `score` measured from the median training score in steps of the
+    /// distance from the median to the threshold: 1 at the threshold, 2 as
+    /// far past it again. Raw scores bunch between 0.5 and 0.7, so this is
+    /// what separates a marginal point from a clear outlier.
+    pub fn calibrate(&self, score: f64) -> f64 {
+        let spread = self.threshold - self.median;
+        if spread <= f64::EPSILON {
+            return if score > self.threshold {
+                f64::INFINITY
+            } else {
+                1.0
+            };
+        }
+        (score - self.median) / spread
+    }
+}
+
+fn grow(points: Vec<Point>, depth: usize, max_depth: usize, rng: &mut SplitMix64) -> Node {
+    if depth >= max_depth || points.len() <= 1 {
+        return Node::Leaf { size: points.len() };
+    }
+
+    let splittable: Vec<(usize, f64, f64)> = (0..FEATURES)
+        .filter_map(|feature| {
+            let (min, max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), p| {
+                (min.min(p[feature]), max.max(p[feature]))
+            });
+            (max > min).then_some((feature, min, max))
+        })
+        .collect();
+    if splittable.is_empty() {
+        return Node::Leaf { size: points.len() };
+    }
+
+    let (feature, min, max) = splittable[rng.below(splittable.len())];
+    let value = min + rng.unit() * (max - min);
+    let (left, right): (Vec<Point>, Vec<Point>) =
+        points.into_iter().partition(|point| point[feature] < value);
+    Node::Split {
+        feature,
+        value,
+        min,
+        max,
+        left: Box::new(grow(left, depth + 1, max_depth, rng)),
+        right: Box::new(grow(right, depth + 1, max_depth, rng)),
+    }
+}
+
+/// A point outside the range a split was grown on is isolated there, so a
+/// forest trained on earlier history still scores values beyond everything
+/// it has seen as anomalous.
+fn path_length(node: &Node, point: &Point, depth: usize) -> f64 {
+    match node {
+        Node::Leaf { size } => depth as f64 + average_path(*size),
+        Node::Split {
+            feature,
+            value,
+            min,
+            max,
+            left,
+            right,
+        } => {
+            if point[*feature] < *min || point[*feature] > *max {
+                return depth as f64 + 1.0;
+            }
+            let next = if point[*feature] < *value {
+                left
+            } else {
+                right
+            };
+            path_length(next, point, depth + 1)
+        }
+    }
+}
+
+/// Average path length of an unsuccessful BST search over `n` points, used
+/// both to normalise scores and to credit unexpanded leaves.
+fn average_path(n: usize) -> f64 {
+    match n {
+        0 | 1 => 0.0,
+        2 => 1.0,
+        n => {
+            let n = n as f64;
+            2.0 * ((n - 1.0).ln() + 0.577_215_664_9) - 2.0 * (n - 1.0) / n
+        }
+    }
+}
+
+/// Small deterministic generator; model quality does not need more.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// `count` distinct points, chosen with a partial Fisher-Yates shuffle.
+    fn sample(&mut self, points: &[Point], count: usize) -> Vec<Point> {
+        let mut indices: Vec<usize> = (0..points.len()).collect();
+        for slot in 0..count.min(indices.len()) {
+            let pick = slot + self.below(indices.len() - slot);
+            indices.swap(slot, pick);
+        }
+        indices
+            .into_iter()
+            .take(count)
+            .map(|index| points[index])
+            .collect()
+    }
+}
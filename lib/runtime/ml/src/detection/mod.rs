@@ -1,5 +1,9 @@
 pub mod anomaly_detection;
+pub mod change_point;
+pub mod isolation_forest;
+pub mod multivariate;
 pub mod root_cause;
+pub mod seasonal;
 
 #[cfg(test)]
 mod tests;
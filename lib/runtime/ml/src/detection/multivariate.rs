@@ -0,0 +1,246 @@
+//! Multivariate detection across one resource's metrics.
+//!
+//! The resource's series are aligned on the timestamps they share, and the
+//! latest shared reading is scored by its Mahalanobis distance from the
+//! mean and covariance of the readings before it. A reading can be far off
+//! in combination, such as network dropping while CPU holds steady, when
+//! each metric on its own is within its usual range.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use phenome_domain::{MetricType, MultivariateConfig, TimeSeries};
+
+use crate::detection::anomaly_detection::mean_stddev;
+
+/// Added to the correlation diagonal so that metrics moving in lockstep
+/// still give an invertible matrix.
+const RIDGE: f64 = 1e-3;
+
+/// One metric's part in a multivariate finding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+    pub metric_type: MetricType,
+    pub value: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Share of the squared distance this metric accounts for. Shares sum
+    /// to one; a metric that pulls the reading back toward normal given
+    /// the others has a negative share.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultivariateFinding {
+    pub timestamp: i64,
+    /// Earliest reading of the history it was scored against.
+    pub since: i64,
+    /// Mahalanobis distance of the latest shared reading.
+    pub distance: f64,
+    /// The distance as the standard normal deviation with the same tail
+    /// probability, so it compares with univariate sigma thresholds.
+    pub deviation_sigma: f64,
+    /// Largest share first.
+    pub contributions: Vec<Contribution>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MultivariateDetector {
+    window: usize,
+    min_points: usize,
+}
+
+impl MultivariateDetector {
+    pub fn from_config(config: &MultivariateConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            window: config.window,
+            min_points: config.min_points,
+        })
+    }
+
+    /// Score the latest reading shared by `series`, all of one resource,
+    /// against up to `window` shared readings before it. Metrics that held
+    /// constant over that history are left out; `None` unless two or more
+    /// remain with `min_points` readings.
+    pub fn score(&self, series: &[&TimeSeries]) -> Option<MultivariateFinding> {
+        let (timestamps, columns) = align(series);
+        let timestamp = *timestamps.last()?;
+        let start = timestamps.len().saturating_sub(self.window + 1);
+        let history_len = timestamps.len() - start - 1;
+        if history_len < self.min_points {
+            return None;
+        }
+
+        let dimensions: Vec<Dimension> = columns
+            .into_iter()
+            .filter_map(|(metric_type, values)| {
+                Dimension::standardise(metric_type, &values[start..], history_len)
+            })
+            .collect();
+        if dimensions.len() < 2 {
+            return None;
+        }
+
+        let k = dimensions.len();
+        let mut correlation = vec![vec![0.0; k]; k];
+        for i in 0..k {
+            for j in 0..=i {
+                let value = dimensions[i]
+                    .history
+                    .iter()
+                    .zip(&dimensions[j].history)
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    / history_len as f64;
+                correlation[i][j] = value;
+                correlation[j][i] = value;
+            }
+            correlation[i][i] += RIDGE;
+        }
+
+        let z: Vec<f64> = dimensions.iter().map(|dimension| dimension.z).collect();
+        let weights = cholesky_solve(&correlation, &z)?;
+        let squared: f64 = z.iter().zip(&weights).map(|(z, w)| z * w).sum();
+        if squared <= f64::EPSILON {
+            return None;
+        }
+
+        let mut contributions: Vec<Contribution> = dimensions
+            .iter()
+            .zip(&weights)
+            .map(|(dimension, weight)| Contribution {
+                metric_type: dimension.metric_type,
+                value: dimension.value,
+                mean: dimension.mean,
+                stddev: dimension.stddev,
+                share: dimension.z * weight / squared,
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.share.total_cmp(&a.share));
+
+        Some(MultivariateFinding {
+            timestamp,
+            since: timestamps[start],
+            distance: squared.sqrt(),
+            deviation_sigma: wilson_hilferty(squared, k),
+            contributions,
+        })
+    }
+}
+
+/// One metric standardised on its history: `history` in standard
+/// deviations from `mean`, and `z` the latest `value` likewise.
+struct Dimension {
+    metric_type: MetricType,
+    value: f64,
+    mean: f64,
+    stddev: f64,
+    z: f64,
+    history: Vec<f64>,
+}
+
+impl Dimension {
+    /// `None` when the metric held constant over its history.
+    fn standardise(metric_type: MetricType, values: &[f64], history_len: usize) -> Option<Self> {
+        let (history, latest) = values.split_at(history_len);
+        let value = *latest.first()?;
+        let (mean, stddev) = mean_stddev(history);
+        if stddev <= f64::EPSILON {
+            return None;
+        }
+        Some(Self {
+            metric_type,
+            value,
+            mean,
+            stddev,
+            z: (value - mean) / stddev,
+            history: history
+                .iter()
+                .map(|value| (value - mean) / stddev)
+                .collect(),
+        })
+    }
+}
+
+/// Timestamps at which every series has a finite value, ascending, and
+/// each series' values at them.
+fn align(series: &[&TimeSeries]) -> (Vec<i64>, Vec<(MetricType, Vec<f64>)>) {
+    let lookups: Vec<HashMap<i64, f64>> = series
+        .iter()
+        .map(|series| {
+            series
+                .points
+                .iter()
+                .filter(|point| point.value.is_finite())
+                .map(|point| (point.timestamp, point.value))
+                .collect()
+        })
+        .collect();
+    let mut shared: Vec<i64> = lookups
+        .first()
+        .map(|first| {
+            first
+                .keys()
+                .filter(|timestamp| {
+                    lookups[1..]
+                        .iter()
+                        .all(|other| other.contains_key(timestamp))
+                })
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default();
+    shared.sort_unstable();
+
+    let columns = series
+        .iter()
+        .zip(&lookups)
+        .map(|(series, lookup)| {
+            let values = shared.iter().map(|timestamp| lookup[timestamp]).collect();
+            (series.metric_type, values)
+        })
+        .collect();
+    (shared, columns)
+}
+
+/// Solve `a x = b` for a symmetric positive definite `a`.
+fn cholesky_solve(a: &[Vec<f64>], b: &[f64]) -> Option<Vec<f64>> {
+    let k = b.len();
+    let mut lower = vec![vec![0.0; k]; k];
+    for i in 0..k {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|m| lower[i][m] * lower[j][m]).sum();
+            if i == j {
+                let diagonal = a[i][i] - sum;
+                if diagonal <= 0.0 {
+                    return None;
+                }
+                lower[i][i] = diagonal.sqrt();
+            } else {
+                lower[i][j] = (a[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+
+    let mut y = vec![0.0; k];
+    for i in 0..k {
+        let sum: f64 = (0..i).map(|m| lower[i][m] * y[m]).sum();
+        y[i] = (b[i] - sum) / lower[i][i];
+    }
+    let mut x = vec![0.0; k];
+    for i in (0..k).rev() {
+        let sum: f64 = (i + 1..k).map(|m| lower[m][i] * x[m]).sum();
+        x[i] = (y[i] - sum) / lower[i][i];
+    }
+    Some(x)
+}
+
+/// Standard normal deviate of a chi-squared value with `dof` degrees of
+/// freedom, by the Wilson-Hilferty cube-root approximation.
+fn wilson_hilferty(chi_squared: f64, dof: usize) -> f64 {
+    let dof = dof as f64;
+    let spread = 2.0 / (9.0 * dof);
+    ((chi_squared / dof).cbrt() - (1.0 - spread)) / spread.sqrt()
+}
@@ -1,6 +1,80 @@
+//! Correlation-based root cause ranking.
+//!
+//! Every other metric seen around an anomaly is a candidate cause. A
+//! candidate is scored by how it relates to the anomalous resource (same
+//! pod, an upstream assembly dependency, same namespace, a node), whether it
+//! was itself anomalous shortly before, and how strongly its series leads
+//! the anomalous one.
+
 use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use phenome_domain::{
+    Anomaly, MetricSample, MetricType, ProbableCause, ResourceType, RootCauseAnalysis,
+    StepDependency,
+};
+
+/// Anomalies this close to the analysed one count as co-occurring.
+const CO_OCCURRENCE_WINDOW_MS: i64 = 15 * 60 * 1000;
+
+/// Largest lead, in samples, tried when correlating a candidate series.
+const MAX_LAG: i64 = 5;
 
-use phenome_domain::{Anomaly, RootCauseAnalysis};
+/// Fewest aligned sample pairs a correlation is computed from.
+const MIN_PAIRS: usize = 8;
+
+/// Candidates scoring below this are not reported.
+const MIN_SCORE: f64 = 0.2;
+
+/// Most causes reported per analysis.
+const MAX_CAUSES: usize = 5;
+
+/// Sample interval assumed when the anomalous series has too few points.
+const DEFAULT_STEP_MS: i64 = 60_000;
+
+/// Data the engine ranks candidates from.
+#[derive(Debug, Clone, Default)]
+pub struct RootCauseContext {
+    /// Anomalies detected in the same cluster around the analysed one.
+    pub anomalies: Vec<Anomaly>,
+    /// Samples of the cluster's metrics leading up to the anomaly.
+    pub samples: Vec<MetricSample>,
+    pub dependencies: Vec<StepDependency>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    SameResource,
+    Upstream,
+    Downstream,
+    SameNamespace,
+    Node,
+}
+
+impl Relation {
+    fn weight(self) -> f64 {
+        match self {
+            Relation::Upstream => 1.0,
+            Relation::SameResource => 0.9,
+            Relation::SameNamespace => 0.7,
+            Relation::Node => 0.6,
+            // A dependent that misbehaves is more often a victim than a cause.
+            Relation::Downstream => 0.4,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Relation::SameResource => "same resource",
+            Relation::Upstream => "upstream dependency",
+            Relation::Downstream => "downstream dependent",
+            Relation::SameNamespace => "same namespace",
+            Relation::Node => "cluster node",
+        }
+    }
+}
+
+type MetricKey = (String, MetricType);
 
 #[derive(Debug, Clone, Default)]
 pub struct RootCauseEngine;
@@ -10,11 +84,273 @@ impl RootCauseEngine {
         Self
     }
 
-    pub fn analyze(&self, anomaly: &Anomaly) -> Result<RootCauseAnalysis> {
+    pub fn analyze(
+        &self,
+        anomaly: &Anomaly,
+        context: &RootCauseContext,
+    ) -> Result<RootCauseAnalysis> {
+        let mut series: HashMap<MetricKey, Vec<(i64, f64)>> = HashMap::new();
+        let mut nodes = HashSet::new();
+        for sample in &context.samples {
+            if sample.resource_type == ResourceType::Node {
+                nodes.insert(sample.resource_id.clone());
+            }
+            series
+                .entry((sample.resource_id.clone(), sample.metric_type))
+                .or_default()
+                .push((sample.timestamp, sample.value));
+        }
+        let target_key = (anomaly.resource_id.clone(), anomaly.metric_type);
+        let target = series.get(&target_key).map(Vec::as_slice).unwrap_or(&[]);
+        let step = step_ms(target);
+        let graph = DependencyGraph::new(&context.dependencies);
+
+        let mut candidates: Vec<MetricKey> = series
+            .keys()
+            .cloned()
+            .chain(
+                context
+                    .anomalies
+                    .iter()
+                    .map(|other| (other.resource_id.clone(), other.metric_type)),
+            )
+            .filter(|key| *key != target_key)
+            .collect();
+        candidates.sort_by(|a, b| (&a.0, a.1.as_str()).cmp(&(&b.0, b.1.as_str())));
+        candidates.dedup();
+
+        let mut causes: Vec<ProbableCause> = candidates
+            .into_iter()
+            .filter_map(|key| {
+                let relation = relation(&anomaly.resource_id, &key.0, &nodes, &graph)?;
+                let mut evidence = vec![relation.describe().to_string()];
+
+                let co_occurrence = co_occurrence(anomaly, &key, &context.anomalies);
+                if let Some((lead_ms, _)) = co_occurrence {
+                    evidence.push(describe_lead(lead_ms));
+                }
+                let correlation = series
+                    .get(&key)
+                    .and_then(|candidate| lagged_correlation(target, candidate, step));
+                if let Some((r, lag)) = correlation {
+                    evidence.push(format!("correlation {r:.2} leading by {lag} samples"));
+                }
+
+                let signal = 0.4 * co_occurrence.map_or(0.0, |(_, strength)| strength)
+                    + 0.6 * correlation.map_or(0.0, |(r, _)| r.abs());
+                let score = (relation.weight() * signal).clamp(0.0, 1.0);
+                (score >= MIN_SCORE).then_some(ProbableCause {
+                    resource_id: key.0,
+                    metric_type: key.1,
+                    score,
+                    evidence,
+                })
+            })
+            .collect();
+        causes.sort_by(|a, b| b.score.total_cmp(&a.score));
+        causes.truncate(MAX_CAUSES);
+
+        let summary = match causes.first() {
+            Some(top) => format!(
+                "Probable cause of anomaly {}: {} {} ({})",
+                anomaly.id,
+                top.resource_id,
+                top.metric_type.as_str(),
+                top.evidence.join(", ")
+            ),
+            None => format!("No root cause found for anomaly {}", anomaly.id),
+        };
         Ok(RootCauseAnalysis {
-            summary: format!("No root cause available for anomaly {}", anomaly.id),
-            confidence: 0.0,
-            related_metrics: Vec::new(),
+            summary,
+            confidence: causes.first().map_or(0.0, |top| top.score),
+            related_metrics: causes.iter().map(ProbableCause::metric_key).collect(),
+            causes,
+        })
+    }
+
+    /// Record `analysis` on the anomaly it explains.
+    pub fn annotate(&self, anomaly: &mut Anomaly, analysis: &RootCauseAnalysis) {
+        if !analysis.causes.is_empty() {
+            anomaly.root_cause = Some(analysis.summary.clone());
+            anomaly.related_metrics = analysis.related_metrics.clone();
+        }
+    }
+}
+
+/// Pods an assembly step runs as, linked through `depends_on`.
+struct DependencyGraph {
+    /// Pod to the pods of the steps it depends on, transitively.
+    upstream: HashMap<String, HashSet<String>>,
+}
+
+impl DependencyGraph {
+    fn new(steps: &[StepDependency]) -> Self {
+        let pods: HashMap<&str, &str> = steps
+            .iter()
+            .filter_map(|step| Some((step.step_id.as_str(), step.pod.as_deref()?)))
+            .collect();
+        let by_id: HashMap<&str, &StepDependency> = steps
+            .iter()
+            .map(|step| (step.step_id.as_str(), step))
+            .collect();
+
+        let mut upstream = HashMap::new();
+        for step in steps {
+            let Some(pod) = step.pod.as_deref() else {
+                continue;
+            };
+            let mut seen = HashSet::new();
+            let mut pending: Vec<&str> = step.depends_on.iter().map(String::as_str).collect();
+            let mut reached = HashSet::new();
+            while let Some(id) = pending.pop() {
+                if !seen.insert(id) {
+                    continue;
+                }
+                if let Some(dependency_pod) = pods.get(id) {
+                    reached.insert(dependency_pod.to_string());
+                }
+                if let Some(dependency) = by_id.get(id) {
+                    pending.extend(dependency.depends_on.iter().map(String::as_str));
+                }
+            }
+            reached.remove(pod);
+            upstream
+                .entry(pod.to_string())
+                .or_insert_with(HashSet::new)
+                .extend(reached);
+        }
+        Self { upstream }
+    }
+
+    /// Whether `resource` runs a step that `dependent` depends on.
+    fn depends_on(&self, dependent: &str, resource: &str) -> bool {
+        self.upstream.iter().any(|(pod, upstream)| {
+            runs_as(dependent, pod) && upstream.iter().any(|up| runs_as(resource, up))
         })
     }
 }
+
+/// Whether the `namespace/name` resource is an instance of `pod`, which may
+/// be a full resource id, a pod name or a workload name prefix.
+fn runs_as(resource_id: &str, pod: &str) -> bool {
+    let name = resource_id
+        .split_once('/')
+        .map_or(resource_id, |(_, name)| name);
+    resource_id == pod
+        || name == pod
+        || name
+            .strip_prefix(pod)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+fn relation(
+    target: &str,
+    candidate: &str,
+    nodes: &HashSet<String>,
+    graph: &DependencyGraph,
+) -> Option<Relation> {
+    if candidate == target {
+        return Some(Relation::SameResource);
+    }
+    if graph.depends_on(target, candidate) {
+        return Some(Relation::Upstream);
+    }
+    if graph.depends_on(candidate, target) {
+        return Some(Relation::Downstream);
+    }
+    let namespace = |id: &str| {
+        id.split_once('/')
+            .map(|(namespace, _)| namespace.to_string())
+    };
+    if namespace(target).is_some() && namespace(target) == namespace(candidate) {
+        return Some(Relation::SameNamespace);
+    }
+    nodes.contains(candidate).then_some(Relation::Node)
+}
+
+/// How long before the analysed anomaly the candidate was anomalous, and
+/// the strength of that signal: full when it led, weaker when it followed.
+fn co_occurrence(anomaly: &Anomaly, key: &MetricKey, anomalies: &[Anomaly]) -> Option<(i64, f64)> {
+    anomalies
+        .iter()
+        .filter(|other| other.id != anomaly.id)
+        .filter(|other| other.resource_id == key.0 && other.metric_type == key.1)
+        .map(|other| anomaly.detected_at - other.detected_at)
+        .filter(|lead| lead.abs() <= CO_OCCURRENCE_WINDOW_MS)
+        .max()
+        .map(|lead| (lead, if lead > 0 { 1.0 } else { 0.6 }))
+}
+
+fn describe_lead(lead_ms: i64) -> String {
+    let minutes = lead_ms.abs() / 60_000;
+    match lead_ms {
+        0 => "anomalous at the same time".to_string(),
+        lead if lead > 0 => format!("anomalous {minutes}m before"),
+        _ => format!("anomalous {minutes}m after"),
+    }
+}
+
+/// Strongest Pearson correlation between the target and the candidate
+/// shifted earlier by 0..=MAX_LAG samples, with the lag that produced it.
+fn lagged_correlation(
+    target: &[(i64, f64)],
+    candidate: &[(i64, f64)],
+    step: i64,
+) -> Option<(f64, i64)> {
+    let target = buckets(target, step);
+    let candidate = buckets(candidate, step);
+    (0..=MAX_LAG)
+        .filter_map(|lag| {
+            let pairs: Vec<(f64, f64)> = target
+                .iter()
+                .filter_map(|(bucket, value)| Some((*value, *candidate.get(&(bucket - lag))?)))
+                .collect();
+            (pairs.len() >= MIN_PAIRS)
+                .then(|| pearson(&pairs))
+                .flatten()
+                .map(|r| (r, lag))
+        })
+        .max_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+}
+
+fn buckets(points: &[(i64, f64)], step: i64) -> BTreeMap<i64, f64> {
+    let mut sums: BTreeMap<i64, (f64, usize)> = BTreeMap::new();
+    for (timestamp, value) in points {
+        let entry = sums.entry(timestamp.div_euclid(step)).or_default();
+        entry.0 += value;
+        entry.1 += 1;
+    }
+    sums.into_iter()
+        .map(|(bucket, (sum, count))| (bucket, sum / count as f64))
+        .collect()
+}
+
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    let denominator = (var_x * var_y).sqrt();
+    (denominator > f64::EPSILON).then(|| cov / denominator)
+}
+
+/// Median sample interval of the anomalous series.
+fn step_ms(points: &[(i64, f64)]) -> i64 {
+    let mut timestamps: Vec<i64> = points.iter().map(|(timestamp, _)| *timestamp).collect();
+    timestamps.sort_unstable();
+    let mut intervals: Vec<i64> = timestamps
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|interval| *interval > 0)
+        .collect();
+    intervals.sort_unstable();
+    intervals
+        .get(intervals.len() / 2)
+        .copied()
+        .unwrap_or(DEFAULT_STEP_MS)
+}
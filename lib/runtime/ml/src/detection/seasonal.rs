@@ -0,0 +1,186 @@
+//! Seasonal-median baselines.
+//!
+//! The series is reduced to a level (its median) plus one median profile per
+//! detected period, learned shortest period first on what the previous
+//! profiles left over. A period is detected when the series covers enough
+//! cycles of it and the remaining signal autocorrelates at that lag.
+
+use serde::{Deserialize, Serialize};
+
+use phenome_domain::{SeasonalConfig, TimeSeriesPoint};
+
+/// Upper bound on phase buckets per period.
+const MAX_BUCKETS: i64 = 288;
+
+/// Samples each phase bucket should collect across the covered cycles.
+/// Medians of only a few samples absorb most of their own noise, which
+/// would understate the residual spread.
+const MIN_BUCKET_SAMPLES: i64 = 8;
+
+/// Scale factor turning a median absolute deviation into a sigma estimate.
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Component {
+    period_ms: i64,
+    bucket_ms: i64,
+    profile: Vec<f64>,
+}
+
+impl Component {
+    fn at(&self, timestamp: i64) -> f64 {
+        let bucket = (timestamp.rem_euclid(self.period_ms) / self.bucket_ms) as usize;
+        self.profile.get(bucket).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalBaseline {
+    level: f64,
+    components: Vec<Component>,
+    residual_median: f64,
+    residual_scale: f64,
+}
+
+impl SeasonalBaseline {
+    /// Learn a baseline from time-ordered `points`. Returns `None` when no
+    /// candidate period is supported by the data.
+    pub fn learn(
+        points: &[TimeSeriesPoint],
+        periods_ms: &[i64],
+        config: &SeasonalConfig,
+    ) -> Option<Self> {
+        let step = median_interval(points)?;
+        let span = points.last()?.timestamp - points.first()?.timestamp;
+        let level = median(points.iter().map(|p| p.value).collect())?;
+        let mut residual: Vec<f64> = points.iter().map(|p| p.value - level).collect();
+
+        let mut periods = periods_ms.to_vec();
+        periods.sort_unstable();
+        let mut components = Vec::new();
+        for period_ms in periods {
+            if period_ms <= step || span < period_ms * config.min_cycles as i64 {
+                continue;
+            }
+            let lag = (period_ms as f64 / step as f64).round() as usize;
+            if lag >= residual.len() || autocorrelation(&residual, lag) < config.min_correlation {
+                continue;
+            }
+
+            let cycles = (span / period_ms).max(1);
+            let samples_per_cycle = (MIN_BUCKET_SAMPLES + cycles - 1) / cycles;
+            let bucket_ms = (step * samples_per_cycle).max(period_ms / MAX_BUCKETS);
+            let buckets = ((period_ms + bucket_ms - 1) / bucket_ms) as usize;
+            let mut grouped = vec![Vec::new(); buckets];
+            for (point, value) in points.iter().zip(&residual) {
+                let bucket = (point.timestamp.rem_euclid(period_ms) / bucket_ms) as usize;
+                grouped[bucket.min(buckets - 1)].push(*value);
+            }
+            let component = Component {
+                period_ms,
+                bucket_ms,
+                profile: grouped
+                    .into_iter()
+                    .map(|values| median(values).unwrap_or(0.0))
+                    .collect(),
+            };
+            for (point, value) in points.iter().zip(residual.iter_mut()) {
+                *value -= component.at(point.timestamp);
+            }
+            components.push(component);
+        }
+        if components.is_empty() {
+            return None;
+        }
+
+        let residual_median = median(residual.clone())?;
+        let mad = median(
+            residual
+                .iter()
+                .map(|value| (value - residual_median).abs())
+                .collect(),
+        )?;
+        let residual_scale = if mad > f64::EPSILON {
+            MAD_TO_SIGMA * mad
+        } else {
+            let variance = residual
+                .iter()
+                .map(|value| (value - residual_median).powi(2))
+                .sum::<f64>()
+                / residual.len() as f64;
+            variance.sqrt()
+        };
+        if residual_scale <= f64::EPSILON {
+            return None;
+        }
+
+        Some(Self {
+            level,
+            components,
+            residual_median,
+            residual_scale,
+        })
+    }
+
+    /// Value the baseline expects at `timestamp`.
+    pub fn expected(&self, timestamp: i64) -> f64 {
+        self.level
+            + self.residual_median
+            + self
+                .components
+                .iter()
+                .map(|component| component.at(timestamp))
+                .sum::<f64>()
+    }
+
+    /// Robust sigma distance of `value` from the expected value.
+    pub fn deviation(&self, timestamp: i64, value: f64) -> f64 {
+        (value - self.expected(timestamp)).abs() / self.residual_scale
+    }
+
+    /// Robust standard deviation of values around the expected value.
+    pub fn scale(&self) -> f64 {
+        self.residual_scale
+    }
+
+    /// Periods the baseline learned, shortest first.
+    pub fn periods_ms(&self) -> Vec<i64> {
+        self.components.iter().map(|c| c.period_ms).collect()
+    }
+}
+
+fn median_interval(points: &[TimeSeriesPoint]) -> Option<i64> {
+    let intervals: Vec<f64> = points
+        .windows(2)
+        .map(|pair| (pair[1].timestamp - pair[0].timestamp) as f64)
+        .filter(|interval| *interval > 0.0)
+        .collect();
+    median(intervals).map(|interval| interval as i64)
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+fn autocorrelation(values: &[f64], lag: usize) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    if variance <= f64::EPSILON {
+        return 0.0;
+    }
+    let covariance: f64 = values
+        .iter()
+        .zip(&values[lag..])
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum();
+    covariance / variance
+}
@@ -1,36 +1,564 @@
-use phenome_domain::{MetricType, TimeSeries, TimeSeriesData, TimeSeriesPoint};
+use phenome_domain::{
+    Anomaly, AnomalyKind, AnomalyModelKind, ChangePointConfig, DownsampleMethod,
+    IsolationForestConfig, MetricSample, MetricType, MlThresholdsConfig, MultivariateConfig,
+    ResourceType, SeasonalConfig, Severity, StepDependency, TimeSeries, TimeSeriesData,
+    TimeSeriesPoint,
+};
 
 use crate::detection::anomaly_detection::AnomalyDetector;
+use crate::detection::isolation_forest::IsolationForest;
+use crate::detection::root_cause::{RootCauseContext, RootCauseEngine};
+use crate::detection::seasonal::SeasonalBaseline;
+
+/// Periodic load with deterministic jitter around one core.
+fn synthetic(len: usize) -> Vec<f64> {
+    let mut state = 17u64;
+    (0..len)
+        .map(|i| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            let jitter = (state >> 40) as f64 / (1u64 << 24) as f64 - 0.5;
+            1.0 + 0.2 * (i as f64 / 8.0).sin() + 0.05 * jitter
+        })
+        .collect()
+}
+
+/// Samples every five minutes from UTC midnight: one core, with a nightly
+/// batch job holding four cores from 02:00 to 03:00.
+const STEP_MS: i64 = 5 * 60 * 1000;
+const DAY: usize = 288;
+
+fn nightly_batch(len: usize) -> Vec<f64> {
+    synthetic(len)
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let jitter = value - 1.0 - 0.2 * (i as f64 / 8.0).sin();
+            let batch = (24..36).contains(&(i % DAY));
+            if batch { 4.0 + jitter } else { 1.0 + jitter }
+        })
+        .collect()
+}
+
+fn data(values: &[f64]) -> TimeSeriesData {
+    series_data(values, 30_000, MetricType::CpuUsage)
+}
+
+fn series_data(values: &[f64], step_ms: i64, metric_type: MetricType) -> TimeSeriesData {
+    let series = TimeSeries {
+        cluster_id: "cluster-1".to_string(),
+        resource_id: "pod-a".to_string(),
+        metric_type,
+        unit: "cores".to_string(),
+        points: values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| TimeSeriesPoint {
+                timestamp: i as i64 * step_ms,
+                value: *value,
+            })
+            .collect(),
+        downsample: DownsampleMethod::Raw,
+    };
+    TimeSeriesData {
+        cluster_id: "cluster-1".to_string(),
+        range: phenome_domain::TimeRange {
+            start_ms: 0,
+            end_ms: values.len() as i64 * step_ms,
+        },
+        series: vec![series],
+    }
+}
 
 #[test]
 fn detects_simple_anomaly() {
     let detector = AnomalyDetector::default();
-    let series = TimeSeries {
+    let mut values = vec![1.0, 1.1, 0.9, 1.0, 1.05, 0.95, 1.0, 1.1, 0.9, 1.0];
+    values.push(10.0);
+
+    let anomalies = detector.detect(&data(&values)).unwrap();
+    assert!(!anomalies.is_empty());
+}
+
+#[test]
+fn anomaly_carries_evidence_of_its_baseline() {
+    let mut values = synthetic(30);
+    values.push(10.0);
+
+    let anomalies = AnomalyDetector::default().detect(&data(&values)).unwrap();
+
+    let evidence = anomalies[0].evidence.as_ref().unwrap();
+    assert_eq!(evidence.baseline_window.start_ms, 0);
+    assert_eq!(evidence.baseline_window.end_ms, 30 * 30_000);
+    assert!(evidence.expected_low < 1.0 && evidence.expected_high < 10.0);
+    assert_eq!(evidence.points.len(), 10);
+    assert_eq!(evidence.points.last().unwrap().value, 10.0);
+    assert!(evidence.seasonal_expected.is_none());
+}
+
+#[test]
+fn forest_scores_injected_outliers_above_threshold() {
+    let mut values = synthetic(400);
+    let outliers = [120, 250, 330];
+    for &index in &outliers {
+        values[index] = 3.5;
+    }
+    let config = IsolationForestConfig::default();
+    let embedded = IsolationForest::embed(&values, config.window);
+    let forest = IsolationForest::fit(&embedded, &config);
+
+    for &index in &outliers {
+        let score = forest.score(&embedded[index - config.window]);
+        assert!(
+            score >= forest.threshold(),
+            "outlier {index} scored {score}"
+        );
+    }
+    let flagged = embedded
+        .iter()
+        .filter(|point| forest.score(point) >= forest.threshold())
+        .count();
+    assert!(flagged <= embedded.len() / 20, "flagged {flagged} points");
+}
+
+#[test]
+fn isolation_forest_flags_latest_outlier() {
+    let detector = AnomalyDetector::default().with_model(AnomalyModelKind::IsolationForest);
+    let mut values = synthetic(300);
+    *values.last_mut().unwrap() = 4.0;
+
+    let anomalies = detector.detect(&data(&values)).unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert!(anomalies[0].confidence >= 0.7);
+    assert!(anomalies[0].description.contains("isolation score"));
+    assert_eq!(anomalies[0].observed_value, 4.0);
+    let contributions = &anomalies[0].evidence.as_ref().unwrap().contributions;
+    assert_eq!(contributions.len(), 3);
+    assert!(contributions[0].share >= contributions[1].share);
+    let total: f64 = contributions.iter().map(|c| c.share).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn forest_scores_are_calibrated_against_training_scores() {
+    let values = synthetic(400);
+    let config = IsolationForestConfig::default();
+    let embedded = IsolationForest::embed(&values, config.window);
+    let forest = IsolationForest::fit(&embedded, &config);
+
+    assert!((forest.calibrate(forest.threshold()) - 1.0).abs() < 1e-9);
+    let ordinary = forest.calibrate(forest.score(&embedded[embedded.len() / 2]));
+    assert!(ordinary < 1.0, "ordinary point calibrated to {ordinary}");
+    let outlier = forest.calibrate(forest.score(&[50.0, 49.0, 49.0]));
+    assert!(outlier > 1.5, "outlier calibrated to {outlier}");
+}
+
+#[test]
+fn isolation_forest_reports_clear_outliers_as_critical() {
+    let detector = AnomalyDetector::default().with_model(AnomalyModelKind::IsolationForest);
+    let mut values = synthetic(300);
+    *values.last_mut().unwrap() = 50.0;
+
+    let anomalies = detector.detect(&data(&values)).unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].severity, Severity::Critical);
+}
+
+#[test]
+fn isolation_forest_ignores_ordinary_latest_point() {
+    let detector = AnomalyDetector::default().with_model(AnomalyModelKind::IsolationForest);
+
+    let anomalies = detector.detect(&data(&synthetic(300))).unwrap();
+
+    assert!(anomalies.is_empty());
+}
+
+#[test]
+fn isolation_forest_falls_back_to_z_score_on_short_series() {
+    let detector = AnomalyDetector::default().with_model(AnomalyModelKind::IsolationForest);
+    let mut values = synthetic(15);
+    values.push(10.0);
+
+    let anomalies = detector.detect(&data(&values)).unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert!(anomalies[0].description.contains("sigma"));
+    assert_eq!(anomalies[0].severity, Severity::Warning);
+}
+
+#[test]
+fn seasonal_baseline_learns_daily_period() {
+    let values = nightly_batch(3 * DAY);
+    let points = series_data(&values, STEP_MS, MetricType::CpuUsage).series[0]
+        .points
+        .clone();
+
+    let baseline = SeasonalBaseline::learn(
+        &points,
+        &[86_400_000, 604_800_000],
+        &SeasonalConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(baseline.periods_ms(), vec![86_400_000]);
+    assert!((baseline.expected(30 * STEP_MS) - 4.0).abs() < 0.1);
+    assert!((baseline.expected(120 * STEP_MS) - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn seasonal_ignores_recurring_batch_window() {
+    let values = nightly_batch(3 * DAY + 30);
+    let data = series_data(&values, STEP_MS, MetricType::CpuUsage);
+
+    let z_score = AnomalyDetector::default().detect(&data).unwrap();
+    let seasonal = AnomalyDetector::default()
+        .with_model(AnomalyModelKind::Seasonal)
+        .detect(&data)
+        .unwrap();
+
+    assert_eq!(z_score.len(), 1);
+    assert!(seasonal.is_empty());
+}
+
+#[test]
+fn seasonal_flags_off_cycle_spike_against_expected_value() {
+    let mut values = nightly_batch(3 * DAY + 120);
+    *values.last_mut().unwrap() = 4.0;
+
+    let anomalies = AnomalyDetector::default()
+        .with_model(AnomalyModelKind::Seasonal)
+        .detect(&series_data(&values, STEP_MS, MetricType::CpuUsage))
+        .unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert!((anomalies[0].baseline_value - 1.0).abs() < 0.1);
+    assert!(anomalies[0].description.contains("seasonal baseline"));
+    assert_eq!(anomalies[0].severity, Severity::Critical);
+    let evidence = anomalies[0].evidence.as_ref().unwrap();
+    assert_eq!(
+        evidence.seasonal_expected,
+        Some(anomalies[0].baseline_value)
+    );
+    assert!(evidence.expected_high < 4.0);
+}
+
+#[test]
+fn selects_model_per_metric_type() {
+    let values = nightly_batch(3 * DAY + 30);
+    let detector = AnomalyDetector::default()
+        .with_metric_model(MetricType::CpuUsage, AnomalyModelKind::Seasonal);
+
+    let cpu = detector
+        .detect(&series_data(&values, STEP_MS, MetricType::CpuUsage))
+        .unwrap();
+    let memory = detector
+        .detect(&series_data(&values, STEP_MS, MetricType::MemoryUsage))
+        .unwrap();
+
+    assert_eq!(
+        detector.model_for(MetricType::MemoryUsage),
+        AnomalyModelKind::ZScore
+    );
+    assert!(cpu.is_empty());
+    assert_eq!(memory.len(), 1);
+}
+
+/// Readings every five minutes around one core with small deterministic
+/// noise, offset by `shape` at each index.
+fn regime(len: usize, shape: impl Fn(usize) -> f64) -> Vec<f64> {
+    (0..len)
+        .map(|i| 1.0 + 0.02 * ((i * 7 % 5) as f64 - 2.0) + shape(i))
+        .collect()
+}
+
+fn regime_changes(detector: &AnomalyDetector, values: &[f64]) -> Vec<Anomaly> {
+    detector
+        .detect(&series_data(values, STEP_MS, MetricType::MemoryUsage))
+        .unwrap()
+        .into_iter()
+        .filter(|anomaly| anomaly.kind != AnomalyKind::Spike)
+        .collect()
+}
+
+#[test]
+fn change_point_reports_sustained_level_shift() {
+    let values = regime(60, |i| if i >= 40 { 1.0 } else { 0.0 });
+
+    let changes = regime_changes(&AnomalyDetector::default(), &values);
+
+    assert_eq!(changes.len(), 1);
+    let change = changes[0].change_point.unwrap();
+    assert_eq!(changes[0].kind, AnomalyKind::LevelShift);
+    assert_eq!(change.changed_at, 40 * STEP_MS);
+    assert!((change.before_mean - 1.0).abs() < 0.05);
+    assert!((change.after_mean - 2.0).abs() < 0.05);
+    assert_eq!(changes[0].id, format!("pod-a-level_shift-{}", 40 * STEP_MS));
+}
+
+#[test]
+fn change_point_reports_trend_change() {
+    let values = regime(60, |i| 0.05 * i.saturating_sub(40) as f64);
+
+    let changes = regime_changes(&AnomalyDetector::default(), &values);
+
+    assert_eq!(changes.len(), 1);
+    let change = changes[0].change_point.unwrap();
+    assert_eq!(changes[0].kind, AnomalyKind::TrendChange);
+    assert!((change.changed_at - 40 * STEP_MS).abs() <= 2 * STEP_MS);
+    assert!(change.before_slope.abs() < 0.1);
+    assert!((change.after_slope - 0.6).abs() < 0.1);
+}
+
+#[test]
+fn change_point_ignores_steady_ramp_and_old_shifts() {
+    let detector = AnomalyDetector::default();
+    let ramp = regime(60, |i| 0.02 * i as f64);
+    let old_shift = regime(120, |i| if i >= 20 { 1.0 } else { 0.0 });
+
+    assert!(regime_changes(&detector, &ramp).is_empty());
+    assert!(regime_changes(&detector, &old_shift).is_empty());
+}
+
+#[test]
+fn change_point_grades_shifts_with_the_detection_thresholds() {
+    // About 4.8 sigma against a shift threshold of 4.
+    let values = regime(60, |i| if i >= 40 { 0.14 } else { 0.0 });
+
+    let changes = regime_changes(&AnomalyDetector::default(), &values);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].severity, Severity::Warning);
+    assert!((changes[0].confidence - 0.8).abs() < 0.01);
+
+    let strict = AnomalyDetector::default()
+        .with_thresholds(MlThresholdsConfig {
+            min_confidence: 0.9,
+            ..MlThresholdsConfig::default()
+        })
+        .unwrap();
+    assert!(regime_changes(&strict, &values).is_empty());
+}
+
+#[test]
+fn change_point_detection_can_be_disabled() {
+    let values = regime(60, |i| if i >= 40 { 1.0 } else { 0.0 });
+    let detector = AnomalyDetector::default()
+        .with_change_point(&ChangePointConfig {
+            enabled: false,
+            ..ChangePointConfig::default()
+        })
+        .unwrap();
+
+    assert!(regime_changes(&detector, &values).is_empty());
+}
+
+/// CPU of one pod and the network traffic it serves, which tracks it at
+/// fifty megabytes a minute per core.
+fn coupled(len: usize) -> (Vec<f64>, Vec<f64>) {
+    let cpu = synthetic(len);
+    let network = cpu
+        .iter()
+        .enumerate()
+        .map(|(i, cpu)| 50.0 * cpu + 0.2 * ((i * 7 % 5) as f64 - 2.0))
+        .collect();
+    (cpu, network)
+}
+
+fn joint_data(cpu: &[f64], network: &[f64]) -> TimeSeriesData {
+    let mut data = series_data(cpu, STEP_MS, MetricType::CpuUsage);
+    data.series
+        .extend(series_data(network, STEP_MS, MetricType::NetworkIn).series);
+    data
+}
+
+#[test]
+fn multivariate_flags_broken_correlation_with_contributors() {
+    let (mut cpu, mut network) = coupled(120);
+    // Within range for each metric alone: CPU one sigma up, traffic two
+    // down.
+    *cpu.last_mut().unwrap() = 1.15;
+    *network.last_mut().unwrap() = 37.0;
+
+    let anomalies = AnomalyDetector::default()
+        .detect(&joint_data(&cpu, &network))
+        .unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    let anomaly = &anomalies[0];
+    assert_eq!(anomaly.model_id.as_deref(), Some("multivariate"));
+    assert_eq!(anomaly.detected_at, 119 * STEP_MS);
+    assert!(anomaly.deviation_sigma >= 3.0);
+    assert_eq!(
+        anomaly.related_metrics.first().map(String::as_str),
+        Some("pod-a:network_in")
+    );
+    assert!(
+        anomaly
+            .related_metrics
+            .contains(&"pod-a:cpu_usage".to_string())
+    );
+}
+
+#[test]
+fn multivariate_ignores_metrics_moving_together() {
+    let (mut cpu, mut network) = coupled(120);
+    *cpu.last_mut().unwrap() = 1.25;
+    *network.last_mut().unwrap() = 62.5;
+
+    let detector = AnomalyDetector::default();
+    let disabled = AnomalyDetector::default()
+        .with_multivariate(&MultivariateConfig {
+            enabled: false,
+            ..MultivariateConfig::default()
+        })
+        .unwrap();
+
+    assert!(
+        detector
+            .detect(&joint_data(&cpu, &network))
+            .unwrap()
+            .is_empty()
+    );
+    *network.last_mut().unwrap() = 37.0;
+    assert!(
+        disabled
+            .detect(&joint_data(&cpu, &network))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+fn samples(resource_id: &str, resource_type: ResourceType, values: &[f64]) -> Vec<MetricSample> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| MetricSample {
+            cluster_id: "cluster-1".to_string(),
+            resource_type,
+            resource_id: resource_id.to_string(),
+            metric_type: MetricType::CpuUsage,
+            timestamp: i as i64 * 60_000,
+            value: *value,
+            unit: "cores".to_string(),
+        })
+        .collect()
+}
+
+fn anomaly_at(resource_id: &str, detected_at: i64) -> Anomaly {
+    Anomaly {
+        id: format!("{resource_id}-{detected_at}"),
         cluster_id: "cluster-1".to_string(),
-        resource_id: "pod-a".to_string(),
+        resource_id: resource_id.to_string(),
+        detected_at,
         metric_type: MetricType::CpuUsage,
-        unit: "cores".to_string(),
-        points: vec![
-            TimeSeriesPoint {
-                timestamp: 1,
-                value: 1.0,
-            },
-            TimeSeriesPoint {
-                timestamp: 2,
-                value: 1.1,
+        severity: Severity::Critical,
+        confidence: 0.95,
+        description: "4.00 sigma deviation".to_string(),
+        baseline_value: 1.0,
+        observed_value: 4.0,
+        deviation_sigma: 4.0,
+        related_metrics: Vec::new(),
+        root_cause: None,
+        model_id: None,
+        model_version: None,
+        kind: AnomalyKind::Spike,
+        change_point: None,
+        feedback: None,
+        evidence: None,
+    }
+}
+
+/// `api` depends on `db`; the database load leads the API load by two
+/// minutes and turned anomalous five minutes before it.
+fn shop_context() -> (Anomaly, RootCauseContext) {
+    let load = synthetic(62);
+    let detected_at = 59 * 60_000;
+    let target = anomaly_at("shop/api-7d9f", detected_at);
+    let mut samples = samples("shop/api-7d9f", ResourceType::Pod, &load[..60]);
+    samples.extend(self::samples("shop/db-0", ResourceType::Pod, &load[2..]));
+    samples.extend(self::samples("other/web-1", ResourceType::Pod, &load[2..]));
+    samples.extend(self::samples(
+        "node-1",
+        ResourceType::Node,
+        &synthetic(60)[..1].repeat(60),
+    ));
+    let context = RootCauseContext {
+        anomalies: vec![
+            target.clone(),
+            anomaly_at("shop/db-0", detected_at - 5 * 60_000),
+        ],
+        samples,
+        dependencies: vec![
+            StepDependency {
+                step_id: "api".to_string(),
+                pod: Some("api".to_string()),
+                depends_on: vec!["db".to_string()],
             },
-            TimeSeriesPoint {
-                timestamp: 3,
-                value: 10.0,
+            StepDependency {
+                step_id: "db".to_string(),
+                pod: Some("db".to_string()),
+                depends_on: Vec::new(),
             },
         ],
     };
-    let data = TimeSeriesData {
-        cluster_id: "cluster-1".to_string(),
-        range: phenome_domain::TimeRange { start_ms: 0, end_ms: 3 },
-        series: vec![series],
-    };
+    (target, context)
+}
 
-    let anomalies = detector.detect(&data).unwrap();
-    assert!(!anomalies.is_empty());
+#[test]
+fn root_cause_ranks_leading_upstream_dependency_first() {
+    let (target, context) = shop_context();
+
+    let analysis = RootCauseEngine::new().analyze(&target, &context).unwrap();
+
+    let top = &analysis.causes[0];
+    assert_eq!(top.resource_id, "shop/db-0");
+    assert!(top.evidence.iter().any(|e| e == "upstream dependency"));
+    assert!(top.evidence.iter().any(|e| e == "anomalous 5m before"));
+    assert!(
+        top.evidence
+            .iter()
+            .any(|e| e.ends_with("leading by 2 samples"))
+    );
+    assert_eq!(analysis.related_metrics[0], "shop/db-0:cpu_usage");
+    assert!(analysis.confidence > 0.8);
+    assert!(
+        analysis
+            .causes
+            .iter()
+            .all(|c| c.resource_id != "other/web-1")
+    );
+}
+
+#[test]
+fn root_cause_reports_nothing_without_related_signals() {
+    let target = anomaly_at("shop/api-7d9f", 0);
+    let engine = RootCauseEngine::new();
+    let mut anomaly = target.clone();
+
+    let analysis = engine
+        .analyze(&target, &RootCauseContext::default())
+        .unwrap();
+    engine.annotate(&mut anomaly, &analysis);
+
+    assert!(analysis.causes.is_empty());
+    assert_eq!(analysis.confidence, 0.0);
+    assert!(analysis.summary.starts_with("No root cause found"));
+    assert_eq!(anomaly.root_cause, None);
+}
+
+#[test]
+fn root_cause_annotates_anomaly() {
+    let (mut target, context) = shop_context();
+    let engine = RootCauseEngine::new();
+
+    let analysis = engine.analyze(&target, &context).unwrap();
+    engine.annotate(&mut target, &analysis);
+
+    assert_eq!(
+        target.root_cause.as_deref(),
+        Some(analysis.summary.as_str())
+    );
+    assert_eq!(target.related_metrics, analysis.related_metrics);
 }
@@ -0,0 +1,250 @@
+//! Labeled datasets for offline evaluation.
+//!
+//! A dataset is a CSV or Parquet table with one row per sample:
+//!
+//! - `timestamp`: epoch milliseconds, or an ISO-8601 string
+//! - `value`: the sample value
+//! - `label`: non-zero (or `true`) inside a ground-truth anomaly window
+//! - `resource_id`, `metric_type`, `unit`: optional; rows are grouped into
+//!   one series per resource and metric
+//!
+//! Each run of consecutive labeled samples in a series becomes one
+//! `AnomalyWindow`. Operator feedback on anomalies exports to the same
+//! format, so production verdicts can score detector changes offline.
+
+use anyhow::{Context, Result, anyhow, bail};
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use phenome_domain::{
+    Anomaly, AnomalyFeedbackKind, AnomalyWindow, DownsampleMethod, LabeledSeries, MetricType,
+    TimeSeries, TimeSeriesPoint, parse_instant,
+};
+
+/// Read a labeled dataset from a `.csv` or `.parquet` file. Without a
+/// `resource_id` column every row belongs to a series named after the file.
+pub fn load_dataset(path: &Path) -> Result<Vec<LabeledSeries>> {
+    let frame = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => CsvReadOptions::default()
+            .with_has_header(true)
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+            .finish(),
+        Some("parquet") => ParquetReader::new(File::open(path)?).finish(),
+        _ => bail!(
+            "unsupported dataset {}: expected .csv or .parquet",
+            path.display()
+        ),
+    }
+    .with_context(|| format!("failed to read dataset {}", path.display()))?;
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    labeled_series(&frame, &name)
+}
+
+/// Write a dataset as `.csv` or `.parquet` in the layout `load_dataset`
+/// reads, with a `label` of 1 inside each window.
+pub fn write_dataset(path: &Path, dataset: &[LabeledSeries]) -> Result<()> {
+    let (mut timestamps, mut values, mut labels) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut resources, mut metrics, mut units) = (Vec::new(), Vec::new(), Vec::new());
+    for labeled in dataset {
+        let series = &labeled.series;
+        for point in &series.points {
+            let inside = labeled
+                .windows
+                .iter()
+                .any(|window| window.contains(point.timestamp));
+            timestamps.push(point.timestamp);
+            values.push(point.value);
+            labels.push(i32::from(inside));
+            resources.push(series.resource_id.as_str());
+            metrics.push(series.metric_type.as_str());
+            units.push(series.unit.as_str());
+        }
+    }
+    let mut frame = df!(
+        "timestamp" => timestamps,
+        "value" => values,
+        "label" => labels,
+        "resource_id" => resources,
+        "metric_type" => metrics,
+        "unit" => units
+    )?;
+
+    let file = File::create(path)
+        .with_context(|| format!("failed to create dataset {}", path.display()))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => CsvWriter::new(file).finish(&mut frame)?,
+        Some("parquet") => {
+            ParquetWriter::new(file).finish(&mut frame)?;
+        }
+        _ => bail!(
+            "unsupported dataset {}: expected .csv or .parquet",
+            path.display()
+        ),
+    }
+    Ok(())
+}
+
+/// Label `series` with the anomalies operators confirmed on it. A confirmed
+/// level shift or trend change labels everything from its change point;
+/// false positives and expected anomalies stay unlabeled, as normal data
+/// the detector should not have flagged.
+pub fn label_feedback(series: TimeSeries, anomalies: &[Anomaly]) -> LabeledSeries {
+    let mut windows: Vec<AnomalyWindow> = anomalies
+        .iter()
+        .filter(|anomaly| {
+            anomaly.resource_id == series.resource_id
+                && anomaly.metric_type == series.metric_type
+                && anomaly
+                    .feedback
+                    .as_ref()
+                    .is_some_and(|feedback| feedback.kind == AnomalyFeedbackKind::Confirmed)
+        })
+        .map(|anomaly| AnomalyWindow {
+            start_ms: anomaly.change_point.map_or(anomaly.detected_at, |change| {
+                change.changed_at.min(anomaly.detected_at)
+            }),
+            end_ms: anomaly.detected_at,
+        })
+        .collect();
+    windows.sort_by_key(|window| window.start_ms);
+
+    let mut merged: Vec<AnomalyWindow> = Vec::new();
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if window.start_ms <= last.end_ms => {
+                last.end_ms = last.end_ms.max(window.end_ms);
+            }
+            _ => merged.push(window),
+        }
+    }
+    LabeledSeries {
+        series,
+        windows: merged,
+    }
+}
+
+/// Samples of one series in file order: timestamp, value and label.
+struct Rows {
+    unit: String,
+    samples: Vec<(i64, f64, bool)>,
+}
+
+fn labeled_series(frame: &DataFrame, name: &str) -> Result<Vec<LabeledSeries>> {
+    let timestamps = timestamps(frame.column("timestamp")?)?;
+    let values = frame.column("value")?.cast(&DataType::Float64)?;
+    let labels = match frame.column("label") {
+        Ok(column) => Some(column.cast(&DataType::Float64)?),
+        Err(_) => None,
+    };
+    let resources = optional_strings(frame, "resource_id")?;
+    let metrics = optional_strings(frame, "metric_type")?;
+    let units = optional_strings(frame, "unit")?;
+
+    let mut grouped: BTreeMap<(String, String), Rows> = BTreeMap::new();
+    for (row, (timestamp, value)) in timestamps.iter().zip(values.f64()?).enumerate() {
+        let (Some(timestamp), Some(value)) = (*timestamp, value) else {
+            continue;
+        };
+        let labeled = labels
+            .as_ref()
+            .and_then(|labels| labels.f64().ok()?.get(row))
+            .is_some_and(|label| label != 0.0);
+        let resource = cell(&resources, row).unwrap_or(name).to_string();
+        let metric = cell(&metrics, row).unwrap_or("cpu_usage").to_string();
+        let unit = cell(&units, row).unwrap_or_default().to_string();
+        grouped
+            .entry((resource, metric))
+            .or_insert_with(|| Rows {
+                unit,
+                samples: Vec::new(),
+            })
+            .samples
+            .push((timestamp, value, labeled));
+    }
+
+    grouped
+        .into_iter()
+        .map(
+            |(
+                (resource_id, metric),
+                Rows {
+                    unit,
+                    samples: mut rows,
+                },
+            )| {
+                let metric_type = MetricType::ALL
+                    .into_iter()
+                    .find(|metric_type| metric_type.as_str() == metric)
+                    .ok_or_else(|| anyhow!("unknown metric type {metric:?}"))?;
+                rows.sort_by_key(|(timestamp, _, _)| *timestamp);
+                Ok(LabeledSeries {
+                    windows: windows(&rows),
+                    series: TimeSeries {
+                        cluster_id: String::new(),
+                        resource_id,
+                        metric_type,
+                        unit,
+                        points: rows
+                            .iter()
+                            .map(|(timestamp, value, _)| TimeSeriesPoint {
+                                timestamp: *timestamp,
+                                value: *value,
+                            })
+                            .collect(),
+                        downsample: DownsampleMethod::Raw,
+                    },
+                })
+            },
+        )
+        .collect()
+}
+
+/// Runs of consecutive labeled rows, from the first to the last of each.
+fn windows(rows: &[(i64, f64, bool)]) -> Vec<AnomalyWindow> {
+    let mut windows: Vec<AnomalyWindow> = Vec::new();
+    let mut open = false;
+    for &(timestamp, _, labeled) in rows {
+        match (labeled, open) {
+            (true, true) => {
+                if let Some(window) = windows.last_mut() {
+                    window.end_ms = timestamp;
+                }
+            }
+            (true, false) => windows.push(AnomalyWindow {
+                start_ms: timestamp,
+                end_ms: timestamp,
+            }),
+            _ => {}
+        }
+        open = labeled;
+    }
+    windows
+}
+
+fn timestamps(column: &Series) -> Result<Vec<Option<i64>>> {
+    if column.dtype() == &DataType::String {
+        return column
+            .str()?
+            .into_iter()
+            .map(|raw| raw.map(|raw| parse_instant(raw, 0)).transpose())
+            .collect();
+    }
+    Ok(column.cast(&DataType::Int64)?.i64()?.into_iter().collect())
+}
+
+fn optional_strings(frame: &DataFrame, name: &str) -> Result<Option<Series>> {
+    match frame.column(name) {
+        Ok(column) => Ok(Some(column.cast(&DataType::String)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn cell(column: &Option<Series>, row: usize) -> Option<&str> {
+    column.as_ref()?.str().ok()?.get(row)
+}
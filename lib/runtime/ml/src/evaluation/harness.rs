@@ -0,0 +1,219 @@
+//! Replays labeled series through detectors and predictors.
+//!
+//! Detection walks each series point by point, as the service would see
+//! it, and asks the detector about the latest point given the trailing
+//! `history`. A labeled window counts as detected when any of its points
+//! is flagged; precision is the share of flagged points inside a window.
+//!
+//! Forecasts are made from rolling origins every `stride` points, each
+//! `horizon` samples ahead, and compared with the sample actually there.
+
+use anyhow::Result;
+use std::time::Duration;
+
+use phenome_domain::{
+    DetectionScore, ForecastScore, LabeledSeries, TimeRange, TimeSeries, TimeSeriesData,
+};
+
+use crate::detection::anomaly_detection::AnomalyDetector;
+use crate::scaling::scaling_prediction::ScalingPredictor;
+
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    warmup: usize,
+    history: usize,
+    horizon: usize,
+    stride: usize,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self {
+            warmup: 30,
+            history: 288,
+            horizon: 12,
+            stride: 12,
+        }
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Points at the start of each series that are never scored.
+    pub fn with_warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup.max(1);
+        self
+    }
+
+    /// Trailing points each detection or forecast sees.
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history.max(2);
+        self
+    }
+
+    /// Samples ahead each forecast is scored at.
+    pub fn with_horizon(mut self, horizon: usize) -> Self {
+        self.horizon = horizon.max(1);
+        self
+    }
+
+    /// Points between forecast origins.
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
+    }
+
+    pub fn detection(
+        &self,
+        candidate: &str,
+        detector: &AnomalyDetector,
+        dataset: &[LabeledSeries],
+    ) -> Result<DetectionScore> {
+        let mut flagged = 0;
+        let mut false_positives = 0;
+        let mut windows = 0;
+        let mut delays = Vec::new();
+        for labeled in dataset {
+            let points = &labeled.series.points;
+            let mut first_flags = vec![None; labeled.windows.len()];
+            for (end, point) in points.iter().enumerate().skip(self.warmup) {
+                let start = (end + 1).saturating_sub(self.history);
+                let data = replay(&labeled.series, start, end + 1);
+                if detector.detect(&data)?.is_empty() {
+                    continue;
+                }
+                flagged += 1;
+                let timestamp = point.timestamp;
+                match labeled
+                    .windows
+                    .iter()
+                    .position(|window| window.contains(timestamp))
+                {
+                    Some(index) => {
+                        first_flags[index].get_or_insert(timestamp);
+                    }
+                    None => false_positives += 1,
+                }
+            }
+            windows += labeled.windows.len();
+            delays.extend(
+                labeled
+                    .windows
+                    .iter()
+                    .zip(first_flags)
+                    .filter_map(|(window, first)| Some((first? - window.start_ms) as f64)),
+            );
+        }
+
+        let detected_windows = delays.len();
+        let precision = ratio(flagged - false_positives, flagged);
+        let recall = ratio(detected_windows, windows);
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+        Ok(DetectionScore {
+            candidate: candidate.to_string(),
+            flagged,
+            false_positives,
+            windows,
+            detected_windows,
+            precision,
+            recall,
+            f1,
+            mean_delay_ms: mean(&delays),
+        })
+    }
+
+    pub fn forecast(
+        &self,
+        candidate: &str,
+        predictor: &ScalingPredictor,
+        dataset: &[LabeledSeries],
+    ) -> Result<ForecastScore> {
+        let mut percentage_errors = Vec::new();
+        let mut scaled_errors = Vec::new();
+        let mut forecasts = 0;
+        for labeled in dataset {
+            let series = &labeled.series;
+            let points = &series.points;
+            let Some(last_origin) = points.len().checked_sub(self.horizon) else {
+                continue;
+            };
+            let naive = naive_error(series);
+            for origin in (self.warmup.max(2)..=last_origin).step_by(self.stride) {
+                let history = &points[origin.saturating_sub(self.history)..origin];
+                let target = &points[origin + self.horizon - 1];
+                let last = history[history.len() - 1].timestamp;
+                let prediction = predictor.predict(
+                    series.resource_id.clone(),
+                    Duration::from_millis((target.timestamp - last).max(0) as u64),
+                    history,
+                    &series.unit,
+                    last,
+                )?;
+                let error = (prediction.predicted_value - target.value).abs();
+                forecasts += 1;
+                if target.value != 0.0 {
+                    percentage_errors.push(error / target.value.abs());
+                }
+                if let Some(naive) = naive {
+                    scaled_errors.push(error / naive);
+                }
+            }
+        }
+
+        Ok(ForecastScore {
+            candidate: candidate.to_string(),
+            forecasts,
+            mape: mean(&percentage_errors),
+            mase: mean(&scaled_errors),
+        })
+    }
+}
+
+/// The points `start..end` of `series`, as detection sees them live.
+fn replay(series: &TimeSeries, start: usize, end: usize) -> TimeSeriesData {
+    let points = series.points[start..end].to_vec();
+    TimeSeriesData {
+        cluster_id: series.cluster_id.clone(),
+        range: TimeRange {
+            start_ms: points[0].timestamp,
+            end_ms: points[points.len() - 1].timestamp,
+        },
+        series: vec![TimeSeries {
+            cluster_id: series.cluster_id.clone(),
+            resource_id: series.resource_id.clone(),
+            metric_type: series.metric_type,
+            unit: series.unit.clone(),
+            points,
+            downsample: series.downsample,
+        }],
+    }
+}
+
+/// Mean absolute one-step naive error over the series, the MASE scale.
+fn naive_error(series: &TimeSeries) -> Option<f64> {
+    let steps: Vec<f64> = series
+        .points
+        .windows(2)
+        .map(|pair| (pair[1].value - pair[0].value).abs())
+        .collect();
+    mean(&steps).filter(|scale| *scale > 0.0)
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
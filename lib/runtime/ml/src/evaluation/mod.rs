@@ -0,0 +1,5 @@
+pub mod dataset;
+pub mod harness;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,243 @@
+use std::fs;
+
+use polars::prelude::*;
+
+use phenome_domain::{
+    Anomaly, AnomalyFeedback, AnomalyFeedbackKind, AnomalyKind, AnomalyWindow, ChangePoint,
+    DownsampleMethod, LabeledSeries, MetricType, ScalingModelKind, Severity, TimeSeries,
+    TimeSeriesPoint,
+};
+
+use crate::detection::anomaly_detection::AnomalyDetector;
+use crate::evaluation::dataset::{label_feedback, load_dataset, write_dataset};
+use crate::evaluation::harness::Evaluator;
+use crate::scaling::scaling_prediction::ScalingPredictor;
+
+const STEP_MS: i64 = 60_000;
+
+fn labeled(values: &[f64], windows: Vec<AnomalyWindow>) -> LabeledSeries {
+    LabeledSeries {
+        series: TimeSeries {
+            cluster_id: String::new(),
+            resource_id: "pod-a".to_string(),
+            metric_type: MetricType::CpuUsage,
+            unit: "cores".to_string(),
+            points: values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| TimeSeriesPoint {
+                    timestamp: i as i64 * STEP_MS,
+                    value: *value,
+                })
+                .collect(),
+            downsample: DownsampleMethod::Raw,
+        },
+        windows,
+    }
+}
+
+/// One core with a small deterministic wobble.
+fn wobble(len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|i| 1.0 + 0.05 * ((i * 7) % 5) as f64)
+        .collect()
+}
+
+#[test]
+fn loads_series_and_windows_from_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("incidents.csv");
+    fs::write(
+        &path,
+        "timestamp,value,label,resource_id,metric_type\n\
+         2026-01-01T00:02:00Z,1.0,0,api,cpu_usage\n\
+         2026-01-01T00:00:00Z,1.0,0,api,cpu_usage\n\
+         2026-01-01T00:01:00Z,5.0,1,api,cpu_usage\n\
+         2026-01-01T00:03:00Z,6.0,1,api,cpu_usage\n\
+         2026-01-01T00:04:00Z,7.0,1,api,cpu_usage\n\
+         2026-01-01T00:00:00Z,512,0,api,memory_usage\n",
+    )
+    .unwrap();
+
+    let dataset = load_dataset(&path).unwrap();
+
+    assert_eq!(dataset.len(), 2);
+    let cpu = &dataset[0];
+    let start = 1_767_225_600_000;
+    assert_eq!(cpu.series.metric_type, MetricType::CpuUsage);
+    assert_eq!(cpu.series.points[0].timestamp, start);
+    assert_eq!(
+        cpu.windows,
+        vec![
+            AnomalyWindow {
+                start_ms: start + STEP_MS,
+                end_ms: start + STEP_MS,
+            },
+            AnomalyWindow {
+                start_ms: start + 3 * STEP_MS,
+                end_ms: start + 4 * STEP_MS,
+            },
+        ]
+    );
+    assert!(dataset[1].windows.is_empty());
+}
+
+#[test]
+fn loads_parquet_named_after_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("checkout.parquet");
+    let mut frame = df!(
+        "timestamp" => [0i64, STEP_MS, 2 * STEP_MS],
+        "value" => [1.0, 9.0, 1.0],
+        "label" => [false, true, false]
+    )
+    .unwrap();
+    ParquetWriter::new(fs::File::create(&path).unwrap())
+        .finish(&mut frame)
+        .unwrap();
+
+    let dataset = load_dataset(&path).unwrap();
+
+    assert_eq!(dataset.len(), 1);
+    assert_eq!(dataset[0].series.resource_id, "checkout");
+    assert_eq!(
+        dataset[0].windows,
+        vec![AnomalyWindow {
+            start_ms: STEP_MS,
+            end_ms: STEP_MS,
+        }]
+    );
+}
+
+fn reviewed(detected_at: i64, kind: AnomalyFeedbackKind) -> Anomaly {
+    Anomaly {
+        id: format!("pod-a-{detected_at}"),
+        cluster_id: String::new(),
+        resource_id: "pod-a".to_string(),
+        detected_at,
+        metric_type: MetricType::CpuUsage,
+        severity: Severity::Warning,
+        confidence: 0.8,
+        description: String::new(),
+        baseline_value: 1.0,
+        observed_value: 5.0,
+        deviation_sigma: 5.0,
+        related_metrics: Vec::new(),
+        root_cause: None,
+        model_id: None,
+        model_version: None,
+        kind: AnomalyKind::Spike,
+        change_point: None,
+        feedback: Some(AnomalyFeedback {
+            kind,
+            submitted_at: detected_at,
+            note: None,
+        }),
+        evidence: None,
+    }
+}
+
+#[test]
+fn exports_feedback_as_a_loadable_dataset() {
+    let mut shift = reviewed(8 * STEP_MS, AnomalyFeedbackKind::Confirmed);
+    shift.kind = AnomalyKind::LevelShift;
+    shift.change_point = Some(ChangePoint {
+        changed_at: 6 * STEP_MS,
+        before_mean: 1.0,
+        after_mean: 5.0,
+        before_stddev: 0.1,
+        after_stddev: 0.1,
+        before_slope: 0.0,
+        after_slope: 0.0,
+    });
+    let feedback = vec![
+        reviewed(2 * STEP_MS, AnomalyFeedbackKind::Confirmed),
+        reviewed(4 * STEP_MS, AnomalyFeedbackKind::FalsePositive),
+        reviewed(5 * STEP_MS, AnomalyFeedbackKind::Expected),
+        shift,
+    ];
+    let labeled = label_feedback(labeled(&wobble(10), Vec::new()).series, &feedback);
+    let expected = vec![
+        AnomalyWindow {
+            start_ms: 2 * STEP_MS,
+            end_ms: 2 * STEP_MS,
+        },
+        AnomalyWindow {
+            start_ms: 6 * STEP_MS,
+            end_ms: 8 * STEP_MS,
+        },
+    ];
+    assert_eq!(labeled.windows, expected);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("feedback.csv");
+    write_dataset(&path, &[labeled]).unwrap();
+    let dataset = load_dataset(&path).unwrap();
+
+    assert_eq!(dataset.len(), 1);
+    assert_eq!(dataset[0].series.resource_id, "pod-a");
+    assert_eq!(dataset[0].series.unit, "cores");
+    assert_eq!(dataset[0].series.points.len(), 10);
+    assert_eq!(dataset[0].windows, expected);
+}
+
+#[test]
+fn scores_detection_against_labeled_windows() {
+    let mut values = wobble(120);
+    for value in &mut values[60..63] {
+        *value = 5.0;
+    }
+    let dataset = [labeled(
+        &values,
+        vec![
+            AnomalyWindow {
+                start_ms: 60 * STEP_MS,
+                end_ms: 62 * STEP_MS,
+            },
+            // Never visible in the values, so never found.
+            AnomalyWindow {
+                start_ms: 100 * STEP_MS,
+                end_ms: 101 * STEP_MS,
+            },
+        ],
+    )];
+
+    let score = Evaluator::new()
+        .detection("z_score", &AnomalyDetector::default(), &dataset)
+        .unwrap();
+
+    assert_eq!(score.windows, 2);
+    assert_eq!(score.detected_windows, 1);
+    assert_eq!(score.false_positives, 0);
+    assert_eq!(score.precision, 1.0);
+    assert_eq!(score.recall, 0.5);
+    assert!((score.f1 - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(score.mean_delay_ms, Some(0.0));
+}
+
+#[test]
+fn compares_forecast_error_across_predictors() {
+    let values: Vec<f64> = (0..200).map(|i| 1.0 + 0.05 * i as f64).collect();
+    let dataset = [labeled(&values, Vec::new())];
+    let evaluator = Evaluator::new().with_history(60);
+
+    let arima = evaluator
+        .forecast(
+            "arima",
+            &ScalingPredictor::new().with_model(ScalingModelKind::Arima),
+            &dataset,
+        )
+        .unwrap();
+    let average = evaluator
+        .forecast(
+            "moving_average",
+            &ScalingPredictor::new().with_model(ScalingModelKind::MovingAverage),
+            &dataset,
+        )
+        .unwrap();
+
+    assert_eq!(arima.forecasts, 14);
+    assert!(arima.mape.unwrap() < 0.01);
+    assert!(arima.mase.unwrap() < average.mase.unwrap());
+    assert!(average.mase.unwrap() > 10.0);
+}
@@ -0,0 +1,4 @@
+pub mod model_factory;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+//! Model construction from the `ml` config section.
+//!
+//! The whole section is validated before anything is built, so a service
+//! reloading its config either gets every model from the new section or
+//! keeps the ones it has.
+
+use anyhow::Result;
+
+use phenome_domain::MlConfig;
+
+use crate::detection::anomaly_detection::AnomalyDetector;
+use crate::recommendations::recommendations::RecommendationEngine;
+use crate::scaling::scaling_prediction::ScalingPredictor;
+
+/// The detector, predictor and recommendation engine a service scores
+/// with.
+#[derive(Debug, Clone, Default)]
+pub struct MlModels {
+    pub anomaly_detector: AnomalyDetector,
+    pub scaling_predictor: ScalingPredictor,
+    pub recommendation_engine: RecommendationEngine,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelFactory {
+    config: MlConfig,
+}
+
+impl ModelFactory {
+    pub fn from_config(config: &MlConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config: config.clone(),
+        })
+    }
+
+    /// Detector for `ml.models.anomaly_detection` and its per-metric
+    /// overrides, graded by `ml.thresholds`.
+    pub fn anomaly_detector(&self) -> Result<AnomalyDetector> {
+        AnomalyDetector::from_config(&self.config)
+    }
+
+    pub fn scaling_predictor(&self) -> Result<ScalingPredictor> {
+        ScalingPredictor::from_config(&self.config)
+    }
+
+    pub fn recommendation_engine(&self) -> Result<RecommendationEngine> {
+        RecommendationEngine::from_config(&self.config)
+    }
+
+    pub fn build(&self) -> Result<MlModels> {
+        Ok(MlModels {
+            anomaly_detector: self.anomaly_detector()?,
+            scaling_predictor: self.scaling_predictor()?,
+            recommendation_engine: self.recommendation_engine()?,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use phenome_domain::{
+    AnomalyModelKind, DownsampleMethod, MetricThresholdsConfig, MetricType, MlConfig,
+    PhenomeConfig, ScalingModelKind, Severity, TimeRange, TimeSeries, TimeSeriesData,
+    TimeSeriesPoint,
+};
+
+use crate::factory::model_factory::ModelFactory;
+
+fn sample_ml() -> MlConfig {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../phenome-config.yaml");
+    PhenomeConfig::load_from_path(&path).unwrap().ml
+}
+
+/// Z-score only, so the spike below is graded by thresholds alone.
+fn z_score_ml() -> MlConfig {
+    let mut ml = sample_ml();
+    ml.models.anomaly_detection = AnomalyModelKind::ZScore;
+    ml.models.anomaly_detection_by_metric.clear();
+    ml.change_point.enabled = false;
+    ml.multivariate.enabled = false;
+    ml
+}
+
+/// Steady CPU with a last point far above it.
+fn spike() -> TimeSeriesData {
+    let mut values: Vec<f64> = (0..40)
+        .map(|i| 1.0 + 0.1 * (i as f64 / 3.0).sin())
+        .collect();
+    values.push(2.0);
+    TimeSeriesData {
+        cluster_id: "cluster-1".to_string(),
+        range: TimeRange {
+            start_ms: 0,
+            end_ms: 41 * 60_000,
+        },
+        series: vec![TimeSeries {
+            cluster_id: "cluster-1".to_string(),
+            resource_id: "pod-a".to_string(),
+            metric_type: MetricType::CpuUsage,
+            unit: "cores".to_string(),
+            points: values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| TimeSeriesPoint {
+                    timestamp: i as i64 * 60_000,
+                    value: *value,
+                })
+                .collect(),
+            downsample: DownsampleMethod::Raw,
+        }],
+    }
+}
+
+#[test]
+fn builds_configured_models_from_sample_config() {
+    let models = ModelFactory::from_config(&sample_ml())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let detector = &models.anomaly_detector;
+    assert_eq!(detector.model(), AnomalyModelKind::IsolationForest);
+    assert_eq!(
+        detector.model_for(MetricType::CpuUsage),
+        AnomalyModelKind::Seasonal
+    );
+    assert_eq!(models.scaling_predictor.model(), ScalingModelKind::Auto);
+}
+
+#[test]
+fn grades_anomalies_by_configured_thresholds() {
+    let ml = z_score_ml();
+    let detect = |ml: &MlConfig| {
+        ModelFactory::from_config(ml)
+            .unwrap()
+            .anomaly_detector()
+            .unwrap()
+            .detect(&spike())
+            .unwrap()
+    };
+
+    let graded = detect(&ml);
+    assert_eq!(graded.len(), 1);
+    let limits = ml.thresholds.for_metric(MetricType::CpuUsage);
+    assert_eq!(graded[0].severity, limits.severity(graded[0].confidence));
+
+    let mut lenient = ml.clone();
+    lenient.thresholds.warning_confidence = 0.0;
+    lenient.thresholds.critical_confidence = 0.0;
+    assert_eq!(detect(&lenient)[0].severity, Severity::Critical);
+
+    let mut strict = ml;
+    strict.thresholds.by_metric.insert(
+        MetricType::CpuUsage,
+        MetricThresholdsConfig {
+            sigma_threshold: Some(100.0),
+            ..MetricThresholdsConfig::default()
+        },
+    );
+    assert!(detect(&strict).is_empty());
+}
+
+#[test]
+fn rejects_invalid_thresholds_before_building() {
+    let mut ml = sample_ml();
+    ml.thresholds.warning_confidence = 0.95;
+
+    assert!(ModelFactory::from_config(&ml).is_err());
+}
@@ -1,10 +1,26 @@
 //! Machine learning models for phenome analytics.
 
 mod detection;
+mod evaluation;
+mod factory;
 mod recommendations;
+mod registry;
 mod scaling;
+mod timing;
 
 pub use detection::anomaly_detection::AnomalyDetector;
-pub use detection::root_cause::RootCauseEngine;
+pub use detection::change_point::{ChangePointDetector, DetectedChange};
+pub use detection::isolation_forest::IsolationForest;
+pub use detection::multivariate::{MultivariateDetector, MultivariateFinding};
+pub use detection::root_cause::{RootCauseContext, RootCauseEngine};
+pub use detection::seasonal::SeasonalBaseline;
+pub use evaluation::dataset::{label_feedback, load_dataset, write_dataset};
+pub use evaluation::harness::Evaluator;
+pub use factory::model_factory::{MlModels, ModelFactory};
 pub use recommendations::recommendations::RecommendationEngine;
+pub use registry::model_registry::{
+    FittedModel, ModelRecord, ModelRegistry, TrainedModel, anomaly_model_id, scaling_model_id,
+};
+pub use scaling::backtest::{BacktestScore, backtest};
 pub use scaling::scaling_prediction::ScalingPredictor;
+pub use timing::step_duration::StepDurationPredictor;
@@ -1 +1,4 @@
 pub mod recommendations;
+
+#[cfg(test)]
+mod tests;
@@ -1,36 +1,623 @@
+//! Rule-based rightsizing.
+//!
+//! Per-replica usage of each container over the lookback window is merged
+//! from the stored per-resource `AggregatedMetric` windows and compared
+//! with the container's requests and limits. Pod CPU running hot or idle
+//! against the pod's summed requests scales the workload; memory and
+//! single-replica CPU resize the request instead. A p99 close to a limit
+//! raises that limit.
+
 use anyhow::Result;
 use chrono::Utc;
+use std::time::Duration;
 
 use phenome_domain::{
-    Priority, Recommendation, RecommendationAction, RecommendationStatus, RecommendationType,
+    AggregatedMetric, ContainerResources, MetricType, MlConfig, Priority, Recommendation,
+    RecommendationAction, RecommendationConfig, RecommendationStatus, RecommendationType,
+    ResourceLimits, ResourceType, Unit, format_quantity,
 };
 
-#[derive(Debug, Clone, Default)]
-pub struct RecommendationEngine;
+/// Resized CPU is rounded up to 10 millicores.
+const CPU_STEP: f64 = 0.01;
+
+/// Resized memory is rounded up to 1 MiB.
+const MEMORY_STEP: f64 = 1024.0 * 1024.0;
+
+/// Samples, as a multiple of `min_samples`, at which confidence stops
+/// growing with coverage.
+const FULL_COVERAGE: f64 = 4.0;
+
+/// Characters Kubernetes generates pod name hashes from.
+const POD_HASH_ALPHABET: &str = "bcdfghjklmnpqrstvwxz2456789";
+
+#[derive(Debug, Clone)]
+pub struct RecommendationEngine {
+    config: RecommendationConfig,
+    lookback: Duration,
+}
+
+impl Default for RecommendationEngine {
+    fn default() -> Self {
+        Self::with_config(RecommendationConfig::default())
+            .expect("default recommendation thresholds are valid")
+    }
+}
 
 impl RecommendationEngine {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Thresholds from `ml.recommendations`.
+    pub fn from_config(config: &MlConfig) -> Result<Self> {
+        Self::with_config(config.recommendations.clone())
+    }
+
+    pub fn with_config(config: RecommendationConfig) -> Result<Self> {
+        config.validate()?;
+        let lookback = Duration::from_millis(config.lookback_ms()? as u64);
+        Ok(Self { config, lookback })
+    }
+
+    /// Window of usage history the rules are evaluated over.
+    pub fn lookback(&self) -> Duration {
+        self.lookback
+    }
+
+    /// Compare each container's usage in the aggregate `windows` with its
+    /// requests and limits, and each workload's with their sum. Containers
+    /// with fewer than `min_samples` samples of a metric get no
+    /// recommendation for it.
+    pub fn generate(
+        &self,
+        cluster_id: String,
+        containers: &[ContainerResources],
+        windows: &[AggregatedMetric],
+    ) -> Result<Vec<Recommendation>> {
+        let created_at = Utc::now().timestamp_millis();
+        let mut workloads: Vec<&str> = Vec::new();
+        for container in containers {
+            if !workloads.contains(&container.workload.as_str()) {
+                workloads.push(&container.workload);
+            }
+        }
+        let drafts: Vec<Draft> = workloads
+            .into_iter()
+            .flat_map(|workload| {
+                let members: Vec<&ContainerResources> = containers
+                    .iter()
+                    .filter(|container| container.workload == workload)
+                    .collect();
+                self.rules(&cluster_id, &members, windows)
+            })
+            .collect();
+
+        Ok(drafts
+            .into_iter()
+            .enumerate()
+            .map(|(index, draft)| Recommendation {
+                id: format!("rec-{created_at}-{index}"),
+                cluster_id: cluster_id.clone(),
+                created_at,
+                recommendation_type: draft.recommendation_type,
+                priority: draft.priority,
+                confidence: draft.confidence,
+                title: draft.title,
+                description: draft.description,
+                impact_estimate: draft.impact_estimate,
+                cost_impact: None,
+                action: draft.action,
+                status: RecommendationStatus::Pending,
+            })
+            .collect())
+    }
+
+    /// At most one scaling draft for the workload `members` share, then a
+    /// rightsizing draft per container.
+    fn rules(
+        &self,
+        cluster_id: &str,
+        members: &[&ContainerResources],
+        windows: &[AggregatedMetric],
+    ) -> Vec<Draft> {
+        let mut drafts = Vec::new();
+        let scaling = self
+            .workload_usage(cluster_id, members, windows)
+            .and_then(|usage| self.scale(members, &usage));
+        let sole = members.len() == 1;
+        let mut resizes = Vec::new();
+        for container in members {
+            let cpu = self.usage(cluster_id, container, sole, MetricType::CpuUsage, windows);
+            let memory = self.usage(
+                cluster_id,
+                container,
+                sole,
+                MetricType::MemoryUsage,
+                windows,
+            );
+            // A scaled workload keeps its CPU requests; only limits move.
+            let cpu_resize = cpu.as_ref().map(|usage| {
+                let request = scaling.is_none().then_some(container.cpu_request);
+                self.resize(usage, request, container.cpu_limit, Unit::Cores)
+            });
+            let memory_resize = memory.as_ref().map(|usage| {
+                let request = Some(container.memory_request);
+                self.resize(usage, request, container.memory_limit, Unit::Bytes)
+            });
+            resizes.extend(self.rightsize(container, cpu_resize, memory_resize));
+        }
+        drafts.extend(scaling);
+        drafts.extend(resizes);
+        drafts
+    }
+
+    /// Per-replica usage of `container`, merged from its container
+    /// windows. Pod windows total every container in the pod, so they stand
+    /// in only for the `sole` container of its workload.
+    fn usage(
+        &self,
+        cluster_id: &str,
+        container: &ContainerResources,
+        sole: bool,
+        metric_type: MetricType,
+        windows: &[AggregatedMetric],
+    ) -> Option<AggregatedMetric> {
+        let resource_id = format!("{}/{}", container.workload, container.container);
+        let selected = select(windows, metric_type, ResourceType::Container, |id| {
+            is_container_of(id, container)
+        });
+        if !selected.is_empty() || !sole {
+            return self.merge(cluster_id, resource_id, ResourceType::Container, &selected);
+        }
+        let selected = select(windows, metric_type, ResourceType::Pod, |id| {
+            is_pod_of(id, &container.workload)
+        });
+        self.merge(cluster_id, resource_id, ResourceType::Pod, &selected)
     }
 
-    pub fn generate(&self, cluster_id: String) -> Result<Vec<Recommendation>> {
-        Ok(vec![Recommendation {
-            id: format!("rec-{}", Utc::now().timestamp_millis()),
+    /// Per-replica CPU usage of the workload `members` share, merged from
+    /// its pod windows, or its sole container's windows without any.
+    fn workload_usage(
+        &self,
+        cluster_id: &str,
+        members: &[&ContainerResources],
+        windows: &[AggregatedMetric],
+    ) -> Option<AggregatedMetric> {
+        let first = members.first()?;
+        let selected = select(windows, MetricType::CpuUsage, ResourceType::Pod, |id| {
+            is_pod_of(id, &first.workload)
+        });
+        if selected.is_empty() && members.len() == 1 {
+            return self.usage(cluster_id, first, true, MetricType::CpuUsage, windows);
+        }
+        self.merge(
             cluster_id,
-            created_at: Utc::now().timestamp_millis(),
-            recommendation_type: RecommendationType::OptimizeResources,
-            priority: Priority::Low,
-            confidence: 0.0,
-            title: "No recommendations available".to_string(),
-            description: "No actionable insights found for this cluster.".to_string(),
-            impact_estimate: "No impact".to_string(),
-            cost_impact: None,
+            first.workload.clone(),
+            ResourceType::Pod,
+            &selected,
+        )
+    }
+
+    /// One aggregate over the lookback from `selected` windows, `None`
+    /// with fewer than `min_samples` samples.
+    fn merge(
+        &self,
+        cluster_id: &str,
+        resource_id: String,
+        resource_type: ResourceType,
+        selected: &[&AggregatedMetric],
+    ) -> Option<AggregatedMetric> {
+        let count: u64 = selected.iter().map(|window| window.count).sum();
+        if count == 0 || count < self.config.min_samples as u64 {
+            return None;
+        }
+
+        let sum: f64 = selected.iter().map(|window| window.sum).sum();
+        Some(AggregatedMetric {
+            cluster_id: cluster_id.to_string(),
+            resource_type,
+            resource_id,
+            metric_type: selected.first()?.metric_type,
+            window_start: selected.iter().map(|window| window.window_start).min()?,
+            window_duration: self.lookback,
+            count,
+            sum,
+            min: selected
+                .iter()
+                .map(|window| window.min)
+                .fold(f64::INFINITY, f64::min),
+            max: selected
+                .iter()
+                .map(|window| window.max)
+                .fold(f64::NEG_INFINITY, f64::max),
+            avg: sum / count as f64,
+            p50: merged_percentile(selected, 0.5, |window| window.p50),
+            p95: merged_percentile(selected, 0.95, |window| window.p95),
+            p99: merged_percentile(selected, 0.99, |window| window.p99),
+        })
+    }
+
+    /// Scale replicas so the pod CPU p95 lands at the target share of the
+    /// pod's summed request, when it is under- or (with spare replicas)
+    /// over-provisioned. Needs a CPU request on every container.
+    fn scale(&self, members: &[&ContainerResources], cpu: &AggregatedMetric) -> Option<Draft> {
+        let requests: Option<Vec<f64>> = members
+            .iter()
+            .map(|container| container.cpu_request.filter(|request| *request > 0.0))
+            .collect();
+        let request: f64 = requests?.iter().sum();
+        let workload = &members.first()?.workload;
+        let from = members.first()?.replicas;
+        if request <= 0.0 || from == 0 {
+            return None;
+        }
+        let config = &self.config;
+        let ratio = cpu.p95 / request;
+        let to = ((from as f64 * ratio / config.target_utilization).ceil() as u32).max(1);
+
+        let (recommendation_type, priority, margin, to) = if ratio > config.under_provisioned {
+            let priority = if ratio > 1.0 {
+                Priority::High
+            } else {
+                Priority::Medium
+            };
+            let margin = (ratio - config.under_provisioned) / config.under_provisioned;
+            (
+                RecommendationType::ScaleUp,
+                priority,
+                margin,
+                to.max(from + 1),
+            )
+        } else if ratio < config.over_provisioned && to < from {
+            let margin = (config.over_provisioned - ratio) / config.over_provisioned;
+            (RecommendationType::ScaleDown, Priority::Low, margin, to)
+        } else {
+            return None;
+        };
+
+        let per_replica = ratio * from as f64 / to as f64 * 100.0;
+        let (title, impact_estimate) = if to > from {
+            (
+                format!("Scale up {workload} to {to} replicas"),
+                format!(
+                    "Adds {}; CPU p95 per replica drops to about {per_replica:.0}% of its request",
+                    replicas(to - from)
+                ),
+            )
+        } else {
+            let freed = request * (from - to) as f64;
+            (
+                format!("Scale down {workload} to {to} replicas"),
+                format!(
+                    "Frees {} and {} of CPU requests; CPU p95 per replica rises to about \
+                     {per_replica:.0}% of its request",
+                    replicas(from - to),
+                    Unit::Cores.format_human(freed)
+                ),
+            )
+        };
+        Some(Draft {
+            recommendation_type,
+            priority,
+            confidence: self.confidence(cpu.count, margin),
+            title,
+            description: format!(
+                "{} across {} over {}.",
+                usage_against(cpu, "p95", cpu.p95, request, "request"),
+                replicas(from),
+                self.config.lookback
+            ),
+            impact_estimate,
             action: RecommendationAction::ScaleDeployment {
-                name: "unknown".to_string(),
-                from: 0,
-                to: 0,
+                name: workload.clone(),
+                from,
+                to,
             },
-            status: RecommendationStatus::Pending,
-        }])
+        })
+    }
+
+    /// Resize `request` to put the p95 at the target share when it is
+    /// unset or outside the provisioning band, and raise `limit` when the
+    /// p99 presses against it. An outer `None` leaves the request alone.
+    fn resize(
+        &self,
+        usage: &AggregatedMetric,
+        request: Option<Option<f64>>,
+        limit: Option<f64>,
+        unit: Unit,
+    ) -> Resize {
+        let config = &self.config;
+        let step = match unit {
+            Unit::Cores => CPU_STEP,
+            _ => MEMORY_STEP,
+        };
+        let sized = round_up(usage.p95 / config.target_utilization, step);
+        let mut resize = Resize {
+            usage_count: Some(usage.count),
+            ..Resize::default()
+        };
+
+        match request.map(|request| request.filter(|request| *request > 0.0)) {
+            Some(Some(request)) => {
+                let ratio = usage.p95 / request;
+                let margin = if ratio > config.under_provisioned {
+                    resize.under = true;
+                    (ratio - config.under_provisioned) / config.under_provisioned
+                } else if ratio < config.over_provisioned {
+                    (config.over_provisioned - ratio) / config.over_provisioned
+                } else {
+                    0.0
+                };
+                if margin > 0.0 && sized != request {
+                    resize.request = Some((Some(request), sized));
+                    resize.margin = margin;
+                    resize
+                        .evidence
+                        .push(usage_against(usage, "p95", usage.p95, request, "request"));
+                }
+            }
+            Some(None) => {
+                resize.under = true;
+                resize.request = Some((None, sized));
+                resize.margin = 1.0;
+                resize.evidence.push(format!(
+                    "{} p95 is {} with no request set",
+                    usage.metric_type.as_str(),
+                    unit.format_human(usage.p95)
+                ));
+            }
+            None => {}
+        }
+
+        if let Some(limit) = limit.filter(|limit| *limit > 0.0) {
+            let ratio = usage.p99 / limit;
+            let new_request = resize.request.map_or(0.0, |(_, new)| new);
+            if ratio > config.limit_pressure {
+                resize.pressure = true;
+                resize.limit = Some((
+                    limit,
+                    round_up(usage.p99 * config.limit_headroom, step).max(new_request),
+                ));
+                resize.margin = resize
+                    .margin
+                    .max((ratio - config.limit_pressure) / config.limit_pressure);
+                resize
+                    .evidence
+                    .push(usage_against(usage, "p99", usage.p99, limit, "limit"));
+            } else if new_request > limit {
+                resize.limit = Some((limit, new_request));
+            }
+        }
+        resize
+    }
+
+    /// One `UpdateResourceLimits` per container covering both resources.
+    fn rightsize(
+        &self,
+        container: &ContainerResources,
+        cpu: Option<Resize>,
+        memory: Option<Resize>,
+    ) -> Option<Draft> {
+        let cpu = cpu.unwrap_or_default();
+        let memory = memory.unwrap_or_default();
+        if cpu.is_unchanged() && memory.is_unchanged() {
+            return None;
+        }
+
+        let pressure = cpu.pressure || memory.pressure;
+        let under = cpu.under || memory.under;
+        let (recommendation_type, priority) = if pressure {
+            (RecommendationType::AdjustLimits, Priority::High)
+        } else if under {
+            (RecommendationType::AdjustLimits, Priority::Medium)
+        } else {
+            (RecommendationType::OptimizeResources, Priority::Low)
+        };
+
+        let resource = format!("{}/{}", container.workload, container.container);
+        let impact: Vec<String> = [(&cpu, Unit::Cores), (&memory, Unit::Bytes)]
+            .into_iter()
+            .flat_map(|(resize, unit)| resize.impact(unit, container.replicas.max(1)))
+            .collect();
+        let confidence = [&cpu, &memory]
+            .into_iter()
+            .filter_map(|resize| resize.usage_count.map(|count| (count, resize.margin)))
+            .map(|(count, margin)| self.confidence(count, margin))
+            .fold(0.0, f64::max);
+
+        Some(Draft {
+            recommendation_type,
+            priority,
+            confidence,
+            title: format!("Rightsize {resource}"),
+            description: format!(
+                "{} over {}.",
+                [cpu.evidence, memory.evidence].concat().join("; "),
+                self.config.lookback
+            ),
+            impact_estimate: impact.join("; "),
+            action: RecommendationAction::UpdateResourceLimits {
+                resource,
+                limits: ResourceLimits {
+                    cpu: cpu.limit.map(|(_, new)| format_quantity(new, Unit::Cores)),
+                    memory: memory
+                        .limit
+                        .map(|(_, new)| format_quantity(new, Unit::Bytes)),
+                    cpu_request: cpu
+                        .request
+                        .map(|(_, new)| format_quantity(new, Unit::Cores)),
+                    memory_request: memory
+                        .request
+                        .map(|(_, new)| format_quantity(new, Unit::Bytes)),
+                },
+            },
+        })
+    }
+
+    /// Grows with sample coverage and with how far past its threshold the
+    /// usage is.
+    fn confidence(&self, count: u64, margin: f64) -> f64 {
+        let full = self.config.min_samples.max(1) as f64 * FULL_COVERAGE;
+        let coverage = (count as f64 / full).min(1.0);
+        coverage * (0.5 + 0.5 * margin.clamp(0.0, 1.0))
+    }
+}
+
+struct Draft {
+    recommendation_type: RecommendationType,
+    priority: Priority,
+    confidence: f64,
+    title: String,
+    description: String,
+    impact_estimate: String,
+    action: RecommendationAction,
+}
+
+/// Request and limit changes for one resource, as (old, new) pairs.
+#[derive(Default)]
+struct Resize {
+    request: Option<(Option<f64>, f64)>,
+    limit: Option<(f64, f64)>,
+    under: bool,
+    pressure: bool,
+    margin: f64,
+    usage_count: Option<u64>,
+    evidence: Vec<String>,
+}
+
+impl Resize {
+    fn is_unchanged(&self) -> bool {
+        self.request.is_none() && self.limit.is_none()
+    }
+
+    fn impact(&self, unit: Unit, replica_count: u32) -> Vec<String> {
+        let name = match unit {
+            Unit::Cores => "CPU",
+            _ => "memory",
+        };
+        let mut impact = Vec::new();
+        if let Some((old, new)) = self.request {
+            let delta = (new - old.unwrap_or(0.0)) * replica_count as f64;
+            let verb = if delta < 0.0 { "frees" } else { "reserves" };
+            impact.push(format!(
+                "{name} request {} -> {} {verb} {} across {}",
+                old.map_or("unset".to_string(), |old| format_quantity(old, unit)),
+                format_quantity(new, unit),
+                unit.format_human(delta.abs()),
+                replicas(replica_count)
+            ));
+        }
+        if let Some((old, new)) = self.limit {
+            impact.push(format!(
+                "{name} limit {} -> {}",
+                format_quantity(old, unit),
+                format_quantity(new, unit)
+            ));
+        }
+        impact
+    }
+}
+
+/// Windows of `metric_type` for `resource_type` resources `matches` picks.
+fn select(
+    windows: &[AggregatedMetric],
+    metric_type: MetricType,
+    resource_type: ResourceType,
+    matches: impl Fn(&str) -> bool,
+) -> Vec<&AggregatedMetric> {
+    windows
+        .iter()
+        .filter(|window| {
+            window.metric_type == metric_type
+                && window.resource_type == resource_type
+                && window.count > 0
+                && matches(&window.resource_id)
+        })
+        .collect()
+}
+
+/// Whether `resource_id` is `container` within one of its workload's pods.
+fn is_container_of(resource_id: &str, container: &ContainerResources) -> bool {
+    resource_id
+        .strip_suffix(container.container.as_str())
+        .and_then(|pod| pod.strip_suffix('/'))
+        .is_some_and(|pod| is_pod_of(pod, &container.workload))
+}
+
+/// Whether `pod` is named as one of `workload`'s pods: the workload itself,
+/// `<name>-<ordinal>` for a StatefulSet, `<name>-<hash>` for a DaemonSet or
+/// Job, or `<name>-<rs-hash>-<hash>` for a Deployment. Hashes are drawn
+/// from Kubernetes' vowel-free alphabet, which keeps `web` from claiming
+/// the pods of `web-api`.
+fn is_pod_of(pod: &str, workload: &str) -> bool {
+    let Some(rest) = pod.strip_prefix(workload) else {
+        return false;
+    };
+    let Some(rest) = rest.strip_prefix('-') else {
+        return rest.is_empty();
+    };
+    let hash = |part: &str, len: std::ops::RangeInclusive<usize>| {
+        len.contains(&part.len()) && part.chars().all(|c| POD_HASH_ALPHABET.contains(c))
+    };
+    match rest.split('-').collect::<Vec<_>>()[..] {
+        [ordinal] if ordinal.chars().all(|c| c.is_ascii_digit()) => true,
+        [suffix] => hash(suffix, 5..=5),
+        [template, suffix] => hash(template, 1..=10) && hash(suffix, 5..=5),
+        _ => false,
+    }
+}
+
+fn usage_against(
+    usage: &AggregatedMetric,
+    stat: &str,
+    value: f64,
+    reference: f64,
+    kind: &str,
+) -> String {
+    let unit = Unit::for_metric(usage.metric_type);
+    format!(
+        "{} {stat} {} is {:.0}% of the {} {kind} ({} samples)",
+        usage.metric_type.as_str(),
+        unit.format_human(value),
+        value / reference * 100.0,
+        format_quantity(reference, unit),
+        usage.count
+    )
+}
+
+fn replicas(count: u32) -> String {
+    if count == 1 {
+        "1 replica".to_string()
+    } else {
+        format!("{count} replicas")
+    }
+}
+
+fn round_up(value: f64, step: f64) -> f64 {
+    ((value / step) - 1e-9).ceil().max(1.0) * step
+}
+
+/// Nearest-rank percentile across windows, each window's own percentile
+/// standing in for all of its samples. Exact while windows hold a sample
+/// each; otherwise an estimate between the windows' percentiles.
+fn merged_percentile(
+    windows: &[&AggregatedMetric],
+    pct: f64,
+    stat: impl Fn(&AggregatedMetric) -> f64,
+) -> f64 {
+    let mut values: Vec<(f64, u64)> = windows
+        .iter()
+        .map(|window| (stat(window), window.count))
+        .collect();
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total: u64 = values.iter().map(|(_, count)| count).sum();
+    let rank = ((total as f64 * pct).ceil() as u64).clamp(1, total.max(1));
+    let mut seen = 0;
+    for (value, count) in &values {
+        seen += count;
+        if seen >= rank {
+            return *value;
+        }
     }
+    values.last().map_or(0.0, |(value, _)| *value)
 }
@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use phenome_domain::{
+    AggregatedMetric, ContainerResources, MetricType, Priority, RecommendationAction,
+    RecommendationConfig, RecommendationType, ResourceType,
+};
+
+use crate::recommendations::recommendations::RecommendationEngine;
+
+const HOUR_MS: i64 = 3_600_000;
+const MI: f64 = 1024.0 * 1024.0;
+
+/// Hourly windows of one sample for each of `resources` over the last
+/// day, following `value` by hour.
+fn hourly(
+    resources: &[impl AsRef<str>],
+    resource_type: ResourceType,
+    metric_type: MetricType,
+    value: impl Fn(usize) -> f64,
+) -> Vec<AggregatedMetric> {
+    resources
+        .iter()
+        .flat_map(|resource_id| {
+            (0..24).map(|hour| {
+                window(
+                    resource_id.as_ref(),
+                    resource_type,
+                    metric_type,
+                    hour,
+                    &[value(hour)],
+                )
+            })
+        })
+        .collect()
+}
+
+/// The aggregate of `values` for `resource_id` in the window at `hour`.
+fn window(
+    resource_id: &str,
+    resource_type: ResourceType,
+    metric_type: MetricType,
+    hour: usize,
+    values: &[f64],
+) -> AggregatedMetric {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let at =
+        |pct: f64| sorted[((sorted.len() as f64 * pct).ceil() as usize).clamp(1, sorted.len()) - 1];
+    let sum: f64 = values.iter().sum();
+    AggregatedMetric {
+        cluster_id: "cluster-1".to_string(),
+        resource_type,
+        resource_id: resource_id.to_string(),
+        metric_type,
+        window_start: hour as i64 * HOUR_MS,
+        window_duration: Duration::from_millis(HOUR_MS as u64),
+        count: values.len() as u64,
+        sum,
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        avg: sum / values.len() as f64,
+        p50: at(0.5),
+        p95: at(0.95),
+        p99: at(0.99),
+    }
+}
+
+fn container(workload: &str, replicas: u32) -> ContainerResources {
+    ContainerResources {
+        workload: workload.to_string(),
+        container: "app".to_string(),
+        replicas,
+        ..ContainerResources::default()
+    }
+}
+
+const API_PODS: [&str; 3] = [
+    "shop/api-7d9f8-x2k4p",
+    "shop/api-7d9f8-b8n6q",
+    "shop/api-7d9f8-r7m5z",
+];
+
+#[test]
+fn scales_up_workload_running_hot_on_cpu() {
+    let history = hourly(&API_PODS, ResourceType::Pod, MetricType::CpuUsage, |hour| {
+        1.0 + 0.3 * (hour as f64 / 3.0).sin()
+    });
+    let api = ContainerResources {
+        cpu_request: Some(1.0),
+        cpu_limit: Some(4.0),
+        ..container("shop/api", 3)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[api], &history)
+        .unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    let scale_up = &recommendations[0];
+    assert_eq!(scale_up.recommendation_type, RecommendationType::ScaleUp);
+    assert_eq!(scale_up.priority, Priority::High);
+    assert!(matches!(
+        &scale_up.action,
+        RecommendationAction::ScaleDeployment { name, from: 3, to: 6 } if name == "shop/api"
+    ));
+    assert!(scale_up.description.contains("across 3 replicas"));
+    assert!(scale_up.confidence > 0.5 && scale_up.confidence <= 1.0);
+}
+
+#[test]
+fn scales_down_idle_workload() {
+    let history = hourly(&API_PODS, ResourceType::Pod, MetricType::CpuUsage, |hour| {
+        0.2 + 0.05 * (hour as f64 / 3.0).sin()
+    });
+    let api = ContainerResources {
+        cpu_request: Some(2.0),
+        ..container("shop/api", 4)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[api], &history)
+        .unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    let scale_down = &recommendations[0];
+    assert_eq!(
+        scale_down.recommendation_type,
+        RecommendationType::ScaleDown
+    );
+    assert_eq!(scale_down.priority, Priority::Low);
+    assert!(matches!(
+        scale_down.action,
+        RecommendationAction::ScaleDeployment { from: 4, to: 1, .. }
+    ));
+    assert!(scale_down.impact_estimate.starts_with("Frees 3 replicas"));
+}
+
+#[test]
+fn raises_memory_request_and_limit_under_pressure() {
+    let history = hourly(
+        &["shop/db-0/app"],
+        ResourceType::Container,
+        MetricType::MemoryUsage,
+        |hour| (900.0 + 4.0 * hour as f64) * MI,
+    );
+    let db = ContainerResources {
+        memory_request: Some(512.0 * MI),
+        memory_limit: Some(1024.0 * MI),
+        ..container("shop/db", 1)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[db], &history)
+        .unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    let rightsize = &recommendations[0];
+    assert_eq!(
+        rightsize.recommendation_type,
+        RecommendationType::AdjustLimits
+    );
+    assert_eq!(rightsize.priority, Priority::High);
+    assert_eq!(rightsize.title, "Rightsize shop/db/app");
+    let RecommendationAction::UpdateResourceLimits { resource, limits } = &rightsize.action else {
+        panic!("expected a limits update, got {:?}", rightsize.action);
+    };
+    assert_eq!(resource, "shop/db/app");
+    // p95 988Mi / 0.7 target; p99 992Mi * 1.5 headroom.
+    assert_eq!(limits.memory_request.as_deref(), Some("1412Mi"));
+    assert_eq!(limits.memory.as_deref(), Some("1488Mi"));
+    assert_eq!(limits.cpu, None);
+    assert!(rightsize.description.contains("memory_usage p99"));
+}
+
+#[test]
+fn lowers_overprovisioned_request_of_single_replica() {
+    let history = hourly(
+        &["shop/worker-0"],
+        ResourceType::Pod,
+        MetricType::CpuUsage,
+        |_| 0.21,
+    );
+    let worker = ContainerResources {
+        cpu_request: Some(2.0),
+        ..container("shop/worker", 1)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[worker], &history)
+        .unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    let rightsize = &recommendations[0];
+    assert_eq!(
+        rightsize.recommendation_type,
+        RecommendationType::OptimizeResources
+    );
+    assert_eq!(rightsize.priority, Priority::Low);
+    assert_eq!(
+        rightsize.impact_estimate,
+        "CPU request 2 -> 300m frees 1.70 cores across 1 replica"
+    );
+}
+
+#[test]
+fn skips_containers_with_sparse_history() {
+    let mut history = hourly(&API_PODS, ResourceType::Pod, MetricType::CpuUsage, |_| 3.0);
+    history.truncate(6);
+    let api = ContainerResources {
+        cpu_request: Some(1.0),
+        ..container("shop/api", 3)
+    };
+    let engine = RecommendationEngine::with_config(RecommendationConfig {
+        min_samples: 12,
+        ..RecommendationConfig::default()
+    })
+    .unwrap();
+
+    let recommendations = engine
+        .generate("cluster-1".to_string(), &[api], &history)
+        .unwrap();
+
+    assert!(recommendations.is_empty());
+}
+
+#[test]
+fn merges_windows_holding_many_samples() {
+    // Three busy hours of 20 samples each among 21 quiet hours of 20.
+    let windows: Vec<AggregatedMetric> = (0..24)
+        .map(|hour| {
+            let level = if hour < 3 { 1.8 } else { 0.5 };
+            window(
+                "shop/api-7d9f8-x2k4p",
+                ResourceType::Pod,
+                MetricType::CpuUsage,
+                hour,
+                &[level; 20],
+            )
+        })
+        .collect();
+    let api = ContainerResources {
+        cpu_request: Some(1.0),
+        ..container("shop/api", 1)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[api], &windows)
+        .unwrap();
+
+    // 60 of 480 samples run hot, so the p95 lands on the busy windows.
+    assert_eq!(recommendations.len(), 1);
+    assert_eq!(
+        recommendations[0].recommendation_type,
+        RecommendationType::ScaleUp
+    );
+    assert!(recommendations[0].description.contains("(480 samples)"));
+}
+
+#[test]
+fn scales_pods_with_sidecars_once_against_their_summed_requests() {
+    // The app runs at 0.6 cores and its proxy at 0.3 in every pod.
+    let mut history = hourly(&API_PODS, ResourceType::Pod, MetricType::CpuUsage, |_| 0.9);
+    let app: Vec<String> = API_PODS.iter().map(|pod| format!("{pod}/app")).collect();
+    let proxy: Vec<String> = API_PODS.iter().map(|pod| format!("{pod}/proxy")).collect();
+    history.extend(hourly(
+        &app,
+        ResourceType::Container,
+        MetricType::CpuUsage,
+        |_| 0.6,
+    ));
+    history.extend(hourly(
+        &proxy,
+        ResourceType::Container,
+        MetricType::CpuUsage,
+        |_| 0.3,
+    ));
+    let containers = [
+        ContainerResources {
+            cpu_request: Some(0.7),
+            ..container("shop/api", 3)
+        },
+        ContainerResources {
+            container: "proxy".to_string(),
+            cpu_request: Some(0.5),
+            ..container("shop/api", 3)
+        },
+    ];
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &containers, &history)
+        .unwrap();
+
+    // 0.9 of 1.2 cores is in band for the pod; neither container is judged
+    // by the whole pod's usage.
+    assert!(recommendations.is_empty(), "{recommendations:?}");
+
+    let hot: Vec<AggregatedMetric> = history
+        .into_iter()
+        .map(|window| match window.resource_type {
+            ResourceType::Pod => AggregatedMetric { p95: 1.5, ..window },
+            _ => window,
+        })
+        .collect();
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &containers, &hot)
+        .unwrap();
+    let scaled: Vec<_> = recommendations
+        .iter()
+        .filter(|rec| matches!(rec.action, RecommendationAction::ScaleDeployment { .. }))
+        .collect();
+    assert_eq!(scaled.len(), 1);
+    assert!(scaled[0].description.contains("of the 1200m request"));
+}
+
+#[test]
+fn keeps_workloads_sharing_a_name_prefix_apart() {
+    let history = hourly(
+        &["shop/web-api-6b8c9-x2k4p", "shop/web-api-x2k4p"],
+        ResourceType::Pod,
+        MetricType::CpuUsage,
+        |_| 3.0,
+    );
+    let web = ContainerResources {
+        cpu_request: Some(1.0),
+        ..container("shop/web", 1)
+    };
+
+    let recommendations = RecommendationEngine::new()
+        .generate("cluster-1".to_string(), &[web], &history)
+        .unwrap();
+
+    assert!(recommendations.is_empty());
+}
@@ -0,0 +1,4 @@
+pub mod model_registry;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,290 @@
+//! Versioned store of trained models.
+//!
+//! Each model is keyed by an id naming what it was trained for, e.g.
+//! `anomaly:seasonal:cpu_usage:shop/api-0`. Registering a model under an id
+//! bumps its version. With a directory, every version is written as
+//! `<escaped id>.v<version>.json` and the newest version of each id is
+//! reloaded on open; only the last `keep_versions` are kept. Models not
+//! registered again within the training window, such as those of deleted
+//! pods, are retired with their files. Files are
+//! written under a temporary name and renamed into place, and a file that
+//! cannot be read is skipped on open, so a crash mid-write leaves the
+//! previous version in use.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use phenome_domain::{AnomalyModelKind, MetricType, ModelRegistryConfig, ModelScope, ScalingModel};
+
+use crate::detection::isolation_forest::IsolationForest;
+use crate::detection::seasonal::SeasonalBaseline;
+
+/// Trained state of one model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrainedModel {
+    ZScore {
+        mean: f64,
+        stddev: f64,
+    },
+    IsolationForest {
+        window: usize,
+        mean: f64,
+        stddev: f64,
+        forest: IsolationForest,
+    },
+    Seasonal {
+        baseline: SeasonalBaseline,
+    },
+    /// Forecasting model an `auto` scaling predictor picked by backtest.
+    ScalingSelection {
+        model: ScalingModel,
+        mae: f64,
+    },
+}
+
+impl TrainedModel {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TrainedModel::ZScore { .. } => "z_score",
+            TrainedModel::IsolationForest { .. } => "isolation_forest",
+            TrainedModel::Seasonal { .. } => "seasonal",
+            TrainedModel::ScalingSelection { .. } => "scaling_selection",
+        }
+    }
+}
+
+/// A freshly fitted model that has not been given a version yet.
+#[derive(Debug, Clone)]
+pub struct FittedModel {
+    pub id: String,
+    /// Samples the model was fitted on.
+    pub samples: usize,
+    pub model: TrainedModel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecord {
+    pub id: String,
+    pub version: u32,
+    pub trained_at: i64,
+    pub samples: usize,
+    pub model: TrainedModel,
+}
+
+/// Id of the anomaly model of `kind` for one series, or for every series of
+/// `metric_type` when `resource_id` is `None`.
+pub fn anomaly_model_id(
+    kind: AnomalyModelKind,
+    metric_type: MetricType,
+    resource_id: Option<&str>,
+) -> String {
+    let class = format!("anomaly:{}:{}", kind.as_str(), metric_type.as_str());
+    match resource_id {
+        Some(resource_id) => format!("{class}:{resource_id}"),
+        None => class,
+    }
+}
+
+/// Id of the `auto` scaling model selection for one resource.
+pub fn scaling_model_id(metric_type: MetricType, resource_id: &str) -> String {
+    format!("scaling:auto:{}:{resource_id}", metric_type.as_str())
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    dir: Option<PathBuf>,
+    scope: ModelScope,
+    retrain_interval: Duration,
+    training_window: Duration,
+    keep_versions: usize,
+    models: HashMap<String, ModelRecord>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::in_memory(&ModelRegistryConfig::default())
+            .expect("default registry settings are valid")
+    }
+}
+
+impl ModelRegistry {
+    /// Registry that keeps trained models in memory only.
+    pub fn in_memory(config: &ModelRegistryConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            dir: None,
+            scope: config.scope,
+            retrain_interval: Duration::from_millis(config.retrain_interval_ms()? as u64),
+            training_window: Duration::from_millis(config.training_window_ms()? as u64),
+            keep_versions: config.keep_versions,
+            models: HashMap::new(),
+        })
+    }
+
+    /// Registry persisted under `config.path`, loaded with the newest
+    /// version of every model found there; in memory without a path.
+    pub fn from_config(config: &ModelRegistryConfig) -> Result<Self> {
+        let registry = Self::in_memory(config)?;
+        match &config.path {
+            Some(path) => registry.open(expand_home(path)),
+            None => Ok(registry),
+        }
+    }
+
+    /// Persist models under `dir`, creating it if needed, and load the
+    /// newest readable version of every model already there.
+    pub fn open(mut self, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create model directory {}", dir.display()))?;
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let record = match read_record(&path) {
+                Ok(record) => record,
+                Err(err) => {
+                    tracing::warn!("skipping model file: {err:#}");
+                    continue;
+                }
+            };
+            let newer = self
+                .models
+                .get(&record.id)
+                .is_none_or(|current| record.version > current.version);
+            if newer {
+                self.models.insert(record.id.clone(), record);
+            }
+        }
+        self.dir = Some(dir);
+        Ok(self)
+    }
+
+    pub fn scope(&self) -> ModelScope {
+        self.scope
+    }
+
+    pub fn retrain_interval(&self) -> Duration {
+        self.retrain_interval
+    }
+
+    pub fn training_window(&self) -> Duration {
+        self.training_window
+    }
+
+    /// Newest version of the model `id`.
+    pub fn get(&self, id: &str) -> Option<&ModelRecord> {
+        self.models.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &ModelRecord> {
+        self.models.values()
+    }
+
+    /// Store `fitted` as the next version of its id.
+    pub fn register(&mut self, fitted: FittedModel, trained_at: i64) -> Result<&ModelRecord> {
+        let version = self
+            .models
+            .get(&fitted.id)
+            .map_or(1, |record| record.version + 1);
+        let record = ModelRecord {
+            id: fitted.id,
+            version,
+            trained_at,
+            samples: fitted.samples,
+            model: fitted.model,
+        };
+        if let Some(dir) = &self.dir {
+            let path = dir.join(file_name(&record.id, version));
+            write_atomic(&path, &serde_json::to_vec(&record)?)
+                .with_context(|| format!("failed to write model {}", path.display()))?;
+            if let Some(expired) = version.checked_sub(self.keep_versions as u32) {
+                let _ = fs::remove_file(dir.join(file_name(&record.id, expired)));
+            }
+        }
+        let id = record.id.clone();
+        self.models.insert(id.clone(), record);
+        Ok(&self.models[&id])
+    }
+
+    /// Drop models last registered more than the training window before
+    /// `now`, and their files. Returns the number retired.
+    pub fn retire(&mut self, now: i64) -> usize {
+        let cutoff = now - self.training_window.as_millis() as i64;
+        let stale: Vec<String> = self
+            .models
+            .values()
+            .filter(|record| record.trained_at < cutoff)
+            .map(|record| record.id.clone())
+            .collect();
+        for id in &stale {
+            let Some(record) = self.models.remove(id) else {
+                continue;
+            };
+            if let Some(dir) = &self.dir {
+                let oldest = (record.version + 1)
+                    .saturating_sub(self.keep_versions as u32)
+                    .max(1);
+                for version in oldest..=record.version {
+                    let _ = fs::remove_file(dir.join(file_name(id, version)));
+                }
+            }
+        }
+        stale.len()
+    }
+}
+
+/// Resolve a leading `~/` against `$HOME`.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Write `bytes` beside `path` and rename them over it, so readers see the
+/// old file or the whole new one.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temp, path)
+}
+
+fn read_record(path: &Path) -> Result<ModelRecord> {
+    let json =
+        fs::read(path).with_context(|| format!("failed to read model {}", path.display()))?;
+    serde_json::from_slice(&json).with_context(|| format!("invalid model {}", path.display()))
+}
+
+/// Ids contain `/` and `:`, so everything but `[A-Za-z0-9._-]` is
+/// percent-escaped to keep file names flat and unambiguous.
+fn file_name(id: &str, version: u32) -> String {
+    let mut name = String::with_capacity(id.len());
+    for byte in id.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_' | b'-') {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    format!("{name}.v{version}.json")
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::time::Duration;
+
+use phenome_domain::{
+    AnomalyModelKind, DownsampleMethod, MetricType, ModelRegistryConfig, ModelScope, ScalingModel,
+    ScalingModelKind, TimeRange, TimeSeries, TimeSeriesData, TimeSeriesPoint,
+};
+
+use crate::detection::anomaly_detection::AnomalyDetector;
+use crate::registry::model_registry::{FittedModel, ModelRegistry, TrainedModel};
+use crate::scaling::scaling_prediction::ScalingPredictor;
+
+const HOUR_MS: i64 = 3_600_000;
+
+/// Load around one core with a slow wave and deterministic jitter.
+fn steady(len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|i| {
+            let jitter = ((i * 7919) % 13) as f64 / 13.0 - 0.5;
+            1.0 + 0.2 * (i as f64 / 8.0).sin() + 0.05 * jitter
+        })
+        .collect()
+}
+
+fn series(resource_id: &str, values: &[f64], step_ms: i64) -> TimeSeries {
+    TimeSeries {
+        cluster_id: "cluster-1".to_string(),
+        resource_id: resource_id.to_string(),
+        metric_type: MetricType::CpuUsage,
+        unit: "cores".to_string(),
+        points: values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| TimeSeriesPoint {
+                timestamp: i as i64 * step_ms,
+                value: *value,
+            })
+            .collect(),
+        downsample: DownsampleMethod::Raw,
+    }
+}
+
+fn data(series: Vec<TimeSeries>) -> TimeSeriesData {
+    TimeSeriesData {
+        cluster_id: "cluster-1".to_string(),
+        range: TimeRange {
+            start_ms: 0,
+            end_ms: 0,
+        },
+        series,
+    }
+}
+
+fn z_score(id: &str, mean: f64) -> FittedModel {
+    FittedModel {
+        id: id.to_string(),
+        samples: 10,
+        model: TrainedModel::ZScore { mean, stddev: 0.1 },
+    }
+}
+
+fn persisted(dir: &std::path::Path, keep_versions: usize) -> ModelRegistry {
+    ModelRegistry::from_config(&ModelRegistryConfig {
+        path: Some(dir.to_string_lossy().to_string()),
+        keep_versions,
+        ..ModelRegistryConfig::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn registers_versions_and_reloads_newest_from_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry = persisted(dir.path(), 3);
+    registry
+        .register(z_score("anomaly:z_score:cpu_usage:shop/api-0", 1.0), 10)
+        .unwrap();
+    let record = registry
+        .register(z_score("anomaly:z_score:cpu_usage:shop/api-0", 2.0), 20)
+        .unwrap();
+    assert_eq!(record.version, 2);
+
+    let reloaded = persisted(dir.path(), 3);
+    let record = reloaded
+        .get("anomaly:z_score:cpu_usage:shop/api-0")
+        .unwrap();
+    assert_eq!(reloaded.len(), 1);
+    assert_eq!((record.version, record.trained_at), (2, 20));
+    assert!(matches!(record.model, TrainedModel::ZScore { mean, .. } if mean == 2.0));
+}
+
+#[test]
+fn keeps_only_recent_versions_on_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry = persisted(dir.path(), 2);
+    for version in 0..3 {
+        registry
+            .register(z_score("anomaly:z_score:cpu_usage", 1.0), version)
+            .unwrap();
+    }
+
+    let mut files: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        vec![
+            "anomaly%3Az_score%3Acpu_usage.v2.json",
+            "anomaly%3Az_score%3Acpu_usage.v3.json",
+        ]
+    );
+}
+
+#[test]
+fn retires_models_not_retrained_within_the_training_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry = persisted(dir.path(), 2);
+    let window = registry.training_window().as_millis() as i64;
+    for version in 0..3 {
+        registry
+            .register(
+                z_score("anomaly:z_score:cpu_usage:shop/gone-0", 1.0),
+                version,
+            )
+            .unwrap();
+    }
+    let now = 2 + window + HOUR_MS;
+    registry
+        .register(z_score("anomaly:z_score:cpu_usage:shop/api-0", 1.0), now)
+        .unwrap();
+
+    assert_eq!(registry.retire(now), 1);
+    assert!(
+        registry
+            .get("anomaly:z_score:cpu_usage:shop/gone-0")
+            .is_none()
+    );
+    let files: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        files,
+        vec!["anomaly%3Az_score%3Acpu_usage%3Ashop%2Fapi-0.v1.json"]
+    );
+}
+
+#[test]
+fn open_skips_unreadable_model_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry = persisted(dir.path(), 3);
+    registry
+        .register(z_score("anomaly:z_score:cpu_usage", 1.0), 10)
+        .unwrap();
+    // A newer version cut short by a crash, and a leftover temporary file.
+    fs::write(
+        dir.path().join("anomaly%3Az_score%3Acpu_usage.v2.json"),
+        "{\"id\":",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("anomaly%3Az_score%3Acpu_usage.v3.json.tmp"),
+        "",
+    )
+    .unwrap();
+
+    let reloaded = persisted(dir.path(), 3);
+    let record = reloaded.get("anomaly:z_score:cpu_usage").unwrap();
+    assert_eq!((record.version, record.trained_at), (1, 10));
+}
+
+#[test]
+fn reloaded_forest_flags_outlier_with_its_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let detector = AnomalyDetector::default().with_model(AnomalyModelKind::IsolationForest);
+    let mut registry = persisted(dir.path(), 3);
+    for fitted in detector.train(
+        &data(vec![series("pod-a", &steady(300), 30_000)]),
+        ModelScope::Series,
+    ) {
+        registry.register(fitted, 0).unwrap();
+    }
+
+    let mut values = steady(40);
+    *values.last_mut().unwrap() = 4.0;
+    let anomalies = detector
+        .detect_with(
+            &data(vec![series("pod-a", &values, 30_000)]),
+            &persisted(dir.path(), 3),
+        )
+        .unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert!(anomalies[0].description.contains("isolation score"));
+    assert_eq!(
+        anomalies[0].model_id.as_deref(),
+        Some("anomaly:isolation_forest:cpu_usage:pod-a")
+    );
+    assert_eq!(anomalies[0].model_version, Some(1));
+}
+
+#[test]
+fn metric_model_covers_series_without_its_own() {
+    let detector = AnomalyDetector::default();
+    let mut registry = ModelRegistry::default();
+    let training = data(vec![
+        series("pod-a", &steady(100), 30_000),
+        series("pod-b", &steady(100), 30_000),
+    ]);
+    for fitted in detector.train(&training, ModelScope::Metric) {
+        registry.register(fitted, 0).unwrap();
+    }
+    assert_eq!(registry.len(), 1);
+
+    // Too flat to flag anything on its own history, but far off the pool.
+    let mut values = vec![1.0; 20];
+    *values.last_mut().unwrap() = 3.0;
+    let anomalies = detector
+        .detect_with(&data(vec![series("pod-c", &values, 30_000)]), &registry)
+        .unwrap();
+
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(
+        anomalies[0].model_id.as_deref(),
+        Some("anomaly:z_score:cpu_usage")
+    );
+    assert_eq!(anomalies[0].model_version, Some(1));
+}
+
+#[test]
+fn fitted_detection_records_model_without_version() {
+    let mut values = steady(20);
+    values.push(10.0);
+
+    let anomalies = AnomalyDetector::default()
+        .detect(&data(vec![series("pod-a", &values, 30_000)]))
+        .unwrap();
+
+    assert_eq!(anomalies[0].model_id.as_deref(), Some("z_score"));
+    assert_eq!(anomalies[0].model_version, None);
+}
+
+#[test]
+fn auto_predictor_uses_trained_selection() {
+    let values: Vec<f64> = (0..24 * 8)
+        .map(|hour| {
+            let phase = (hour % 24) as f64 / 24.0 * std::f64::consts::TAU;
+            2.0 + 0.01 * hour as f64 + phase.sin()
+        })
+        .collect();
+    let history = series("deployment-a", &values, HOUR_MS);
+    let predictor = ScalingPredictor::new()
+        .with_model(ScalingModelKind::Auto)
+        .with_season(Some(24 * HOUR_MS));
+    let mut registry = ModelRegistry::default();
+    registry
+        .register(predictor.train(&history).unwrap(), 0)
+        .unwrap();
+
+    let prediction = predictor
+        .predict_with(&history, Duration::from_secs(6 * 3600), 0, &registry)
+        .unwrap();
+
+    assert_eq!(prediction.model, ScalingModel::HoltWinters);
+    assert_eq!(
+        prediction.model_id.as_deref(),
+        Some("scaling:auto:cpu_usage:deployment-a")
+    );
+    assert_eq!(prediction.model_version, Some(1));
+}
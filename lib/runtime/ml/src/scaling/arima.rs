@@ -0,0 +1,141 @@
+//! ARIMA(p, 1, 0) with drift.
+//!
+//! The series is differenced once and an AR(p) model is fitted to the
+//! differences with Yule-Walker (Levinson-Durbin). Forecasts are integrated
+//! back, and interval widths follow the psi weights of the integrated model.
+
+use crate::scaling::forecast::Forecast;
+
+/// Autoregressive order fitted to the differenced series.
+const ORDER: usize = 2;
+
+/// Shortest history the differenced AR fit is attempted on.
+const MIN_HISTORY: usize = 4 * (ORDER + 1);
+
+#[derive(Debug, Clone)]
+pub struct Arima {
+    /// AR coefficients on the differences, lag 1 first.
+    phi: Vec<f64>,
+    /// Mean of the differences, i.e. the drift per step.
+    drift: f64,
+    /// Most recent differences, newest last.
+    recent: Vec<f64>,
+    last: f64,
+    sigma: f64,
+}
+
+impl Arima {
+    pub fn fit(history: &[f64]) -> Option<Self> {
+        if history.len() < MIN_HISTORY {
+            return None;
+        }
+        let diffs: Vec<f64> = history.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let drift = diffs.iter().sum::<f64>() / diffs.len() as f64;
+        let centered: Vec<f64> = diffs.iter().map(|d| d - drift).collect();
+
+        let autocov: Vec<f64> = (0..=ORDER)
+            .map(|lag| {
+                centered
+                    .iter()
+                    .zip(&centered[lag..])
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    / centered.len() as f64
+            })
+            .collect();
+        let phi = levinson_durbin(&autocov)?;
+
+        let residuals: Vec<f64> = (ORDER..centered.len())
+            .map(|t| {
+                let predicted: f64 = phi
+                    .iter()
+                    .enumerate()
+                    .map(|(lag, coefficient)| coefficient * centered[t - lag - 1])
+                    .sum();
+                centered[t] - predicted
+            })
+            .collect();
+        let sigma =
+            (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len().max(1) as f64).sqrt();
+
+        Some(Self {
+            phi,
+            drift,
+            recent: centered[centered.len() - ORDER..].to_vec(),
+            last: *history.last()?,
+            sigma,
+        })
+    }
+
+    pub fn forecast(&self, steps: usize) -> Forecast {
+        let mut recent = self.recent.clone();
+        let mut level = self.last;
+        let mut mean = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let next: f64 = self
+                .phi
+                .iter()
+                .enumerate()
+                .map(|(lag, coefficient)| coefficient * recent[recent.len() - lag - 1])
+                .sum();
+            recent.push(next);
+            level += next + self.drift;
+            mean.push(level);
+        }
+
+        // Psi weights of (1 - phi(B))(1 - B), whose coefficients are
+        // a_1 = phi_1 + 1, a_i = phi_i - phi_{i-1} and a_{p+1} = -phi_p.
+        let integrated: Vec<f64> = (0..=ORDER)
+            .map(|i| {
+                let current = self.phi.get(i).copied().unwrap_or(0.0);
+                let previous = if i == 0 { -1.0 } else { self.phi[i - 1] };
+                current - previous
+            })
+            .collect();
+        let mut psi: Vec<f64> = vec![1.0];
+        let mut stderr = Vec::with_capacity(steps);
+        let mut accumulated = 0.0_f64;
+        for step in 0..steps {
+            if step > 0 {
+                let weight = integrated
+                    .iter()
+                    .enumerate()
+                    .take(step)
+                    .map(|(i, a)| a * psi[step - i - 1])
+                    .sum();
+                psi.push(weight);
+            }
+            accumulated += psi[step] * psi[step];
+            stderr.push(self.sigma * accumulated.sqrt());
+        }
+        Forecast { mean, stderr }
+    }
+}
+
+/// Solve the Yule-Walker equations for AR coefficients from autocovariances
+/// at lags `0..=p`.
+fn levinson_durbin(autocov: &[f64]) -> Option<Vec<f64>> {
+    let order = autocov.len() - 1;
+    if autocov[0] <= f64::EPSILON {
+        return Some(vec![0.0; order]);
+    }
+    let mut phi = vec![0.0; order];
+    let mut error = autocov[0];
+    for k in 0..order {
+        let mut reflection = autocov[k + 1];
+        for j in 0..k {
+            reflection -= phi[j] * autocov[k - j];
+        }
+        reflection /= error;
+        let previous = phi.clone();
+        phi[k] = reflection;
+        for j in 0..k {
+            phi[j] = previous[j] - reflection * previous[k - j - 1];
+        }
+        error *= 1.0 - reflection * reflection;
+        if error <= 0.0 || !error.is_finite() {
+            return None;
+        }
+    }
+    Some(phi)
+}
@@ -0,0 +1,42 @@
+use phenome_domain::ScalingModel;
+
+use crate::scaling::forecast::forecast;
+
+/// Largest share of the history held out for scoring.
+const MAX_HOLDOUT_FRACTION: usize = 4;
+
+/// Out-of-sample error of one model on one series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestScore {
+    pub model: ScalingModel,
+    /// Mean absolute error over the held-out tail.
+    pub mae: f64,
+}
+
+/// Hold out the last `steps` samples (at most a quarter of the history), fit
+/// each model on the rest and score its forecast of the held-out tail.
+/// Models the history is too short for are left out. Best model first.
+pub fn backtest(history: &[f64], season: usize, steps: usize) -> Vec<BacktestScore> {
+    let holdout = steps.min(history.len() / MAX_HOLDOUT_FRACTION);
+    if holdout == 0 {
+        return Vec::new();
+    }
+    let (train, actual) = history.split_at(history.len() - holdout);
+
+    let mut scores: Vec<BacktestScore> = ScalingModel::ALL
+        .into_iter()
+        .filter_map(|model| {
+            let predicted = forecast(model, train, season, holdout)?;
+            let mae = predicted
+                .mean
+                .iter()
+                .zip(actual)
+                .map(|(predicted, actual)| (predicted - actual).abs())
+                .sum::<f64>()
+                / holdout as f64;
+            mae.is_finite().then_some(BacktestScore { model, mae })
+        })
+        .collect();
+    scores.sort_by(|a, b| a.mae.total_cmp(&b.mae));
+    scores
+}
@@ -0,0 +1,57 @@
+use phenome_domain::ScalingModel;
+
+use crate::scaling::{arima, holt_winters};
+
+/// Two-sided 95% normal quantile used for prediction intervals.
+pub const INTERVAL_Z: f64 = 1.96;
+
+/// Point forecasts and their standard errors, one per step ahead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    pub mean: Vec<f64>,
+    pub stderr: Vec<f64>,
+}
+
+impl Forecast {
+    /// Forecast `step` steps ahead (1-based) with its 95% interval.
+    pub fn at(&self, step: usize) -> Option<(f64, f64, f64)> {
+        let index = step.checked_sub(1)?;
+        let mean = *self.mean.get(index)?;
+        let spread = INTERVAL_Z * self.stderr.get(index)?;
+        Some((mean, mean - spread, mean + spread))
+    }
+}
+
+/// Forecast `steps` steps past the end of `history` with `model`. `season`
+/// is the period in samples, or 0 when the series has none. Returns `None`
+/// when the history is too short for the model.
+pub fn forecast(
+    model: ScalingModel,
+    history: &[f64],
+    season: usize,
+    steps: usize,
+) -> Option<Forecast> {
+    match model {
+        ScalingModel::MovingAverage => moving_average(history, steps),
+        ScalingModel::HoltWinters => {
+            holt_winters::HoltWinters::fit(history, season).map(|model| model.forecast(steps))
+        }
+        ScalingModel::Arima => arima::Arima::fit(history).map(|model| model.forecast(steps)),
+    }
+}
+
+/// Flat forecast at the history mean; the interval is the spread of the
+/// history itself.
+fn moving_average(history: &[f64], steps: usize) -> Option<Forecast> {
+    if history.is_empty() {
+        return None;
+    }
+    let n = history.len() as f64;
+    let mean = history.iter().sum::<f64>() / n;
+    let variance = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let stderr = (variance * (1.0 + 1.0 / n)).sqrt();
+    Some(Forecast {
+        mean: vec![mean; steps],
+        stderr: vec![stderr; steps],
+    })
+}
@@ -0,0 +1,131 @@
+//! Additive Holt-Winters exponential smoothing.
+//!
+//! Level, trend and (when the history spans two seasons) a seasonal profile
+//! are smoothed with parameters picked by grid search on one-step-ahead
+//! squared error. Without a usable season this is Holt's linear trend.
+
+use crate::scaling::forecast::Forecast;
+
+/// Smoothing parameters tried for each of alpha, beta and gamma.
+const GRID: [f64; 5] = [0.05, 0.2, 0.4, 0.6, 0.8];
+
+/// Shortest history a trend can be fitted to.
+const MIN_HISTORY: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct HoltWinters {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    level: f64,
+    trend: f64,
+    /// Seasonal offsets indexed by sample position modulo the season; empty
+    /// without a season.
+    seasonals: Vec<f64>,
+    /// Position of the next sample, used to pick its seasonal offset.
+    next: usize,
+    sigma: f64,
+}
+
+impl HoltWinters {
+    /// Fit to `history` with a season of `season` samples (0 for none).
+    pub fn fit(history: &[f64], season: usize) -> Option<Self> {
+        if history.len() < MIN_HISTORY {
+            return None;
+        }
+        let season = if season >= 2 && history.len() >= 2 * season {
+            season
+        } else {
+            0
+        };
+        let gammas: &[f64] = if season == 0 { &[0.0] } else { &GRID };
+
+        let mut best: Option<(f64, Self)> = None;
+        for &alpha in &GRID {
+            for &beta in &GRID {
+                for &gamma in gammas {
+                    let (sse, model) = Self::smooth(history, season, alpha, beta, gamma);
+                    if best.as_ref().is_none_or(|(best_sse, _)| sse < *best_sse) {
+                        best = Some((sse, model));
+                    }
+                }
+            }
+        }
+        best.map(|(_, model)| model)
+    }
+
+    fn smooth(history: &[f64], season: usize, alpha: f64, beta: f64, gamma: f64) -> (f64, Self) {
+        let (mut level, mut trend, mut seasonals, start) = if season == 0 {
+            (history[0], history[1] - history[0], Vec::new(), 1)
+        } else {
+            let first = history[..season].iter().sum::<f64>() / season as f64;
+            let second = history[season..2 * season].iter().sum::<f64>() / season as f64;
+            let trend = (second - first) / season as f64;
+            // Offsets are taken from the detrended first season, and the level
+            // starts on the trend line at its last sample, where smoothing picks up.
+            let centre = (season - 1) as f64 / 2.0;
+            let seasonals = history[..season]
+                .iter()
+                .enumerate()
+                .map(|(index, value)| value - first - trend * (index as f64 - centre))
+                .collect();
+            (first + trend * centre, trend, seasonals, season)
+        };
+
+        let mut sse = 0.0;
+        for (index, &value) in history.iter().enumerate().skip(start) {
+            let offset = if season == 0 {
+                0.0
+            } else {
+                seasonals[index % season]
+            };
+            let error = value - (level + trend + offset);
+            sse += error * error;
+
+            let previous = level;
+            level = alpha * (value - offset) + (1.0 - alpha) * (level + trend);
+            trend = beta * (level - previous) + (1.0 - beta) * trend;
+            if season != 0 {
+                seasonals[index % season] = gamma * (value - level) + (1.0 - gamma) * offset;
+            }
+        }
+
+        let fitted = (history.len() - start).max(1);
+        let model = Self {
+            alpha,
+            beta,
+            gamma,
+            level,
+            trend,
+            seasonals,
+            next: history.len(),
+            sigma: (sse / fitted as f64).sqrt(),
+        };
+        (sse, model)
+    }
+
+    pub fn forecast(&self, steps: usize) -> Forecast {
+        let season = self.seasonals.len();
+        let mut mean = Vec::with_capacity(steps);
+        let mut stderr = Vec::with_capacity(steps);
+        // Var(h) = sigma^2 * (1 + sum_{j<h} (alpha (1 + j beta) + gamma [j mod m = 0])^2)
+        let mut accumulated = 1.0_f64;
+        for step in 1..=steps {
+            let offset = if season == 0 {
+                0.0
+            } else {
+                self.seasonals[(self.next + step - 1) % season]
+            };
+            mean.push(self.level + step as f64 * self.trend + offset);
+            stderr.push(self.sigma * accumulated.sqrt());
+
+            let seasonal_term = if season != 0 && step % season == 0 {
+                self.gamma
+            } else {
+                0.0
+            };
+            accumulated += (self.alpha * (1.0 + step as f64 * self.beta) + seasonal_term).powi(2);
+        }
+        Forecast { mean, stderr }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod arima;
+pub mod backtest;
+pub mod forecast;
+pub mod holt_winters;
 pub mod scaling_prediction;
 
 #[cfg(test)]
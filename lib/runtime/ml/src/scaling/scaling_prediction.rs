@@ -1,28 +1,169 @@
 use anyhow::Result;
 use std::time::Duration;
 
-use phenome_domain::ScalingPrediction;
+use phenome_domain::{
+    MlConfig, ScalingModel, ScalingModelKind, ScalingPrediction, SeasonalConfig, TimeSeries,
+    TimeSeriesPoint,
+};
 
-#[derive(Debug, Clone, Default)]
-pub struct ScalingPredictor;
+use crate::registry::model_registry::{FittedModel, ModelRegistry, TrainedModel, scaling_model_id};
+use crate::scaling::backtest::backtest;
+use crate::scaling::forecast::forecast;
+
+#[derive(Debug, Clone)]
+pub struct ScalingPredictor {
+    model: ScalingModelKind,
+    /// Seasonal period handed to Holt-Winters; `None` disables seasonality.
+    season_ms: Option<i64>,
+}
+
+impl Default for ScalingPredictor {
+    fn default() -> Self {
+        Self {
+            model: ScalingModelKind::MovingAverage,
+            season_ms: SeasonalConfig::default()
+                .period_ms()
+                .ok()
+                .and_then(|periods| periods.into_iter().min()),
+        }
+    }
+}
 
 impl ScalingPredictor {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
+    /// Model from `ml.models.scaling_prediction`; the shortest of
+    /// `ml.seasonal.periods` is the Holt-Winters season.
+    pub fn from_config(config: &MlConfig) -> Result<Self> {
+        Ok(Self {
+            model: config.models.scaling_prediction,
+            season_ms: config.seasonal.period_ms()?.into_iter().min(),
+        })
+    }
+
+    pub fn with_model(mut self, model: ScalingModelKind) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_season(mut self, season_ms: Option<i64>) -> Self {
+        self.season_ms = season_ms;
+        self
+    }
+
+    pub fn model(&self) -> ScalingModelKind {
+        self.model
+    }
+
+    /// Forecast the value `horizon` past the last point of time-ordered
+    /// `history`. Models the history is too short for fall back to the
+    /// moving average.
     pub fn predict(
         &self,
         resource_id: String,
         horizon: Duration,
-        history: &[f64],
+        history: &[TimeSeriesPoint],
         unit: &str,
         generated_at: i64,
     ) -> Result<ScalingPrediction> {
-        let predicted_value = if history.is_empty() {
-            0.0
+        self.predict_series(resource_id, horizon, history, unit, generated_at, None)
+    }
+
+    /// Forecast `series` as `predict` does, except that an `auto` predictor
+    /// takes the model selection trained for the series in `registry`
+    /// instead of backtesting on every call.
+    pub fn predict_with(
+        &self,
+        series: &TimeSeries,
+        horizon: Duration,
+        generated_at: i64,
+        registry: &ModelRegistry,
+    ) -> Result<ScalingPrediction> {
+        let selection = match self.model {
+            ScalingModelKind::Auto => {
+                registry.get(&scaling_model_id(series.metric_type, &series.resource_id))
+            }
+            _ => None,
+        }
+        .and_then(|record| match record.model {
+            TrainedModel::ScalingSelection { model, .. } => {
+                Some((model, record.id.clone(), record.version))
+            }
+            _ => None,
+        });
+        self.predict_series(
+            series.resource_id.clone(),
+            horizon,
+            &series.points,
+            &series.unit,
+            generated_at,
+            selection,
+        )
+    }
+
+    /// Backtest an `auto` predictor's models on `history` one season ahead
+    /// (a quarter of the history without a season) and keep the best.
+    /// Predictors with a fixed model have nothing to train.
+    pub fn train(&self, series: &TimeSeries) -> Option<FittedModel> {
+        if self.model != ScalingModelKind::Auto {
+            return None;
+        }
+        let (values, step_ms) = finite_values(&series.points);
+        let season = self.season_steps(step_ms);
+        let steps = if season > 0 { season } else { values.len() / 4 };
+        let best = *backtest(&values, season, steps).first()?;
+        Some(FittedModel {
+            id: scaling_model_id(series.metric_type, &series.resource_id),
+            samples: values.len(),
+            model: TrainedModel::ScalingSelection {
+                model: best.model,
+                mae: best.mae,
+            },
+        })
+    }
+
+    fn predict_series(
+        &self,
+        resource_id: String,
+        horizon: Duration,
+        history: &[TimeSeriesPoint],
+        unit: &str,
+        generated_at: i64,
+        selection: Option<(ScalingModel, String, u32)>,
+    ) -> Result<ScalingPrediction> {
+        let (values, step_ms) = finite_values(history);
+        let (steps, season) = if step_ms > 0 {
+            let horizon_ms = horizon.as_millis() as i64;
+            let steps = ((horizon_ms + step_ms - 1) / step_ms).max(1) as usize;
+            (steps, self.season_steps(step_ms))
         } else {
-            history.iter().sum::<f64>() / history.len() as f64
+            (1, 0)
+        };
+
+        let model = match &selection {
+            Some((model, _, _)) => *model,
+            None => self
+                .model
+                .model()
+                .or_else(|| backtest(&values, season, steps).first().map(|s| s.model))
+                .unwrap_or_default(),
+        };
+        let (model, forecast, selection) = match forecast(model, &values, season, steps) {
+            Some(forecast) => (model, Some(forecast), selection),
+            None => (
+                ScalingModel::MovingAverage,
+                forecast(ScalingModel::MovingAverage, &values, season, steps),
+                None,
+            ),
+        };
+        let (predicted_value, lower_bound, upper_bound) = forecast
+            .and_then(|forecast| forecast.at(steps))
+            .unwrap_or_default();
+        let (model_id, model_version) = match selection {
+            Some((_, id, version)) => (id, Some(version)),
+            None => (model.as_str().to_string(), None),
         };
 
         Ok(ScalingPrediction {
@@ -31,6 +172,32 @@ impl ScalingPredictor {
             horizon,
             predicted_value,
             unit: unit.to_string(),
+            lower_bound,
+            upper_bound,
+            model,
+            model_id: Some(model_id),
+            model_version,
         })
     }
+
+    /// Season length in samples of `step_ms`, 0 without a season.
+    fn season_steps(&self, step_ms: i64) -> usize {
+        if step_ms <= 0 {
+            return 0;
+        }
+        self.season_ms
+            .map_or(0, |season| (season / step_ms) as usize)
+    }
+}
+
+/// Finite values of `history` and their mean sampling step.
+fn finite_values(history: &[TimeSeriesPoint]) -> (Vec<f64>, i64) {
+    let points: Vec<&TimeSeriesPoint> = history.iter().filter(|p| p.value.is_finite()).collect();
+    let step_ms = match (points.first(), points.last()) {
+        (Some(first), Some(last)) if points.len() > 1 => {
+            (last.timestamp - first.timestamp) / (points.len() as i64 - 1)
+        }
+        _ => 0,
+    };
+    (points.iter().map(|p| p.value).collect(), step_ms)
 }
@@ -1,7 +1,37 @@
 use std::time::Duration;
 
+use phenome_domain::{ScalingModel, ScalingModelKind, TimeSeriesPoint};
+
+use crate::scaling::arima::Arima;
+use crate::scaling::backtest::backtest;
+use crate::scaling::holt_winters::HoltWinters;
 use crate::scaling::scaling_prediction::ScalingPredictor;
 
+/// Hourly samples: a slow upward trend with a daily cycle and a little
+/// deterministic noise.
+const HOUR_MS: i64 = 3_600_000;
+
+fn daily_load(hours: usize) -> Vec<f64> {
+    (0..hours)
+        .map(|hour| {
+            let phase = (hour % 24) as f64 / 24.0 * std::f64::consts::TAU;
+            let noise = ((hour * 7919) % 13) as f64 / 13.0 - 0.5;
+            2.0 + 0.01 * hour as f64 + phase.sin() + 0.05 * noise
+        })
+        .collect()
+}
+
+fn points(values: &[f64], step_ms: i64) -> Vec<TimeSeriesPoint> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| TimeSeriesPoint {
+            timestamp: i as i64 * step_ms,
+            value: *value,
+        })
+        .collect()
+}
+
 #[test]
 fn predicts_average_for_history() {
     let predictor = ScalingPredictor::new();
@@ -9,11 +39,89 @@ fn predicts_average_for_history() {
         .predict(
             "deployment-a".to_string(),
             Duration::from_secs(3600),
-            &[1.0, 2.0, 3.0],
+            &points(&[1.0, 2.0, 3.0], 60_000),
+            "cores",
+            0,
+        )
+        .unwrap();
+
+    assert_eq!(prediction.predicted_value, 2.0);
+    assert_eq!(prediction.model, ScalingModel::MovingAverage);
+    assert!(prediction.lower_bound < 2.0 && prediction.upper_bound > 2.0);
+}
+
+#[test]
+fn holt_winters_follows_trend_and_season() {
+    let values = daily_load(24 * 9);
+    let model = HoltWinters::fit(&values[..24 * 8], 24).unwrap();
+    let forecast = model.forecast(24);
+
+    for (step, actual) in values[24 * 8..].iter().enumerate() {
+        let (mean, lower, upper) = forecast.at(step + 1).unwrap();
+        assert!(
+            (mean - actual).abs() < 0.2,
+            "step {step}: {mean} vs {actual}"
+        );
+        assert!(lower <= *actual && *actual <= upper);
+    }
+}
+
+#[test]
+fn arima_extrapolates_drift_with_widening_interval() {
+    let values: Vec<f64> = (0..60)
+        .map(|i| 10.0 + 0.5 * i as f64 + if i % 2 == 0 { 0.1 } else { -0.1 })
+        .collect();
+    let forecast = Arima::fit(&values).unwrap().forecast(10);
+
+    let (mean, _, _) = forecast.at(10).unwrap();
+    assert!((mean - (10.0 + 0.5 * 69.0)).abs() < 0.5, "forecast {mean}");
+    assert!(forecast.stderr[9] >= forecast.stderr[0]);
+}
+
+#[test]
+fn backtest_prefers_seasonal_model_on_seasonal_series() {
+    let scores = backtest(&daily_load(24 * 8), 24, 24);
+
+    assert_eq!(scores.len(), ScalingModel::ALL.len());
+    assert_eq!(scores[0].model, ScalingModel::HoltWinters);
+    assert!(scores.windows(2).all(|pair| pair[0].mae <= pair[1].mae));
+}
+
+#[test]
+fn auto_selects_model_by_backtest() {
+    let values = daily_load(24 * 9);
+    let predictor = ScalingPredictor::new()
+        .with_model(ScalingModelKind::Auto)
+        .with_season(Some(24 * HOUR_MS));
+
+    let prediction = predictor
+        .predict(
+            "deployment-a".to_string(),
+            Duration::from_secs(6 * 3600),
+            &points(&values[..24 * 8], HOUR_MS),
+            "cores",
+            0,
+        )
+        .unwrap();
+
+    let actual = values[24 * 8 + 5];
+    assert_eq!(prediction.model, ScalingModel::HoltWinters);
+    assert!(prediction.lower_bound <= actual && actual <= prediction.upper_bound);
+}
+
+#[test]
+fn falls_back_to_moving_average_on_short_history() {
+    let predictor = ScalingPredictor::new().with_model(ScalingModelKind::Arima);
+    let prediction = predictor
+        .predict(
+            "deployment-a".to_string(),
+            Duration::from_secs(3600),
+            &points(&[1.0, 3.0], 60_000),
             "cores",
             0,
         )
         .unwrap();
 
+    assert_eq!(prediction.model, ScalingModel::MovingAverage);
     assert_eq!(prediction.predicted_value, 2.0);
 }
@@ -0,0 +1,4 @@
+pub mod step_duration;
+
+#[cfg(test)]
+mod tests;
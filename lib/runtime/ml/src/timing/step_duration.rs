@@ -0,0 +1,251 @@
+//! Bootstrap step durations learned from earlier runs.
+//!
+//! Each step's successful durations are fitted with a log-normal: step
+//! times are positive and right-skewed, a slow image pull or webhook wait
+//! stretching a few runs well past the rest. The ETA walks the dependency
+//! graph and reports the chain of unfinished steps expected to end last.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use phenome_domain::{BootstrapEta, StepEstimate, StepProgress, StepTiming};
+
+/// Most recent runs learned from; older runs describe a different cluster.
+const MAX_RUNS: usize = 20;
+
+/// Runs a step needs before its estimate counts toward ETA confidence.
+const MIN_SAMPLES: usize = 3;
+
+/// z of the 90th percentile of a standard normal.
+const P90_Z: f64 = 1.281_551_565_5;
+
+/// Longest duration estimated for a step; a widely scattered history
+/// would otherwise extrapolate to centuries.
+const MAX_ESTIMATE: Duration = Duration::from_secs(24 * 3600);
+
+/// Log-normal fit of one step's durations, in log milliseconds.
+#[derive(Debug, Clone, Copy)]
+struct Fit {
+    mu: f64,
+    sigma: f64,
+    samples: usize,
+}
+
+impl Fit {
+    fn estimate(&self) -> StepEstimate {
+        // exp(mu) is the median; the mean sits above it by the skew, and
+        // past the p90 once the spread is wide enough that a few outliers
+        // would set the ETA.
+        let p90 = millis((self.mu + P90_Z * self.sigma).exp());
+        StepEstimate {
+            expected: millis((self.mu + self.sigma.powi(2) / 2.0).exp()).min(p90),
+            p90,
+            samples: self.samples,
+        }
+    }
+}
+
+/// How long an unfinished step is expected to take from now.
+#[derive(Debug, Clone, Copy, Default)]
+struct Remaining {
+    expected: Duration,
+    p90: Duration,
+    /// Enough history, and the step is not already past its p90.
+    known: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StepDurationPredictor {
+    steps: HashMap<String, Fit>,
+}
+
+impl StepDurationPredictor {
+    /// Learn from `runs`, oldest first. Failed steps are left out, since
+    /// their duration is the time to failure.
+    pub fn learn(runs: &[Vec<StepTiming>]) -> Self {
+        let mut durations: HashMap<&str, Vec<f64>> = HashMap::new();
+        for run in &runs[runs.len().saturating_sub(MAX_RUNS)..] {
+            for step in run.iter().filter(|step| step.success) {
+                let ms = step.duration.as_secs_f64() * 1_000.0;
+                durations
+                    .entry(&step.step_id)
+                    .or_default()
+                    .push(ms.max(1.0).ln());
+            }
+        }
+        let steps = durations
+            .into_iter()
+            .map(|(step_id, logs)| {
+                let samples = logs.len();
+                let mu = logs.iter().sum::<f64>() / samples as f64;
+                let variance =
+                    logs.iter().map(|log| (log - mu).powi(2)).sum::<f64>() / samples as f64;
+                let fit = Fit {
+                    mu,
+                    sigma: variance.sqrt(),
+                    samples,
+                };
+                (step_id.to_string(), fit)
+            })
+            .collect();
+        Self { steps }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn estimate(&self, step_id: &str) -> Option<StepEstimate> {
+        self.steps.get(step_id).map(Fit::estimate)
+    }
+
+    /// Time left in a run whose steps and their dependencies are `graph`,
+    /// given each step's `progress`. Steps missing from `progress` count
+    /// as done; steps without history as instant. `None` once nothing is
+    /// left or nothing has been learned.
+    pub fn eta(
+        &self,
+        graph: &[(&str, &[String])],
+        progress: &HashMap<String, StepProgress>,
+    ) -> Option<BootstrapEta> {
+        if self.is_empty() {
+            return None;
+        }
+        let dependencies: HashMap<&str, &[String]> = graph.iter().copied().collect();
+        let mut walk = Walk {
+            predictor: self,
+            dependencies: &dependencies,
+            progress,
+            finish: HashMap::new(),
+            visiting: Vec::new(),
+        };
+        let mut last: Option<(&str, Finish)> = None;
+        for (step_id, _) in graph {
+            let finish = walk.finish(step_id);
+            if last.is_none_or(|(_, latest)| finish.expected > latest.expected) {
+                last = Some((step_id, finish));
+            }
+        }
+        let (mut step_id, finish) = last?;
+        if finish.expected.is_zero() && finish.p90.is_zero() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        for _ in 0..graph.len() {
+            let remaining = walk.remaining(step_id);
+            if !remaining.expected.is_zero() || !remaining.known {
+                path.push((step_id.to_string(), remaining));
+            }
+            // Bounded, since a cut cycle can link back to a step already
+            // on the path.
+            match walk.finish.get(step_id).and_then(|finish| finish.via) {
+                Some(previous) => step_id = previous,
+                None => break,
+            }
+        }
+        path.reverse();
+
+        let known = path.iter().filter(|(_, remaining)| remaining.known).count();
+        let coverage = known as f64 / path.len().max(1) as f64;
+        let spread = if finish.p90.is_zero() {
+            1.0
+        } else {
+            finish.expected.as_secs_f64() / finish.p90.as_secs_f64()
+        };
+        Some(BootstrapEta {
+            remaining: finish.expected,
+            p90_remaining: finish.p90.max(finish.expected),
+            confidence: (coverage * spread).clamp(0.0, 1.0),
+            critical_path: path.into_iter().map(|(step_id, _)| step_id).collect(),
+        })
+    }
+
+    fn remaining(&self, step_id: &str, progress: Option<&StepProgress>) -> Remaining {
+        let elapsed = match progress {
+            None | Some(StepProgress::Done) => {
+                return Remaining {
+                    known: true,
+                    ..Remaining::default()
+                };
+            }
+            Some(StepProgress::Pending) => Duration::ZERO,
+            Some(StepProgress::Running { elapsed }) => *elapsed,
+        };
+        let Some(estimate) = self.estimate(step_id) else {
+            return Remaining::default();
+        };
+        Remaining {
+            expected: estimate.expected.saturating_sub(elapsed),
+            p90: estimate.p90.saturating_sub(elapsed),
+            known: estimate.samples >= MIN_SAMPLES && elapsed < estimate.p90,
+        }
+    }
+}
+
+/// When a step is expected to finish, counted from now, and the
+/// dependency that finishes last before it.
+#[derive(Debug, Clone, Copy)]
+struct Finish<'a> {
+    expected: Duration,
+    p90: Duration,
+    via: Option<&'a str>,
+}
+
+/// Memoized walk of the dependency graph; a cycle is cut where it closes.
+struct Walk<'a> {
+    predictor: &'a StepDurationPredictor,
+    dependencies: &'a HashMap<&'a str, &'a [String]>,
+    progress: &'a HashMap<String, StepProgress>,
+    finish: HashMap<&'a str, Finish<'a>>,
+    visiting: Vec<&'a str>,
+}
+
+impl<'a> Walk<'a> {
+    fn remaining(&self, step_id: &str) -> Remaining {
+        self.predictor
+            .remaining(step_id, self.progress.get(step_id))
+    }
+
+    fn finish(&mut self, step_id: &'a str) -> Finish<'a> {
+        if let Some(finish) = self.finish.get(step_id) {
+            return *finish;
+        }
+        let mut start = Finish {
+            expected: Duration::ZERO,
+            p90: Duration::ZERO,
+            via: None,
+        };
+        if !self.visiting.contains(&step_id) {
+            self.visiting.push(step_id);
+            let dependencies = self.dependencies.get(step_id).copied().unwrap_or(&[]);
+            for dependency in dependencies {
+                let Some((&dependency, _)) = self.dependencies.get_key_value(dependency.as_str())
+                else {
+                    continue;
+                };
+                let finish = self.finish(dependency);
+                if finish.expected > start.expected || start.via.is_none() {
+                    start.expected = start.expected.max(finish.expected);
+                    start.via = Some(dependency);
+                }
+                start.p90 = start.p90.max(finish.p90);
+            }
+            self.visiting.pop();
+        }
+        let remaining = self.remaining(step_id);
+        let finish = Finish {
+            expected: start.expected + remaining.expected,
+            p90: start.p90 + remaining.p90,
+            via: start.via,
+        };
+        self.finish.insert(step_id, finish);
+        finish
+    }
+}
+
+/// `ms` as a duration, capped at `MAX_ESTIMATE`.
+fn millis(ms: f64) -> Duration {
+    Duration::try_from_secs_f64(ms.max(0.0) / 1_000.0)
+        .map_or(MAX_ESTIMATE, |duration| duration.min(MAX_ESTIMATE))
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use phenome_domain::{StepProgress, StepTiming};
+
+use crate::timing::step_duration::StepDurationPredictor;
+
+fn step(step_id: &str, secs: u64, success: bool) -> StepTiming {
+    StepTiming {
+        step_id: step_id.to_string(),
+        duration: Duration::from_secs(secs),
+        success,
+    }
+}
+
+/// Five runs: `cilium` 10s then `cert-manager` 30s and `dns` 5s after it,
+/// with one failed `cert-manager` run that hit a 600s timeout.
+fn history() -> Vec<Vec<StepTiming>> {
+    let mut runs: Vec<Vec<StepTiming>> = (0..5)
+        .map(|_| {
+            vec![
+                step("cilium", 10, true),
+                step("cert-manager", 30, true),
+                step("dns", 5, true),
+            ]
+        })
+        .collect();
+    runs.push(vec![
+        step("cilium", 10, true),
+        step("cert-manager", 600, false),
+    ]);
+    runs
+}
+
+fn graph() -> Vec<(String, Vec<String>)> {
+    vec![
+        ("cilium".to_string(), Vec::new()),
+        ("cert-manager".to_string(), vec!["cilium".to_string()]),
+        ("dns".to_string(), vec!["cilium".to_string()]),
+    ]
+}
+
+fn eta_of(
+    predictor: &StepDurationPredictor,
+    progress: &[(&str, StepProgress)],
+) -> Option<phenome_domain::BootstrapEta> {
+    let graph = graph();
+    let view: Vec<(&str, &[String])> = graph
+        .iter()
+        .map(|(id, deps)| (id.as_str(), deps.as_slice()))
+        .collect();
+    let progress: HashMap<String, StepProgress> = progress
+        .iter()
+        .map(|(id, progress)| (id.to_string(), *progress))
+        .collect();
+    predictor.eta(&view, &progress)
+}
+
+#[test]
+fn learns_step_durations_from_successful_runs() {
+    let predictor = StepDurationPredictor::learn(&history());
+
+    let estimate = predictor.estimate("cert-manager").unwrap();
+    assert_eq!(estimate.samples, 5);
+    assert!((estimate.expected.as_secs_f64() - 30.0).abs() < 0.01);
+    assert!(estimate.p90 >= estimate.expected);
+    assert_eq!(predictor.estimate("cilium").unwrap().samples, 6);
+    assert!(predictor.estimate("unknown").is_none());
+}
+
+#[test]
+fn expected_duration_is_the_mean_of_skewed_runs() {
+    let runs: Vec<Vec<StepTiming>> = [10, 10, 10, 40]
+        .into_iter()
+        .map(|secs| vec![step("image-pull", secs, true)])
+        .collect();
+    let predictor = StepDurationPredictor::learn(&runs);
+
+    // ln-space mean and spread of 10, 10, 10, 40 seconds.
+    let mu = (3.0 * 10_000f64.ln() + 40_000f64.ln()) / 4.0;
+    let sigma = 4f64.ln() * 3f64.sqrt() / 4.0;
+    let mean = (mu + sigma * sigma / 2.0).exp() / 1_000.0;
+    let estimate = predictor.estimate("image-pull").unwrap();
+    assert!((estimate.expected.as_secs_f64() - mean).abs() < 0.01);
+    assert!(estimate.expected.as_secs_f64() > mu.exp() / 1_000.0);
+}
+
+#[test]
+fn widely_scattered_durations_stay_within_bounds() {
+    // Instant runs beside ones that never seemed to end.
+    let runs: Vec<Vec<StepTiming>> = [0, 0, u64::MAX / 2, u64::MAX / 2]
+        .into_iter()
+        .map(|secs| vec![step("cilium", secs, true)])
+        .collect();
+    let predictor = StepDurationPredictor::learn(&runs);
+
+    let estimate = predictor.estimate("cilium").unwrap();
+    assert_eq!(estimate.p90, Duration::from_secs(24 * 3600));
+    assert!(estimate.expected <= estimate.p90);
+    let eta = eta_of(&predictor, &[("cilium", StepProgress::Pending)]).unwrap();
+    assert_eq!(eta.remaining, estimate.expected);
+}
+
+#[test]
+fn eta_follows_the_longest_unfinished_chain() {
+    let predictor = StepDurationPredictor::learn(&history());
+
+    let eta = eta_of(
+        &predictor,
+        &[
+            (
+                "cilium",
+                StepProgress::Running {
+                    elapsed: Duration::from_secs(4),
+                },
+            ),
+            ("cert-manager", StepProgress::Pending),
+            ("dns", StepProgress::Pending),
+        ],
+    )
+    .unwrap();
+
+    assert!((eta.remaining.as_secs_f64() - 36.0).abs() < 0.01);
+    assert_eq!(eta.critical_path, vec!["cilium", "cert-manager"]);
+    assert!(eta.confidence > 0.9);
+}
+
+#[test]
+fn eta_is_less_confident_about_unseen_steps_and_ends_with_the_run() {
+    let mut runs = history();
+    runs.truncate(2);
+    let predictor = StepDurationPredictor::learn(&runs);
+
+    let early = eta_of(
+        &predictor,
+        &[
+            ("cilium", StepProgress::Done),
+            ("cert-manager", StepProgress::Pending),
+        ],
+    )
+    .unwrap();
+    assert!(early.confidence < 0.5);
+
+    let done = eta_of(
+        &predictor,
+        &[
+            ("cilium", StepProgress::Done),
+            ("cert-manager", StepProgress::Done),
+            ("dns", StepProgress::Done),
+        ],
+    );
+    assert!(done.is_none());
+}
@@ -4,4 +4,4 @@ mod assembly;
 mod problems;
 
 pub use assembly::{AssemblyGroup, AssemblyStepInfo, assembly_groups};
-pub use problems::problem_lines;
+pub use problems::{data_quality_problem_lines, problem_lines};
@@ -1,4 +1,5 @@
-//! Problem formatting: derive human-readable issues from health + assembly state.
+//! Problem formatting: derive human-readable issues from health, assembly and
+//! data-quality state.
 //!
 //! ## Responsibility
 //! - Translate health/assembly signals into short, user-facing problem lines.
@@ -20,8 +21,11 @@
 //!
 //! ## Extension points
 //! - Add new health status variants with matching labels.
+//! - Add new data-quality kinds via `DataQualityIssueKind::as_str`.
 
-use phenome_domain::{AssemblyStepStatus, ComponentHealthStatus, HealthSnapshot, Snapshot};
+use phenome_domain::{
+    AssemblyStepStatus, ComponentHealthStatus, DataQualityIssue, HealthSnapshot, Snapshot,
+};
 
 /// Build user-facing problem lines from a snapshot and optional health data.
 ///
@@ -60,6 +64,29 @@ pub fn problem_lines(snapshot: &Snapshot, health: Option<&HealthSnapshot>) -> Ve
     problems
 }
 
+/// Build problem lines for collection gaps, stale series and bad samples.
+///
+/// ## Inputs
+/// - `issues`: data-quality issues reported by the analytics service.
+///
+/// ## Output
+/// - One line per issue, prefixed with the affected cluster and series.
+pub fn data_quality_problem_lines(issues: &[DataQualityIssue]) -> Vec<String> {
+    issues
+        .iter()
+        .map(|issue| {
+            let target = match (&issue.resource_id, issue.metric_type) {
+                (Some(resource), Some(metric)) => {
+                    format!("{}/{resource} {metric:?}", issue.cluster_id)
+                }
+                (Some(resource), None) => format!("{}/{resource}", issue.cluster_id),
+                _ => issue.cluster_id.clone(),
+            };
+            format!("data {}: {target}: {}", issue.kind.as_str(), issue.message)
+        })
+        .collect()
+}
+
 fn health_problem_lines(
     health: &std::collections::HashMap<String, ComponentHealthStatus>,
 ) -> Vec<String> {
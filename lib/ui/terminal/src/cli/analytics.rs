@@ -0,0 +1,661 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{Local, Utc};
+use clap::{Args, Subcommand};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tonic::transport::Channel;
+
+use phenome_adapter_analytics::downsample::rollup;
+use phenome_adapter_analytics::grpc::analytics::{
+    self as proto, GetAnomaliesRequest, GetAnomalyFeedbackRequest, GetIncidentsRequest,
+    GetTimeSeriesRequest, QueryMetricsRequest, SubmitAnomalyFeedbackRequest,
+    analytics_service_client::AnalyticsServiceClient,
+};
+use phenome_domain::{
+    Anomaly, AnomalyFeedbackKind, AnomalyModelKind, DashboardPanel, DownsampleMethod,
+    EvaluationReport, Incident, IncidentStatus, MetricSample, MetricType, PhenomeConfig,
+    ScalingModelKind, TimeRange, TimeSeries, TimeSeriesPoint, parse_duration,
+};
+use phenome_ml::{
+    AnomalyDetector, Evaluator, ModelFactory, ScalingPredictor, label_feedback, load_dataset,
+    write_dataset,
+};
+
+use crate::{
+    OutputMode, format_anomalies, format_dashboard, format_evaluation, format_incidents,
+    format_metrics, format_time_series,
+};
+
+/// Buckets a dashboard panel without a configured step is resolved to.
+const DASHBOARD_POINTS: u32 = 120;
+
+/// Samples fetched per `QueryMetrics` page while filling a dashboard.
+const DASHBOARD_PAGE_SIZE: u32 = 20_000;
+
+#[derive(Debug, Subcommand)]
+pub enum AnalyticsCommand {
+    /// Query stored metric samples
+    Metrics(MetricsArgs),
+
+    /// List detected anomalies
+    Anomalies(AnomaliesArgs),
+
+    /// List incidents grouping related anomalies
+    Incidents(IncidentsArgs),
+
+    /// Fetch one resource's series, downsampled to a point budget
+    Series(SeriesArgs),
+
+    /// Run every panel of a dashboard defined in the config file
+    Dashboard(DashboardArgs),
+
+    /// Replay a labeled dataset through detectors and predictors offline
+    Evaluate(EvaluateArgs),
+
+    /// Mark an anomaly confirmed, false_positive or expected so detection
+    /// on its series adapts
+    Feedback(FeedbackArgs),
+
+    /// Write anomalies with feedback, and the series around them, as a
+    /// labeled dataset for `evaluate`
+    ExportFeedback(ExportFeedbackArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+    /// Time range: `last 1h`, `today`, `yesterday`, `now-30m`,
+    /// an ISO-8601 instant, or `<from>..<to>`. Calendar days use local time.
+    #[arg(long, default_value = "last 1h")]
+    pub range: String,
+
+    /// Output format (plain, json, ndjson)
+    #[arg(long, default_value = "plain")]
+    pub output: OutputMode,
+
+    /// Analytics service endpoint
+    #[arg(
+        long,
+        env = "PHENOME_ANALYTICS_URL",
+        default_value = "http://localhost:50051"
+    )]
+    pub endpoint: String,
+}
+
+#[derive(Debug, Args)]
+pub struct MetricsArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Restrict to a single cluster
+    #[arg(long)]
+    pub cluster: Option<String>,
+
+    /// Resource ids to include (repeatable)
+    #[arg(long = "resource")]
+    pub resources: Vec<String>,
+
+    /// Metric types to include (repeatable), e.g. cpu_usage
+    #[arg(long = "metric", value_parser = parse_metric_type)]
+    pub metrics: Vec<MetricType>,
+}
+
+#[derive(Debug, Args)]
+pub struct AnomaliesArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Restrict to a single cluster
+    #[arg(long)]
+    pub cluster: Option<String>,
+
+    /// Restrict to a single resource
+    #[arg(long)]
+    pub resource: Option<String>,
+
+    /// Maximum number of anomalies to return
+    #[arg(long)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct IncidentsArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Restrict to a single cluster
+    #[arg(long)]
+    pub cluster: Option<String>,
+
+    /// Restrict to incidents involving a resource
+    #[arg(long)]
+    pub resource: Option<String>,
+
+    /// Restrict to open or resolved incidents
+    #[arg(long, value_parser = parse_kind::<IncidentStatus>)]
+    pub status: Option<IncidentStatus>,
+
+    /// Maximum number of incidents to return
+    #[arg(long)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct SeriesArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Resource id to fetch
+    pub resource: String,
+
+    /// Metric type, e.g. memory_usage
+    #[arg(long, default_value = "cpu_usage", value_parser = parse_metric_type)]
+    pub metric: MetricType,
+
+    /// Maximum points to return; 0 returns every sample
+    #[arg(long, default_value_t = 120)]
+    pub max_points: u32,
+}
+
+#[derive(Debug, Args)]
+pub struct DashboardArgs {
+    /// Dashboard name, as listed under `dashboards` in the config
+    pub name: String,
+
+    /// Config file defining the dashboard
+    #[arg(
+        long,
+        env = "PHENOME_CONFIG_PATH",
+        default_value = "phenome-config.yaml"
+    )]
+    pub config: PathBuf,
+
+    /// Output format (plain, json, ndjson)
+    #[arg(long, default_value = "plain")]
+    pub output: OutputMode,
+
+    /// Analytics service endpoint
+    #[arg(
+        long,
+        env = "PHENOME_ANALYTICS_URL",
+        default_value = "http://localhost:50051"
+    )]
+    pub endpoint: String,
+}
+
+#[derive(Debug, Args)]
+pub struct EvaluateArgs {
+    /// Labeled CSV or Parquet dataset with `timestamp`, `value` and `label`
+    /// columns
+    pub dataset: PathBuf,
+
+    /// Config files to compare, each scored with its `ml` section
+    /// (repeatable); the first is also the base for `--detector` and
+    /// `--predictor`
+    #[arg(long = "config")]
+    pub configs: Vec<PathBuf>,
+
+    /// Anomaly models to compare (repeatable), e.g. isolation_forest
+    #[arg(long = "detector", value_parser = parse_kind::<AnomalyModelKind>)]
+    pub detectors: Vec<AnomalyModelKind>,
+
+    /// Scaling models to compare (repeatable), e.g. holt_winters
+    #[arg(long = "predictor", value_parser = parse_kind::<ScalingModelKind>)]
+    pub predictors: Vec<ScalingModelKind>,
+
+    /// Points at the start of each series that are never scored
+    #[arg(long, default_value_t = 30)]
+    pub warmup: usize,
+
+    /// Trailing points each detection or forecast sees
+    #[arg(long, default_value_t = 288)]
+    pub history: usize,
+
+    /// Samples ahead each forecast is scored at
+    #[arg(long, default_value_t = 12)]
+    pub horizon: usize,
+
+    /// Points between forecast origins
+    #[arg(long, default_value_t = 12)]
+    pub stride: usize,
+
+    /// Output format (plain, json, ndjson)
+    #[arg(long, default_value = "plain")]
+    pub output: OutputMode,
+}
+
+#[derive(Debug, Args)]
+pub struct FeedbackArgs {
+    /// Anomaly id, as listed by `anomalies`
+    pub anomaly_id: String,
+
+    /// Verdict: confirmed, false_positive or expected
+    #[arg(value_parser = parse_kind::<AnomalyFeedbackKind>)]
+    pub kind: AnomalyFeedbackKind,
+
+    /// Free-form context kept with the verdict
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Output format (plain, json, ndjson)
+    #[arg(long, default_value = "plain")]
+    pub output: OutputMode,
+
+    /// Analytics service endpoint
+    #[arg(
+        long,
+        env = "PHENOME_ANALYTICS_URL",
+        default_value = "http://localhost:50051"
+    )]
+    pub endpoint: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportFeedbackArgs {
+    /// Destination `.csv` or `.parquet` file
+    pub out: PathBuf,
+
+    /// Export anomalies detected in this range
+    #[arg(long, default_value = "last 7d")]
+    pub range: String,
+
+    /// Restrict to a single cluster
+    #[arg(long)]
+    pub cluster: Option<String>,
+
+    /// Restrict to a single resource
+    #[arg(long)]
+    pub resource: Option<String>,
+
+    /// Series history to include either side of the reviewed anomalies
+    #[arg(long, default_value = "1h")]
+    pub context: String,
+
+    /// Analytics service endpoint
+    #[arg(
+        long,
+        env = "PHENOME_ANALYTICS_URL",
+        default_value = "http://localhost:50051"
+    )]
+    pub endpoint: String,
+}
+
+pub async fn run(command: AnalyticsCommand) -> Result<()> {
+    let output = match command {
+        AnalyticsCommand::Metrics(args) => {
+            let mode = args.query.output;
+            format_metrics(mode, &query_metrics(args).await?)?
+        }
+        AnalyticsCommand::Anomalies(args) => {
+            let mode = args.query.output;
+            format_anomalies(mode, &query_anomalies(args).await?)?
+        }
+        AnalyticsCommand::Incidents(args) => {
+            let mode = args.query.output;
+            format_incidents(mode, &query_incidents(args).await?)?
+        }
+        AnalyticsCommand::Series(args) => {
+            let mode = args.query.output;
+            format_time_series(mode, &query_series(args).await?)?
+        }
+        AnalyticsCommand::Dashboard(args) => {
+            let mode = args.output;
+            format_dashboard(mode, &query_dashboard(args).await?)?
+        }
+        AnalyticsCommand::Evaluate(args) => format_evaluation(args.output, &evaluate(&args)?)?,
+        AnalyticsCommand::Feedback(args) => {
+            let mode = args.output;
+            format_anomalies(mode, &[submit_feedback(args).await?])?
+        }
+        AnalyticsCommand::ExportFeedback(args) => export_feedback(args).await?,
+    };
+    if !output.is_empty() {
+        println!("{output}");
+    }
+    Ok(())
+}
+
+async fn query_metrics(args: MetricsArgs) -> Result<Vec<MetricSample>> {
+    let range = resolve_range(&args.query.range)?;
+    let mut client = connect(&args.query.endpoint).await?;
+    let request = QueryMetricsRequest {
+        cluster_id: args.cluster,
+        resource_type: None,
+        resource_ids: args.resources,
+        metric_types: args
+            .metrics
+            .into_iter()
+            .map(|metric| proto::MetricType::from(metric).into())
+            .collect(),
+        time_range: Some(range.into()),
+        limit: None,
+        cursor: None,
+    };
+    let samples = client.query_metrics(request).await?.into_inner().samples;
+
+    samples
+        .into_iter()
+        .map(|s| s.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to convert metrics")
+}
+
+async fn query_anomalies(args: AnomaliesArgs) -> Result<Vec<Anomaly>> {
+    let range = resolve_range(&args.query.range)?;
+    let mut client = connect(&args.query.endpoint).await?;
+    let request = GetAnomaliesRequest {
+        cluster_id: args.cluster,
+        resource_id: args.resource,
+        time_range: Some(range.into()),
+        limit: args.limit,
+        ..Default::default()
+    };
+    let anomalies = client.get_anomalies(request).await?.into_inner().anomalies;
+
+    anomalies
+        .into_iter()
+        .map(|a| a.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to convert anomalies")
+}
+
+async fn query_incidents(args: IncidentsArgs) -> Result<Vec<Incident>> {
+    let range = resolve_range(&args.query.range)?;
+    let mut client = connect(&args.query.endpoint).await?;
+    let request = GetIncidentsRequest {
+        cluster_id: args.cluster,
+        status: args
+            .status
+            .map(|status| proto::IncidentStatus::from(status).into()),
+        resource_id: args.resource,
+        time_range: Some(range.into()),
+        limit: args.limit,
+    };
+    let incidents = client.get_incidents(request).await?.into_inner().incidents;
+
+    incidents
+        .into_iter()
+        .map(|i| i.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to convert incidents")
+}
+
+async fn query_series(args: SeriesArgs) -> Result<TimeSeries> {
+    let range = resolve_range(&args.query.range)?;
+    let mut client = connect(&args.query.endpoint).await?;
+    let request = GetTimeSeriesRequest {
+        resource_id: args.resource,
+        metric_type: proto::MetricType::from(args.metric).into(),
+        time_range: Some(range.into()),
+        max_points: Some(args.max_points),
+    };
+    let response = client.get_time_series(request).await?.into_inner();
+    let method = response.method();
+    let mut series: TimeSeries = response
+        .series
+        .ok_or_else(|| anyhow!("analytics service returned no series"))?
+        .try_into()?;
+    series.downsample = method.try_into()?;
+    Ok(series)
+}
+
+/// Resolve each panel's range in local time and average its samples into
+/// the panel's step, one series per resource. Panels without a step get the
+/// smallest one that fits `DASHBOARD_POINTS`.
+async fn query_dashboard(args: DashboardArgs) -> Result<Vec<DashboardPanel>> {
+    let config = PhenomeConfig::load_from_path(&args.config)
+        .with_context(|| format!("failed to load config {}", args.config.display()))?;
+    let dashboard = config
+        .dashboards
+        .into_iter()
+        .find(|dashboard| dashboard.name == args.name)
+        .ok_or_else(|| anyhow!("no dashboard {:?} in {}", args.name, args.config.display()))?;
+
+    let mut client = connect(&args.endpoint).await?;
+    let now = Utc::now().timestamp_millis();
+    let offset = Local::now().offset().local_minus_utc();
+    let mut panels = Vec::new();
+    for panel in dashboard.panels {
+        let context = || format!("dashboard {:?} panel {:?}", dashboard.name, panel.title);
+        let range =
+            TimeRange::parse_with_offset(&panel.range, now, offset).with_context(context)?;
+        let step_ms = panel
+            .step_ms()
+            .with_context(context)?
+            .unwrap_or_else(|| range.auto_step(DASHBOARD_POINTS));
+
+        let mut request = QueryMetricsRequest {
+            cluster_id: None,
+            resource_type: None,
+            resource_ids: panel.resource_ids.clone(),
+            metric_types: vec![proto::MetricType::from(panel.metric_type).into()],
+            time_range: Some(range.into()),
+            limit: Some(DASHBOARD_PAGE_SIZE),
+            cursor: None,
+        };
+        let mut by_resource: BTreeMap<(String, String), TimeSeries> = BTreeMap::new();
+        loop {
+            let page = client.query_metrics(request.clone()).await?.into_inner();
+            for sample in page.samples {
+                let sample: MetricSample =
+                    sample.try_into().context("failed to convert metrics")?;
+                by_resource
+                    .entry((sample.cluster_id.clone(), sample.resource_id.clone()))
+                    .or_insert_with(|| TimeSeries {
+                        cluster_id: sample.cluster_id,
+                        resource_id: sample.resource_id,
+                        metric_type: sample.metric_type,
+                        unit: sample.unit,
+                        points: Vec::new(),
+                        downsample: DownsampleMethod::Rollup,
+                    })
+                    .points
+                    .push(TimeSeriesPoint {
+                        timestamp: sample.timestamp,
+                        value: sample.value,
+                    });
+            }
+            match page.next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        let series = by_resource
+            .into_values()
+            .map(|mut series| {
+                series.points.sort_by_key(|point| point.timestamp);
+                series.points = rollup(&series.points, step_ms);
+                series
+            })
+            .collect();
+        panels.push(DashboardPanel {
+            title: panel.title,
+            range,
+            step_ms,
+            series,
+        });
+    }
+    Ok(panels)
+}
+
+async fn submit_feedback(args: FeedbackArgs) -> Result<Anomaly> {
+    let mut client = connect(&args.endpoint).await?;
+    let request = SubmitAnomalyFeedbackRequest {
+        anomaly_id: args.anomaly_id,
+        kind: proto::AnomalyFeedbackKind::from(args.kind).into(),
+        note: args.note,
+    };
+    client
+        .submit_anomaly_feedback(request)
+        .await?
+        .into_inner()
+        .anomaly
+        .ok_or_else(|| anyhow!("analytics service returned no anomaly"))?
+        .try_into()
+        .context("failed to convert anomaly")
+}
+
+/// Fetch every series with reviewed anomalies, `--context` either side of
+/// them, and write them labeled with the confirmed ones.
+async fn export_feedback(args: ExportFeedbackArgs) -> Result<String> {
+    let range = resolve_range(&args.range)?;
+    let context = parse_duration(&args.context)
+        .with_context(|| format!("invalid --context {:?}", args.context))?;
+    let mut client = connect(&args.endpoint).await?;
+    let request = GetAnomalyFeedbackRequest {
+        cluster_id: args.cluster,
+        resource_id: args.resource,
+        time_range: Some(range.into()),
+        ..Default::default()
+    };
+    let anomalies = client
+        .get_anomaly_feedback(request)
+        .await?
+        .into_inner()
+        .anomalies
+        .into_iter()
+        .map(|a| a.try_into())
+        .collect::<Result<Vec<Anomaly>, _>>()
+        .context("failed to convert anomalies")?;
+
+    let mut by_series: BTreeMap<(String, &str), Vec<Anomaly>> = BTreeMap::new();
+    for anomaly in &anomalies {
+        by_series
+            .entry((anomaly.resource_id.clone(), anomaly.metric_type.as_str()))
+            .or_default()
+            .push(anomaly.clone());
+    }
+
+    let mut dataset = Vec::new();
+    for reviewed in by_series.values() {
+        let first = &reviewed[0];
+        let start = reviewed
+            .iter()
+            .map(|anomaly| {
+                anomaly.change_point.map_or(anomaly.detected_at, |change| {
+                    change.changed_at.min(anomaly.detected_at)
+                })
+            })
+            .min()
+            .unwrap_or(first.detected_at);
+        let end = reviewed
+            .iter()
+            .map(|anomaly| anomaly.detected_at)
+            .max()
+            .unwrap_or(first.detected_at);
+        let request = GetTimeSeriesRequest {
+            resource_id: first.resource_id.clone(),
+            metric_type: proto::MetricType::from(first.metric_type).into(),
+            time_range: Some(
+                TimeRange {
+                    start_ms: start - context,
+                    end_ms: end + context,
+                }
+                .into(),
+            ),
+            max_points: None,
+        };
+        let series: TimeSeries = client
+            .get_time_series(request)
+            .await?
+            .into_inner()
+            .series
+            .ok_or_else(|| anyhow!("analytics service returned no series"))?
+            .try_into()?;
+        dataset.push(label_feedback(series, reviewed));
+    }
+
+    write_dataset(&args.out, &dataset)?;
+    let windows: usize = dataset.iter().map(|labeled| labeled.windows.len()).sum();
+    Ok(format!(
+        "Wrote {} series with {windows} labeled windows from {} reviewed anomalies to {}",
+        dataset.len(),
+        anomalies.len(),
+        args.out.display()
+    ))
+}
+
+/// Score every candidate on the dataset. Without any candidate flags the
+/// default detector and predictor are scored.
+fn evaluate(args: &EvaluateArgs) -> Result<EvaluationReport> {
+    let dataset = load_dataset(&args.dataset)?;
+    let evaluator = Evaluator::new()
+        .with_warmup(args.warmup)
+        .with_history(args.history)
+        .with_horizon(args.horizon)
+        .with_stride(args.stride);
+
+    let mut configs = Vec::new();
+    for path in &args.configs {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let config = PhenomeConfig::load_from_path(path)
+            .with_context(|| format!("failed to load config {}", path.display()))?;
+        configs.push((name, config.ml));
+    }
+
+    let mut detectors = Vec::new();
+    let mut predictors = Vec::new();
+    let mut base = None;
+    for (name, ml) in &configs {
+        let models = ModelFactory::from_config(ml)?.build()?;
+        detectors.push((name.clone(), models.anomaly_detector.clone()));
+        predictors.push((name.clone(), models.scaling_predictor.clone()));
+        base.get_or_insert(models);
+    }
+    let base = base.unwrap_or_default();
+    for kind in &args.detectors {
+        let detector = base.anomaly_detector.clone();
+        detectors.push((kind.as_str().to_string(), detector.with_model(*kind)));
+    }
+    for kind in &args.predictors {
+        let predictor = base.scaling_predictor.clone();
+        predictors.push((kind.as_str().to_string(), predictor.with_model(*kind)));
+    }
+    if detectors.is_empty() && predictors.is_empty() {
+        detectors.push(("default".to_string(), AnomalyDetector::default()));
+        predictors.push(("default".to_string(), ScalingPredictor::new()));
+    }
+
+    Ok(EvaluationReport {
+        dataset: args.dataset.display().to_string(),
+        series: dataset.len(),
+        detection: detectors
+            .iter()
+            .map(|(name, detector)| evaluator.detection(name, detector, &dataset))
+            .collect::<Result<_>>()?,
+        forecast: predictors
+            .iter()
+            .map(|(name, predictor)| evaluator.forecast(name, predictor, &dataset))
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Resolve the range locally so `today`/`yesterday` follow the caller's
+/// time zone rather than the server's.
+fn resolve_range(expr: &str) -> Result<TimeRange> {
+    let offset = Local::now().offset().local_minus_utc();
+    TimeRange::parse_with_offset(expr, Utc::now().timestamp_millis(), offset)
+        .with_context(|| format!("invalid --range {expr:?}"))
+}
+
+async fn connect(endpoint: &str) -> Result<AnalyticsServiceClient<Channel>> {
+    AnalyticsServiceClient::connect(endpoint.to_string())
+        .await
+        .with_context(|| format!("failed to connect to analytics service at {endpoint}"))
+}
+
+fn parse_kind<T: DeserializeOwned>(value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| anyhow!("unknown value {value:?}"))
+}
+
+fn parse_metric_type(value: &str) -> Result<MetricType> {
+    MetricType::ALL
+        .into_iter()
+        .find(|metric| metric.as_str() == value)
+        .ok_or_else(|| anyhow!("unknown metric type {value:?}"))
+}
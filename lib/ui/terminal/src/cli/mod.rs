@@ -3,3 +3,6 @@ pub mod primer;
 
 #[cfg(feature = "plasmid-cli")]
 pub mod plasmid;
+
+#[cfg(feature = "analytics-cli")]
+pub mod analytics;
@@ -176,6 +176,13 @@ pub enum Commands {
 
     /// Visualization tools
     Visualize(VisualizeArgs),
+
+    /// Query the analytics service
+    #[cfg(feature = "analytics-cli")]
+    Analytics {
+        #[command(subcommand)]
+        command: crate::cli::analytics::AnalyticsCommand,
+    },
 }
 
 #[derive(Subcommand)]
@@ -445,5 +452,7 @@ async fn dispatch(command: Commands, gitops_dir: String) -> anyhow::Result<()> {
                 }
             },
         },
+        #[cfg(feature = "analytics-cli")]
+        Commands::Analytics { command } => crate::cli::analytics::run(command).await,
     }
 }
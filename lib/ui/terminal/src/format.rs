@@ -3,7 +3,10 @@
 use anyhow::{Result, anyhow};
 use serde::Serialize;
 
-use phenome_domain::{ActionDefinition, Event, Snapshot};
+use phenome_domain::{
+    ActionDefinition, Anomaly, AnomalyEvidence, DashboardPanel, EvaluationReport, Event, Incident,
+    MetricSample, Snapshot, TimeSeries, Unit,
+};
 use phenome_ui_presentation::formatting;
 
 use super::OutputMode;
@@ -181,6 +184,356 @@ pub fn format_problems(mode: OutputMode, problems: &[String]) -> Result<String>
     }
 }
 
+/// Format metric samples for CLI output, with values in human units.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_metrics, OutputMode};
+/// use phenome_domain::{MetricSample, MetricType, ResourceType};
+///
+/// let samples = [MetricSample {
+///     cluster_id: "c1".to_string(),
+///     resource_type: ResourceType::Pod,
+///     resource_id: "api".to_string(),
+///     metric_type: MetricType::CpuUsage,
+///     timestamp: 1_000,
+///     value: 0.25,
+///     unit: "cores".to_string(),
+/// }];
+/// let output = format_metrics(OutputMode::Plain, &samples).unwrap();
+/// assert_eq!(output, "1000 c1/api cpu_usage 250 millicores");
+/// ```
+pub fn format_metrics(mode: OutputMode, samples: &[MetricSample]) -> Result<String> {
+    match mode {
+        OutputMode::Plain => Ok(samples
+            .iter()
+            .map(|sample| {
+                format!(
+                    "{} {}/{} {} {}",
+                    sample.timestamp,
+                    sample.cluster_id,
+                    sample.resource_id,
+                    sample.metric_type.as_str(),
+                    Unit::for_metric(sample.metric_type).format_human(sample.value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+        OutputMode::Json => Ok(serde_json::to_string_pretty(samples)?),
+        OutputMode::Ndjson => samples
+            .iter()
+            .map(to_ndjson)
+            .collect::<Result<Vec<_>>>()
+            .map(|lines| lines.join("\n")),
+    }
+}
+
+/// Format a single time series for CLI output, noting how it was
+/// downsampled.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_time_series, OutputMode};
+/// use phenome_domain::{DownsampleMethod, MetricType, TimeSeries, TimeSeriesPoint};
+///
+/// let series = TimeSeries {
+///     cluster_id: "c1".to_string(),
+///     resource_id: "api".to_string(),
+///     metric_type: MetricType::MemoryUsage,
+///     unit: "bytes".to_string(),
+///     points: vec![TimeSeriesPoint { timestamp: 1_000, value: 2048.0 }],
+///     downsample: DownsampleMethod::Lttb,
+/// };
+/// let output = format_time_series(OutputMode::Plain, &series).unwrap();
+/// assert_eq!(output, "c1/api memory_usage (1 points, lttb)\n1000 2.00 KiB");
+/// ```
+pub fn format_time_series(mode: OutputMode, series: &TimeSeries) -> Result<String> {
+    match mode {
+        OutputMode::Plain => {
+            let unit = Unit::for_metric(series.metric_type);
+            let mut lines = vec![format!(
+                "{}/{} {} ({} points, {})",
+                series.cluster_id,
+                series.resource_id,
+                series.metric_type.as_str(),
+                series.points.len(),
+                series.downsample.as_str()
+            )];
+            lines.extend(
+                series
+                    .points
+                    .iter()
+                    .map(|point| format!("{} {}", point.timestamp, unit.format_human(point.value))),
+            );
+            Ok(lines.join("\n"))
+        }
+        OutputMode::Json => Ok(serde_json::to_string_pretty(series)?),
+        OutputMode::Ndjson => to_ndjson(series),
+    }
+}
+
+/// Format dashboard panels for CLI output: a heading per panel with its
+/// resolved range and step, followed by each of its series.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_dashboard, OutputMode};
+/// use phenome_domain::{DashboardPanel, TimeRange};
+///
+/// let panels = [DashboardPanel {
+///     title: "CPU".to_string(),
+///     range: TimeRange { start_ms: 0, end_ms: 3_600_000 },
+///     step_ms: 300_000,
+///     series: Vec::new(),
+/// }];
+/// let output = format_dashboard(OutputMode::Plain, &panels).unwrap();
+/// assert_eq!(output, "== CPU (0..3600000, step 300s)\nno samples");
+/// ```
+pub fn format_dashboard(mode: OutputMode, panels: &[DashboardPanel]) -> Result<String> {
+    match mode {
+        OutputMode::Plain => {
+            let mut blocks = Vec::new();
+            for panel in panels {
+                let mut lines = vec![format!(
+                    "== {} ({}..{}, step {}s)",
+                    panel.title,
+                    panel.range.start_ms,
+                    panel.range.end_ms,
+                    panel.step_ms / 1000
+                )];
+                if panel.series.is_empty() {
+                    lines.push("no samples".to_string());
+                }
+                for series in &panel.series {
+                    lines.push(format_time_series(mode, series)?);
+                }
+                blocks.push(lines.join("\n"));
+            }
+            Ok(blocks.join("\n\n"))
+        }
+        OutputMode::Json => Ok(serde_json::to_string_pretty(panels)?),
+        OutputMode::Ndjson => panels
+            .iter()
+            .map(to_ndjson)
+            .collect::<Result<Vec<_>>>()
+            .map(|lines| lines.join("\n")),
+    }
+}
+
+/// Format detected anomalies for CLI output, one line per anomaly ending
+/// with its id and any feedback verdict, followed by an indented line of
+/// evidence when the detector attached some.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_anomalies, OutputMode};
+///
+/// let output = format_anomalies(OutputMode::Plain, &[]).unwrap();
+/// assert_eq!(output, "");
+/// ```
+pub fn format_anomalies(mode: OutputMode, anomalies: &[Anomaly]) -> Result<String> {
+    match mode {
+        OutputMode::Plain => Ok(anomalies
+            .iter()
+            .map(|anomaly| {
+                let unit = Unit::for_metric(anomaly.metric_type);
+                let verdict = anomaly
+                    .feedback
+                    .as_ref()
+                    .map(|feedback| format!(" [{}]", feedback.kind.as_str()))
+                    .unwrap_or_default();
+                let line = format!(
+                    "[{:<8}] {} {}/{} {} {} observed {} vs baseline {} ({:.1} sigma) {}{}",
+                    anomaly.severity.as_str(),
+                    anomaly.detected_at,
+                    anomaly.cluster_id,
+                    anomaly.resource_id,
+                    anomaly.metric_type.as_str(),
+                    anomaly.kind.as_str(),
+                    unit.format_human(anomaly.observed_value),
+                    unit.format_human(anomaly.baseline_value),
+                    anomaly.deviation_sigma,
+                    anomaly.id,
+                    verdict
+                );
+                match &anomaly.evidence {
+                    Some(evidence) => format!("{line}\n{}", format_evidence(evidence, unit)),
+                    None => line,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+        OutputMode::Json => Ok(serde_json::to_string_pretty(anomalies)?),
+        OutputMode::Ndjson => anomalies
+            .iter()
+            .map(to_ndjson)
+            .collect::<Result<Vec<_>>>()
+            .map(|lines| lines.join("\n")),
+    }
+}
+
+fn format_evidence(evidence: &AnomalyEvidence, unit: Unit) -> String {
+    let mut line = format!(
+        "  expected {} to {} from {}..{}",
+        unit.format_human(evidence.expected_low),
+        unit.format_human(evidence.expected_high),
+        evidence.baseline_window.start_ms,
+        evidence.baseline_window.end_ms
+    );
+    if let Some(seasonal) = evidence.seasonal_expected {
+        line.push_str(&format!(", seasonal {}", unit.format_human(seasonal)));
+    }
+    let drivers: Vec<String> = evidence
+        .contributions
+        .iter()
+        .map(|contribution| {
+            format!(
+                "{} {:.0}%",
+                contribution.feature,
+                contribution.share * 100.0
+            )
+        })
+        .collect();
+    if !drivers.is_empty() {
+        line.push_str(&format!(", driven by {}", drivers.join(", ")));
+    }
+    line
+}
+
+/// Format incidents for CLI output, one line per incident.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_incidents, OutputMode};
+/// use phenome_domain::{Incident, IncidentStatus, Severity};
+///
+/// let incident = Incident {
+///     id: "7f3a".to_string(),
+///     cluster_id: "cluster-1".to_string(),
+///     status: IncidentStatus::Open,
+///     severity: Severity::Critical,
+///     title: "memory_usage anomaly on shop/api-0".to_string(),
+///     opened_at: 1_000,
+///     updated_at: 61_000,
+///     resolved_at: None,
+///     resources: vec!["shop/api-0".to_string(), "shop/db-0".to_string()],
+///     anomaly_ids: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+///     timeline: Vec::new(),
+/// };
+/// let output = format_incidents(OutputMode::Plain, &[incident]).unwrap();
+/// assert_eq!(
+///     output,
+///     "[critical] open     1000 cluster-1/7f3a memory_usage anomaly on shop/api-0 \
+///      (3 anomalies across 2 resources)"
+/// );
+/// ```
+pub fn format_incidents(mode: OutputMode, incidents: &[Incident]) -> Result<String> {
+    match mode {
+        OutputMode::Plain => Ok(incidents
+            .iter()
+            .map(|incident| {
+                format!(
+                    "[{:<8}] {:<8} {} {}/{} {} ({} anomalies across {} resources)",
+                    incident.severity.as_str(),
+                    incident.status.as_str(),
+                    incident.opened_at,
+                    incident.cluster_id,
+                    incident.id,
+                    incident.title,
+                    incident.anomaly_ids.len(),
+                    incident.resources.len()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+        OutputMode::Json => Ok(serde_json::to_string_pretty(incidents)?),
+        OutputMode::Ndjson => incidents
+            .iter()
+            .map(to_ndjson)
+            .collect::<Result<Vec<_>>>()
+            .map(|lines| lines.join("\n")),
+    }
+}
+
+/// Format an offline evaluation report, one row per candidate.
+///
+/// # Examples
+/// ```rust
+/// use phenome_ui_terminal::{format_evaluation, OutputMode};
+/// use phenome_domain::{DetectionScore, EvaluationReport};
+///
+/// let report = EvaluationReport {
+///     dataset: "incidents.csv".to_string(),
+///     series: 2,
+///     detection: vec![DetectionScore {
+///         candidate: "z_score".to_string(),
+///         flagged: 4,
+///         false_positives: 1,
+///         windows: 2,
+///         detected_windows: 1,
+///         precision: 0.75,
+///         recall: 0.5,
+///         f1: 0.6,
+///         mean_delay_ms: Some(90_000.0),
+///     }],
+///     forecast: vec![],
+/// };
+/// let output = format_evaluation(OutputMode::Plain, &report).unwrap();
+/// assert!(output.starts_with("incidents.csv (2 series)"));
+/// assert!(output.contains("z_score                0.75    0.50  0.60  1/2       90.0s"));
+/// ```
+pub fn format_evaluation(mode: OutputMode, report: &EvaluationReport) -> Result<String> {
+    match mode {
+        OutputMode::Plain => {
+            let mut lines = vec![format!("{} ({} series)", report.dataset, report.series)];
+            if !report.detection.is_empty() {
+                lines.push(format!(
+                    "{:<20} {:>6} {:>7} {:>5}  {:<7} {:>7}",
+                    "detector", "prec", "recall", "f1", "windows", "delay"
+                ));
+                lines.extend(report.detection.iter().map(|score| {
+                    format!(
+                        "{:<20} {:>6.2} {:>7.2} {:>5.2}  {:<7} {:>7}",
+                        score.candidate,
+                        score.precision,
+                        score.recall,
+                        score.f1,
+                        format!("{}/{}", score.detected_windows, score.windows),
+                        score
+                            .mean_delay_ms
+                            .map(|delay| format!("{:.1}s", delay / 1000.0))
+                            .unwrap_or_else(|| "-".to_string())
+                    )
+                }));
+            }
+            if !report.forecast.is_empty() {
+                lines.push(format!(
+                    "{:<20} {:>6} {:>7} {:>9}",
+                    "predictor", "mape", "mase", "forecasts"
+                ));
+                lines.extend(report.forecast.iter().map(|score| {
+                    format!(
+                        "{:<20} {:>6} {:>7} {:>9}",
+                        score.candidate,
+                        score
+                            .mape
+                            .map(|mape| format!("{:.1}%", mape * 100.0))
+                            .unwrap_or_else(|| "-".to_string()),
+                        score
+                            .mase
+                            .map(|mase| format!("{mase:.2}"))
+                            .unwrap_or_else(|| "-".to_string()),
+                        score.forecasts
+                    )
+                }));
+            }
+            Ok(lines.join("\n"))
+        }
+        OutputMode::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputMode::Ndjson => to_ndjson(report),
+    }
+}
+
 /// Serialize a value as single-line JSON suitable for NDJSON output.
 fn to_ndjson<T: Serialize + ?Sized>(value: &T) -> Result<String> {
     let json = serde_json::to_string(value)?;
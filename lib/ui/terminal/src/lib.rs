@@ -10,10 +10,16 @@ mod output_mode;
 
 #[doc(inline)]
 pub use format::{
-    format_actions, format_assembly, format_events, format_problems, format_snapshot,
+    format_actions, format_anomalies, format_assembly, format_dashboard, format_evaluation,
+    format_events, format_incidents, format_metrics, format_problems, format_snapshot,
+    format_time_series,
 };
 #[doc(inline)]
 pub use output_mode::OutputMode;
 
-#[cfg(any(feature = "primer-cli", feature = "plasmid-cli"))]
+#[cfg(any(
+    feature = "primer-cli",
+    feature = "plasmid-cli",
+    feature = "analytics-cli"
+))]
 pub mod cli;
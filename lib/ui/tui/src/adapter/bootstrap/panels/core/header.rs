@@ -38,6 +38,18 @@ pub fn render(frame: &mut Frame, area: Rect, ports: &PortSet) {
         .map(|start| start.elapsed())
         .unwrap_or_default();
     let elapsed_text = format_duration(elapsed);
+    let eta_text = ports
+        .bootstrap
+        .eta()
+        .filter(|_| status.total_duration.is_none())
+        .map(|eta| {
+            format!(
+                "ETA ~{} (p90 {}, {:.0}% conf)",
+                format_duration(eta.remaining),
+                format_duration(eta.p90_remaining),
+                eta.confidence * 100.0
+            )
+        });
 
     let progress = if total == 0 {
         0.0
@@ -54,6 +66,10 @@ pub fn render(frame: &mut Frame, area: Rect, ports: &PortSet) {
             Span::raw("  "),
             Span::raw(format!("Elapsed: {elapsed_text}")),
             Span::raw("  "),
+            Span::styled(
+                eta_text.map(|text| format!("{text}  ")).unwrap_or_default(),
+                Style::default().fg(Color::Yellow),
+            ),
             Span::raw(format!("OK {completed}/{total}")),
             Span::raw("  "),
             Span::raw(format!("RUN {running}")),
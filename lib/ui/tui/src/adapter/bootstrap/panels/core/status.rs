@@ -7,7 +7,7 @@ use ratatui::prelude::Frame;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
-use phenome_ports::{ComponentState, PortSet};
+use phenome_ports::{ComponentState, ComponentStatus, PortSet};
 
 pub fn render(frame: &mut Frame, area: Rect, ports: &PortSet, ui: &mut BootstrapUiState) {
     let assembly = ports.bootstrap.dependency_graph();
@@ -55,11 +55,19 @@ pub fn render(frame: &mut Frame, area: Rect, ports: &PortSet, ui: &mut Bootstrap
 
 fn format_component_summary(state: &ComponentState, widths: &[usize; 4]) -> String {
     let status_text = format_status(state);
-    let elapsed_text = state
+    let elapsed = state.timing.current_elapsed().map(format_duration);
+    let expected = state
         .timing
-        .current_elapsed()
-        .map(format_duration)
-        .unwrap_or_else(|| "-".to_string());
+        .estimate
+        .map(|estimate| format!("~{}", format_duration(estimate.expected)));
+    let elapsed_text = match (state.status, elapsed, expected) {
+        (ComponentStatus::Running, Some(elapsed), Some(expected)) => {
+            format!("{elapsed}/{expected}")
+        }
+        (ComponentStatus::Pending, _, Some(expected)) => expected,
+        (_, Some(elapsed), _) => elapsed,
+        _ => "-".to_string(),
+    };
     let progress = state
         .readiness
         .as_ref()
@@ -1,7 +1,11 @@
 use anyhow::Result;
 
-use phenome_adapter_analytics::grpc::analytics::GetAnomaliesRequest;
-use phenome_domain::{Anomaly, MetricType, Severity};
+use phenome_adapter_analytics::grpc::analytics::{
+    AnomalyFeedbackKind as GrpcFeedbackKind, GetAnomaliesRequest, SubmitAnomalyFeedbackRequest,
+};
+use phenome_domain::{
+    Anomaly, AnomalyFeedback, AnomalyFeedbackKind, AnomalyKind, ChangePoint, MetricType, Severity,
+};
 
 use super::AnalyticsClient;
 
@@ -14,28 +18,82 @@ pub(super) async fn fetch_anomalies(client: &AnalyticsClient) -> Result<Vec<Anom
     let response = grpc.get_anomalies(request).await?;
     let anomalies = response.into_inner().anomalies;
 
-    Ok(anomalies
-        .into_iter()
-        .map(|a| {
-            let metric_type = map_metric_type(a.metric_type());
-            let severity = map_severity(a.severity());
-            Anomaly {
-                id: a.id,
-                cluster_id: a.cluster_id,
-                resource_id: a.resource_id,
-                detected_at: a.detected_at,
-                metric_type,
-                severity,
-                confidence: a.confidence,
-                description: a.description,
-                baseline_value: a.baseline_value,
-                observed_value: a.observed_value,
-                deviation_sigma: a.deviation_sigma,
-                related_metrics: a.related_metrics,
-                root_cause: a.root_cause,
-            }
-        })
-        .collect())
+    Ok(anomalies.into_iter().map(map_anomaly).collect())
+}
+
+pub(super) fn map_anomaly(a: phenome_adapter_analytics::grpc::analytics::Anomaly) -> Anomaly {
+    let metric_type = map_metric_type(a.metric_type());
+    let severity = map_severity(a.severity());
+    let kind = map_kind(a.kind());
+    Anomaly {
+        id: a.id,
+        cluster_id: a.cluster_id,
+        resource_id: a.resource_id,
+        detected_at: a.detected_at,
+        metric_type,
+        severity,
+        confidence: a.confidence,
+        description: a.description,
+        baseline_value: a.baseline_value,
+        observed_value: a.observed_value,
+        deviation_sigma: a.deviation_sigma,
+        related_metrics: a.related_metrics,
+        root_cause: a.root_cause,
+        model_id: a.model_id,
+        model_version: a.model_version,
+        kind,
+        change_point: a.change_point.map(|change| ChangePoint {
+            changed_at: change.changed_at,
+            before_mean: change.before_mean,
+            after_mean: change.after_mean,
+            before_stddev: change.before_stddev,
+            after_stddev: change.after_stddev,
+            before_slope: change.before_slope,
+            after_slope: change.after_slope,
+        }),
+        feedback: a.feedback.and_then(map_feedback),
+        evidence: a.evidence.and_then(|evidence| evidence.try_into().ok()),
+    }
+}
+
+pub(super) async fn submit_feedback(
+    client: &AnalyticsClient,
+    anomaly_id: String,
+    kind: AnomalyFeedbackKind,
+) -> Result<Option<AnomalyFeedback>> {
+    let mut grpc = client.client.clone();
+    let request = SubmitAnomalyFeedbackRequest {
+        anomaly_id,
+        kind: match kind {
+            AnomalyFeedbackKind::Confirmed => GrpcFeedbackKind::Confirmed,
+            AnomalyFeedbackKind::FalsePositive => GrpcFeedbackKind::FalsePositive,
+            AnomalyFeedbackKind::Expected => GrpcFeedbackKind::Expected,
+        }
+        .into(),
+        note: None,
+    };
+    let response = grpc.submit_anomaly_feedback(request).await?;
+    Ok(response
+        .into_inner()
+        .anomaly
+        .and_then(|anomaly| anomaly.feedback)
+        .and_then(map_feedback))
+}
+
+fn map_feedback(
+    feedback: phenome_adapter_analytics::grpc::analytics::AnomalyFeedback,
+) -> Option<AnomalyFeedback> {
+    let kind = match feedback.kind() {
+        GrpcFeedbackKind::Confirmed => AnomalyFeedbackKind::Confirmed,
+        GrpcFeedbackKind::FalsePositive => AnomalyFeedbackKind::FalsePositive,
+        GrpcFeedbackKind::Expected => AnomalyFeedbackKind::Expected,
+        GrpcFeedbackKind::Unspecified => return None,
+    };
+    Some(AnomalyFeedback {
+        kind,
+        submitted_at: feedback.submitted_at,
+        note: feedback.note,
+    })
 }
 
 fn map_metric_type(metric: phenome_adapter_analytics::grpc::analytics::MetricType) -> MetricType {
@@ -62,3 +120,15 @@ fn map_severity(severity: phenome_adapter_analytics::grpc::analytics::Severity)
         _ => Severity::Info,
     }
 }
+
+fn map_kind(kind: phenome_adapter_analytics::grpc::analytics::AnomalyKind) -> AnomalyKind {
+    match kind {
+        phenome_adapter_analytics::grpc::analytics::AnomalyKind::LevelShift => {
+            AnomalyKind::LevelShift
+        }
+        phenome_adapter_analytics::grpc::analytics::AnomalyKind::TrendChange => {
+            AnomalyKind::TrendChange
+        }
+        _ => AnomalyKind::Spike,
+    }
+}
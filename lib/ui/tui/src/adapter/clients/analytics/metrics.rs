@@ -1,10 +1,13 @@
 use anyhow::{Context, Result};
 
-use phenome_adapter_analytics::grpc::analytics::QueryMetricsRequest;
+use phenome_adapter_analytics::grpc::analytics::{QueryMetricsRequest, TimeRange};
 use phenome_domain::MetricSample;
 
 use super::AnalyticsClient;
 
+/// Window the dashboard polls; resolved by the analytics service.
+const METRICS_RANGE: &str = "last 15m";
+
 pub(super) async fn fetch_metrics(client: &AnalyticsClient) -> Result<Vec<MetricSample>> {
     let mut grpc = client.client.clone();
     let request = QueryMetricsRequest {
@@ -12,7 +15,12 @@ pub(super) async fn fetch_metrics(client: &AnalyticsClient) -> Result<Vec<Metric
         resource_type: None,
         resource_ids: Vec::new(),
         metric_types: Vec::new(),
-        time_range: None,
+        time_range: Some(TimeRange {
+            expression: Some(METRICS_RANGE.to_string()),
+            ..Default::default()
+        }),
+        limit: None,
+        cursor: None,
     };
     let response = grpc.query_metrics(request).await?;
     let samples = response.into_inner().samples;
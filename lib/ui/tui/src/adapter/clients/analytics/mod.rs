@@ -2,12 +2,18 @@ use anyhow::Result;
 use tonic::transport::Channel;
 
 use phenome_adapter_analytics::grpc::analytics::analytics_service_client::AnalyticsServiceClient;
-use phenome_domain::{Anomaly, MetricSample, Recommendation};
+use phenome_domain::{
+    Anomaly, AnomalyFeedback, AnomalyFeedbackKind, DataQualityIssue, MetricSample, Recommendation,
+};
 
 mod anomalies;
 mod connection;
 mod metrics;
+mod quality;
 mod recommendations;
+mod watch;
+
+pub use watch::{AnomaliesWatch, MetricsWatch, RecommendationsWatch, Watch, WatchBatch};
 
 #[derive(Debug, Clone)]
 pub struct AnalyticsClient {
@@ -27,7 +33,37 @@ impl AnalyticsClient {
         anomalies::fetch_anomalies(self).await
     }
 
+    /// Record an operator verdict on an anomaly; returns the feedback as
+    /// stored.
+    pub async fn submit_feedback(
+        &self,
+        anomaly_id: String,
+        kind: AnomalyFeedbackKind,
+    ) -> Result<Option<AnomalyFeedback>> {
+        anomalies::submit_feedback(self, anomaly_id, kind).await
+    }
+
     pub async fn fetch_recommendations(&self) -> Result<Vec<Recommendation>> {
         recommendations::fetch_recommendations(self).await
     }
+
+    /// Follow stored samples from after `cursor`, or from now.
+    pub async fn watch_metrics(&self, cursor: Option<u64>) -> Result<MetricsWatch> {
+        watch::watch_metrics(self, cursor).await
+    }
+
+    /// Follow new anomalies and feedback changes from after `cursor`, or
+    /// from now.
+    pub async fn watch_anomalies(&self, cursor: Option<u64>) -> Result<AnomaliesWatch> {
+        watch::watch_anomalies(self, cursor).await
+    }
+
+    /// Follow recommendation changes from after `cursor`, or from now.
+    pub async fn watch_recommendations(&self, cursor: Option<u64>) -> Result<RecommendationsWatch> {
+        watch::watch_recommendations(self, cursor).await
+    }
+
+    pub async fn fetch_data_quality(&self) -> Result<Vec<DataQualityIssue>> {
+        quality::fetch_data_quality(self).await
+    }
 }
@@ -0,0 +1,22 @@
+use anyhow::{Context, Result};
+
+use phenome_adapter_analytics::grpc::analytics::GetDataQualityRequest;
+use phenome_domain::DataQualityIssue;
+
+use super::AnalyticsClient;
+
+pub(super) async fn fetch_data_quality(client: &AnalyticsClient) -> Result<Vec<DataQualityIssue>> {
+    let mut grpc = client.client.clone();
+    let request = GetDataQualityRequest {
+        limit: Some(50),
+        ..Default::default()
+    };
+    let response = grpc.get_data_quality(request).await?;
+    let issues = response.into_inner().issues;
+
+    issues
+        .into_iter()
+        .map(|issue| issue.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to convert data quality issues")
+}
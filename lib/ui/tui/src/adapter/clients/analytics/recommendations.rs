@@ -21,42 +21,41 @@ pub(super) async fn fetch_recommendations(client: &AnalyticsClient) -> Result<Ve
     let response = grpc.get_recommendations(request).await?;
     let recs = response.into_inner().recommendations;
 
-    Ok(recs
-        .into_iter()
-        .map(|r| {
-            let recommendation_type = map_type(r.recommendation_type());
-            let priority = map_priority(r.priority());
-            Recommendation {
-                id: r.id,
-                cluster_id: r.cluster_id,
-                created_at: r.created_at,
-                recommendation_type,
-                priority,
-            confidence: r.confidence,
-            title: r.title,
-            description: r.description,
-            impact_estimate: r.impact_estimate,
-            cost_impact: r.cost_impact.map(|cost| CostImpact {
-                daily_change: cost.daily_change,
-                currency: cost.currency,
-            }),
-            action: r
-                .action
-                .and_then(|a| a.action)
-                .map(map_action)
-                .unwrap_or(RecommendationAction::ScaleDeployment {
-                    name: "unknown".into(),
-                    from: 0,
-                    to: 0,
-                }),
-            status: r
-                .status
-                .and_then(|s| s.status)
-                .map(map_status)
-                .unwrap_or(RecommendationStatus::Pending),
-            }
-        })
-        .collect())
+    Ok(recs.into_iter().map(map_recommendation).collect())
+}
+
+pub(super) fn map_recommendation(
+    r: phenome_adapter_analytics::grpc::analytics::Recommendation,
+) -> Recommendation {
+    let recommendation_type = map_type(r.recommendation_type());
+    let priority = map_priority(r.priority());
+    Recommendation {
+        id: r.id,
+        cluster_id: r.cluster_id,
+        created_at: r.created_at,
+        recommendation_type,
+        priority,
+        confidence: r.confidence,
+        title: r.title,
+        description: r.description,
+        impact_estimate: r.impact_estimate,
+        cost_impact: r.cost_impact.map(|cost| CostImpact {
+            daily_change: cost.daily_change,
+            currency: cost.currency,
+        }),
+        action: r.action.and_then(|a| a.action).map(map_action).unwrap_or(
+            RecommendationAction::ScaleDeployment {
+                name: "unknown".into(),
+                from: 0,
+                to: 0,
+            },
+        ),
+        status: r
+            .status
+            .and_then(|s| s.status)
+            .map(map_status)
+            .unwrap_or(RecommendationStatus::Pending),
+    }
 }
 
 fn map_type(rec_type: GrpcType) -> RecommendationType {
@@ -91,6 +90,8 @@ fn map_action(action: GrpcAction) -> RecommendationAction {
             limits: ResourceLimits {
                 cpu: u.limits.as_ref().and_then(|l| l.cpu.clone()),
                 memory: u.limits.as_ref().and_then(|l| l.memory.clone()),
+                cpu_request: u.limits.as_ref().and_then(|l| l.cpu_request.clone()),
+                memory_request: u.limits.as_ref().and_then(|l| l.memory_request.clone()),
             },
         },
         GrpcAction::ReclaimStorage(rs) => RecommendationAction::ReclaimStorage {
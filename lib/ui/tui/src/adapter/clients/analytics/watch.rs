@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use tonic::Streaming;
+
+use phenome_adapter_analytics::grpc::analytics::{
+    WatchAnomaliesRequest, WatchAnomaliesResponse, WatchMetricsRequest, WatchMetricsResponse,
+    WatchRecommendationsRequest, WatchRecommendationsResponse,
+};
+use phenome_domain::{Anomaly, MetricSample, Recommendation};
+
+use super::AnalyticsClient;
+use super::anomalies::map_anomaly;
+use super::recommendations::map_recommendation;
+
+/// Changes received from one of the service's `Watch*` streams.
+#[derive(Debug, Clone)]
+pub struct WatchBatch<T> {
+    /// Pass back when reconnecting to resume after this batch.
+    pub cursor: u64,
+    pub items: Vec<T>,
+    /// Changes were missed; re-query before applying `items`.
+    pub reset: bool,
+}
+
+/// An open `Watch*` stream.
+#[derive(Debug)]
+pub struct Watch<R, T> {
+    stream: Streaming<R>,
+    convert: fn(R) -> Result<WatchBatch<T>>,
+}
+
+pub type MetricsWatch = Watch<WatchMetricsResponse, MetricSample>;
+pub type AnomaliesWatch = Watch<WatchAnomaliesResponse, Anomaly>;
+pub type RecommendationsWatch = Watch<WatchRecommendationsResponse, Recommendation>;
+
+impl<R, T> Watch<R, T> {
+    /// The next batch; `None` once the service closes the stream.
+    pub async fn next(&mut self) -> Result<Option<WatchBatch<T>>> {
+        match self.stream.message().await? {
+            Some(response) => (self.convert)(response).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+pub(super) async fn watch_metrics(
+    client: &AnalyticsClient,
+    cursor: Option<u64>,
+) -> Result<MetricsWatch> {
+    let mut grpc = client.client.clone();
+    let request = WatchMetricsRequest {
+        cursor,
+        ..Default::default()
+    };
+    let stream = grpc.watch_metrics(request).await?.into_inner();
+    Ok(Watch {
+        stream,
+        convert: |response| {
+            Ok(WatchBatch {
+                cursor: response.cursor,
+                items: response
+                    .samples
+                    .into_iter()
+                    .map(|s| s.try_into())
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("failed to convert metrics")?,
+                reset: response.reset,
+            })
+        },
+    })
+}
+
+pub(super) async fn watch_anomalies(
+    client: &AnalyticsClient,
+    cursor: Option<u64>,
+) -> Result<AnomaliesWatch> {
+    let mut grpc = client.client.clone();
+    let request = WatchAnomaliesRequest {
+        cursor,
+        ..Default::default()
+    };
+    let stream = grpc.watch_anomalies(request).await?.into_inner();
+    Ok(Watch {
+        stream,
+        convert: |response| {
+            Ok(WatchBatch {
+                cursor: response.cursor,
+                items: response.anomalies.into_iter().map(map_anomaly).collect(),
+                reset: response.reset,
+            })
+        },
+    })
+}
+
+pub(super) async fn watch_recommendations(
+    client: &AnalyticsClient,
+    cursor: Option<u64>,
+) -> Result<RecommendationsWatch> {
+    let mut grpc = client.client.clone();
+    let request = WatchRecommendationsRequest {
+        cursor,
+        ..Default::default()
+    };
+    let stream = grpc.watch_recommendations(request).await?.into_inner();
+    Ok(Watch {
+        stream,
+        convert: |response| {
+            Ok(WatchBatch {
+                cursor: response.cursor,
+                items: response
+                    .recommendations
+                    .into_iter()
+                    .map(map_recommendation)
+                    .collect(),
+                reset: response.reset,
+            })
+        },
+    })
+}
@@ -1 +1,2 @@
 pub mod analytics;
+pub mod scheduler;
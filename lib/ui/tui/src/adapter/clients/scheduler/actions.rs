@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+
+use phenome_adapter_analytics::grpc::analytics::{
+    CancelScheduledActionRequest, GetScheduledActionRequest, ListScheduledActionsRequest,
+    RescheduleActionRequest, ScheduleStatusKind as GrpcStatusKind, ScheduledActionResponse,
+};
+use phenome_domain::{ScheduleFilter, ScheduleId, ScheduledAction};
+
+use super::SchedulerClient;
+
+pub(super) async fn list_actions(
+    client: &SchedulerClient,
+    filter: ScheduleFilter,
+) -> Result<Vec<ScheduledAction>> {
+    let mut grpc = client.client.clone();
+    let request = ListScheduledActionsRequest {
+        recommendation_id: filter.recommendation_id,
+        status: filter
+            .status
+            .map(|status| GrpcStatusKind::from(status).into()),
+        limit: filter.limit,
+    };
+    let response = grpc.list_scheduled_actions(request).await?;
+
+    response
+        .into_inner()
+        .actions
+        .into_iter()
+        .map(|action| action.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to convert scheduled actions")
+}
+
+pub(super) async fn get_action(
+    client: &SchedulerClient,
+    id: ScheduleId,
+) -> Result<ScheduledAction> {
+    let mut grpc = client.client.clone();
+    let response = grpc
+        .get_scheduled_action(GetScheduledActionRequest { id })
+        .await?;
+    map_response(response.into_inner())
+}
+
+pub(super) async fn cancel_action(
+    client: &SchedulerClient,
+    id: ScheduleId,
+) -> Result<ScheduledAction> {
+    let mut grpc = client.client.clone();
+    let response = grpc
+        .cancel_scheduled_action(CancelScheduledActionRequest { id })
+        .await?;
+    map_response(response.into_inner())
+}
+
+pub(super) async fn reschedule_action(
+    client: &SchedulerClient,
+    id: ScheduleId,
+    execute_at: i64,
+) -> Result<ScheduledAction> {
+    let mut grpc = client.client.clone();
+    let response = grpc
+        .reschedule_action(RescheduleActionRequest { id, execute_at })
+        .await?;
+    map_response(response.into_inner())
+}
+
+fn map_response(response: ScheduledActionResponse) -> Result<ScheduledAction> {
+    response
+        .action
+        .context("scheduler returned no action")?
+        .try_into()
+        .context("failed to convert scheduled action")
+}
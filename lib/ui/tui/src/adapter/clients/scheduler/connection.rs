@@ -0,0 +1,15 @@
+use anyhow::{Context, Result};
+
+use phenome_adapter_analytics::grpc::analytics::scheduler_service_client::SchedulerServiceClient;
+
+use super::SchedulerClient;
+
+/// The scheduler is served alongside analytics, so it shares its URL.
+pub(super) async fn connect_from_env() -> Result<SchedulerClient> {
+    let endpoint =
+        std::env::var("PHENOME_ANALYTICS_URL").unwrap_or_else(|_| "http://localhost:50051".into());
+    let client = SchedulerServiceClient::connect(endpoint)
+        .await
+        .context("failed to connect to scheduler service")?;
+    Ok(SchedulerClient { client })
+}
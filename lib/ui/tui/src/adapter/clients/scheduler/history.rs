@@ -0,0 +1,26 @@
+use anyhow::Result;
+
+use phenome_adapter_analytics::grpc::analytics::GetExecutionHistoryRequest;
+use phenome_domain::{ScheduleExecution, ScheduleExecutionFilter};
+
+use super::SchedulerClient;
+
+pub(super) async fn execution_history(
+    client: &SchedulerClient,
+    filter: ScheduleExecutionFilter,
+) -> Result<Vec<ScheduleExecution>> {
+    let mut grpc = client.client.clone();
+    let request = GetExecutionHistoryRequest {
+        schedule_id: filter.schedule_id,
+        recommendation_id: filter.recommendation_id,
+        limit: filter.limit,
+    };
+    let response = grpc.get_execution_history(request).await?;
+
+    Ok(response
+        .into_inner()
+        .executions
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use tonic::transport::Channel;
+
+use phenome_adapter_analytics::grpc::analytics::scheduler_service_client::SchedulerServiceClient;
+use phenome_domain::{
+    ScheduleExecution, ScheduleExecutionFilter, ScheduleFilter, ScheduleId, ScheduledAction,
+};
+
+mod actions;
+mod connection;
+mod history;
+
+/// Client for the analytics service's scheduled actions.
+#[derive(Debug, Clone)]
+pub struct SchedulerClient {
+    client: SchedulerServiceClient<Channel>,
+}
+
+impl SchedulerClient {
+    pub async fn connect_from_env() -> Result<Self> {
+        connection::connect_from_env().await
+    }
+
+    /// Matching actions, soonest first.
+    pub async fn list_actions(&self, filter: ScheduleFilter) -> Result<Vec<ScheduledAction>> {
+        actions::list_actions(self, filter).await
+    }
+
+    pub async fn get_action(&self, id: ScheduleId) -> Result<ScheduledAction> {
+        actions::get_action(self, id).await
+    }
+
+    /// Cancel a pending action; returns it as stored.
+    pub async fn cancel_action(&self, id: ScheduleId) -> Result<ScheduledAction> {
+        actions::cancel_action(self, id).await
+    }
+
+    /// Move a pending action to `execute_at`; returns it as stored.
+    pub async fn reschedule_action(
+        &self,
+        id: ScheduleId,
+        execute_at: i64,
+    ) -> Result<ScheduledAction> {
+        actions::reschedule_action(self, id, execute_at).await
+    }
+
+    /// Matching runs, most recent first.
+    pub async fn execution_history(
+        &self,
+        filter: ScheduleExecutionFilter,
+    ) -> Result<Vec<ScheduleExecution>> {
+        history::execution_history(self, filter).await
+    }
+}
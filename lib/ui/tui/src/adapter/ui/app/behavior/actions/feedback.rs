@@ -0,0 +1,42 @@
+use phenome_domain::AnomalyFeedbackKind;
+
+use crate::app::App;
+
+impl App {
+    pub fn select_next_anomaly(&mut self) {
+        let count = self.analytics_anomalies.as_ref().map_or(0, Vec::len);
+        if count > 0 {
+            self.analytics_selected_anomaly = (self.analytics_selected_anomaly + 1) % count;
+        }
+    }
+
+    pub fn select_previous_anomaly(&mut self) {
+        let count = self.analytics_anomalies.as_ref().map_or(0, Vec::len);
+        if count > 0 {
+            self.analytics_selected_anomaly = (self.analytics_selected_anomaly + count - 1) % count;
+        }
+    }
+
+    /// Send a verdict on the selected insights anomaly and show it at once
+    /// rather than on the next poll.
+    pub fn submit_anomaly_feedback(&mut self, kind: AnomalyFeedbackKind) {
+        let Some(client) = self.analytics_client.clone() else {
+            return;
+        };
+        let Some(anomaly) = self
+            .analytics_anomalies
+            .as_mut()
+            .and_then(|anomalies| anomalies.get_mut(self.analytics_selected_anomaly))
+        else {
+            return;
+        };
+        let id = anomaly.id.clone();
+        let result = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(client.submit_feedback(id.clone(), kind))
+        });
+        match result {
+            Ok(feedback) => anomaly.feedback = feedback,
+            Err(err) => tracing::warn!("Failed to submit feedback on anomaly {}: {}", id, err),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod confirm;
+mod feedback;
 mod graph;
 mod logs;
 mod selection;
@@ -8,7 +8,9 @@ use std::time::Instant;
 use crate::app::{GraphRenderState, NavSection, NavView};
 use crate::state::UiState;
 use phenome_application::Runtime;
-use phenome_domain::{ActionId, ActionSafety, Anomaly, MetricSample, Recommendation};
+use phenome_domain::{
+    ActionId, ActionSafety, Anomaly, DataQualityIssue, MetricSample, Recommendation,
+};
 use phenome_ports::PortSet;
 
 use crate::analytics_client::AnalyticsClient;
@@ -71,7 +73,10 @@ pub struct App {
     pub nav_sub_index: [usize; 3],
     pub analytics_metrics: Option<Vec<MetricSample>>,
     pub analytics_anomalies: Option<Vec<Anomaly>>,
+    /// Anomaly that insights feedback keys apply to.
+    pub analytics_selected_anomaly: usize,
     pub analytics_recommendations: Option<Vec<Recommendation>>,
+    pub analytics_data_quality: Option<Vec<DataQualityIssue>>,
     pub analytics_cache_timestamp: Option<Instant>,
     pub analytics_client: Option<AnalyticsClient>,
     pub analytics_rx: Option<tokio::sync::mpsc::Receiver<AnalyticsUpdate>>,
@@ -82,6 +87,7 @@ pub enum AnalyticsUpdate {
     Metrics(Vec<MetricSample>),
     Anomalies(Vec<Anomaly>),
     Recommendations(Vec<Recommendation>),
+    DataQuality(Vec<DataQualityIssue>),
 }
 
 /// Confirmation prompt details for high-risk actions.
@@ -1,9 +1,19 @@
-use std::time::{Duration, Instant};
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use phenome_domain::{Anomaly, MetricSample, Recommendation};
+use tokio::sync::mpsc::Sender;
+
+use crate::analytics_client::{AnalyticsClient, Watch, WatchBatch};
 use crate::app::App;
+use crate::app::core::AnalyticsUpdate;
 
 const ANALYTICS_POLL_INTERVAL: Duration = Duration::from_secs(5);
 const ANALYTICS_MAX_UPDATES_PER_TICK: usize = 32;
+/// Samples kept from streamed batches; matches the window polled.
+const METRICS_WINDOW_MS: i64 = 15 * 60 * 1000;
+const MAX_ANOMALIES: usize = 50;
+const MAX_RECOMMENDATIONS: usize = 20;
 
 impl App {
     pub(super) fn start_analytics(&mut self) {
@@ -16,42 +26,7 @@ impl App {
             let (tx, rx) = tokio::sync::mpsc::channel(10);
             self.analytics_rx = Some(rx);
 
-            tokio::spawn(async move {
-                let mut tick = tokio::time::interval(ANALYTICS_POLL_INTERVAL);
-                loop {
-                    if tx.is_closed() {
-                        break;
-                    }
-                    if let Ok(metrics) = client.fetch_metrics().await {
-                        if tx
-                            .send(crate::app::core::AnalyticsUpdate::Metrics(metrics))
-                            .await
-                            .is_err()
-                        {
-                            break;
-                        }
-                    }
-                    if let Ok(anomalies) = client.fetch_anomalies().await {
-                        if tx
-                            .send(crate::app::core::AnalyticsUpdate::Anomalies(anomalies))
-                            .await
-                            .is_err()
-                        {
-                            break;
-                        }
-                    }
-                    if let Ok(recs) = client.fetch_recommendations().await {
-                        if tx
-                            .send(crate::app::core::AnalyticsUpdate::Recommendations(recs))
-                            .await
-                            .is_err()
-                        {
-                            break;
-                        }
-                    }
-                    tick.tick().await;
-                }
-            });
+            tokio::spawn(follow_analytics(client, tx));
         }
     }
 
@@ -73,6 +48,9 @@ impl App {
                     crate::app::core::AnalyticsUpdate::Recommendations(r) => {
                         self.analytics_recommendations = Some(r)
                     }
+                    crate::app::core::AnalyticsUpdate::DataQuality(q) => {
+                        self.analytics_data_quality = Some(q)
+                    }
                 }
                 self.analytics_cache_timestamp = Some(Instant::now());
                 drained += 1;
@@ -86,3 +64,196 @@ impl App {
         }
     }
 }
+
+/// One kind of analytics record followed through a watch, or polled while
+/// no watch is open.
+struct Followed<R, T> {
+    watch: Option<Watch<R, T>>,
+    cursor: Option<u64>,
+    items: Vec<T>,
+}
+
+impl<R, T> Default for Followed<R, T> {
+    fn default() -> Self {
+        Self {
+            watch: None,
+            cursor: None,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl<R, T> Followed<R, T> {
+    /// Keep a newly opened watch; whether a full fetch is still needed,
+    /// because the watch failed to open or starts from now.
+    fn reopened(&mut self, opened: anyhow::Result<Watch<R, T>>) -> bool {
+        match opened {
+            Ok(watch) => {
+                self.watch = Some(watch);
+                self.cursor.is_none()
+            }
+            Err(e) => {
+                tracing::debug!("analytics watch unavailable, polling: {e}");
+                true
+            }
+        }
+    }
+
+    /// Replace the held records with a full fetch; whether it succeeded.
+    fn refill(&mut self, fetched: anyhow::Result<Vec<T>>) -> bool {
+        match fetched {
+            Ok(items) => {
+                self.items = items;
+                true
+            }
+            Err(e) => {
+                tracing::debug!("analytics fetch failed: {e}");
+                false
+            }
+        }
+    }
+}
+
+enum Event {
+    Tick,
+    Metrics(Option<WatchBatch<MetricSample>>),
+    Anomalies(Option<WatchBatch<Anomaly>>),
+    Recommendations(Option<WatchBatch<Recommendation>>),
+}
+
+/// Stream metrics, anomalies and recommendations where the service can
+/// watch them, falling back to polling each one while its watch is down.
+/// Data quality has no watch and is always polled. Every change is sent
+/// on as a full snapshot.
+async fn follow_analytics(client: AnalyticsClient, tx: Sender<AnalyticsUpdate>) {
+    let mut metrics = Followed::default();
+    let mut anomalies = Followed::default();
+    let mut recommendations = Followed::default();
+    let mut tick = tokio::time::interval(ANALYTICS_POLL_INTERVAL);
+    loop {
+        if tx.is_closed() {
+            break;
+        }
+        let event = tokio::select! {
+            _ = tick.tick() => Event::Tick,
+            batch = next_batch(&mut metrics.watch) => Event::Metrics(batch),
+            batch = next_batch(&mut anomalies.watch) => Event::Anomalies(batch),
+            batch = next_batch(&mut recommendations.watch) => Event::Recommendations(batch),
+        };
+
+        let mut updates = Vec::new();
+        match event {
+            Event::Tick => {
+                if metrics.watch.is_none()
+                    && metrics.reopened(client.watch_metrics(metrics.cursor).await)
+                    && metrics.refill(client.fetch_metrics().await)
+                {
+                    updates.push(AnalyticsUpdate::Metrics(metrics.items.clone()));
+                }
+                if anomalies.watch.is_none()
+                    && anomalies.reopened(client.watch_anomalies(anomalies.cursor).await)
+                    && anomalies.refill(client.fetch_anomalies().await)
+                {
+                    updates.push(AnalyticsUpdate::Anomalies(anomalies.items.clone()));
+                }
+                if recommendations.watch.is_none()
+                    && recommendations
+                        .reopened(client.watch_recommendations(recommendations.cursor).await)
+                    && recommendations.refill(client.fetch_recommendations().await)
+                {
+                    updates.push(AnalyticsUpdate::Recommendations(
+                        recommendations.items.clone(),
+                    ));
+                }
+                if let Ok(issues) = client.fetch_data_quality().await {
+                    updates.push(AnalyticsUpdate::DataQuality(issues));
+                }
+            }
+            Event::Metrics(None) => metrics.watch = None,
+            Event::Metrics(Some(batch)) => {
+                if batch.reset {
+                    metrics.refill(client.fetch_metrics().await);
+                }
+                metrics.cursor = Some(batch.cursor);
+                merge_metrics(&mut metrics.items, batch.items);
+                updates.push(AnalyticsUpdate::Metrics(metrics.items.clone()));
+            }
+            Event::Anomalies(None) => anomalies.watch = None,
+            Event::Anomalies(Some(batch)) => {
+                if batch.reset {
+                    anomalies.refill(client.fetch_anomalies().await);
+                }
+                anomalies.cursor = Some(batch.cursor);
+                upsert(&mut anomalies.items, batch.items, MAX_ANOMALIES, |a| &a.id);
+                updates.push(AnalyticsUpdate::Anomalies(anomalies.items.clone()));
+            }
+            Event::Recommendations(None) => recommendations.watch = None,
+            Event::Recommendations(Some(batch)) => {
+                if batch.reset {
+                    recommendations.refill(client.fetch_recommendations().await);
+                }
+                recommendations.cursor = Some(batch.cursor);
+                upsert(
+                    &mut recommendations.items,
+                    batch.items,
+                    MAX_RECOMMENDATIONS,
+                    |r| &r.id,
+                );
+                updates.push(AnalyticsUpdate::Recommendations(
+                    recommendations.items.clone(),
+                ));
+            }
+        }
+
+        for update in updates {
+            if tx.send(update).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The next batch from an open watch; `None` once it ends or fails. Never
+/// resolves without a watch, leaving that record to the poll tick.
+async fn next_batch<R, T>(watch: &mut Option<Watch<R, T>>) -> Option<WatchBatch<T>> {
+    match watch {
+        Some(watch) => match watch.next().await {
+            Ok(batch) => batch,
+            Err(e) => {
+                tracing::debug!("analytics watch ended: {e}");
+                None
+            }
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// Append streamed samples, skipping ones already held, and drop samples
+/// older than the dashboard window.
+fn merge_metrics(samples: &mut Vec<MetricSample>, batch: Vec<MetricSample>) {
+    let held: HashSet<_> = samples
+        .iter()
+        .map(|s| (s.resource_id.clone(), s.metric_type, s.timestamp))
+        .collect();
+    samples.extend(
+        batch
+            .into_iter()
+            .filter(|s| !held.contains(&(s.resource_id.clone(), s.metric_type, s.timestamp))),
+    );
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64);
+    samples.retain(|s| s.timestamp >= now_ms - METRICS_WINDOW_MS);
+}
+
+/// Replace records by id or append them, keeping the newest `cap`.
+fn upsert<T>(items: &mut Vec<T>, batch: Vec<T>, cap: usize, id: impl Fn(&T) -> &String) {
+    for item in batch {
+        match items.iter().position(|held| id(held) == id(&item)) {
+            Some(index) => items[index] = item,
+            None => items.push(item),
+        }
+    }
+    let excess = items.len().saturating_sub(cap);
+    items.drain(..excess);
+}
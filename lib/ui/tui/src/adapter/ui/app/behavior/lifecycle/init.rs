@@ -50,7 +50,9 @@ impl App {
             analytics_client: None,
             analytics_metrics: None,
             analytics_anomalies: None,
+            analytics_selected_anomaly: 0,
             analytics_recommendations: None,
+            analytics_data_quality: None,
             analytics_cache_timestamp: None,
             analytics_rx: None,
         };
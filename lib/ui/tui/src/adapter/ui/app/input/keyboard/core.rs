@@ -1,6 +1,8 @@
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+use phenome_domain::AnomalyFeedbackKind;
+
 use crate::app::{App, NavView};
 
 impl App {
@@ -36,6 +38,15 @@ impl App {
                     self.refresh_log_cache(true);
                 }
             }
+            KeyCode::Char('c') if matches!(view, NavView::AnalyticsInsights) => {
+                self.submit_anomaly_feedback(AnomalyFeedbackKind::Confirmed);
+            }
+            KeyCode::Char('x') if matches!(view, NavView::AnalyticsInsights) => {
+                self.submit_anomaly_feedback(AnomalyFeedbackKind::FalsePositive);
+            }
+            KeyCode::Char('e') if matches!(view, NavView::AnalyticsInsights) => {
+                self.submit_anomaly_feedback(AnomalyFeedbackKind::Expected);
+            }
             KeyCode::Char('n') => self.toggle_notifications_panel(),
             KeyCode::Char('w') => self.ui.auto_refresh = !self.ui.auto_refresh,
             KeyCode::Char('a') => self.set_active_nav(crate::app::NavSection::Analytics),
@@ -61,6 +72,8 @@ impl App {
             KeyCode::Up | KeyCode::Char('k') => {
                 if matches!(view, NavView::TerminalCommands) {
                     self.select_previous_action();
+                } else if matches!(view, NavView::AnalyticsInsights) {
+                    self.select_previous_anomaly();
                 } else {
                     self.prev_nav_sub();
                 }
@@ -68,6 +81,8 @@ impl App {
             KeyCode::Down | KeyCode::Char('j') => {
                 if matches!(view, NavView::TerminalCommands) {
                     self.select_next_action();
+                } else if matches!(view, NavView::AnalyticsInsights) {
+                    self.select_next_anomaly();
                 } else {
                     self.next_nav_sub();
                 }
@@ -63,6 +63,10 @@ fn help_lines(app: &App) -> Vec<Line<'_>> {
         | crate::app::NavView::AnalyticsInsights => {
             lines.push(section_title("Analytics"));
             lines.push(Line::from("1-4: switch analytics views"));
+            if app.active_view() == crate::app::NavView::AnalyticsInsights {
+                lines.push(Line::from("up/down or j/k: select anomaly"));
+                lines.push(Line::from("c: confirm  x: false positive  e: expected"));
+            }
         }
         crate::app::NavView::TopologyAssembly
         | crate::app::NavView::TopologyDomains
@@ -6,20 +6,49 @@ use ratatui::{
     widgets::{Paragraph, Wrap},
 };
 
+use phenome_domain::{Anomaly, Unit};
+
 use crate::app::App;
 
+const VISIBLE_ANOMALIES: usize = 8;
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
 pub fn render_insights(frame: &mut Frame, area: Rect, app: &mut App) {
     let mut lines = Vec::new();
     lines.push(section_title("Insights"));
 
     match app.analytics_anomalies.as_ref() {
         Some(anomalies) if !anomalies.is_empty() => {
-            for anomaly in anomalies.iter().take(8) {
-                lines.push(Line::from(format!(
-                    "- [{}] {}",
+            let selected = app.analytics_selected_anomaly.min(anomalies.len() - 1);
+            let start = (selected + 1).saturating_sub(VISIBLE_ANOMALIES);
+            for (index, anomaly) in anomalies
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(VISIBLE_ANOMALIES)
+            {
+                let unit = Unit::for_metric(anomaly.metric_type);
+                let verdict = anomaly
+                    .feedback
+                    .as_ref()
+                    .map(|feedback| format!(" [{}]", feedback.kind.as_str().replace('_', " ")))
+                    .unwrap_or_default();
+                let line = Line::from(format!(
+                    "{} [{}] {}: {} (observed {}, baseline {}){}",
+                    if index == selected { ">" } else { "-" },
                     format!("{:?}", anomaly.severity).to_lowercase(),
-                    anomaly.description
-                )));
+                    anomaly.kind.as_str().replace('_', " "),
+                    anomaly.description,
+                    unit.format_human(anomaly.observed_value),
+                    unit.format_human(anomaly.baseline_value),
+                    verdict
+                ));
+                if index == selected {
+                    lines.push(line.style(Style::default().add_modifier(Modifier::BOLD)));
+                    lines.extend(evidence_lines(anomaly, unit));
+                } else {
+                    lines.push(line);
+                }
             }
         }
         _ => {
@@ -31,6 +60,82 @@ pub fn render_insights(frame: &mut Frame, area: Rect, app: &mut App) {
     frame.render_widget(paragraph, area);
 }
 
+/// Why the selected anomaly was flagged: the range its baseline expected,
+/// the history that baseline came from, and what drove the score.
+fn evidence_lines(anomaly: &Anomaly, unit: Unit) -> Vec<Line<'static>> {
+    let Some(evidence) = anomaly.evidence.as_ref() else {
+        return Vec::new();
+    };
+    let detail = Style::default().fg(Color::DarkGray);
+    let window = &evidence.baseline_window;
+    let mut expected = format!(
+        "    expected {} to {} from {} of history",
+        unit.format_human(evidence.expected_low),
+        unit.format_human(evidence.expected_high),
+        format_span(window.end_ms - window.start_ms)
+    );
+    if let Some(seasonal) = evidence.seasonal_expected {
+        expected.push_str(&format!(
+            ", {} for this time of day",
+            unit.format_human(seasonal)
+        ));
+    }
+    let mut lines = vec![Line::from(Span::styled(expected, detail))];
+
+    if evidence.points.len() > 1 {
+        let (low, high) = evidence
+            .points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), point| {
+                (low.min(point.value), high.max(point.value))
+            });
+        let spread = (high - low).max(f64::EPSILON);
+        let spark: String = evidence
+            .points
+            .iter()
+            .map(|point| {
+                let level = ((point.value - low) / spread * (SPARK.len() - 1) as f64).round();
+                SPARK[level as usize]
+            })
+            .collect();
+        lines.push(Line::from(Span::styled(
+            format!("    recent {spark}"),
+            detail,
+        )));
+    }
+
+    let drivers: Vec<String> = evidence
+        .contributions
+        .iter()
+        .filter(|contribution| contribution.share > 0.0)
+        .map(|contribution| {
+            format!(
+                "{} {:.0}%",
+                contribution.feature.replace('_', " "),
+                contribution.share * 100.0
+            )
+        })
+        .collect();
+    if !drivers.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("    driven by {}", drivers.join(", ")),
+            detail,
+        )));
+    }
+    lines
+}
+
+fn format_span(ms: i64) -> String {
+    let minutes = ms.max(0) / 60_000;
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else if minutes < 48 * 60 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}d", minutes / (24 * 60))
+    }
+}
+
 fn section_title(label: &'static str) -> Line<'static> {
     Line::from(Span::styled(
         label,
@@ -3,11 +3,12 @@ use ratatui::prelude::Frame;
 use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::widgets::{Block, Borders, Padding, Paragraph};
 
+use phenome_domain::Unit;
+
 use crate::app::App;
 use crate::util::centered_rect;
 
 mod cards;
-mod format;
 mod stats;
 
 pub fn render_realtime(frame: &mut Frame, area: Rect, app: &mut App) {
@@ -55,7 +56,7 @@ pub fn render_realtime(frame: &mut Frame, area: Rect, app: &mut App) {
         .split(chunks[1]);
 
     let cpu_text = if totals.cpu_valid {
-        format!("{:.2} cores", totals.cpu_sum)
+        Unit::Cores.format_human(totals.cpu_sum)
     } else {
         "N/A".to_string()
     };
@@ -68,7 +69,7 @@ pub fn render_realtime(frame: &mut Frame, area: Rect, app: &mut App) {
     );
 
     let mem_text = if totals.mem_valid {
-        format::format_bytes(totals.mem_sum)
+        Unit::Bytes.format_human(totals.mem_sum)
     } else {
         "N/A".to_string()
     };
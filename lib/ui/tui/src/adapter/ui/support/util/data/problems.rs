@@ -5,5 +5,9 @@ use phenome_ui_presentation::formatting;
 /// Gather formatted problem lines from the current runtime state.
 pub fn collect_problems(app: &crate::app::App) -> Vec<String> {
     let health = app.context.ports.health.snapshot();
-    formatting::problem_lines(app.runtime.snapshot(), Some(&health))
+    let mut problems = formatting::problem_lines(app.runtime.snapshot(), Some(&health));
+    if let Some(issues) = &app.analytics_data_quality {
+        problems.extend(formatting::data_quality_problem_lines(issues));
+    }
+    problems
 }
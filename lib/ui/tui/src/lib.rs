@@ -20,6 +20,7 @@ mod runtime;
 
 pub use adapter::bootstrap;
 pub use adapter::clients::analytics as analytics_client;
+pub use adapter::clients::scheduler as scheduler_client;
 pub use adapter::ui::app;
 pub use adapter::ui::layout;
 pub use adapter::ui::panels;
@@ -8,6 +8,11 @@ use tokio::sync::watch;
 use phenome_adapter_analytics::AnalyticsService;
 use phenome_adapter_analytics::cluster_manager::ClusterManager;
 use phenome_adapter_analytics::grpc::GrpcServer;
+use phenome_adapter_analytics::feedback::FeedbackTuner;
+use phenome_adapter_analytics::incidents::IncidentTracker;
+use phenome_adapter_analytics::quality::DataQualityMonitor;
+use phenome_adapter_analytics::streaming::StreamingDetector;
+use phenome_adapter_analytics::validation::IngestValidator;
 use phenome_adapter_analytics::storage::sqlite::{RetentionConfig, SqliteStorage};
 use phenome_domain::PhenomeConfig;
 
@@ -36,17 +41,35 @@ async fn main() -> anyhow::Result<()> {
     let ml_url = config.services.ml_url.clone();
     let ml_client = phenome_adapter_analytics::grpc::MlClient::connect(&ml_url).await?;
 
-    let service = AnalyticsService::new(storage.clone(), ml_client);
+    let quality = DataQualityMonitor::new(config.analytics.quality.clone());
+    let service = AnalyticsService::new(storage.clone(), ml_client)
+        .with_quality_monitor(quality.clone())
+        .with_ingest_validator(IngestValidator::new(config.analytics.ingest.clone()))
+        .with_incident_tracker(IncidentTracker::from_config(&config.analytics.incidents)?)
+        .with_streaming_detector(
+            StreamingDetector::from_config(&config.analytics.streaming)?
+                .with_thresholds(config.ml.thresholds.clone())?,
+        )
+        .with_feedback_tuner(FeedbackTuner::from_config(&config.analytics.feedback)?)
+        .with_watch_config(config.analytics.watch.clone())?;
+    service.restore_incidents().await?;
+    service.restore_feedback().await?;
+    service.restore_recommendations().await?;
     let service = Arc::new(service);
 
-    let cm = ClusterManager::new();
+    // Clusters added over gRPC persist. Configured clusters are added when
+    // missing, except those removed over gRPC, which stay removed until
+    // added back.
+    let cm = ClusterManager::new().with_storage(storage.clone());
+    cm.restore_clusters().await?;
     for cluster_config in config.clusters {
-        cm.add_cluster(cluster_config.context).await?;
+        cm.add_configured_cluster(cluster_config.name, cluster_config.context).await?;
     }
     let mc = phenome_adapter_analytics::metrics_collector::MetricsCollector::new(
-        cm,
+        cm.clone(),
         Duration::from_secs(config.collection.interval),
-    );
+    )
+    .with_quality_monitor(quality);
     let _hc = tokio::spawn(mc.run_polling_loop_with_shutdown(shutdown_rx.clone()));
 
     tokio::spawn(
@@ -93,18 +116,33 @@ async fn main() -> anyhow::Result<()> {
     }
 
     if let Some(kube_client) = kube_client {
-        tokio::spawn(
-            phenome_adapter_analytics::scheduler::SchedulerService::run_minute_with_shutdown(
-                storage.clone(),
-                kube_client,
-                shutdown_rx.clone(),
-            ),
-        );
+        // Run the service's own scheduler so finished runs settle their
+        // recommendations.
+        let scheduler = service.scheduler().clone();
+        let shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            scheduler.run_scheduler_loop(kube_client, shutdown_rx).await;
+        });
+    }
+    {
+        let service = service.clone();
+        let shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            service.follow_schedules_with_shutdown(shutdown_rx).await;
+        });
+    }
+    {
+        let service = service.clone();
+        let clusters = cm.clone();
+        let shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            service.recommend_with_shutdown(clusters, shutdown_rx).await;
+        });
     }
 
     let addr = parse_addr(&config.services.analytics_url)
         .unwrap_or_else(|| "127.0.0.1:50051".parse().expect("invalid fallback addr"));
-    GrpcServer::serve(addr, service).await?;
+    GrpcServer::serve(addr, service, cm).await?;
     Ok(())
 }
 
@@ -1,20 +1,33 @@
 use std::env;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use phenome_adapter_ml::grpc::GrpcServer;
 use phenome_adapter_ml::MlService;
+use phenome_adapter_ml::grpc::{AnalyticsClient, GrpcServer};
 use phenome_domain::PhenomeConfig;
+use phenome_ml::{ModelFactory, ModelRegistry};
+
+/// How often the config file is checked for changes to the `ml` section.
+const CONFIG_POLL: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let config_path = config_path();
     let config = PhenomeConfig::load_from_path(&config_path)?;
-    let _service = MlService::new();
+
+    let analytics_client = AnalyticsClient::connect_lazy(config.services.analytics_url.clone())?;
+    let service = MlService::new(analytics_client)
+        .with_models(ModelFactory::from_config(&config.ml)?.build()?)
+        .with_model_registry(ModelRegistry::from_config(&config.ml.registry)?);
+    let service = Arc::new(service);
+    tokio::spawn(service.clone().run_retraining());
+    tokio::spawn(service.clone().watch_config(config_path, CONFIG_POLL));
 
     let addr = parse_addr(&config.services.ml_url)
         .unwrap_or_else(|| "127.0.0.1:50052".parse().expect("invalid fallback addr"));
-    GrpcServer::serve(addr).await?;
+    GrpcServer::serve(addr, service).await?;
     Ok(())
 }
 